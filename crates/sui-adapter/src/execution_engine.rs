@@ -148,6 +148,7 @@ fn execute_transaction<
     // we must still ensure an effect is committed and all objects versions incremented.
     let result = charge_gas_for_object_read(temporary_store, &mut gas_status);
     let mut result = result.and_then(|()| {
+        gas_status.start_execution_timeout();
         let mut execution_result = execution_loop::<Mode, _>(
             temporary_store,
             transaction_kind,
@@ -157,6 +158,18 @@ fn execute_transaction<
             &mut gas_status,
             protocol_config,
         );
+        gas_status.stop_execution_timeout();
+        if gas_status.execution_timed_out() {
+            // The charge that timed out failed as if the gas ran out, report the timeout instead
+            let mut error = ExecutionError::new_with_source(
+                ExecutionErrorKind::ExecutionTimeout,
+                "Transaction execution timed out",
+            );
+            if let Some(command) = execution_result.as_ref().err().and_then(|e| e.command()) {
+                error = error.with_command_index(command);
+            }
+            execution_result = Err(error);
+        }
 
         let effects_estimated_size = temporary_store.estimate_effects_size_upperbound();
 
@@ -72,6 +72,10 @@ pub fn execute<E: fmt::Debug, S: StorageView<E>, Mode: ExecutionMode>(
     // execute commands
     let mut mode_results = Mode::empty_results();
     for (idx, command) in commands.into_iter().enumerate() {
+        #[cfg(feature = "testing")]
+        if let Some(kind) = super::failure_injection::injected_failure(idx) {
+            return Err(ExecutionError::from_kind(kind).with_command_index(idx));
+        }
         execute_command::<_, _, Mode>(&mut context, &mut mode_results, command)
             .map_err(|e| e.with_command_index(idx))?
     }
@@ -0,0 +1,41 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Test-only hooks to make a chosen command of a programmable transaction fail, so that error
+//! handling can be exercised without a Move module that aborts at the right point.
+
+use std::{cell::RefCell, collections::BTreeMap};
+
+use sui_types::messages::{CommandIndex, ExecutionFailureStatus};
+
+thread_local! {
+    static INJECTED_FAILURES: RefCell<BTreeMap<CommandIndex, ExecutionFailureStatus>> =
+        RefCell::new(BTreeMap::new());
+}
+
+#[must_use]
+pub struct FailureInjectionGuard;
+
+impl Drop for FailureInjectionGuard {
+    fn drop(&mut self) {
+        INJECTED_FAILURES.with(|failures| failures.borrow_mut().clear());
+    }
+}
+
+/// Make the command at `idx` of every programmable transaction executed on this thread fail with
+/// `kind` instead of running, until the returned guard is dropped. The failure is reported like
+/// any other, with `command: Some(idx)` in the effects' status.
+pub fn inject_command_failure(
+    idx: CommandIndex,
+    kind: ExecutionFailureStatus,
+) -> FailureInjectionGuard {
+    INJECTED_FAILURES.with(|failures| {
+        let prev = failures.borrow_mut().insert(idx, kind);
+        assert!(prev.is_none(), "failure already injected at command {idx}");
+        FailureInjectionGuard
+    })
+}
+
+pub(crate) fn injected_failure(idx: CommandIndex) -> Option<ExecutionFailureStatus> {
+    INJECTED_FAILURES.with(|failures| failures.borrow().get(&idx).cloned())
+}
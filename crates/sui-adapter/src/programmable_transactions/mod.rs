@@ -3,4 +3,6 @@
 
 pub mod context;
 pub mod execution;
+#[cfg(feature = "testing")]
+pub mod failure_injection;
 pub mod types;
@@ -14,6 +14,7 @@ use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use std::usize;
 use sui_keys::keypair_file::{read_authority_keypair_from_file, read_keypair_from_file};
 use sui_protocol_config::SupportedProtocolVersions;
@@ -98,6 +99,15 @@ pub struct NodeConfig {
 
     #[serde(default)]
     pub indirect_objects_threshold: usize,
+
+    /// Abort the dry runs and dev-inspect calls that run for longer than this, charging their
+    /// whole gas budget, so that a transaction that hangs the VM does not tie up the node serving
+    /// them. Certified transactions are only ever bounded by gas: whether a transaction times out
+    /// depends on the speed of the machine, and all nodes must agree on their effects.
+    ///
+    /// If unspecified, dry runs and dev-inspect calls are only bounded by gas.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_timeout_ms: Option<u64>,
 }
 
 fn default_authority_store_pruning_config() -> AuthorityStorePruningConfig {
@@ -193,6 +203,10 @@ impl NodeConfig {
         self.db_path.join("live")
     }
 
+    pub fn execution_timeout(&self) -> Option<Duration> {
+        self.execution_timeout_ms.map(Duration::from_millis)
+    }
+
     pub fn db_checkpoint_path(&self) -> PathBuf {
         self.db_path.join("db_checkpoints")
     }
@@ -14,8 +14,10 @@ use fastcrypto::encoding::Encoding;
 use fastcrypto::traits::KeyPair;
 use itertools::Itertools;
 use move_binary_format::compatibility::Compatibility;
+use move_binary_format::errors::VMError;
 use move_binary_format::CompiledModule;
 use move_core_types::language_storage::ModuleId;
+use move_core_types::vm_status::{StatusCode, StatusType};
 use parking_lot::Mutex;
 use prometheus::{
     register_histogram_with_registry, register_int_counter_vec_with_registry,
@@ -65,8 +67,8 @@ use sui_types::event::{Event, EventID};
 use sui_types::gas::{GasCostSummary, GasPrice, SuiCostTable, SuiGasStatus};
 use sui_types::message_envelope::Message;
 use sui_types::messages_checkpoint::{
-    CheckpointContents, CheckpointContentsDigest, CheckpointDigest, CheckpointSequenceNumber,
-    CheckpointSummary, CheckpointTimestamp, VerifiedCheckpoint,
+    CertifiedCheckpointSummary, CheckpointContents, CheckpointContentsDigest, CheckpointDigest,
+    CheckpointSequenceNumber, CheckpointTimestamp, VerifiedCheckpoint,
 };
 use sui_types::messages_checkpoint::{CheckpointRequest, CheckpointResponse};
 use sui_types::object::{MoveObject, Owner, PastObjectRead, OBJECT_START_VERSION};
@@ -83,7 +85,7 @@ use sui_types::{
     base_types::*,
     committee::Committee,
     crypto::AuthoritySignature,
-    error::{SuiError, SuiResult},
+    error::{ExecutionError, SuiError, SuiResult},
     fp_ensure,
     messages::*,
     object::{Object, ObjectFormatOptions, ObjectRead},
@@ -184,6 +186,9 @@ pub struct AuthorityMetrics {
     commit_certificate_latency: Histogram,
     db_checkpoint_latency: Histogram,
 
+    /// Computation gas units charged per executed transaction, regardless of the gas price
+    pub(crate) computation_gas_units: Histogram,
+
     pub(crate) transaction_manager_num_enqueued_certificates: IntCounterVec,
     pub(crate) transaction_manager_num_missing_objects: IntGauge,
     pub(crate) transaction_manager_num_pending_certificates: IntGauge,
@@ -206,6 +211,9 @@ pub struct AuthorityMetrics {
     pub consensus_handler_processed_batches: IntCounter,
     pub consensus_handler_processed_bytes: IntCounter,
     pub consensus_handler_processed: IntCounterVec,
+
+    /// Packages rejected by the bytecode verifiers on publish or upgrade, by failure category
+    pub(crate) publish_verification_failures: IntCounterVec,
 }
 
 // Override default Prom buckets for positive numbers in 0-50k range
@@ -213,6 +221,12 @@ const POSITIVE_INT_BUCKETS: &[f64] = &[
     1., 2., 5., 10., 20., 50., 100., 200., 500., 1000., 2000., 5000., 10000., 20000., 50000.,
 ];
 
+// Computation is charged in buckets of these gas units, transactions above the last one are
+// charged for what they use
+const COMPUTATION_GAS_UNITS_BUCKETS: &[f64] = &[
+    1000., 5000., 10000., 20000., 50000., 1_000_000., 5_000_000., 10_000_000.,
+];
+
 const LATENCY_SEC_BUCKETS: &[f64] = &[
     0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10., 20., 30., 60., 90.,
 ];
@@ -327,6 +341,13 @@ impl AuthorityMetrics {
                 LATENCY_SEC_BUCKETS.to_vec(),
                 registry,
             ).unwrap(),
+            computation_gas_units: register_histogram_with_registry!(
+                "authority_computation_gas_units",
+                "Distribution of computation gas units consumed per executed transaction",
+                COMPUTATION_GAS_UNITS_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
             transaction_manager_num_enqueued_certificates: register_int_counter_vec_with_registry!(
                 "transaction_manager_num_enqueued_certificates",
                 "Current number of certificates enqueued to TransactionManager",
@@ -411,11 +432,54 @@ impl AuthorityMetrics {
                 registry
             ).unwrap(),
             consensus_handler_processed: register_int_counter_vec_with_registry!("consensus_handler_processed", "Number of transactions processed by consensus handler", &["class"], registry)
-                .unwrap()
+                .unwrap(),
+            publish_verification_failures: register_int_counter_vec_with_registry!(
+                "publish_verification_failures",
+                "Number of published or upgraded packages that failed verification, by category",
+                &["category"],
+                registry,
+            )
+            .unwrap(),
         }
     }
 }
 
+/// Classifies a failed publish or upgrade by the verifier check that rejected the package, or
+/// returns None if the failure was not a verification failure.
+fn publish_verification_failure_category(error: &ExecutionError) -> Option<&'static str> {
+    match error.kind() {
+        ExecutionFailureStatus::SuiMoveVerificationError => Some("sui"),
+        ExecutionFailureStatus::VMVerificationOrDeserializationError => {
+            let status = error
+                .source()
+                .as_ref()
+                .and_then(|source| source.downcast_ref::<VMError>())
+                .map(|vm_error| vm_error.major_status());
+            Some(match status {
+                Some(code) if code.status_type() == StatusType::Deserialization => {
+                    "deserialization"
+                }
+                Some(StatusCode::INDEX_OUT_OF_BOUNDS | StatusCode::RANGE_OUT_OF_BOUNDS) => "bounds",
+                Some(
+                    StatusCode::TYPE_MISMATCH
+                    | StatusCode::BR_TYPE_MISMATCH_ERROR
+                    | StatusCode::STLOC_TYPE_MISMATCH_ERROR
+                    | StatusCode::RET_TYPE_MISMATCH_ERROR
+                    | StatusCode::CALL_TYPE_MISMATCH_ERROR
+                    | StatusCode::FREEZEREF_TYPE_MISMATCH_ERROR
+                    | StatusCode::READREF_TYPE_MISMATCH_ERROR
+                    | StatusCode::WRITEREF_TYPE_MISMATCH_ERROR
+                    | StatusCode::EQUALITY_OP_TYPE_MISMATCH_ERROR
+                    | StatusCode::INTEGER_OP_TYPE_MISMATCH_ERROR
+                    | StatusCode::BOOLEAN_OP_TYPE_MISMATCH_ERROR,
+                ) => "type_safety",
+                _ => "other",
+            })
+        }
+        _ => None,
+    }
+}
+
 /// a Trait object for `Signer` that is:
 /// - Pin, i.e. confined to one place in memory (we don't want to copy private keys).
 /// - Sync, i.e. can be safely shared between threads.
@@ -456,6 +520,11 @@ pub struct AuthorityState {
 
     /// Take db checkpoints af different dbs
     db_checkpoint_config: DBCheckpointConfig,
+
+    /// Dry runs and dev-inspect calls whose execution runs for longer than this are aborted with
+    /// `ExecutionFailureStatus::ExecutionTimeout`. Never applied to certificates, their effects
+    /// must not depend on the speed of the machine executing them.
+    execution_timeout: Option<Duration>,
 }
 
 /// The authority state encapsulates all state, drives execution, and ensures safety.
@@ -975,7 +1044,12 @@ impl AuthorityState {
         );
         let transaction_data = &certificate.data().intent_message().value;
         let (kind, signer, gas) = transaction_data.execution_parts();
-        let (inner_temp_store, effects, _execution_error) =
+        let publishes_package = matches!(
+            &kind,
+            TransactionKind::ProgrammableTransaction(pt)
+                if pt.commands.iter().any(|c| matches!(c, Command::Publish(_) | Command::Upgrade(..)))
+        );
+        let (inner_temp_store, effects, execution_error) =
             execution_engine::execute_transaction_to_effects::<execution_mode::Normal, _>(
                 shared_object_refs,
                 temporary_store,
@@ -990,6 +1064,26 @@ impl AuthorityState {
                 epoch_store.protocol_config(),
             );
 
+        // The computation cost is charged in SUI, at the gas price of the transaction.
+        if let Some(gas_units) = effects
+            .gas_cost_summary()
+            .computation_cost
+            .checked_div(transaction_data.gas_price())
+        {
+            self.metrics.computation_gas_units.observe(gas_units as f64);
+        }
+
+        if let Err(error) = &execution_error {
+            if publishes_package {
+                if let Some(category) = publish_verification_failure_category(error) {
+                    self.metrics
+                        .publish_verification_failures
+                        .with_label_values(&[category])
+                        .inc();
+                }
+            }
+        }
+
         Ok((inner_temp_store, effects))
     }
 
@@ -1015,7 +1109,7 @@ impl AuthorityState {
 
         // make a gas object if one was not provided
         let mut gas_object_refs = transaction.gas().to_vec();
-        let (gas_status, input_objects) = if transaction.gas().is_empty() {
+        let (mut gas_status, input_objects) = if transaction.gas().is_empty() {
             let sender = transaction.sender();
             let protocol_config = epoch_store.protocol_config();
             let max_tx_gas = protocol_config.max_tx_gas();
@@ -1042,6 +1136,7 @@ impl AuthorityState {
             )
             .await?
         };
+        gas_status.set_execution_timeout(self.execution_timeout);
 
         let shared_object_refs = input_objects.filter_shared_objects();
 
@@ -1153,6 +1248,7 @@ impl AuthorityState {
             SuiCostTable::new(protocol_config),
         );
         gas_status.charge_min_tx_gas()?;
+        gas_status.set_execution_timeout(self.execution_timeout);
         let move_vm = Arc::new(
             adapter::new_move_vm(
                 epoch_store.native_functions().clone(),
@@ -1598,6 +1694,7 @@ impl AuthorityState {
         pruning_config: AuthorityStorePruningConfig,
         genesis_objects: &[Object],
         db_checkpoint_config: &DBCheckpointConfig,
+        execution_timeout: Option<Duration>,
     ) -> Arc<Self> {
         Self::check_protocol_version(supported_protocol_versions, epoch_store.protocol_version());
 
@@ -1635,6 +1732,7 @@ impl AuthorityState {
             _objects_pruner,
             _authority_per_epoch_pruner,
             db_checkpoint_config: db_checkpoint_config.clone(),
+            execution_timeout,
         });
 
         // Process tx recovery log first, so that checkpoint recovery (below)
@@ -1665,6 +1763,23 @@ impl AuthorityState {
         key: &AuthorityKeyPair,
         store_base_path: Option<PathBuf>,
         genesis: &Genesis,
+    ) -> Arc<Self> {
+        Self::new_for_testing_with_execution_timeout(
+            genesis_committee,
+            key,
+            store_base_path,
+            genesis,
+            None,
+        )
+        .await
+    }
+
+    pub async fn new_for_testing_with_execution_timeout(
+        genesis_committee: Committee,
+        key: &AuthorityKeyPair,
+        store_base_path: Option<PathBuf>,
+        genesis: &Genesis,
+        execution_timeout: Option<Duration>,
     ) -> Arc<Self> {
         let secret = Arc::pin(key.copy());
         let name: AuthorityName = secret.public().into();
@@ -1727,6 +1842,7 @@ impl AuthorityState {
             AuthorityStorePruningConfig::default(),
             genesis.objects(),
             &DBCheckpointConfig::default(),
+            execution_timeout,
         )
         .await;
 
@@ -2356,15 +2472,16 @@ impl AuthorityState {
             .ok_or_else(|| anyhow!("Latest checkpoint sequence number not found"))
     }
 
-    pub fn get_checkpoint_summary_by_sequence_number(
+    /// Returns the checkpoint summary along with the validator signatures certifying it
+    pub fn get_certified_checkpoint_by_sequence_number(
         &self,
         sequence_number: CheckpointSequenceNumber,
-    ) -> Result<CheckpointSummary, anyhow::Error> {
+    ) -> Result<CertifiedCheckpointSummary, anyhow::Error> {
         let verified_checkpoint = self
             .get_checkpoint_store()
             .get_checkpoint_by_sequence_number(sequence_number)?;
         match verified_checkpoint {
-            Some(verified_checkpoint) => Ok(verified_checkpoint.into_inner().into_data()),
+            Some(verified_checkpoint) => Ok(verified_checkpoint.into_inner()),
             None => Err(anyhow!(
                 "Verified checkpoint not found for sequence number {}",
                 sequence_number
@@ -2372,15 +2489,16 @@ impl AuthorityState {
         }
     }
 
-    pub fn get_checkpoint_summary_by_digest(
+    /// Returns the checkpoint summary along with the validator signatures certifying it
+    pub fn get_certified_checkpoint_by_digest(
         &self,
         digest: CheckpointDigest,
-    ) -> Result<CheckpointSummary, anyhow::Error> {
+    ) -> Result<CertifiedCheckpointSummary, anyhow::Error> {
         let verified_checkpoint = self
             .get_checkpoint_store()
             .get_checkpoint_by_digest(&digest)?;
         match verified_checkpoint {
-            Some(verified_checkpoint) => Ok(verified_checkpoint.into_inner().into_data()),
+            Some(verified_checkpoint) => Ok(verified_checkpoint.into_inner()),
             None => Err(anyhow!(
                 "Verified checkpoint not found for digest: {}",
                 Base58::encode(digest)
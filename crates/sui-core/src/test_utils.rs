@@ -22,8 +22,11 @@ use sui_types::crypto::{
     NetworkKeyPair, SuiKeyPair,
 };
 use sui_types::crypto::{AuthorityKeyPair, Signer};
+use sui_types::committee::StakeUnit;
+use sui_types::error::{SuiError, SuiResult};
 use sui_types::messages::{
-    SignedTransaction, TransactionData, VerifiedTransaction, DUMMY_GAS_PRICE,
+    CertifiedTransactionEffects, SignedTransaction, TransactionData, VerifiedCertificate,
+    VerifiedCertifiedTransactionEffects, VerifiedTransaction, DUMMY_GAS_PRICE,
 };
 use sui_types::object::OBJECT_START_VERSION;
 use sui_types::utils::create_fake_transaction;
@@ -92,6 +95,60 @@ pub async fn wait_for_all_txes(digests: Vec<TransactionDigest>, state: Arc<Autho
     }
 }
 
+/// The live objects of an authority by id, see `snapshot_objects`.
+pub type ObjectSnapshot = BTreeMap<ObjectID, Object>;
+
+/// Capture the live objects of `authority`, at their latest version, so that the state reached by
+/// two authorities can be compared with `diff_snapshots`.
+pub fn snapshot_objects(authority: &AuthorityState) -> ObjectSnapshot {
+    authority
+        .database
+        .iter_live_object_set()
+        .map(|(id, version, _)| {
+            let object = authority
+                .database
+                .get_object_by_key(&id, version)
+                .unwrap()
+                .unwrap_or_else(|| panic!("live object {id} at version {version} is missing"));
+            (id, object)
+        })
+        .collect()
+}
+
+/// The objects that differ between two snapshots.
+#[derive(Debug, Default)]
+pub struct SnapshotDiff {
+    /// Objects only in the second snapshot.
+    pub added: Vec<Object>,
+    /// Objects only in the first snapshot.
+    pub removed: Vec<Object>,
+    /// Objects in both snapshots with different versions or contents, first snapshot first.
+    pub changed: Vec<(Object, Object)>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+pub fn diff_snapshots(first: &ObjectSnapshot, second: &ObjectSnapshot) -> SnapshotDiff {
+    let mut diff = SnapshotDiff::default();
+    for (id, object) in first {
+        match second.get(id) {
+            None => diff.removed.push(object.clone()),
+            Some(other) if other != object => diff.changed.push((object.clone(), other.clone())),
+            Some(_) => (),
+        }
+    }
+    diff.added = second
+        .iter()
+        .filter(|(id, _)| !first.contains_key(id))
+        .map(|(_, object)| object.clone())
+        .collect();
+    diff
+}
+
 pub fn create_fake_cert_and_effect_digest<'a>(
     signers: impl Iterator<
         Item = (
@@ -249,6 +306,58 @@ pub async fn init_local_authorities_with_genesis(
     )
 }
 
+/// Certify `transaction` and its effects with the signatures of a quorum of `authorities`, which
+/// must share a committee, e.g. those of `init_local_authorities`. Unlike an
+/// `AuthorityAggregator`, every authority is called directly, so that tests of any committee size
+/// can check each step of the aggregation. Transactions on shared objects are not supported, as
+/// there is no consensus to sequence them.
+pub async fn certify_transaction_and_effects(
+    authorities: &[Arc<AuthorityState>],
+    transaction: VerifiedTransaction,
+) -> SuiResult<(VerifiedCertificate, VerifiedCertifiedTransactionEffects)> {
+    let committee = authorities[0].clone_committee_for_testing();
+    let quorum_threshold = committee.quorum_threshold();
+
+    let mut votes = vec![];
+    let mut stake = 0;
+    for authority in authorities {
+        if stake >= quorum_threshold {
+            break;
+        }
+        let epoch_store = authority.load_epoch_store_one_call_per_task();
+        let response = authority
+            .handle_transaction(&epoch_store, transaction.clone())
+            .await?;
+        votes.push(response.status.into_signed_for_testing());
+        stake += committee.weight(&authority.name);
+    }
+    let certificate = CertifiedTransaction::new(transaction.into_message(), votes, &committee)?
+        .verify(&committee)?;
+
+    // Every authority executes the certificate, the effects are certified once a quorum of them
+    // signed the same effects.
+    let mut effects_map: BTreeMap<_, (Vec<AuthorityName>, StakeUnit)> = BTreeMap::new();
+    let mut signed_effects = BTreeMap::new();
+    for authority in authorities {
+        let effects = authority.try_execute_for_test(&certificate).await?;
+        let digest = *effects.digest();
+        let (signers, effects_stake) = effects_map.entry(digest).or_default();
+        signers.push(authority.name);
+        *effects_stake += committee.weight(&authority.name);
+        let (_, signatures) = signed_effects
+            .entry(digest)
+            .or_insert_with(|| (effects.data().clone(), vec![]));
+        signatures.push(effects.auth_sig().clone());
+        if *effects_stake >= quorum_threshold {
+            let (effects, signatures) = signed_effects.remove(&digest).unwrap();
+            let effects = CertifiedTransactionEffects::new(effects, signatures, &committee)?
+                .verify(&committee)?;
+            return Ok((certificate, effects));
+        }
+    }
+    Err(SuiError::QuorumFailedToGetEffectsQuorumWhenProcessingTransaction { effects_map })
+}
+
 pub fn make_transfer_sui_transaction(
     gas_object: ObjectRef,
     recipient: SuiAddress,
@@ -27,7 +27,7 @@ use crate::test_authority_clients::{
     HandleTransactionTestAuthorityClient, LocalAuthorityClient, LocalAuthorityClientFaultConfig,
     MockAuthorityApi,
 };
-use crate::test_utils::init_local_authorities;
+use crate::test_utils::{certify_transaction_and_effects, init_local_authorities};
 use sui_types::utils::to_sender_signed_transaction;
 use tokio::time::Instant;
 
@@ -1801,6 +1801,41 @@ async fn test_fork_panic_process_cert_4_auths() {
     let _ = process_with_cert(2, 4).await;
 }
 
+#[tokio::test]
+async fn test_certify_transaction_and_effects() {
+    for committee_size in [1, 4, 7] {
+        let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+        let (recipient, _): (_, AccountKeyPair) = get_key_pair();
+        let gas_object = Object::with_owner_for_testing(sender);
+        let (_, authorities, genesis, _) =
+            init_local_authorities(committee_size, vec![gas_object.clone()]).await;
+        let committee = genesis.committee().unwrap();
+        let gas_object = genesis.object(gas_object.id()).unwrap();
+
+        let tx = make_transfer_sui_transaction(
+            gas_object.compute_object_reference(),
+            recipient,
+            Some(1),
+            sender,
+            &sender_key,
+            None,
+        );
+        let (cert, effects) = certify_transaction_and_effects(&authorities, tx)
+            .await
+            .unwrap();
+        assert_eq!(effects.transaction_digest(), cert.digest());
+        assert!(effects.status().is_ok());
+        assert!(effects
+            .clone()
+            .into_inner()
+            .verify_signature(&committee)
+            .is_ok());
+        for authority in &authorities {
+            assert!(authority.is_tx_already_executed(cert.digest()).unwrap());
+        }
+    }
+}
+
 // Aggregator aggregate signatures from authorities and process the transaction as signed.
 // Test [fn handle_transaction_response_with_signed].
 async fn run_aggregator(
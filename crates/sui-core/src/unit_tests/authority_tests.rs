@@ -10,6 +10,7 @@ use std::task::{Context, Poll};
 use std::{convert::TryInto, env};
 
 use bcs;
+use expect_test::expect;
 use futures::{stream::FuturesUnordered, StreamExt};
 use move_binary_format::{
     file_format::{self, AddressIdentifierIndex, IdentifierIndex, ModuleHandle},
@@ -40,7 +41,9 @@ use sui_types::epoch_data::EpochData;
 use sui_types::error::UserInputError;
 use sui_types::gas_coin::GasCoin;
 use sui_types::object::Data;
-use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::programmable_transaction_builder::{
+    option_type_tag, pure_bytes_of_type, ProgrammableTransactionBuilder,
+};
 use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemState;
 use sui_types::sui_system_state::SuiSystemStateWrapper;
 use sui_types::utils::{
@@ -58,24 +61,46 @@ use sui_types::{
 use sui_types::{SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION, SUI_FRAMEWORK_OBJECT_ID};
 
 use crate::authority::move_integration_tests::build_and_publish_test_package_with_upgrade_cap;
-use crate::consensus_handler::SequencedConsensusTransaction;
+use crate::consensus_handler::{SequencedConsensusTransaction, SequencedConsensusTransactionKind};
 use crate::epoch::epoch_metrics::EpochMetrics;
 use crate::{
     authority_client::{AuthorityAPI, NetworkAuthorityClient},
     authority_server::AuthorityServer,
     checkpoints::CheckpointServiceNoop,
-    test_utils::init_state_parameters_from_rng,
+    test_utils::{diff_snapshots, init_state_parameters_from_rng, snapshot_objects},
 };
 
 use super::*;
 
 pub enum TestCallArg {
     Pure(Vec<u8>),
+    /// An object passed according to its current owner, shared objects always by mutable
+    /// reference
     Object(ObjectID),
     ObjVec(Vec<ObjectID>),
+    /// The system clock at `0x6`, which is only ever passed by immutable reference
+    Clock,
+    /// An owned or immutable object, passed by its latest reference. Panics if the object is
+    /// shared.
+    Owned(ObjectID),
+    /// A shared object, passed by mutable reference if `mutable` is set. Panics if the object is
+    /// not shared.
+    Shared { id: ObjectID, mutable: bool },
 }
 
 impl TestCallArg {
+    /// A pure argument of Move type `std::option::Option<type_>`, panics if `value` is not a
+    /// value of that type.
+    pub fn pure_option<T: Serialize>(type_: TypeTag, value: Option<T>) -> Self {
+        Self::Pure(pure_bytes_of_type(&option_type_tag(type_), &value).unwrap())
+    }
+
+    /// A pure argument of Move type `vector<type_>`, panics if `values` is not a value of that
+    /// type.
+    pub fn pure_vec<T: Serialize>(type_: TypeTag, values: Vec<T>) -> Self {
+        Self::Pure(pure_bytes_of_type(&TypeTag::Vector(Box::new(type_)), &values).unwrap())
+    }
+
     pub async fn to_call_arg(
         self,
         builder: &mut ProgrammableTransactionBuilder,
@@ -95,6 +120,33 @@ impl TestCallArg {
                 }
                 builder.make_obj_vec(refs).unwrap()
             }
+            Self::Clock => builder
+                .input(CallArg::Object(ObjectArg::SharedObject {
+                    id: SUI_CLOCK_OBJECT_ID,
+                    initial_shared_version: SUI_CLOCK_OBJECT_SHARED_VERSION,
+                    mutable: false,
+                }))
+                .unwrap(),
+            Self::Owned(object_id) => {
+                let object = state.get_object(&object_id).await.unwrap().unwrap();
+                assert!(!object.is_shared(), "object {object_id} is shared, not owned");
+                builder
+                    .obj(ObjectArg::ImmOrOwnedObject(object.compute_object_reference()))
+                    .unwrap()
+            }
+            Self::Shared { id, mutable } => {
+                let object = state.get_object(&id).await.unwrap().unwrap();
+                let Owner::Shared { initial_shared_version } = object.owner else {
+                    panic!("object {id} is not shared, its owner is {}", object.owner);
+                };
+                builder
+                    .obj(ObjectArg::SharedObject {
+                        id,
+                        initial_shared_version,
+                        mutable,
+                    })
+                    .unwrap()
+            }
         }
     }
 
@@ -1489,12 +1541,46 @@ pub async fn send_and_confirm_transaction(
     .await
 }
 
+/// Like `send_and_confirm_transaction`, but also return the transaction as signed by the
+/// authority, before its signature is aggregated into the certificate.
+pub async fn send_and_confirm_transaction_with_signed(
+    authority: &AuthorityState,
+    transaction: VerifiedTransaction,
+) -> Result<(SignedTransaction, SignedTransactionEffects), SuiError> {
+    let (signed, _, effects) = send_and_confirm_transaction_impl(
+        authority,
+        None, /* no fullnode_key_pair */
+        transaction,
+        false, /* no shared objects */
+    )
+    .await?;
+    Ok((signed, effects))
+}
+
 pub async fn send_and_confirm_transaction_(
     authority: &AuthorityState,
     fullnode: Option<&AuthorityState>,
     transaction: VerifiedTransaction,
     with_shared: bool, // transaction includes shared objects
 ) -> Result<(CertifiedTransaction, SignedTransactionEffects), SuiError> {
+    let (_, certificate, effects) =
+        send_and_confirm_transaction_impl(authority, fullnode, transaction, with_shared).await?;
+    Ok((certificate, effects))
+}
+
+async fn send_and_confirm_transaction_impl(
+    authority: &AuthorityState,
+    fullnode: Option<&AuthorityState>,
+    transaction: VerifiedTransaction,
+    with_shared: bool,
+) -> Result<
+    (
+        SignedTransaction,
+        CertifiedTransaction,
+        SignedTransactionEffects,
+    ),
+    SuiError,
+> {
     // Make the initial request
     let epoch_store = authority.load_epoch_store_one_call_per_task();
     let response = authority
@@ -1520,7 +1606,9 @@ pub async fn send_and_confirm_transaction_(
     if let Some(fullnode) = fullnode {
         fullnode.try_execute_for_test(&certificate).await?;
     }
-    Ok((certificate.into_inner(), result.into_inner()))
+    let certificate = certificate.into_inner();
+    let signed = SignedTransaction::new_from_data_and_sig(certificate.data().clone(), vote);
+    Ok((signed, certificate, result.into_inner()))
 }
 
 /// Create a `CompiledModule` that depends on `m`
@@ -1941,22 +2029,7 @@ async fn test_transaction_expiration() {
     let object_id = ObjectID::random();
     let authority_state = init_state_with_ids(vec![(sender, object_id)]).await;
 
-    let mut committee = authority_state
-        .epoch_store_for_testing()
-        .committee()
-        .to_owned();
-    committee.epoch = 1;
-    let system_state = EpochStartSystemState::new_for_testing_with_epoch(1);
-
-    authority_state
-        .reconfigure(
-            &authority_state.epoch_store_for_testing(),
-            SupportedProtocolVersions::SYSTEM_DEFAULT,
-            committee,
-            EpochStartConfiguration::new_v1(system_state, Default::default()),
-        )
-        .await
-        .unwrap();
+    advance_epoch(&authority_state).await;
 
     let object = authority_state
         .get_object(&object_id)
@@ -1992,6 +2065,58 @@ async fn test_transaction_expiration() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_transfer_transaction_expires_with_epoch() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let recipient = dbg_addr(2);
+    let object_id = ObjectID::random();
+    let gas_object_id = ObjectID::random();
+    let authority_state =
+        init_state_with_ids(vec![(sender, object_id), (sender, gas_object_id)]).await;
+    let object_ref = authority_state
+        .get_object(&object_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .compute_object_reference();
+    let gas_object_ref = authority_state
+        .get_object(&gas_object_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .compute_object_reference();
+
+    // A transaction expiring at epoch 0 is no longer accepted once epoch 1 has started
+    let epoch_store = advance_epoch(&authority_state).await;
+    assert_eq!(epoch_store.epoch(), 1);
+    let expired_transaction = init_transfer_transaction_with_expiration(
+        sender,
+        &sender_key,
+        recipient,
+        object_ref,
+        gas_object_ref,
+        Some(TransactionExpiration::Epoch(0)),
+    );
+    let result = authority_state
+        .handle_transaction(&epoch_store, expired_transaction)
+        .await;
+    assert!(matches!(result.unwrap_err(), SuiError::TransactionExpired));
+
+    // The expiration epoch itself is still accepted
+    let transaction = init_transfer_transaction_with_expiration(
+        sender,
+        &sender_key,
+        recipient,
+        object_ref,
+        gas_object_ref,
+        Some(TransactionExpiration::Epoch(1)),
+    );
+    authority_state
+        .handle_transaction(&epoch_store, transaction)
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn test_missing_package() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
@@ -2168,6 +2293,54 @@ async fn test_handle_confirmation_transaction_receiver_equal_sender() {
         .is_some());
 }
 
+#[tokio::test]
+async fn test_send_and_confirm_transaction_with_signed() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let recipient = dbg_addr(2);
+    let object_id = ObjectID::random();
+    let gas_object_id = ObjectID::random();
+    let authority_state =
+        init_state_with_ids(vec![(sender, object_id), (sender, gas_object_id)]).await;
+    let object = authority_state
+        .get_object(&object_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let gas_object = authority_state
+        .get_object(&gas_object_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let transaction = init_transfer_transaction(
+        sender,
+        &sender_key,
+        recipient,
+        object.compute_object_reference(),
+        gas_object.compute_object_reference(),
+    );
+
+    let (signed, effects) =
+        send_and_confirm_transaction_with_signed(&authority_state, transaction.clone())
+            .await
+            .unwrap();
+    let committee = authority_state.clone_committee_for_testing();
+    // both the transaction and its effects are signed by the authority alone
+    assert_eq!(signed.digest(), transaction.digest());
+    assert_eq!(signed.auth_sig().authority, authority_state.name);
+    signed.verify_signature(&committee).unwrap();
+    assert_eq!(effects.auth_sig().authority, authority_state.name);
+    effects.verify_signature(&committee).unwrap();
+    assert_eq!(effects.transaction_digest(), transaction.digest());
+    assert_eq!(
+        effects.digest(),
+        &authority_state
+            .get_executed_effects(*transaction.digest())
+            .await
+            .unwrap()
+            .digest()
+    );
+}
+
 #[tokio::test]
 async fn test_handle_confirmation_transaction_ok() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
@@ -2547,6 +2720,77 @@ async fn test_move_call_mutable_object_not_mutated() {
     );
 }
 
+#[tokio::test]
+async fn test_move_call_with_owned_and_shared_args() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let (authority_state, pkg_ref) =
+        init_state_with_ids_and_object_basics(vec![(sender, gas_object_id)]).await;
+
+    let effects = call_move_with_args(
+        &authority_state,
+        None,
+        &gas_object_id,
+        &sender,
+        &sender_key,
+        &pkg_ref.0,
+        "object_basics",
+        "share",
+        vec![],
+        vec![],
+    )
+    .await
+    .unwrap();
+    assert!(effects.status().is_ok());
+    let shared_object_id = effects.created()[0].0 .0;
+
+    let effects = create_move_object(
+        &pkg_ref.0,
+        &authority_state,
+        &gas_object_id,
+        &sender,
+        &sender_key,
+    )
+    .await
+    .unwrap();
+    assert!(effects.status().is_ok());
+    let owned_object_id = effects.created()[0].0 .0;
+
+    // `update` takes the shared object by mutable reference and the owned one by reference.
+    let effects = call_move_with_args(
+        &authority_state,
+        None,
+        &gas_object_id,
+        &sender,
+        &sender_key,
+        &pkg_ref.0,
+        "object_basics",
+        "update",
+        vec![],
+        vec![
+            TestCallArg::Shared {
+                id: shared_object_id,
+                mutable: true,
+            },
+            TestCallArg::Owned(owned_object_id),
+        ],
+    )
+    .await
+    .unwrap();
+    assert!(effects.status().is_ok());
+    assert_eq!(effects.shared_objects().len(), 1);
+    assert_eq!(effects.shared_objects()[0].0, shared_object_id);
+    let mutated: HashSet<_> = effects.mutated().iter().map(|((id, _, _), _)| *id).collect();
+    assert!(mutated.contains(&shared_object_id));
+    assert!(mutated.contains(&owned_object_id));
+    assert!(authority_state
+        .get_object(&shared_object_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .is_shared());
+}
+
 // skipped because it violates SUI conservation checks
 #[tokio::test]
 async fn test_move_call_insufficient_gas() {
@@ -2905,6 +3149,7 @@ async fn test_authority_persist() {
             AuthorityStorePruningConfig::default(),
             &[], // no genesis objects
             &DBCheckpointConfig::default(),
+            None,
         )
         .await
     }
@@ -3111,6 +3356,127 @@ async fn test_valid_immutable_clock_parameter() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_clock_argument_reads_advanced_timestamp() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let (authority_state, package_object_ref) =
+        init_state_with_ids_and_object_basics(vec![(sender, gas_object_id)]).await;
+
+    for timestamp_ms in [1_000, 2_500] {
+        let prologue_effects = advance_clock(&authority_state, timestamp_ms).await;
+        assert!(prologue_effects.status().is_ok());
+
+        let effects = call_move_(
+            &authority_state,
+            None,
+            &gas_object_id,
+            &sender,
+            &sender_key,
+            &package_object_ref.0,
+            "object_basics",
+            "create_at_timestamp",
+            vec![],
+            vec![TestCallArg::Clock],
+            /* with_shared */ true,
+        )
+        .await
+        .unwrap();
+        assert!(effects.status().is_ok());
+
+        let created_object_id = effects.created()[0].0 .0;
+        let created = authority_state
+            .get_object(&created_object_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let (_, value): (ObjectID, u64) =
+            bcs::from_bytes(created.data.try_as_move().unwrap().contents()).unwrap();
+        assert_eq!(value, timestamp_ms);
+    }
+}
+
+#[test]
+fn test_effects_json_normalizes_ids() {
+    let sender = SuiAddress::random_for_testing_only();
+    let gas = ObjectID::random();
+    let shared = ObjectID::random();
+    let deleted = ObjectID::random();
+    let parent = ObjectID::random();
+    let child = ObjectID::random();
+    let v = SequenceNumber::from_u64;
+    let object_ref = |id, version| (id, v(version), ObjectDigest::random());
+
+    let effects = TransactionEffects::new_from_execution(
+        ProtocolVersion::MAX,
+        ExecutionStatus::Success,
+        0,
+        GasCostSummary::new(100, 50, 10),
+        vec![(gas, v(1)), (shared, v(1)), (deleted, v(1))],
+        vec![object_ref(shared, 1)],
+        TransactionDigest::random(),
+        vec![
+            (object_ref(parent, 2), Owner::AddressOwner(sender)),
+            (object_ref(child, 2), Owner::ObjectOwner(parent.into())),
+        ],
+        vec![
+            (object_ref(gas, 2), Owner::AddressOwner(sender)),
+            (
+                object_ref(shared, 2),
+                Owner::Shared {
+                    initial_shared_version: v(1),
+                },
+            ),
+        ],
+        vec![],
+        vec![(deleted, v(2), ObjectDigest::OBJECT_DIGEST_DELETED)],
+        vec![],
+        vec![],
+        (object_ref(gas, 2), Owner::AddressOwner(sender)),
+        None,
+        vec![TransactionDigest::random()],
+    );
+
+    let expected = expect![[r#"
+        {
+          "status": "Success",
+          "executed_epoch": 0,
+          "gas_used": {
+            "computationCost": 100,
+            "storageCost": 50,
+            "storageRebate": 10
+          },
+          "modified_at_versions": [
+            "gas@1",
+            "object_0@1",
+            "object_1@1"
+          ],
+          "shared_objects": [
+            "object_0@1"
+          ],
+          "created": {
+            "object_2@2": "AddressOwner(address_0)",
+            "object_3@2": "ObjectOwner(object_2)"
+          },
+          "mutated": {
+            "gas@2": "AddressOwner(address_0)",
+            "object_0@2": "Shared(1)"
+          },
+          "unwrapped": {},
+          "deleted": [
+            "object_1@2"
+          ],
+          "unwrapped_then_deleted": [],
+          "wrapped": [],
+          "gas_object": {
+            "gas@2": "AddressOwner(address_0)"
+          },
+          "has_events": false,
+          "dependencies": 1
+        }"#]];
+    expected.assert_eq(&EffectsJson::new().with_name(gas, "gas").render(&effects));
+}
+
 #[tokio::test]
 async fn test_genesis_sui_system_state_object() {
     // This test verifies that we can read the genesis SuiSystemState object.
@@ -4061,6 +4427,52 @@ async fn test_iter_live_object_set() {
     );
 }
 
+#[tokio::test]
+async fn test_snapshot_objects_after_same_transactions() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let (validator, fullnode, object_basics) =
+        init_state_with_ids_and_object_basics_with_fullnode(vec![(sender, gas_object_id)]).await;
+
+    let before = snapshot_objects(&validator);
+    let diff = diff_snapshots(&before, &snapshot_objects(&fullnode));
+    assert!(diff.is_empty(), "{:?}", diff);
+
+    let effects = call_move_(
+        &validator,
+        Some(&fullnode),
+        &gas_object_id,
+        &sender,
+        &sender_key,
+        &object_basics.0,
+        "object_basics",
+        "create",
+        vec![],
+        vec![
+            TestCallArg::Pure(bcs::to_bytes(&(16_u64)).unwrap()),
+            TestCallArg::Pure(bcs::to_bytes(&sender).unwrap()),
+        ],
+        false,
+    )
+    .await
+    .unwrap();
+
+    // Both authorities reach the same state.
+    let after = snapshot_objects(&validator);
+    let diff = diff_snapshots(&after, &snapshot_objects(&fullnode));
+    assert!(diff.is_empty(), "{:?}", diff);
+
+    // The transaction created an object and mutated the gas coin.
+    let diff = diff_snapshots(&before, &after);
+    let added: Vec<_> = diff.added.iter().map(|o| o.id()).collect();
+    assert_eq!(added, vec![effects.created()[0].0 .0]);
+    assert!(diff.removed.is_empty());
+    let changed: Vec<_> = diff.changed.iter().map(|(o, _)| o.id()).collect();
+    assert_eq!(changed, vec![gas_object_id]);
+    let (gas_before, gas_after) = &diff.changed[0];
+    assert!(gas_after.version() > gas_before.version());
+}
+
 // helpers
 
 #[cfg(test)]
@@ -4104,6 +4516,40 @@ pub async fn init_state() -> Arc<AuthorityState> {
     init_state_with_committee(&genesis, &keypair).await
 }
 
+/// Like `init_state_validator_with_fullnode`, for authorities aborting the dry runs and
+/// dev-inspect calls that run for longer than `execution_timeout`.
+#[cfg(test)]
+pub async fn init_state_validator_with_fullnode_and_execution_timeout(
+    execution_timeout: Duration,
+) -> (Arc<AuthorityState>, Arc<AuthorityState>) {
+    use sui_types::crypto::get_authority_key_pair;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let network_config = sui_config::builder::ConfigBuilder::new(&dir).build();
+    let genesis = network_config.genesis;
+    let keypair = network_config.validator_configs[0]
+        .protocol_key_pair()
+        .copy();
+
+    let validator = AuthorityState::new_for_testing_with_execution_timeout(
+        genesis.committee().unwrap(),
+        &keypair,
+        None,
+        &genesis,
+        Some(execution_timeout),
+    )
+    .await;
+    let fullnode = AuthorityState::new_for_testing_with_execution_timeout(
+        genesis.committee().unwrap(),
+        &get_authority_key_pair().1,
+        None,
+        &genesis,
+        Some(execution_timeout),
+    )
+    .await;
+    (validator, fullnode)
+}
+
 #[cfg(test)]
 pub async fn init_state_validator_with_fullnode() -> (Arc<AuthorityState>, Arc<AuthorityState>) {
     use sui_types::crypto::get_authority_key_pair;
@@ -4142,6 +4588,53 @@ pub async fn init_state_with_ids<I: IntoIterator<Item = (SuiAddress, ObjectID)>>
     state
 }
 
+/// Seed used by `TestObjectIds::default`.
+#[cfg(test)]
+pub const TEST_OBJECT_ID_SEED: u64 = 0;
+
+/// Hands out object IDs derived from a fixed seed instead of `ObjectID::random()`, so that a
+/// failing test sees the same IDs (and prints the same output) every time it is rerun.
+#[cfg(test)]
+pub struct TestObjectIds {
+    rng: StdRng,
+}
+
+#[cfg(test)]
+impl TestObjectIds {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn next_id(&mut self) -> ObjectID {
+        ObjectID::random_from_rng(&mut self.rng)
+    }
+}
+
+#[cfg(test)]
+impl Default for TestObjectIds {
+    fn default() -> Self {
+        Self::new(TEST_OBJECT_ID_SEED)
+    }
+}
+
+/// Like `init_state_with_ids`, but the object given to each owner gets an ID derived from `seed`.
+/// Returns the IDs in the same order as `owners`.
+#[cfg(test)]
+pub async fn init_state_with_seeded_ids<I: IntoIterator<Item = SuiAddress>>(
+    seed: u64,
+    owners: I,
+) -> (Arc<AuthorityState>, Vec<ObjectID>) {
+    let mut ids = TestObjectIds::new(seed);
+    let objects: Vec<_> = owners
+        .into_iter()
+        .map(|owner| (owner, ids.next_id()))
+        .collect();
+    let object_ids = objects.iter().map(|(_, id)| *id).collect();
+    (init_state_with_ids(objects).await, object_ids)
+}
+
 #[cfg(test)]
 pub async fn init_state_with_objects_and_object_basics<I: IntoIterator<Item = Object>>(
     objects: I,
@@ -4266,6 +4759,26 @@ pub async fn init_state_with_object_id(
     init_state_with_ids(std::iter::once((address, object))).await
 }
 
+/// Reconfigures the authority into the next epoch, keeping its committee, and returns the
+/// epoch store of the new epoch.
+#[cfg(test)]
+pub async fn advance_epoch(authority_state: &AuthorityState) -> Arc<AuthorityPerEpochStore> {
+    let epoch_store = authority_state.epoch_store_for_testing();
+    let mut committee = epoch_store.committee().to_owned();
+    committee.epoch = epoch_store.epoch() + 1;
+    let system_state = EpochStartSystemState::new_for_testing_with_epoch(committee.epoch);
+
+    authority_state
+        .reconfigure(
+            &epoch_store,
+            SupportedProtocolVersions::SYSTEM_DEFAULT,
+            committee,
+            EpochStartConfiguration::new_v1(system_state, Default::default()),
+        )
+        .await
+        .unwrap()
+}
+
 #[cfg(test)]
 pub fn init_transfer_transaction(
     sender: SuiAddress,
@@ -4274,13 +4787,36 @@ pub fn init_transfer_transaction(
     object_ref: ObjectRef,
     gas_object_ref: ObjectRef,
 ) -> VerifiedTransaction {
-    let data = TransactionData::new_transfer_with_dummy_gas_price(
+    init_transfer_transaction_with_expiration(
+        sender,
+        secret,
+        recipient,
+        object_ref,
+        gas_object_ref,
+        None,
+    )
+}
+
+/// Like `init_transfer_transaction`, but the transaction expires after the given epoch, if any.
+#[cfg(test)]
+pub fn init_transfer_transaction_with_expiration(
+    sender: SuiAddress,
+    secret: &AccountKeyPair,
+    recipient: SuiAddress,
+    object_ref: ObjectRef,
+    gas_object_ref: ObjectRef,
+    expiration: Option<TransactionExpiration>,
+) -> VerifiedTransaction {
+    let mut data = TransactionData::new_transfer_with_dummy_gas_price(
         recipient,
         object_ref,
         sender,
         gas_object_ref,
         10000,
     );
+    if let Some(expiration) = expiration {
+        *data.expiration_mut() = expiration;
+    }
     to_sender_signed_transaction(data, secret)
 }
 
@@ -4379,6 +4915,156 @@ pub(crate) async fn send_consensus_no_execution(
     }
 }
 
+/// Set the system clock to `timestamp_ms` with a consensus commit prologue, sequenced and executed
+/// the way consensus would, so that the following calls taking `TestCallArg::Clock` observe it.
+#[cfg(test)]
+pub(crate) async fn advance_clock(
+    authority: &AuthorityState,
+    timestamp_ms: u64,
+) -> TransactionEffects {
+    let epoch_store = authority.epoch_store_for_testing();
+    // The clock version is used as the round, so that every prologue has a distinct digest.
+    let clock = authority
+        .get_object(&SUI_CLOCK_OBJECT_ID)
+        .await
+        .unwrap()
+        .unwrap();
+    let prologue = VerifiedExecutableTransaction::new_system(
+        VerifiedTransaction::new_consensus_commit_prologue(
+            epoch_store.epoch(),
+            clock.version().value(),
+            timestamp_ms,
+        ),
+        epoch_store.epoch(),
+    );
+    let transaction = SequencedConsensusTransaction {
+        transaction: SequencedConsensusTransactionKind::System(prologue.clone()),
+        certificate: Default::default(),
+        consensus_index: Default::default(),
+    };
+
+    let transaction = epoch_store
+        .verify_consensus_transaction(transaction, &authority.metrics.skipped_consensus_txns)
+        .unwrap();
+    epoch_store
+        .process_consensus_transaction(
+            transaction,
+            &Arc::new(CheckpointServiceNoop {}),
+            authority.db(),
+        )
+        .await
+        .unwrap();
+    authority
+        .try_execute_immediately(&prologue, &epoch_store)
+        .await
+        .unwrap()
+}
+
+/// Renders `TransactionEffects` as pretty-printed JSON that is stable across runs, for golden
+/// comparisons with `expect_test`. Object IDs and addresses are replaced by `object_<n>` and
+/// `address_<n>` in order of first appearance (or by a name registered with `with_name`), object
+/// refs are written as `<id>@<version>`, and digests are reduced to whether they are present,
+/// since they change with every random ID.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct EffectsJson {
+    names: HashMap<SuiAddress, String>,
+    objects: usize,
+    addresses: usize,
+}
+
+#[cfg(test)]
+impl EffectsJson {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Print `id` as `name` rather than an index, e.g. to tell the gas coin or a package apart.
+    pub fn with_name(mut self, id: impl Into<SuiAddress>, name: &str) -> Self {
+        self.names.insert(id.into(), name.to_string());
+        self
+    }
+
+    pub fn render(mut self, effects: &TransactionEffects) -> String {
+        let json = json!({
+            "status": effects.status(),
+            "executed_epoch": effects.executed_epoch(),
+            "gas_used": effects.gas_cost_summary(),
+            "modified_at_versions": effects
+                .modified_at_versions()
+                .iter()
+                .map(|(id, version)| format!("{}@{}", self.object(id), version.value()))
+                .collect::<Vec<_>>(),
+            "shared_objects": self.refs(effects.shared_objects()),
+            "created": self.owned_refs(effects.created()),
+            "mutated": self.owned_refs(effects.mutated()),
+            "unwrapped": self.owned_refs(effects.unwrapped()),
+            "deleted": self.refs(effects.deleted()),
+            "unwrapped_then_deleted": self.refs(effects.unwrapped_then_deleted()),
+            "wrapped": self.refs(effects.wrapped()),
+            "gas_object": self.owned_refs(std::slice::from_ref(effects.gas_object())),
+            "has_events": effects.events_digest().is_some(),
+            "dependencies": effects.dependencies().len(),
+        });
+        serde_json::to_string_pretty(&json).unwrap()
+    }
+
+    fn object(&mut self, id: &ObjectID) -> String {
+        let count = &mut self.objects;
+        self.names
+            .entry((*id).into())
+            .or_insert_with(|| {
+                *count += 1;
+                format!("object_{}", *count - 1)
+            })
+            .clone()
+    }
+
+    fn address(&mut self, address: &SuiAddress) -> String {
+        let count = &mut self.addresses;
+        self.names
+            .entry(*address)
+            .or_insert_with(|| {
+                *count += 1;
+                format!("address_{}", *count - 1)
+            })
+            .clone()
+    }
+
+    fn object_ref(&mut self, (id, version, _): &ObjectRef) -> String {
+        format!("{}@{}", self.object(id), version.value())
+    }
+
+    fn owner(&mut self, owner: &Owner) -> String {
+        match owner {
+            Owner::AddressOwner(address) => format!("AddressOwner({})", self.address(address)),
+            Owner::ObjectOwner(parent) => {
+                format!("ObjectOwner({})", self.object(&ObjectID::from(*parent)))
+            }
+            Owner::Shared {
+                initial_shared_version,
+            } => format!("Shared({})", initial_shared_version.value()),
+            Owner::Immutable => "Immutable".to_string(),
+        }
+    }
+
+    fn refs(&mut self, refs: &[ObjectRef]) -> Vec<String> {
+        refs.iter().map(|r| self.object_ref(r)).collect()
+    }
+
+    /// Owned refs are rendered as a map from the ref to its owner, one line per object.
+    fn owned_refs(&mut self, refs: &[(ObjectRef, Owner)]) -> serde_json::Value {
+        let mut map = serde_json::Map::new();
+        for (object_ref, owner) in refs {
+            let key = self.object_ref(object_ref);
+            map.insert(key, self.owner(owner).into());
+        }
+        map.into()
+    }
+}
+
+/// Call `module::function` of `package` with the arguments `test_args` and return the effects
+/// of the transaction, whose digest is `effects.transaction_digest()`.
 pub async fn call_move(
     authority: &AuthorityState,
     gas_object_id: &ObjectID,
@@ -4422,17 +5108,16 @@ pub async fn call_move_(
     let gas_object = authority.get_object(gas_object_id).await.unwrap();
     let gas_object_ref = gas_object.unwrap().compute_object_reference();
     let mut builder = ProgrammableTransactionBuilder::new();
-    let mut args = vec![];
-    for arg in test_args.into_iter() {
-        args.push(arg.to_call_arg(&mut builder, authority).await);
-    }
-    builder.command(Command::move_call(
-        *package,
-        Identifier::new(module).unwrap(),
-        Identifier::new(function).unwrap(),
+    add_move_call(
+        &mut builder,
+        authority,
+        package,
+        module,
+        function,
         type_args,
-        args,
-    ));
+        test_args,
+    )
+    .await;
     let data = TransactionData::new_programmable_with_dummy_gas_price(
         *sender,
         vec![gas_object_ref],
@@ -4448,6 +5133,104 @@ pub async fn call_move_(
     Ok(signed_effects.into_data())
 }
 
+/// Like `call_move`, also returning the events emitted by the transaction, in the order they
+/// were emitted. See `assert_event_emitted` to check them.
+pub async fn call_move_with_events(
+    authority: &AuthorityState,
+    gas_object_id: &ObjectID,
+    sender: &SuiAddress,
+    sender_key: &AccountKeyPair,
+    package: &ObjectID,
+    module: &'_ str,
+    function: &'_ str,
+    type_args: Vec<TypeTag>,
+    test_args: Vec<TestCallArg>,
+) -> SuiResult<(TransactionEffects, Vec<Event>)> {
+    let effects = call_move(
+        authority,
+        gas_object_id,
+        sender,
+        sender_key,
+        package,
+        module,
+        function,
+        type_args,
+        test_args,
+    )
+    .await?;
+    let events = transaction_events(authority, &effects)?;
+    Ok((effects, events))
+}
+
+/// Like `call_move_`, but whether the transaction goes through consensus follows from its
+/// inputs: it does if any argument resolves to a shared object. Declare each object argument as
+/// `TestCallArg::Owned` or `TestCallArg::Shared` to mix owned and shared objects in one call.
+pub async fn call_move_with_args(
+    authority: &AuthorityState,
+    fullnode: Option<&AuthorityState>,
+    gas_object_id: &ObjectID,
+    sender: &SuiAddress,
+    sender_key: &AccountKeyPair,
+    package: &ObjectID,
+    module: &'_ str,
+    function: &'_ str,
+    type_args: Vec<TypeTag>,
+    test_args: Vec<TestCallArg>,
+) -> SuiResult<TransactionEffects> {
+    let mut builder = ProgrammableTransactionBuilder::new();
+    add_move_call(
+        &mut builder,
+        authority,
+        package,
+        module,
+        function,
+        type_args,
+        test_args,
+    )
+    .await;
+    let pt = builder.finish();
+    let with_shared = pt
+        .inputs
+        .iter()
+        .any(|input| matches!(input, CallArg::Object(ObjectArg::SharedObject { .. })));
+    execute_programmable_transaction_(
+        authority,
+        fullnode,
+        gas_object_id,
+        sender,
+        sender_key,
+        pt,
+        with_shared,
+    )
+    .await
+}
+
+/// Add a call to `module::function` of `package` to `builder`, resolving each of `test_args`
+/// against the objects of `state` by the kind it declares.
+pub async fn add_move_call(
+    builder: &mut ProgrammableTransactionBuilder,
+    state: &AuthorityState,
+    package: &ObjectID,
+    module: &'_ str,
+    function: &'_ str,
+    type_args: Vec<TypeTag>,
+    test_args: Vec<TestCallArg>,
+) -> Argument {
+    let mut args = vec![];
+    for arg in test_args.into_iter() {
+        args.push(arg.to_call_arg(builder, state).await);
+    }
+    builder.command(Command::move_call(
+        *package,
+        Identifier::new(module).unwrap(),
+        Identifier::new(function).unwrap(),
+        type_args,
+        args,
+    ))
+}
+
+/// Execute `pt` and return the effects of the transaction, whose digest is
+/// `effects.transaction_digest()`.
 pub async fn execute_programmable_transaction(
     authority: &AuthorityState,
     gas_object_id: &ObjectID,
@@ -4467,6 +5250,48 @@ pub async fn execute_programmable_transaction(
     .await
 }
 
+/// Like `execute_programmable_transaction`, also returning the events emitted by the
+/// transaction, in the order they were emitted. See `assert_event_emitted` to check them.
+pub async fn execute_programmable_transaction_with_events(
+    authority: &AuthorityState,
+    gas_object_id: &ObjectID,
+    sender: &SuiAddress,
+    sender_key: &AccountKeyPair,
+    pt: ProgrammableTransaction,
+) -> SuiResult<(TransactionEffects, Vec<Event>)> {
+    let effects =
+        execute_programmable_transaction(authority, gas_object_id, sender, sender_key, pt).await?;
+    let events = transaction_events(authority, &effects)?;
+    Ok((effects, events))
+}
+
+/// The events emitted by the transaction of `effects`, which `authority` executed.
+pub fn transaction_events(
+    authority: &AuthorityState,
+    effects: &TransactionEffects,
+) -> SuiResult<Vec<Event>> {
+    match effects.events_digest() {
+        Some(digest) => Ok(authority.get_transaction_events(digest)?.data),
+        None => Ok(vec![]),
+    }
+}
+
+/// Assert that one of `events` is of type `type_` and holds `expected_fields`, a value whose BCS
+/// encoding is that of the Move event, e.g. a tuple of its fields in declaration order.
+pub fn assert_event_emitted<T: Serialize + std::fmt::Debug>(
+    events: &[Event],
+    type_: &StructTag,
+    expected_fields: &T,
+) {
+    let contents = bcs::to_bytes(expected_fields).unwrap();
+    assert!(
+        events
+            .iter()
+            .any(|event| &event.type_ == type_ && event.contents == contents),
+        "no event of type {type_} with fields {expected_fields:?} in {events:?}"
+    );
+}
+
 pub async fn execute_programmable_transaction_(
     authority: &AuthorityState,
     fullnode: Option<&AuthorityState>,
@@ -4669,6 +5494,47 @@ pub async fn call_dev_inspect(
         .await
 }
 
+/// Dev inspect `pt` and return the element type and length of the vector made by its command
+/// `command`, a `MakeMoveVec`, ex to check the type inferred for it when the tag is omitted.
+pub async fn dev_inspect_make_move_vec(
+    authority: &AuthorityState,
+    sender: &SuiAddress,
+    pt: ProgrammableTransaction,
+    command: usize,
+) -> Result<(TypeTag, usize), anyhow::Error> {
+    assert!(
+        matches!(pt.commands[command], Command::MakeMoveVec(..)),
+        "command {command} is not a MakeMoveVec: {:?}",
+        pt.commands[command]
+    );
+    let kind = TransactionKind::programmable(pt);
+    let DevInspectResults { results, error, .. } = authority
+        .dev_inspect_transaction(*sender, kind, Some(1))
+        .await?;
+    let Some(results) = results else {
+        anyhow::bail!("dev inspect failed: {error:?}");
+    };
+    let SuiExecutionResult {
+        mut return_values,
+        ..
+    } = results.into_iter().nth(command).unwrap();
+    assert_eq!(return_values.len(), 1);
+    let (bytes, type_) = return_values.pop().unwrap();
+    let type_: TypeTag = type_.try_into()?;
+    let TypeTag::Vector(element_type) = type_ else {
+        anyhow::bail!("MakeMoveVec did not make a vector");
+    };
+    // The BCS of a vector starts with its length, as a ULEB128
+    let mut len = 0;
+    for (i, byte) in bytes.iter().enumerate() {
+        len |= ((byte & 0x7f) as usize) << (7 * i);
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    Ok((*element_type, len))
+}
+
 #[cfg(test)]
 async fn make_test_transaction(
     sender: &SuiAddress,
@@ -13,7 +13,8 @@ use authority_tests::send_and_confirm_transaction;
 use move_core_types::{account_address::AccountAddress, ident_str};
 use sui_types::{
     crypto::{get_key_pair, AccountKeyPair},
-    object::Owner,
+    gas_coin::GasCoin,
+    object::{Owner, GAS_VALUE_FOR_TESTING},
 };
 
 #[tokio::test]
@@ -92,6 +93,95 @@ async fn test_batch_transaction_ok() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_batch_transfer_objects() -> anyhow::Result<()> {
+    // Split two new coins off the gas coin and transfer both with a single command.
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let (recipient, _): (_, AccountKeyPair) = get_key_pair();
+    let gas_id = ObjectID::random();
+    let (authority_state, _) = init_state_with_ids_and_object_basics(vec![(sender, gas_id)]).await;
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let coins = (0..2)
+        .map(|_| {
+            let amount = builder.pure(100u64).unwrap();
+            builder.command(Command::SplitCoin(Argument::GasCoin, amount))
+        })
+        .collect();
+    builder.transfer_objects(coins, recipient);
+    let data = TransactionData::new_programmable_with_dummy_gas_price(
+        sender,
+        vec![authority_state
+            .get_object(&gas_id)
+            .await?
+            .unwrap()
+            .compute_object_reference()],
+        builder.finish(),
+        1000000,
+    );
+
+    let tx = to_sender_signed_transaction(data, &sender_key);
+    let response = send_and_confirm_transaction(&authority_state, tx).await?;
+    let effects = response.1.into_data();
+    assert!(effects.status().is_ok());
+    assert_eq!(
+        effects
+            .created()
+            .iter()
+            .filter(|(_, owner)| owner == &Owner::AddressOwner(recipient))
+            .count(),
+        2
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch_merge_coins() -> anyhow::Result<()> {
+    // Merge two coins into a third with a single command, paying gas with a fourth.
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let ids = (0..4).map(|_| ObjectID::random()).collect::<Vec<_>>();
+    let (authority_state, _) =
+        init_state_with_ids_and_object_basics(ids.iter().map(|id| (sender, *id))).await;
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let mut coins = vec![];
+    for id in &ids[..3] {
+        let coin = authority_state.get_object(id).await?.unwrap();
+        coins.push(builder.obj(ObjectArg::ImmOrOwnedObject(coin.compute_object_reference()))?);
+    }
+    builder.merge_coins(coins[0], coins[1..].to_vec());
+    let data = TransactionData::new_programmable_with_dummy_gas_price(
+        sender,
+        vec![authority_state
+            .get_object(&ids[3])
+            .await?
+            .unwrap()
+            .compute_object_reference()],
+        builder.finish(),
+        1000000,
+    );
+
+    let tx = to_sender_signed_transaction(data, &sender_key);
+    let response = send_and_confirm_transaction(&authority_state, tx).await?;
+    let effects = response.1.into_data();
+    assert!(effects.status().is_ok());
+    let mut deleted: Vec<_> = effects.deleted().iter().map(|(id, _, _)| *id).collect();
+    deleted.sort();
+    let mut merged = ids[1..3].to_vec();
+    merged.sort();
+    assert_eq!(deleted, merged);
+    for id in &merged {
+        assert!(authority_state.get_object(id).await?.is_none());
+    }
+    // The primary coin holds the summed balance.
+    let primary = authority_state.get_object(&ids[0]).await?.unwrap();
+    assert_eq!(
+        GasCoin::try_from(&primary)?.value(),
+        3 * GAS_VALUE_FOR_TESTING
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_batch_transaction_last_one_fail() -> anyhow::Result<()> {
     // This test tests the case where the last transaction in a batch transaction would fail to execute.
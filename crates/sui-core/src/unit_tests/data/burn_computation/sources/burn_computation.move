@@ -0,0 +1,13 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module burn_computation::burn_computation {
+    /// Runs until the transaction runs out of gas, or out of time
+    public entry fun spin() {
+        let i = 0;
+        loop {
+            i = i + 1;
+            if (i == 1_000_000) i = 0;
+        }
+    }
+}
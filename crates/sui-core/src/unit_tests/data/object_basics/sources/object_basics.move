@@ -3,7 +3,7 @@
 
 /// Test CTURD object basics (create, transfer, update, read, delete)
 module examples::object_basics {
-    use sui::clock::Clock;
+    use sui::clock::{Self, Clock};
     use sui::dynamic_object_field as ofield;
     use sui::event;
     use sui::object::{Self, UID, ID};
@@ -142,4 +142,11 @@ module examples::object_basics {
     public entry fun generic_test<T>() {}
 
     public entry fun use_clock(_clock: &Clock) {}
+
+    public entry fun create_at_timestamp(clock: &Clock, ctx: &mut TxContext) {
+        transfer::transfer(
+            Object { id: object::new(ctx), value: clock::timestamp_ms(clock) },
+            tx_context::sender(ctx)
+        )
+    }
 }
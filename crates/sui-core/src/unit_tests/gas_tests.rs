@@ -4,7 +4,8 @@
 use super::*;
 
 use super::authority_tests::{
-    create_genesis_module_packages, init_state_with_ids, send_and_confirm_transaction,
+    call_move, call_move_with_gas_coins, create_genesis_module_packages, init_state_with_ids,
+    send_and_confirm_transaction, TestCallArg,
 };
 use super::move_integration_tests::build_and_try_publish_test_package;
 use crate::authority::authority_tests::{init_state, init_state_with_ids_and_object_basics};
@@ -106,6 +107,7 @@ async fn test_native_transfer_sufficient_gas() -> SuiResult {
         GasCoin::try_from(&gas_object)?.value(),
         *MAX_GAS_BUDGET - gas_cost.gas_used()
     );
+    assert_gas_charged(&result.authority_state, *MAX_GAS_BUDGET, &effects)?;
 
     // Mimic the process of gas charging, to check that we are charging
     // exactly what we should be charging.
@@ -252,6 +254,7 @@ async fn test_native_transfer_insufficient_gas_execution() {
         .unwrap();
     let gas_coin = GasCoin::try_from(&gas_object).unwrap();
     assert_eq!(gas_coin.value(), 0);
+    assert_gas_charged(&result.authority_state, budget, &effects).unwrap();
     // After a failed transfer, the version should have been incremented,
     // but the owner of the object should remain the same, unchanged.
     let ((_, version, _), owner) = effects.mutated_excluding_gas().first().unwrap();
@@ -351,6 +354,7 @@ async fn test_publish_gas() -> anyhow::Result<()> {
     .await;
     let effects = response.1.into_data();
     let gas_cost = effects.gas_cost_summary().clone();
+    assert_gas_charged(&authority_state, expected_gas_balance, &effects)?;
     let err = effects.into_status().unwrap_err().0;
 
     assert_eq!(err, ExecutionFailureStatus::InsufficientGas);
@@ -431,6 +435,7 @@ async fn test_move_call_gas() -> SuiResult {
         GasCoin::try_from(&gas_object)?.value(),
         expected_gas_balance,
     );
+    assert_gas_charged(&authority_state, GAS_VALUE_FOR_TESTING, &effects)?;
 
     // Mimic the gas charge behavior and cross check the result with above. Do not include
     // computation cost calculation as it would require hard-coding a constant representing VM
@@ -487,7 +492,9 @@ async fn test_move_call_gas() -> SuiResult {
     assert!(gas_cost.storage_cost > 0 && gas_cost.storage_cost < gas_cost.storage_rebate);
     // Check that we have storage rebate that's the same as previous cost.
     assert_eq!(gas_cost.storage_rebate, prev_storage_cost);
+    let gas_balance = assert_gas_charged(&authority_state, expected_gas_balance, &effects)?;
     let expected_gas_balance = expected_gas_balance - gas_cost.gas_used() + gas_cost.storage_rebate;
+    assert_eq!(gas_balance, expected_gas_balance);
 
     // Create a transaction with gas budget that should run out during Move VM execution.
     let gas_object = authority_state.get_object(&gas_object_id).await?.unwrap();
@@ -508,6 +515,7 @@ async fn test_move_call_gas() -> SuiResult {
     let response = send_and_confirm_transaction(&authority_state, transaction).await?;
     let effects = response.1.into_data();
     let gas_cost = effects.gas_cost_summary().clone();
+    assert_gas_charged(&authority_state, expected_gas_balance, &effects)?;
     let err = effects.into_status().unwrap_err().0;
     // We will run out of gas during VM execution.
     assert!(matches!(err, ExecutionFailureStatus::InsufficientGas));
@@ -520,6 +528,163 @@ async fn test_move_call_gas() -> SuiResult {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_move_call_out_of_gas_creates_nothing() -> SuiResult {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let (authority_state, package_object_ref) =
+        init_state_with_ids_and_object_basics(vec![(sender, gas_object_id)]).await;
+
+    let effects = call_move_out_of_gas(
+        &authority_state,
+        &gas_object_id,
+        &sender,
+        &sender_key,
+        &package_object_ref,
+        "create",
+        vec![
+            TestCallArg::Pure(bcs::to_bytes(&16u64).unwrap()),
+            TestCallArg::Pure(bcs::to_bytes(&sender).unwrap()),
+        ],
+    )
+    .await?;
+    // Only the gas coin is written.
+    assert!(effects.mutated_excluding_gas().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_move_call_out_of_gas_rolls_back_mutation() -> SuiResult {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let (authority_state, package_object_ref) =
+        init_state_with_ids_and_object_basics(vec![(sender, gas_object_id)]).await;
+
+    let effects = call_move(
+        &authority_state,
+        &gas_object_id,
+        &sender,
+        &sender_key,
+        &package_object_ref.0,
+        "object_basics",
+        "create",
+        vec![],
+        vec![
+            TestCallArg::Pure(bcs::to_bytes(&16u64).unwrap()),
+            TestCallArg::Pure(bcs::to_bytes(&sender).unwrap()),
+        ],
+    )
+    .await?;
+    assert!(effects.status().is_ok());
+    let object_id = effects.created()[0].0 .0;
+
+    let effects = call_move_out_of_gas(
+        &authority_state,
+        &gas_object_id,
+        &sender,
+        &sender_key,
+        &package_object_ref,
+        "set_value",
+        vec![
+            TestCallArg::Object(object_id),
+            TestCallArg::Pure(bcs::to_bytes(&42u64).unwrap()),
+        ],
+    )
+    .await?;
+    // The object's version is bumped, but it keeps its owner.
+    let (_, owner) = effects.mutated_excluding_gas()[0];
+    assert_eq!(owner, &Owner::AddressOwner(sender));
+    Ok(())
+}
+
+/// Call `object_basics::function` with a budget that covers the charges made before the Move VM
+/// runs (the minimum transaction cost and reading the package, the gas coin and the object
+/// arguments) but runs out during execution. Checks that the whole budget was charged to the gas
+/// coin and that the call neither created, deleted nor changed the contents of any object, and
+/// returns the effects of the failed call.
+async fn call_move_out_of_gas(
+    authority_state: &AuthorityState,
+    gas_object_id: &ObjectID,
+    sender: &SuiAddress,
+    sender_key: &AccountKeyPair,
+    package_object_ref: &ObjectRef,
+    function: &str,
+    test_args: Vec<TestCallArg>,
+) -> SuiResult<TransactionEffects> {
+    let gas_object = authority_state.get_object(gas_object_id).await?.unwrap();
+    let gas_balance = GasCoin::try_from(&gas_object)?.value();
+    let package_object = authority_state
+        .get_object(&package_object_ref.0)
+        .await?
+        .unwrap();
+    let mut objects = vec![];
+    for arg in &test_args {
+        if let TestCallArg::Object(object_id) = arg {
+            objects.push(authority_state.get_object(object_id).await?.unwrap());
+        }
+    }
+
+    let mut gas_status = SuiGasStatus::new_with_budget(
+        GAS_VALUE_FOR_TESTING,
+        1.into(),
+        1.into(),
+        SuiCostTable::new_for_testing(),
+    );
+    gas_status.charge_min_tx_gas()?;
+    gas_status.charge_storage_read(
+        package_object.object_size_for_gas_metering()
+            + gas_object.object_size_for_gas_metering()
+            + objects
+                .iter()
+                .map(|o| o.object_size_for_gas_metering())
+                .sum::<usize>(),
+    )?;
+    let budget = gas_status.summary().gas_used() + 1;
+
+    let effects = call_move_with_gas_coins(
+        authority_state,
+        None,
+        &[*gas_object_id],
+        budget,
+        sender,
+        sender_key,
+        &package_object_ref.0,
+        "object_basics",
+        function,
+        vec![],
+        test_args,
+        false,
+    )
+    .await?;
+
+    assert_eq!(
+        effects.status(),
+        &ExecutionStatus::new_failure(ExecutionFailureStatus::InsufficientGas, None)
+    );
+    // The whole budget is charged, and nothing is paid for storage.
+    let gas_cost = effects.gas_cost_summary();
+    assert_eq!(gas_cost.gas_used(), budget);
+    assert_eq!(gas_cost.storage_rebate, 0);
+    let gas_balance = assert_gas_charged(authority_state, gas_balance, &effects)?;
+    assert_eq!(
+        GasCoin::try_from(&authority_state.get_object(gas_object_id).await?.unwrap())?.value(),
+        gas_balance,
+    );
+
+    assert!(effects.created().is_empty());
+    assert!(effects.deleted().is_empty());
+    assert!(effects.wrapped().is_empty());
+    assert!(effects.unwrapped().is_empty());
+    for object in objects {
+        let after = authority_state.get_object(&object.id()).await?.unwrap();
+        assert_eq!(
+            after.data.try_as_move().unwrap().contents(),
+            object.data.try_as_move().unwrap().contents(),
+        );
+    }
+    Ok(effects)
+}
+
 #[tokio::test]
 async fn test_storage_gas_unit_price() -> SuiResult {
     let mut gas_status1 = SuiGasStatus::new_with_budget(
@@ -562,6 +727,34 @@ async fn test_tx_gas_price_less_than_reference_gas_price() {
     );
 }
 
+/// Check that the gas coin of `effects` was charged exactly the net gas usage of the
+/// transaction, computation and storage costs minus the storage rebate, from
+/// `gas_balance_before`. Returns the balance of the gas coin at the version `effects` wrote.
+fn assert_gas_charged(
+    authority_state: &AuthorityState,
+    gas_balance_before: u64,
+    effects: &TransactionEffects,
+) -> SuiResult<u64> {
+    let ((gas_object_id, version, digest), _) = *effects.gas_object();
+    let gas_object = authority_state
+        .database
+        .get_object_by_key(&gas_object_id, version)?
+        .expect("Gas object at the version written by the effects should exist");
+    assert_eq!(gas_object.digest(), digest);
+    let gas_balance = GasCoin::try_from(&gas_object)?.value();
+    let summary = effects.gas_cost_summary();
+    assert_eq!(
+        gas_balance_before as i64 - gas_balance as i64,
+        summary.net_gas_usage(),
+        "Gas coin balance went from {gas_balance_before} to {gas_balance}, expected a charge of \
+         {} computation + {} storage - {} rebate",
+        summary.computation_cost,
+        summary.storage_cost,
+        summary.storage_rebate,
+    );
+    Ok(gas_balance)
+}
+
 struct TransferResult {
     pub authority_state: Arc<AuthorityState>,
     pub object_id: ObjectID,
@@ -4,25 +4,28 @@
 
 use super::*;
 use crate::authority::authority_tests::{
-    call_move, call_move_, execute_programmable_transaction, init_state_with_ids,
-    send_and_confirm_transaction, TestCallArg,
+    assert_event_emitted, call_move, call_move_, call_move_with_events, dev_inspect_make_move_vec,
+    execute_programmable_transaction, init_state_validator_with_fullnode_and_execution_timeout,
+    init_state_with_ids, send_and_confirm_transaction, TestCallArg, TestObjectIds,
 };
 use move_core_types::identifier::Identifier;
 use sui_types::{
-    error::ExecutionErrorKind, object::Data,
-    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    error::ExecutionErrorKind,
+    object::Data,
+    parse_sui_type_tag,
+    programmable_transaction_builder::{option_type_tag, ProgrammableTransactionBuilder},
     utils::to_sender_signed_transaction,
 };
 
-use move_core_types::language_storage::TypeTag;
+use move_core_types::language_storage::{StructTag, TypeTag};
 use move_package::source_package::manifest_parser;
 use sui_framework_build::compiled_package::{
-    check_unpublished_dependencies, gather_dependencies, BuildConfig,
+    check_unpublished_dependencies, gather_dependencies, BuildConfig, DebugSymbols,
 };
 use sui_types::{
     crypto::{get_key_pair, AccountKeyPair},
     error::SuiError,
-    messages::ExecutionStatus,
+    messages::{ExecutionStatus, MoveLocationOpt},
 };
 
 use expect_test::expect;
@@ -37,7 +40,7 @@ const MAX_GAS: u64 = 10000;
 #[cfg_attr(msim, ignore)]
 async fn test_publishing_with_unpublished_deps() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -50,10 +53,8 @@ async fn test_publishing_with_unpublished_deps() {
     )
     .await;
 
-    let ObjectRead::Exists(read_ref, package_obj, _) = authority
-        .get_object_read(&package.0)
-        .await
-        .unwrap()
+    let ObjectRead::Exists(read_ref, package_obj, _) =
+        authority.get_object_read(&package.0).await.unwrap()
     else {
         panic!("Can't read package")
     };
@@ -98,11 +99,61 @@ async fn test_publishing_with_unpublished_deps() {
     ));
 }
 
+#[tokio::test]
+#[cfg_attr(msim, ignore)]
+async fn test_publish_links_against_on_chain_dependencies() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas = TestObjectIds::default().next_id();
+    let authority = init_state_with_ids(vec![(sender, gas)]).await;
+
+    // `Publish` does not take a list of dependencies: the package is linked against the on-chain
+    // packages at the addresses its modules refer to, which are loaded as implicit inputs.
+    let modules = build_test_package("object_basics", /* with_unpublished_deps */ false);
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.publish_immutable(modules);
+    let effects =
+        execute_programmable_transaction(&authority, &gas, &sender, &sender_key, builder.finish())
+            .await
+            .unwrap();
+    assert!(effects.status().is_ok());
+
+    let ((package_id, _, _), _) = effects
+        .created()
+        .iter()
+        .find(|(_, owner)| matches!(owner, Owner::Immutable))
+        .copied()
+        .unwrap();
+    let package = authority.get_object(&package_id).await.unwrap().unwrap();
+    let Data::Package(move_package) = package.data else {
+        panic!("Not a package")
+    };
+    let modules: Vec<_> = move_package
+        .serialized_module_map()
+        .values()
+        .map(|bytes| CompiledModule::deserialize(bytes).unwrap())
+        .collect();
+
+    // The published modules live at the new package's address, and the link to the framework
+    // they were built against is recorded in their module handles.
+    assert!(modules
+        .iter()
+        .all(|m| ObjectID::from(*m.self_id().address()) == package_id));
+    let dependencies: HashSet<_> = Transaction::input_objects_in_compiled_modules(&modules)
+        .iter()
+        .map(InputObjectKind::object_id)
+        .collect();
+    assert!(dependencies.contains(&SUI_FRAMEWORK_OBJECT_ID));
+    assert!(dependencies.is_subset(&HashSet::from([
+        MOVE_STDLIB_OBJECT_ID,
+        SUI_FRAMEWORK_OBJECT_ID
+    ])));
+}
+
 #[tokio::test]
 #[cfg_attr(msim, ignore)]
 async fn test_publish_empty_package() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
     let gas_object = authority.get_object(&gas).await.unwrap();
     let gas_object_ref = gas_object.unwrap().compute_object_reference();
@@ -139,14 +190,22 @@ async fn test_publish_empty_package() {
             error: ExecutionFailureStatus::VMVerificationOrDeserializationError,
             command: Some(0)
         }
-    )
+    );
+    assert_eq!(
+        authority
+            .metrics
+            .publish_verification_failures
+            .with_label_values(&["deserialization"])
+            .get(),
+        1
+    );
 }
 
 #[tokio::test]
 #[cfg_attr(msim, ignore)]
 async fn test_publish_duplicate_modules() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
     let gas_object = authority.get_object(&gas).await.unwrap();
     let gas_object_ref = gas_object.unwrap().compute_object_reference();
@@ -171,11 +230,171 @@ async fn test_publish_duplicate_modules() {
     )
 }
 
+#[tokio::test]
+#[cfg_attr(msim, ignore)]
+async fn test_publish_with_gas_budget() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas = TestObjectIds::default().next_id();
+    let authority = init_state_with_ids(vec![(sender, gas)]).await;
+
+    // a budget above the default is honoured
+    let (package, _) = build_and_publish_test_package_with_gas_budget(
+        &authority,
+        &sender,
+        &sender_key,
+        &gas,
+        "object_wrapping",
+        10 * MAX_GAS,
+        /* with_unpublished_deps */ false,
+    )
+    .await;
+    assert!(authority.get_object(&package.0).await.unwrap().is_some());
+
+    // find out what the publish costs, then under-fund it by one unit
+    let (_, effects) = build_and_try_publish_test_package(
+        &authority,
+        &sender,
+        &sender_key,
+        &gas,
+        "object_wrapping",
+        MAX_GAS,
+        /* with_unpublished_deps */ false,
+    )
+    .await;
+    let budget = effects.data().gas_cost_summary().gas_used() - 1;
+    let (_, effects) = build_and_try_publish_test_package(
+        &authority,
+        &sender,
+        &sender_key,
+        &gas,
+        "object_wrapping",
+        budget,
+        /* with_unpublished_deps */ false,
+    )
+    .await;
+    assert_eq!(
+        effects.into_data().into_status().unwrap_err().0,
+        ExecutionFailureStatus::InsufficientGas,
+    );
+}
+
+#[tokio::test]
+#[cfg_attr(msim, ignore)]
+async fn test_computation_gas_units_metric() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas = TestObjectIds::default().next_id();
+    let authority = init_state_with_ids(vec![(sender, gas)]).await;
+
+    let package = build_and_publish_test_package(
+        &authority,
+        &sender,
+        &sender_key,
+        &gas,
+        "object_basics",
+        /* with_unpublished_deps */ false,
+    )
+    .await;
+
+    let histogram = &authority.metrics.computation_gas_units;
+    let samples_before = histogram.get_sample_count();
+    let sum_before = histogram.get_sample_sum();
+
+    for value in 0..3_u64 {
+        let effects = call_move(
+            &authority,
+            &gas,
+            &sender,
+            &sender_key,
+            &package.0,
+            "object_basics",
+            "create",
+            vec![],
+            vec![
+                TestCallArg::Pure(bcs::to_bytes(&value).unwrap()),
+                TestCallArg::Pure(bcs::to_bytes(&sender).unwrap()),
+            ],
+        )
+        .await
+        .unwrap();
+        assert!(effects.status().is_ok());
+    }
+
+    // Every executed call is sampled, and is charged at least the minimal computation bucket.
+    assert_eq!(histogram.get_sample_count(), samples_before + 3);
+    assert!(histogram.get_sample_sum() - sum_before >= 3.0 * 1000.0);
+}
+
+#[tokio::test]
+#[cfg_attr(msim, ignore)]
+async fn test_execution_timeout() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas = TestObjectIds::default().next_id();
+    // A timeout of zero expires on the first charge, whatever the speed of the machine.
+    let (validator, fullnode) =
+        init_state_validator_with_fullnode_and_execution_timeout(Duration::ZERO).await;
+    validator
+        .insert_genesis_object(Object::with_id_owner_for_testing(gas, sender))
+        .await;
+
+    // Certificates are not subject to the timeout, only to their gas budget.
+    let package = build_and_publish_test_package(
+        &validator,
+        &sender,
+        &sender_key,
+        &gas,
+        "burn_computation",
+        /* with_unpublished_deps */ false,
+    )
+    .await;
+    let effects = call_move(
+        &validator,
+        &gas,
+        &sender,
+        &sender_key,
+        &package.0,
+        "burn_computation",
+        "spin",
+        vec![],
+        vec![],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        effects.into_status().unwrap_err().0,
+        ExecutionFailureStatus::InsufficientGas
+    );
+
+    // Dev-inspect calls are.
+    let package = validator.get_object(&package.0).await.unwrap().unwrap();
+    fullnode.insert_genesis_object(package.clone()).await;
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder
+        .move_call(
+            package.id(),
+            Identifier::new("burn_computation").unwrap(),
+            Identifier::new("spin").unwrap(),
+            vec![],
+            vec![],
+        )
+        .unwrap();
+    let kind = TransactionKind::programmable(builder.finish());
+    let DevInspectResults { error, .. } = fullnode
+        .dev_inspect_transaction(sender, kind, Some(1))
+        .await
+        .unwrap();
+    let error = error.unwrap();
+    assert!(
+        error.contains("kind: ExecutionTimeout"),
+        "unexpected error: {}",
+        error
+    );
+}
+
 #[tokio::test]
 #[cfg_attr(msim, ignore)]
 async fn test_object_wrapping_unwrapping() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -379,11 +598,286 @@ async fn test_object_wrapping_unwrapping() {
     check_latest_object_ref(&authority, &expected_parent_object_ref, true).await;
 }
 
+#[tokio::test]
+#[cfg_attr(msim, ignore)]
+async fn test_effects_transaction_digest() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas = TestObjectIds::default().next_id();
+    let authority = init_state_with_ids(vec![(sender, gas)]).await;
+
+    let package = build_and_publish_test_package(
+        &authority,
+        &sender,
+        &sender_key,
+        &gas,
+        "object_basics",
+        /* with_unpublished_deps */ false,
+    )
+    .await;
+
+    let create_effects = call_move(
+        &authority,
+        &gas,
+        &sender,
+        &sender_key,
+        &package.0,
+        "object_basics",
+        "create",
+        vec![],
+        vec![
+            TestCallArg::Pure(bcs::to_bytes(&16_u64).unwrap()),
+            TestCallArg::Pure(bcs::to_bytes(&sender).unwrap()),
+        ],
+    )
+    .await
+    .unwrap();
+    assert!(create_effects.status().is_ok());
+    let create_digest = *create_effects.transaction_digest();
+    let object_id = create_effects.created()[0].0 .0;
+
+    let set_effects = call_move(
+        &authority,
+        &gas,
+        &sender,
+        &sender_key,
+        &package.0,
+        "object_basics",
+        "set_value",
+        vec![],
+        vec![
+            TestCallArg::Object(object_id),
+            TestCallArg::Pure(bcs::to_bytes(&42_u64).unwrap()),
+        ],
+    )
+    .await
+    .unwrap();
+    assert!(set_effects.status().is_ok());
+    let set_digest = *set_effects.transaction_digest();
+
+    // The second transaction depends on the first one, which created its input object.
+    assert_ne!(create_digest, set_digest);
+    assert!(set_effects.dependencies().contains(&create_digest));
+    let object = authority.get_object(&object_id).await.unwrap().unwrap();
+    assert_eq!(object.previous_transaction, set_digest);
+
+    // Both are found in the history by their digest.
+    let (transaction, effects) = authority
+        .get_executed_transaction_and_effects(create_digest)
+        .await
+        .unwrap();
+    assert_eq!(*transaction.digest(), create_digest);
+    assert_eq!(effects, create_effects);
+    let (_, effects) = authority
+        .get_executed_transaction_and_effects(set_digest)
+        .await
+        .unwrap();
+    assert_eq!(effects, set_effects);
+}
+
+#[tokio::test]
+#[cfg_attr(msim, ignore)]
+async fn test_emitted_events() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas = TestObjectIds::default().next_id();
+    let authority = init_state_with_ids(vec![(sender, gas)]).await;
+
+    let package = build_and_publish_test_package(
+        &authority,
+        &sender,
+        &sender_key,
+        &gas,
+        "object_basics",
+        /* with_unpublished_deps */ false,
+    )
+    .await;
+
+    let mut object_ids = vec![];
+    for value in [16_u64, 42] {
+        let (effects, events) = call_move_with_events(
+            &authority,
+            &gas,
+            &sender,
+            &sender_key,
+            &package.0,
+            "object_basics",
+            "create",
+            vec![],
+            vec![
+                TestCallArg::Pure(bcs::to_bytes(&value).unwrap()),
+                TestCallArg::Pure(bcs::to_bytes(&sender).unwrap()),
+            ],
+        )
+        .await
+        .unwrap();
+        assert!(effects.status().is_ok());
+        // `create` emits no event
+        assert!(events.is_empty());
+        object_ids.push(effects.created()[0].0 .0);
+    }
+
+    // `update` copies the value of the second object into the first and emits it
+    let (effects, events) = call_move_with_events(
+        &authority,
+        &gas,
+        &sender,
+        &sender_key,
+        &package.0,
+        "object_basics",
+        "update",
+        vec![],
+        vec![
+            TestCallArg::Object(object_ids[0]),
+            TestCallArg::Object(object_ids[1]),
+        ],
+    )
+    .await
+    .unwrap();
+    assert!(effects.status().is_ok());
+    assert!(effects.events_digest().is_some());
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].package_id, package.0);
+    assert_eq!(events[0].transaction_module.as_str(), "object_basics");
+    assert_eq!(events[0].sender, sender);
+
+    #[derive(Debug, Serialize)]
+    struct NewValueEvent {
+        new_value: u64,
+    }
+    let event_type = StructTag {
+        address: package.0.into(),
+        module: Identifier::new("object_basics").unwrap(),
+        name: Identifier::new("NewValueEvent").unwrap(),
+        type_params: vec![],
+    };
+    assert_event_emitted(&events, &event_type, &NewValueEvent { new_value: 42 });
+}
+
+#[tokio::test]
+#[cfg_attr(msim, ignore)]
+async fn test_freeze_object() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas = TestObjectIds::default().next_id();
+    let authority = init_state_with_ids(vec![(sender, gas)]).await;
+
+    let package = build_and_publish_test_package(
+        &authority,
+        &sender,
+        &sender_key,
+        &gas,
+        "object_basics",
+        /* with_unpublished_deps */ false,
+    )
+    .await;
+
+    // Create an object owned by the sender.
+    let effects = call_move(
+        &authority,
+        &gas,
+        &sender,
+        &sender_key,
+        &package.0,
+        "object_basics",
+        "create",
+        vec![],
+        vec![
+            TestCallArg::Pure(bcs::to_bytes(&16_u64).unwrap()),
+            TestCallArg::Pure(bcs::to_bytes(&sender).unwrap()),
+        ],
+    )
+    .await
+    .unwrap();
+    assert!(
+        matches!(effects.status(), ExecutionStatus::Success { .. }),
+        "{:?}",
+        effects.status()
+    );
+    let object_id = effects.created()[0].0 .0;
+
+    // Freeze it.
+    let effects = call_move(
+        &authority,
+        &gas,
+        &sender,
+        &sender_key,
+        &package.0,
+        "object_basics",
+        "freeze_object",
+        vec![],
+        vec![TestCallArg::Object(object_id)],
+    )
+    .await
+    .unwrap();
+    assert!(
+        matches!(effects.status(), ExecutionStatus::Success { .. }),
+        "{:?}",
+        effects.status()
+    );
+    let frozen_object_ref = assert_object_frozen(&effects, &object_id);
+    check_latest_object_ref(&authority, &frozen_object_ref, false).await;
+    let frozen_object = authority.get_object(&object_id).await.unwrap().unwrap();
+    assert!(frozen_object.is_immutable());
+
+    // The frozen object can no longer be mutated...
+    let effects = call_move(
+        &authority,
+        &gas,
+        &sender,
+        &sender_key,
+        &package.0,
+        "object_basics",
+        "set_value",
+        vec![],
+        vec![
+            TestCallArg::Object(object_id),
+            TestCallArg::Pure(bcs::to_bytes(&42_u64).unwrap()),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        effects.status(),
+        &ExecutionStatus::Failure {
+            error: ExecutionErrorKind::CommandArgumentError {
+                arg_idx: 0,
+                kind: CommandArgumentError::InvalidObjectByMutRef,
+            },
+            command: Some(0)
+        }
+    );
+
+    // ...nor deleted.
+    let effects = call_move(
+        &authority,
+        &gas,
+        &sender,
+        &sender_key,
+        &package.0,
+        "object_basics",
+        "delete",
+        vec![],
+        vec![TestCallArg::Object(object_id)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        effects.status(),
+        &ExecutionStatus::Failure {
+            error: ExecutionErrorKind::CommandArgumentError {
+                arg_idx: 0,
+                kind: CommandArgumentError::InvalidObjectByValue,
+            },
+            command: Some(0)
+        }
+    );
+    // Neither failed transaction touched it.
+    check_latest_object_ref(&authority, &frozen_object_ref, false).await;
+}
+
 #[tokio::test]
 #[cfg_attr(msim, ignore)]
 async fn test_object_owning_another_object() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -462,18 +956,15 @@ async fn test_object_owning_another_object() {
     .await
     .unwrap();
     effects.status().unwrap();
-    let child_effect = effects
-        .mutated()
-        .iter()
-        .find(|((id, _, _), _)| id == &child.0)
-        .unwrap();
-    // Check that the child is now owned by the parent.
-    let field_id = match child_effect.1 {
-        Owner::ObjectOwner(field_id) => field_id.into(),
-        Owner::Shared { .. } | Owner::Immutable | Owner::AddressOwner(_) => panic!(),
-    };
-    let field_object = authority.get_object(&field_id).await.unwrap().unwrap();
-    assert_eq!(field_object.owner, parent.0);
+    // Check that the child is now owned by the field of the parent it was added to.
+    let field = created_object_owned_by(&effects, &parent.0);
+    assert_owner_transition(
+        &authority,
+        &effects,
+        &child.0,
+        Owner::AddressOwner(sender),
+        Owner::ObjectOwner(field.into()),
+    );
 
     // Mutate the child directly will now fail because we need the parent to authenticate.
     let result = call_move(
@@ -543,6 +1034,15 @@ async fn test_object_owning_another_object() {
     .unwrap();
 
     assert!(effects.status().is_ok());
+    // The child moved from the field of the parent to a field of the new parent.
+    let new_field = created_object_owned_by(&effects, &new_parent.0);
+    assert_owner_transition(
+        &authority,
+        &effects,
+        &child.0,
+        Owner::ObjectOwner(field.into()),
+        Owner::ObjectOwner(new_field.into()),
+    );
 
     // Delete the child. This should fail as the child cannot be used as a transaction argument
     let effects = call_move(
@@ -564,7 +1064,7 @@ async fn test_object_owning_another_object() {
 #[cfg_attr(msim, ignore)]
 async fn test_create_then_delete_parent_child() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -625,7 +1125,7 @@ async fn test_create_then_delete_parent_child() {
 #[cfg_attr(msim, ignore)]
 async fn test_create_then_delete_parent_child_wrap() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -718,7 +1218,7 @@ async fn test_create_then_delete_parent_child_wrap() {
 #[cfg_attr(msim, ignore)]
 async fn test_create_then_delete_parent_child_wrap_separate() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -812,7 +1312,7 @@ async fn test_create_then_delete_parent_child_wrap_separate() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_vector_empty() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -825,9 +1325,21 @@ async fn test_entry_point_vector_empty() {
     )
     .await;
 
-    // call a function with an empty vector
+    // make an empty vector
     let type_tag =
         TypeTag::from_str(format!("{}::entry_point_vector::Obj", package.0).as_str()).unwrap();
+    let pt = {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder.command(Command::MakeMoveVec(Some(type_tag.clone()), vec![]));
+        builder.finish()
+    };
+    let (element_type, len) = dev_inspect_make_move_vec(&authority, &sender, pt, 0)
+        .await
+        .unwrap();
+    assert_eq!(element_type, type_tag);
+    assert_eq!(len, 0);
+
+    // call a function with an empty vector
     let pt = {
         let mut builder = ProgrammableTransactionBuilder::new();
         let empty_vec = builder.command(Command::MakeMoveVec(Some(type_tag.clone()), vec![]));
@@ -923,7 +1435,7 @@ async fn test_entry_point_vector_empty() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_vector_primitive() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -959,11 +1471,51 @@ async fn test_entry_point_vector_primitive() {
     );
 }
 
+#[tokio::test]
+#[cfg_attr(msim, ignore)]
+async fn test_move_abort_source_location() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let gas = TestObjectIds::default().next_id();
+    let authority = init_state_with_ids(vec![(sender, gas)]).await;
+
+    let (package, debug_symbols) = build_and_publish_test_package_with_debug_symbols(
+        &authority,
+        &sender,
+        &sender_key,
+        &gas,
+        "entry_point_vector",
+        /* with_unpublished_deps */ false,
+    )
+    .await;
+
+    // the entry function asserts that it gets a vector of 2 values
+    let effects = call_move(
+        &authority,
+        &gas,
+        &sender,
+        &sender_key,
+        &package.0,
+        "entry_point_vector",
+        "prim_vec_len",
+        vec![],
+        vec![TestCallArg::Pure(
+            bcs::to_bytes(&vec![7_u64, 42_u64, 0_u64]).unwrap(),
+        )],
+    )
+    .await
+    .unwrap();
+    let source_location = assert_move_abort(&effects, &debug_symbols, 0);
+    assert!(
+        source_location.ends_with("objects_vector.move:73"),
+        "{source_location}"
+    );
+}
+
 #[tokio::test]
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_vector() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -996,6 +1548,28 @@ async fn test_entry_point_vector() {
         effects.status()
     );
     let (obj_id, _, _) = effects.created()[0].0;
+    // without a type tag, the type of the vector is the type of its first object
+    let pt = {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let obj_ref = authority
+            .get_object(&obj_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .compute_object_reference();
+        builder
+            .make_obj_vec([ObjectArg::ImmOrOwnedObject(obj_ref)])
+            .unwrap();
+        builder.finish()
+    };
+    let (element_type, len) = dev_inspect_make_move_vec(&authority, &sender, pt, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        element_type,
+        TypeTag::from_str(format!("{}::entry_point_vector::Obj", package.0).as_str()).unwrap()
+    );
+    assert_eq!(len, 1);
     // call a function with a vector containing one owned object
     let effects = call_move(
         &authority,
@@ -1084,7 +1658,7 @@ async fn test_entry_point_vector() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_vector_error() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -1344,7 +1918,7 @@ async fn test_entry_point_vector_error() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_vector_any() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -1468,7 +2042,7 @@ async fn test_entry_point_vector_any() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_vector_any_error() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -1730,7 +2304,7 @@ async fn test_entry_point_vector_any_error() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_string() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -1814,7 +2388,7 @@ async fn test_entry_point_string() {
 #[cfg_attr(msim, ignore)]
 async fn test_nested_string() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -1827,9 +2401,12 @@ async fn test_nested_string() {
     )
     .await;
 
+    let utf8 = parse_sui_type_tag("0x1::string::String").unwrap();
+    let option_utf8 = option_type_tag(utf8.clone());
+    let vec_option_utf8 = TypeTag::Vector(Box::new(option_utf8.clone()));
+
     // pass an option utf8 string
-    let utf8_str = Some("çå∞≠¢õß∂ƒ∫");
-    let utf_str_bcs = bcs::to_bytes(&utf8_str).unwrap();
+    let arg = TestCallArg::pure_option(utf8.clone(), Some("çå∞≠¢õß∂ƒ∫"));
     let effects = call_move(
         &authority,
         &gas,
@@ -1839,15 +2416,14 @@ async fn test_nested_string() {
         "entry_point_string",
         "option_utf8_arg",
         vec![],
-        vec![TestCallArg::Pure(utf_str_bcs)],
+        vec![arg],
     )
     .await
     .unwrap();
     assert_eq!(effects.status(), &ExecutionStatus::Success);
 
     // vector option utf8 string
-    let utf8_str = vec![Some("çå∞≠¢õß∂ƒ∫")];
-    let utf_str_bcs = bcs::to_bytes(&utf8_str).unwrap();
+    let arg = TestCallArg::pure_vec(option_utf8.clone(), vec![Some("çå∞≠¢õß∂ƒ∫")]);
     let effects = call_move(
         &authority,
         &gas,
@@ -1857,15 +2433,14 @@ async fn test_nested_string() {
         "entry_point_string",
         "vec_option_utf8_arg",
         vec![],
-        vec![TestCallArg::Pure(utf_str_bcs)],
+        vec![arg],
     )
     .await
     .unwrap();
     assert_eq!(effects.status(), &ExecutionStatus::Success);
 
     // vector option utf8 string
-    let utf8_str = Some(vec![Some("çå∞≠¢õß∂ƒ∫")]);
-    let utf_str_bcs = bcs::to_bytes(&utf8_str).unwrap();
+    let arg = TestCallArg::pure_option(vec_option_utf8.clone(), Some(vec![Some("çå∞≠¢õß∂ƒ∫")]));
     let effects = call_move(
         &authority,
         &gas,
@@ -1875,15 +2450,14 @@ async fn test_nested_string() {
         "entry_point_string",
         "option_vec_option_utf8_arg",
         vec![],
-        vec![TestCallArg::Pure(utf_str_bcs)],
+        vec![arg],
     )
     .await
     .unwrap();
     assert_eq!(effects.status(), &ExecutionStatus::Success);
 
     // pass an empty option utf8 string
-    let utf8_str: Option<String> = None;
-    let utf_str_bcs = bcs::to_bytes(&utf8_str).unwrap();
+    let arg = TestCallArg::pure_option::<String>(utf8, None);
     let effects = call_move(
         &authority,
         &gas,
@@ -1893,15 +2467,14 @@ async fn test_nested_string() {
         "entry_point_string",
         "option_utf8_arg",
         vec![],
-        vec![TestCallArg::Pure(utf_str_bcs)],
+        vec![arg],
     )
     .await
     .unwrap();
     assert_eq!(effects.status(), &ExecutionStatus::Success);
 
     // an empty vector option utf8 string
-    let utf8_str: Vec<Option<String>> = vec![];
-    let utf_str_bcs = bcs::to_bytes(&utf8_str).unwrap();
+    let arg = TestCallArg::pure_vec::<Option<String>>(option_utf8.clone(), vec![]);
     let effects = call_move(
         &authority,
         &gas,
@@ -1911,15 +2484,14 @@ async fn test_nested_string() {
         "entry_point_string",
         "vec_option_utf8_arg",
         vec![],
-        vec![TestCallArg::Pure(utf_str_bcs)],
+        vec![arg],
     )
     .await
     .unwrap();
     assert_eq!(effects.status(), &ExecutionStatus::Success);
 
     // an vector of None
-    let utf8_str: Vec<Option<String>> = vec![None, None];
-    let utf_str_bcs = bcs::to_bytes(&utf8_str).unwrap();
+    let arg = TestCallArg::pure_vec::<Option<String>>(option_utf8, vec![None, None]);
     let effects = call_move(
         &authority,
         &gas,
@@ -1929,15 +2501,15 @@ async fn test_nested_string() {
         "entry_point_string",
         "vec_option_utf8_arg",
         vec![],
-        vec![TestCallArg::Pure(utf_str_bcs)],
+        vec![arg],
     )
     .await
     .unwrap();
     assert_eq!(effects.status(), &ExecutionStatus::Success);
 
     // vector option utf8 string
-    let utf8_str: Option<Vec<Option<String>>> = Some(vec![None, None]);
-    let utf_str_bcs = bcs::to_bytes(&utf8_str).unwrap();
+    let arg =
+        TestCallArg::pure_option::<Vec<Option<String>>>(vec_option_utf8, Some(vec![None, None]));
     let effects = call_move(
         &authority,
         &gas,
@@ -1947,7 +2519,7 @@ async fn test_nested_string() {
         "entry_point_string",
         "option_vec_option_utf8_arg",
         vec![],
-        vec![TestCallArg::Pure(utf_str_bcs)],
+        vec![arg],
     )
     .await
     .unwrap();
@@ -1958,7 +2530,7 @@ async fn test_nested_string() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_string_vec() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -1999,7 +2571,7 @@ async fn test_entry_point_string_vec() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_string_error() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -2118,7 +2690,7 @@ async fn test_entry_point_string_error() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_string_vec_error() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -2173,7 +2745,7 @@ async fn test_entry_point_string_vec_error() {
 #[cfg_attr(msim, ignore)]
 async fn test_entry_point_string_option_error() {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
-    let gas = ObjectID::random();
+    let gas = TestObjectIds::default().next_id();
     let authority = init_state_with_ids(vec![(sender, gas)]).await;
 
     let package = build_and_publish_test_package(
@@ -2391,7 +2963,7 @@ async fn test_custom_property_check_unpublished_dependencies() {
         check_unpublished_dependencies(gather_dependencies(&resolution_graph).unpublished)
             .err()
             .unwrap()
-     else {
+    else {
         panic!("Expected ModulePublishFailure")
     };
 
@@ -2401,16 +2973,32 @@ async fn test_custom_property_check_unpublished_dependencies() {
     expected.assert_eq(&error)
 }
 
-pub fn build_test_package(test_dir: &str, with_unpublished_deps: bool) -> Vec<Vec<u8>> {
-    let build_config = BuildConfig::new_for_testing();
+fn test_package_path(test_dir: &str) -> PathBuf {
     let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     path.push("src");
     path.push("unit_tests");
     path.push("data");
     path.push(test_dir);
-    sui_framework::build_move_package(&path, build_config)
+    path
+}
+
+pub fn build_test_package(test_dir: &str, with_unpublished_deps: bool) -> Vec<Vec<u8>> {
+    BuildConfig::new_for_testing()
+        .build_package_bytes(test_package_path(test_dir), with_unpublished_deps)
         .unwrap()
-        .get_package_bytes(with_unpublished_deps)
+}
+
+/// Like `build_test_package`, also keeping the source maps of the package's modules, for
+/// `assert_move_abort` to report where the package aborted.
+pub fn build_test_package_with_debug_symbols(
+    test_dir: &str,
+    with_unpublished_deps: bool,
+) -> (Vec<Vec<u8>>, DebugSymbols) {
+    let mut build_config = BuildConfig::new_for_testing();
+    build_config.retain_source_maps = true;
+    let package = build_config.build(test_package_path(test_dir)).unwrap();
+    let all_module_bytes = package.get_package_bytes(with_unpublished_deps);
+    (all_module_bytes, package.debug_symbols.unwrap())
 }
 
 pub async fn build_and_try_publish_test_package(
@@ -2423,7 +3011,25 @@ pub async fn build_and_try_publish_test_package(
     with_unpublished_deps: bool,
 ) -> (Transaction, SignedTransactionEffects) {
     let all_module_bytes = build_test_package(test_dir, with_unpublished_deps);
+    try_publish_package_bytes(
+        authority,
+        sender,
+        sender_key,
+        gas_object_id,
+        all_module_bytes,
+        gas_budget,
+    )
+    .await
+}
 
+async fn try_publish_package_bytes(
+    authority: &AuthorityState,
+    sender: &SuiAddress,
+    sender_key: &AccountKeyPair,
+    gas_object_id: &ObjectID,
+    all_module_bytes: Vec<Vec<u8>>,
+    gas_budget: u64,
+) -> (Transaction, SignedTransactionEffects) {
     let gas_object = authority.get_object(gas_object_id).await.unwrap();
     let gas_object_ref = gas_object.unwrap().compute_object_reference();
 
@@ -2472,7 +3078,7 @@ pub async fn build_and_publish_test_package_with_upgrade_cap(
     test_dir: &str,
     with_unpublished_deps: bool,
 ) -> (ObjectRef, ObjectRef) {
-    let effects = build_and_try_publish_test_package(
+    build_and_publish_test_package_with_gas_budget(
         authority,
         sender,
         sender_key,
@@ -2482,6 +3088,30 @@ pub async fn build_and_publish_test_package_with_upgrade_cap(
         with_unpublished_deps,
     )
     .await
+}
+
+/// Publish the package in `test_dir` with the given `gas_budget` rather than `MAX_GAS`, asserting
+/// that the publish succeeds. Returns the package and its upgrade cap. Use
+/// `build_and_try_publish_test_package` to observe a failed publish, e.g. one that runs out of gas.
+pub async fn build_and_publish_test_package_with_gas_budget(
+    authority: &AuthorityState,
+    sender: &SuiAddress,
+    sender_key: &AccountKeyPair,
+    gas_object_id: &ObjectID,
+    test_dir: &str,
+    gas_budget: u64,
+    with_unpublished_deps: bool,
+) -> (ObjectRef, ObjectRef) {
+    let effects = build_and_try_publish_test_package(
+        authority,
+        sender,
+        sender_key,
+        gas_object_id,
+        test_dir,
+        gas_budget,
+        with_unpublished_deps,
+    )
+    .await
     .1
     .into_data();
     assert!(
@@ -2504,6 +3134,134 @@ pub async fn build_and_publish_test_package_with_upgrade_cap(
     (package.0, upgrade_cap.0)
 }
 
+/// Publish the package in `test_dir` like `build_and_publish_test_package`, also returning its
+/// debug symbols to pass to `assert_move_abort`.
+pub async fn build_and_publish_test_package_with_debug_symbols(
+    authority: &AuthorityState,
+    sender: &SuiAddress,
+    sender_key: &AccountKeyPair,
+    gas_object_id: &ObjectID,
+    test_dir: &str,
+    with_unpublished_deps: bool,
+) -> (ObjectRef, DebugSymbols) {
+    let (all_module_bytes, debug_symbols) =
+        build_test_package_with_debug_symbols(test_dir, with_unpublished_deps);
+    let effects = try_publish_package_bytes(
+        authority,
+        sender,
+        sender_key,
+        gas_object_id,
+        all_module_bytes,
+        MAX_GAS,
+    )
+    .await
+    .1
+    .into_data();
+    assert!(
+        matches!(effects.status(), ExecutionStatus::Success { .. }),
+        "{:?}",
+        effects.status()
+    );
+
+    let package = effects
+        .created()
+        .iter()
+        .find(|(_, owner)| matches!(owner, Owner::Immutable))
+        .unwrap();
+    (package.0, debug_symbols)
+}
+
+/// Assert that `effects` failed with a Move abort of `code`, and return the `file:line` the abort
+/// comes from. On any other outcome the panic message says where the package aborted or hit a
+/// runtime error, as far as the `debug_symbols` of the package know.
+pub fn assert_move_abort(
+    effects: &TransactionEffects,
+    debug_symbols: &DebugSymbols,
+    code: u64,
+) -> String {
+    let (location, abort_code) = match effects.status() {
+        ExecutionStatus::Failure {
+            error: ExecutionFailureStatus::MoveAbort(location, abort_code),
+            ..
+        } => (Some(location), Some(*abort_code)),
+        ExecutionStatus::Failure {
+            error: ExecutionFailureStatus::MovePrimitiveRuntimeError(MoveLocationOpt(location)),
+            ..
+        } => (location.as_ref(), None),
+        _ => (None, None),
+    };
+    let source_location = location
+        .and_then(|location| debug_symbols.source_location(location))
+        .unwrap_or_else(|| "an unknown location".to_string());
+    assert_eq!(
+        abort_code,
+        Some(code),
+        "Expected a Move abort with code {code}, got {:?} at {source_location}",
+        effects.status()
+    );
+    source_location
+}
+
+/// Assert that `effects` mutated the object `object_id` into an immutable object, e.g. by
+/// freezing it, and return its new reference.
+fn assert_object_frozen(effects: &TransactionEffects, object_id: &ObjectID) -> ObjectRef {
+    let (object_ref, owner) = effects
+        .mutated()
+        .iter()
+        .find(|(object_ref, _)| object_ref.0 == *object_id)
+        .unwrap_or_else(|| panic!("{object_id} is not mutated by {effects:?}"));
+    assert!(
+        matches!(owner, Owner::Immutable),
+        "{object_id} is owned by {owner:?}, it was not frozen"
+    );
+    *object_ref
+}
+
+/// Assert that `effects` changed the owner of the object `object_id` from `from` to `to`, and
+/// return its new reference. Effects only record the owner after the transaction, the owner
+/// before it is read from the version of the object the transaction modified.
+fn assert_owner_transition(
+    authority: &AuthorityState,
+    effects: &TransactionEffects,
+    object_id: &ObjectID,
+    from: Owner,
+    to: Owner,
+) -> ObjectRef {
+    let (object_ref, owner) = effects
+        .mutated()
+        .iter()
+        .find(|(object_ref, _)| object_ref.0 == *object_id)
+        .unwrap_or_else(|| panic!("{object_id} is not mutated by {effects:?}"));
+    let (_, version) = effects
+        .modified_at_versions()
+        .iter()
+        .find(|(id, _)| id == object_id)
+        .unwrap_or_else(|| panic!("{object_id} is not an input of {effects:?}"));
+    let previous = authority
+        .database
+        .get_object_by_key(object_id, *version)
+        .unwrap()
+        .unwrap_or_else(|| panic!("{object_id} does not exist at version {version}"));
+    assert!(
+        previous.owner == from && *owner == to,
+        "Expected the owner of {object_id} to change from {from:?} to {to:?}, it changed from \
+         {:?} to {owner:?}",
+        previous.owner
+    );
+    *object_ref
+}
+
+/// The id of the object created by `effects` that is owned by the object `owner`, e.g. the field
+/// through which a dynamic object field is added to its parent.
+fn created_object_owned_by(effects: &TransactionEffects, owner: &ObjectID) -> ObjectID {
+    let ((object_id, _, _), _) = effects
+        .created()
+        .iter()
+        .find(|(_, created_owner)| *created_owner == Owner::ObjectOwner((*owner).into()))
+        .unwrap_or_else(|| panic!("No object owned by {owner} is created by {effects:?}"));
+    *object_id
+}
+
 async fn check_latest_object_ref(
     authority: &AuthorityState,
     object_ref: &ObjectRef,
@@ -8,6 +8,7 @@ use crate::authority::AuthorityState;
 use futures::future::join_all;
 use std::collections::HashMap;
 use std::sync::Arc;
+use sui_adapter::programmable_transactions::failure_injection::inject_command_failure;
 use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
 use sui_types::crypto::AccountKeyPair;
 use sui_types::error::UserInputError;
@@ -143,6 +144,40 @@ async fn test_pay_sui_failure_insufficient_total_balance_multiple_input_coins()
     );
 }
 
+#[tokio::test]
+async fn test_pay_sui_injected_command_failure() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let coin_id = ObjectID::random();
+    let coin1 = Object::with_id_owner_gas_for_testing(coin_id, sender, 2000);
+    let recipient1 = dbg_addr(1);
+    let recipient2 = dbg_addr(2);
+
+    // split and transfer to recipient1, then fail splitting for recipient2
+    let _guard = inject_command_failure(2, ExecutionFailureStatus::CoinBalanceOverflow);
+    let res = execute_pay_sui(
+        vec![coin1],
+        vec![recipient1, recipient2],
+        vec![100, 200],
+        sender,
+        sender_key,
+        1000,
+    )
+    .await;
+
+    let effects = res.txn_result.unwrap().into_data();
+    assert_eq!(
+        effects.status(),
+        &ExecutionStatus::Failure {
+            error: ExecutionFailureStatus::CoinBalanceOverflow,
+            command: Some(2)
+        },
+    );
+    // the effects of the commands before the failing one are reverted, only gas is charged
+    assert!(effects.created().is_empty());
+    assert_eq!(effects.mutated().len(), 1);
+    assert_eq!(effects.mutated()[0].0 .0, coin_id);
+}
+
 #[tokio::test]
 async fn test_pay_sui_success_one_input_coin() -> anyhow::Result<()> {
     let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::ops::Mul;
+use std::time::Instant;
 
 use move_binary_format::errors::{PartialVMError, PartialVMResult};
 use move_binary_format::file_format_common::Opcodes::{self};
@@ -40,6 +41,10 @@ pub const STRUCT_SIZE: AbstractMemorySize = AbstractMemorySize::new(2);
 /// For exists checks on data that doesn't exists this is the multiplier that is used.
 pub const MIN_EXISTS_DATA_SIZE: AbstractMemorySize = AbstractMemorySize::new(100);
 
+/// Number of charges between two checks of the deadline, reading the clock on every instruction
+/// would slow down execution.
+const DEADLINE_CHECK_INTERVAL: u32 = 1024;
+
 static ZERO_COST_SCHEDULE: Lazy<CostTable> = Lazy::new(zero_cost_schedule);
 
 /// The Move VM implementation of state for gas metering.
@@ -53,6 +58,9 @@ pub struct GasStatus<'a> {
     cost_table: &'a CostTable,
     gas_left: InternalGas,
     charge: bool,
+    deadline: Option<Instant>,
+    charges_until_deadline_check: u32,
+    deadline_exceeded: bool,
 }
 
 impl<'a> GasStatus<'a> {
@@ -65,6 +73,9 @@ impl<'a> GasStatus<'a> {
             gas_left: gas_left.to_unit(),
             cost_table,
             charge: true,
+            deadline: None,
+            charges_until_deadline_check: 0,
+            deadline_exceeded: false,
         }
     }
 
@@ -81,6 +92,9 @@ impl<'a> GasStatus<'a> {
             gas_left: InternalGas::new(0),
             cost_table: &ZERO_COST_SCHEDULE,
             charge: false,
+            deadline: None,
+            charges_until_deadline_check: 0,
+            deadline_exceeded: false,
         }
     }
 
@@ -94,11 +108,44 @@ impl<'a> GasStatus<'a> {
         self.gas_left.to_unit_round_down()
     }
 
+    /// Fail the charges made after `deadline` as if the gas ran out, or stop checking the
+    /// deadline if None. The charges are not checked against the clock one by one, so the
+    /// deadline can be overrun by up to `DEADLINE_CHECK_INTERVAL` charges.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+        self.charges_until_deadline_check = 0;
+    }
+
+    /// Whether a charge failed because the deadline passed, all the gas left is then used up.
+    pub fn deadline_exceeded(&self) -> bool {
+        self.deadline_exceeded
+    }
+
+    fn check_deadline(&mut self) -> PartialVMResult<()> {
+        let Some(deadline) = self.deadline else {
+            return Ok(());
+        };
+        if self.charges_until_deadline_check > 0 {
+            self.charges_until_deadline_check -= 1;
+            return Ok(());
+        }
+        self.charges_until_deadline_check = DEADLINE_CHECK_INTERVAL;
+        if Instant::now() < deadline {
+            return Ok(());
+        }
+        self.deadline = None;
+        self.deadline_exceeded = true;
+        self.gas_left = InternalGas::new(0);
+        Err(PartialVMError::new(StatusCode::OUT_OF_GAS)
+            .with_message("Execution deadline exceeded".to_string()))
+    }
+
     /// Charge a given amount of gas and fail if not enough gas units are left.
     pub fn deduct_gas(&mut self, amount: InternalGas) -> PartialVMResult<()> {
         if !self.charge {
             return Ok(());
         }
+        self.check_deadline()?;
 
         match self.gas_left.checked_sub(amount) {
             Some(gas_left) => {
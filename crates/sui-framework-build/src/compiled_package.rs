@@ -3,17 +3,22 @@
 
 use std::{
     collections::{BTreeMap, BTreeSet, HashSet},
+    fs,
     io::Write,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
     str::FromStr,
 };
 
-use fastcrypto::encoding::Base64;
+use fastcrypto::encoding::{Base64, Encoding, Hex};
+use fastcrypto::hash::{HashFunction, Sha256};
 use move_binary_format::{
     access::ModuleAccess,
+    file_format::FunctionDefinitionIndex,
     normalized::{self, Type},
     CompiledModule,
 };
+use move_bytecode_source_map::source_map::SourceMap;
 use move_bytecode_utils::{layout::SerdeLayoutBuilder, module_cache::GetModule, Modules};
 use move_compiler::{
     compiled_unit::{
@@ -41,6 +46,7 @@ use serde_reflection::Registry;
 use sui_types::{
     base_types::ObjectID,
     error::{SuiError, SuiResult},
+    messages::MoveLocation,
     move_package::{FnInfo, FnInfoKey, FnInfoMap},
     MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS,
 };
@@ -53,6 +59,31 @@ pub struct CompiledPackage {
     pub package: MoveCompiledPackage,
     /// Path to the Move package (i.e., where the Move.toml file is)
     pub path: PathBuf,
+    /// Source maps of the package's own modules, only kept when built with
+    /// `BuildConfig::retain_source_maps`
+    pub debug_symbols: Option<DebugSymbols>,
+}
+
+/// Environment variable naming the `package_cache_dir` of `BuildConfig::new_for_testing`.
+pub const PACKAGE_CACHE_DIR_ENV_VAR: &str = "SUI_MOVE_PACKAGE_CACHE_DIR";
+
+/// Revision of the tree this crate was built from. Part of the key of cached builds, so that a
+/// change to the compiler or the verifiers invalidates them.
+const GIT_REVISION: &str = {
+    if let Some(revision) = option_env!("GIT_REVISION") {
+        revision
+    } else {
+        git_version::git_version!(
+            args = ["--always", "--dirty", "--exclude", "*"],
+            fallback = ""
+        )
+    }
+};
+
+/// Builds are only cached when `GIT_REVISION` pins the compiler and the verifiers, which an
+/// unknown revision does not, nor does a "-dirty" one: every uncommitted change shares it.
+pub(crate) fn caches_builds() -> bool {
+    !GIT_REVISION.is_empty() && !GIT_REVISION.ends_with("-dirty")
 }
 
 /// Wrapper around the core Move `BuildConfig` with some Sui-specific info
@@ -63,12 +94,25 @@ pub struct BuildConfig {
     pub run_bytecode_verifier: bool,
     /// If true, print build diagnostics to stderr--no printing if false
     pub print_diags_to_stderr: bool,
+    /// If set, `build_package_bytes` keeps the bytes of the packages it builds in this directory,
+    /// keyed by the source digests of the package and its dependencies, and reuses them as long
+    /// as none of these sources change.
+    pub package_cache_dir: Option<PathBuf>,
+    /// If true, `build` keeps the source maps of the package's modules and the sources they point
+    /// into, see `DebugSymbols`. Meant for tests, to report where an abort happened.
+    pub retain_source_maps: bool,
+    /// How many threads verify the bytecode of the package's modules, one per core if unset.
+    /// The Move compiler itself runs on a single thread. Builds produce the same modules and
+    /// report the same verification error whatever the number of threads.
+    pub verifier_threads: Option<usize>,
 }
 
 impl BuildConfig {
     pub fn new_for_testing() -> Self {
         let mut build_config: Self = Default::default();
         build_config.config.install_dir = Some(tempfile::TempDir::new().unwrap().into_path());
+        build_config.package_cache_dir =
+            std::env::var_os(PACKAGE_CACHE_DIR_ENV_VAR).map(PathBuf::from);
         build_config
     }
 
@@ -131,13 +175,80 @@ impl BuildConfig {
     pub fn build(self, path: PathBuf) -> SuiResult<CompiledPackage> {
         let print_diags_to_stderr = self.print_diags_to_stderr;
         let run_bytecode_verifier = self.run_bytecode_verifier;
+        let retain_source_maps = self.retain_source_maps;
+        let verifier_threads = self.verifier_threads;
         let resolution_graph = self.resolution_graph(&path)?;
-        build_from_resolution_graph(
+        let mut compiled_package = build_from_resolution_graph(
             path,
             resolution_graph,
             run_bytecode_verifier,
             print_diags_to_stderr,
-        )
+            verifier_threads,
+        )?;
+        if retain_source_maps {
+            compiled_package.debug_symbols = Some(DebugSymbols::new(&compiled_package.package)?);
+        }
+        Ok(compiled_package)
+    }
+
+    /// Build the package in `path` and return the bytes of its modules, see
+    /// `CompiledPackage::get_package_bytes`. With a `package_cache_dir`, the bytes of a previous
+    /// build are returned instead when the sources of the package and its dependencies, the
+    /// Sui framework included, its named address overrides and the revision of this crate did
+    /// not change since. Nothing is cached when this crate is built from a tree with
+    /// uncommitted changes, see `caches_builds`.
+    pub fn build_package_bytes(
+        self,
+        path: PathBuf,
+        with_unpublished_deps: bool,
+    ) -> SuiResult<Vec<Vec<u8>>> {
+        let Some(cache_dir) = self.package_cache_dir.clone().filter(|_| caches_builds()) else {
+            return Ok(self.build(path)?.get_package_bytes(with_unpublished_deps));
+        };
+        let print_diags_to_stderr = self.print_diags_to_stderr;
+        let run_bytecode_verifier = self.run_bytecode_verifier;
+        let verifier_threads = self.verifier_threads;
+        let mut hasher = Sha256::default();
+        hasher.update(format!("{}-{};", env!("CARGO_PKG_VERSION"), GIT_REVISION));
+        hasher.update([
+            self.config.dev_mode as u8,
+            self.config.test_mode as u8,
+            run_bytecode_verifier as u8,
+            with_unpublished_deps as u8,
+        ]);
+        for (name, address) in &self.config.additional_named_addresses {
+            hasher.update(format!("{}={};", name, address));
+        }
+        let resolution_graph = self.resolution_graph(&path)?;
+        for (name, package) in &resolution_graph.package_table {
+            hasher.update(format!("{}:{};", name, package.source_digest));
+        }
+        let cache_path = cache_dir.join(format!("{}.bcs", Hex::encode(hasher.finalize())));
+
+        if let Some(bytes) = fs::read(&cache_path)
+            .ok()
+            .and_then(|cached| bcs::from_bytes(&cached).ok())
+        {
+            return Ok(bytes);
+        }
+        let bytes = build_from_resolution_graph(
+            path,
+            resolution_graph,
+            run_bytecode_verifier,
+            print_diags_to_stderr,
+            verifier_threads,
+        )?
+        .get_package_bytes(with_unpublished_deps);
+        // The cache is best effort, failing to fill it only costs a build next time. Write to a
+        // temporary file first so that concurrent builds never read a partial entry.
+        let _ = fs::create_dir_all(&cache_dir)
+            .and_then(|()| tempfile::NamedTempFile::new_in(&cache_dir))
+            .and_then(|mut file| {
+                file.write_all(&bcs::to_bytes(&bytes).unwrap())?;
+                file.persist(&cache_path).map_err(|e| e.error)?;
+                Ok(())
+            });
+        Ok(bytes)
     }
 
     pub fn resolution_graph(self, path: &Path) -> SuiResult<ResolvedGraph> {
@@ -152,6 +263,16 @@ impl BuildConfig {
             error: format!("{:?}", err),
         })
     }
+
+    /// Build the package at `path` and compare its modules against `on_chain_modules`, the
+    /// serialized modules of a published package. See `CompiledPackage::verify_against`.
+    pub fn verify_source(
+        self,
+        path: PathBuf,
+        on_chain_modules: &[Vec<u8>],
+    ) -> SuiResult<SourceVerificationReport> {
+        self.build(path)?.verify_against(on_chain_modules)
+    }
 }
 
 pub fn build_from_resolution_graph(
@@ -159,6 +280,7 @@ pub fn build_from_resolution_graph(
     resolution_graph: ResolvedGraph,
     run_bytecode_verifier: bool,
     print_diags_to_stderr: bool,
+    verifier_threads: Option<usize>,
 ) -> SuiResult<CompiledPackage> {
     let result = if print_diags_to_stderr {
         BuildConfig::compile_package(resolution_graph, &mut std::io::stderr())
@@ -177,17 +299,47 @@ pub fn build_from_resolution_graph(
     };
     let compiled_modules = package.root_modules_map();
     if run_bytecode_verifier {
-        for m in compiled_modules.iter_modules() {
-            move_bytecode_verifier::verify_module(m).map_err(|err| {
-                SuiError::ModuleVerificationFailure {
-                    error: err.to_string(),
-                }
-            })?;
-            sui_bytecode_verifier::verify_module(m, &fn_info)?;
-        }
+        let threads = verifier_threads.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, NonZeroUsize::get)
+        });
+        verify_modules(&compiled_modules.iter_modules(), &fn_info, threads)?;
         // TODO(https://github.com/MystenLabs/sui/issues/69): Run Move linker
     }
-    Ok(CompiledPackage { package, path })
+    Ok(CompiledPackage {
+        package,
+        path,
+        debug_symbols: None,
+    })
+}
+
+/// Run the Move and Sui bytecode verifiers on `modules`, split in contiguous chunks across up to
+/// `threads` threads. The error returned is that of the first module to fail in the order of
+/// `modules`, so it does not depend on the number of threads.
+fn verify_modules(modules: &[&CompiledModule], fn_info: &FnInfoMap, threads: usize) -> SuiResult {
+    let verify = |m: &CompiledModule| -> SuiResult {
+        move_bytecode_verifier::verify_module(m).map_err(|err| {
+            SuiError::ModuleVerificationFailure {
+                error: err.to_string(),
+            }
+        })?;
+        sui_bytecode_verifier::verify_module(m, fn_info)?;
+        Ok(())
+    };
+    let threads = threads.clamp(1, modules.len().max(1));
+    if threads == 1 {
+        return modules.iter().try_for_each(|m| verify(m));
+    }
+    let chunk_size = (modules.len() + threads - 1) / threads;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = modules
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().try_for_each(|m| verify(m))))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
 }
 
 impl CompiledPackage {
@@ -430,6 +582,57 @@ impl CompiledPackage {
         layout_builder.into_registry()
     }
 
+    /// Compare the root modules of this package, module by module, against the serialized
+    /// modules of an on-chain package. Locally built modules that are still at address 0x0 are
+    /// given the address of their on-chain counterpart before comparing, mirroring the rewrite
+    /// done at publish time.
+    pub fn verify_against(
+        &self,
+        on_chain_modules: &[Vec<u8>],
+    ) -> SuiResult<SourceVerificationReport> {
+        let mut on_chain = BTreeMap::new();
+        for bytes in on_chain_modules {
+            let module = CompiledModule::deserialize(bytes).map_err(|err| {
+                SuiError::ModuleDeserializationFailure {
+                    error: err.to_string(),
+                }
+            })?;
+            on_chain.insert(module.name().to_string(), (module, bytes));
+        }
+
+        let mut report = SourceVerificationReport::default();
+        for module in self.get_modules() {
+            let name = module.name().to_string();
+            let Some((on_chain_module, on_chain_bytes)) = on_chain.remove(&name) else {
+                report.missing_on_chain.push(name);
+                continue;
+            };
+
+            let local = if *module.address() == AccountAddress::ZERO {
+                substitute_self_address(module, *on_chain_module.address()).map_err(|err| {
+                    SuiError::ModuleVerificationFailure {
+                        error: err.to_string(),
+                    }
+                })?
+            } else {
+                module.clone()
+            };
+
+            let mut local_bytes = Vec::new();
+            local.serialize(&mut local_bytes).map_err(|err| {
+                SuiError::ModuleVerificationFailure {
+                    error: err.to_string(),
+                }
+            })?;
+            if &local_bytes != on_chain_bytes {
+                report.mismatched.push(name);
+            }
+        }
+        report.missing_locally = on_chain.into_keys().collect();
+
+        Ok(report)
+    }
+
     /// Checks whether this package corresponds to a built-in framework
     pub fn is_framework(&self) -> bool {
         let package_name = self.package.compiled_package_info.package_name.as_str();
@@ -453,12 +656,58 @@ impl CompiledPackage {
     }
 }
 
+/// Outcome of comparing a locally built package against its on-chain modules, see
+/// `CompiledPackage::verify_against`. Modules are identified by name.
+#[derive(Debug, Default)]
+pub struct SourceVerificationReport {
+    /// Modules present on both sides whose bytecode differs
+    pub mismatched: Vec<String>,
+    /// On-chain modules that the local build does not produce
+    pub missing_locally: Vec<String>,
+    /// Locally built modules that are not part of the on-chain package
+    pub missing_on_chain: Vec<String>,
+}
+
+impl SourceVerificationReport {
+    /// True if every module matches its on-chain counterpart and no module is missing on either
+    /// side.
+    pub fn is_verified(&self) -> bool {
+        self.mismatched.is_empty()
+            && self.missing_locally.is_empty()
+            && self.missing_on_chain.is_empty()
+    }
+}
+
+/// Copy of `module`, compiled at address 0x0, with `address` as its self-address, as done when
+/// its package is published at `address`. Fails if the module's self-address is not 0x0.
+pub fn substitute_self_address(
+    module: &CompiledModule,
+    address: AccountAddress,
+) -> anyhow::Result<CompiledModule> {
+    let mut module = module.clone();
+    let address_idx = module.self_handle().address;
+
+    let Some(addr) = module.address_identifiers.get_mut(address_idx.0 as usize) else {
+        anyhow::bail!("Self address field missing");
+    };
+
+    if *addr != AccountAddress::ZERO {
+        anyhow::bail!("Self address already populated");
+    }
+
+    *addr = address;
+    Ok(module)
+}
+
 impl Default for BuildConfig {
     fn default() -> Self {
         BuildConfig {
             config: MoveBuildConfig::default(),
             run_bytecode_verifier: true,
             print_diags_to_stderr: false,
+            package_cache_dir: None,
+            retain_source_maps: false,
+            verifier_threads: None,
         }
     }
 }
@@ -473,6 +722,61 @@ impl GetModule for CompiledPackage {
     }
 }
 
+/// The source maps of the modules of a package, along with the sources they map into, to
+/// translate the location of a Move abort or runtime error into the `file:line` it comes from.
+pub struct DebugSymbols {
+    /// Keyed by module name rather than module id: packages are built at address 0x0 and only get
+    /// their address when published.
+    modules: BTreeMap<String, ModuleSymbols>,
+}
+
+struct ModuleSymbols {
+    source_path: PathBuf,
+    source: String,
+    source_map: SourceMap,
+}
+
+impl DebugSymbols {
+    fn new(package: &MoveCompiledPackage) -> SuiResult<Self> {
+        let mut modules = BTreeMap::new();
+        for unit in &package.root_compiled_units {
+            let CompiledUnitEnum::Module(m) = &unit.unit else {
+                continue;
+            };
+            let source = fs::read_to_string(&unit.source_path).map_err(|err| {
+                SuiError::ModuleBuildFailure {
+                    error: format!("Cannot read {}: {}", unit.source_path.display(), err),
+                }
+            })?;
+            modules.insert(
+                m.module.self_id().name().to_string(),
+                ModuleSymbols {
+                    source_path: unit.source_path.clone(),
+                    source,
+                    source_map: m.source_map.clone(),
+                },
+            );
+        }
+        Ok(Self { modules })
+    }
+
+    /// The `file:line` of the instruction at `location`, or None if that instruction is not in one
+    /// of the modules of this package.
+    pub fn source_location(&self, location: &MoveLocation) -> Option<String> {
+        let module = self.modules.get(location.module.name().as_str())?;
+        let loc = module
+            .source_map
+            .get_code_location(
+                FunctionDefinitionIndex(location.function),
+                location.instruction,
+            )
+            .ok()?;
+        let preceding = module.source.get(..loc.start() as usize)?;
+        let line = preceding.matches('\n').count() + 1;
+        Some(format!("{}:{}", module.source_path.display(), line))
+    }
+}
+
 pub const PUBLISHED_AT_MANIFEST_FIELD: &str = "published-at";
 
 pub struct SuiPackageHooks {}
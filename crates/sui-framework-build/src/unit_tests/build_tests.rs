@@ -3,7 +3,11 @@
 
 use std::path::Path;
 
-use crate::compiled_package::BuildConfig;
+use move_binary_format::access::ModuleAccess;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use sui_types::{messages::MoveLocation, SUI_FRAMEWORK_ADDRESS};
+
+use crate::compiled_package::{caches_builds, BuildConfig};
 
 #[test]
 fn generate_struct_layouts() {
@@ -26,3 +30,145 @@ fn generate_struct_layouts() {
         "0000000000000000000000000000000000000000000000000000000000000002::tx_context::TxContext"
     ));
 }
+
+#[test]
+fn verify_source_against_on_chain_modules() {
+    let mut path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    path.push("sui-framework");
+    let pkg = BuildConfig::new_for_testing().build(path).unwrap();
+    let on_chain = pkg.get_package_bytes(false);
+
+    // the package's own bytecode matches its sources
+    let report = pkg.verify_against(&on_chain).unwrap();
+    assert!(report.is_verified(), "{:?}", report);
+
+    // tamper with a single module and make sure it is the one that gets reported
+    let tampered: Vec<Vec<u8>> = pkg
+        .get_modules()
+        .map(|m| {
+            let mut m = m.clone();
+            if m.name().as_str() == "coin" {
+                m.identifiers.push(Identifier::new("tampered").unwrap());
+            }
+            let mut bytes = Vec::new();
+            m.serialize(&mut bytes).unwrap();
+            bytes
+        })
+        .collect();
+    let report = pkg.verify_against(&tampered).unwrap();
+    assert!(!report.is_verified());
+    assert_eq!(report.mismatched, vec!["coin".to_string()]);
+    assert!(report.missing_locally.is_empty());
+    assert!(report.missing_on_chain.is_empty());
+}
+
+#[test]
+fn build_package_bytes_with_cache() {
+    let mut path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    path.push("sui-framework");
+    let cache_dir = tempfile::TempDir::new().unwrap();
+    let build = || {
+        let mut config = BuildConfig::new_for_testing();
+        config.package_cache_dir = Some(cache_dir.path().to_path_buf());
+        config.build_package_bytes(path.clone(), false).unwrap()
+    };
+
+    let bytes = build();
+    assert_eq!(
+        bytes,
+        BuildConfig::new_for_testing()
+            .build(path.clone())
+            .unwrap()
+            .get_package_bytes(false)
+    );
+    let entries: Vec<_> = std::fs::read_dir(cache_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    // builds of a tree with uncommitted changes are not cached
+    if !caches_builds() {
+        assert!(entries.is_empty());
+        return;
+    }
+    assert_eq!(entries.len(), 1);
+
+    // the second build is served from the cache entry of the first one
+    std::fs::write(&entries[0], bcs::to_bytes(&vec![vec![42u8]]).unwrap()).unwrap();
+    assert_eq!(build(), vec![vec![42u8]]);
+}
+
+#[test]
+fn source_location_of_move_instruction() {
+    let mut path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    path.push("sui-framework");
+    // source maps are only kept when asked for
+    let pkg = BuildConfig::new_for_testing().build(path.clone()).unwrap();
+    assert!(pkg.debug_symbols.is_none());
+
+    let mut config = BuildConfig::new_for_testing();
+    config.retain_source_maps = true;
+    let pkg = config.build(path.clone()).unwrap();
+    let debug_symbols = pkg.debug_symbols.as_ref().unwrap();
+    let coin = pkg
+        .get_modules()
+        .find(|m| m.name().as_str() == "coin")
+        .unwrap();
+    let function = coin
+        .function_defs
+        .iter()
+        .position(|f| f.code.is_some())
+        .unwrap();
+    let location = MoveLocation {
+        module: coin.self_id(),
+        function: function as u16,
+        instruction: 0,
+        function_name: None,
+    };
+
+    let source_location = debug_symbols.source_location(&location).unwrap();
+    let (file, line) = source_location.rsplit_once(':').unwrap();
+    assert!(file.ends_with("coin.move"), "{}", source_location);
+    let line: usize = line.parse().unwrap();
+    let lines = std::fs::read_to_string(path.join("sources").join("coin.move"))
+        .unwrap()
+        .lines()
+        .count();
+    assert!((1..=lines).contains(&line), "{}", source_location);
+
+    // instructions outside of the package have no source location
+    let location = MoveLocation {
+        module: ModuleId::new(
+            SUI_FRAMEWORK_ADDRESS,
+            Identifier::new("no_such_module").unwrap(),
+        ),
+        ..location
+    };
+    assert_eq!(debug_symbols.source_location(&location), None);
+}
+
+#[test]
+fn build_with_verifier_threads() {
+    let mut path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    path.push("sui-framework");
+    let build = |threads| {
+        let mut config = BuildConfig::new_for_testing();
+        config.verifier_threads = Some(threads);
+        config.build(path.clone()).unwrap().get_package_bytes(false)
+    };
+    // the modules are the same whether they are verified on one thread or on several
+    let bytes = build(1);
+    assert_eq!(bytes, build(4));
+    assert_eq!(bytes, build(usize::MAX));
+}
@@ -7,7 +7,7 @@ mod test {
     use std::path::{Path, PathBuf};
     use sui_framework::build_move_package;
     use sui_framework_build::compiled_package::BuildConfig;
-    use sui_move::unit_test::run_move_unit_tests;
+    use sui_move::unit_test::{run_move_unit_tests, run_move_unit_tests_for_results};
 
     #[test]
     #[cfg_attr(msim, ignore)]
@@ -50,6 +50,23 @@ mod test {
         });
     }
 
+    #[test]
+    #[cfg_attr(msim, ignore)]
+    fn move_unit_test_results_are_reported_per_test() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.extend(["..", "..", "sui_programmability", "examples", "basics"]);
+        let mut config = BuildConfig::new_for_testing().config;
+        config.dev_mode = true;
+
+        let results = run_move_unit_tests_for_results(&path, config, None).unwrap();
+        assert!(results.all_passed(), "failed Move unit tests: {:?}", results.failed);
+        assert!(results
+            .passed
+            .iter()
+            .any(|test| test.ends_with("::counter_test::test_counter")));
+        assert_eq!(results.total(), results.passed.len());
+    }
+
     fn check_move_unit_tests(path: &Path) {
         let mut config = BuildConfig::new_for_testing();
         // Make sure to verify tests
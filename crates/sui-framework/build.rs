@@ -86,6 +86,9 @@ fn build_framework_and_stdlib_with_move_config(
         config,
         run_bytecode_verifier: true,
         print_diags_to_stderr: false,
+        package_cache_dir: None,
+        retain_source_maps: false,
+        verifier_threads: None,
     }
     .build(sui_framework_path.to_path_buf())
     .unwrap();
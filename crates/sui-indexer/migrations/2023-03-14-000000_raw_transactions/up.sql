@@ -0,0 +1,10 @@
+CREATE TABLE raw_transactions (
+    transaction_digest         VARCHAR(255) PRIMARY KEY,
+    checkpoint_sequence_number BIGINT       NOT NULL,
+    -- BCS encoded SenderSignedData, including the tx signatures
+    raw_transaction            BYTEA        NOT NULL,
+    -- BCS encoded TransactionEffects
+    raw_effects                BYTEA        NOT NULL
+);
+
+CREATE INDEX raw_transactions_checkpoint_sequence_number ON raw_transactions (checkpoint_sequence_number);
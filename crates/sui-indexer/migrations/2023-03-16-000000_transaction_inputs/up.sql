@@ -0,0 +1,20 @@
+CREATE TABLE transaction_inputs (
+    transaction_digest         VARCHAR(255) NOT NULL,
+    checkpoint_sequence_number BIGINT       NOT NULL,
+    -- position of the move call in the transaction, and of the argument in the call
+    command_index              INT          NOT NULL,
+    argument_index             INT          NOT NULL,
+    -- position of the argument in the transaction inputs
+    input_index                INT          NOT NULL,
+    -- parameter type of the called function, NULL if it could not be resolved
+    parameter_type             TEXT,
+    -- BCS bytes of pure arguments, the object ID of object arguments
+    raw_value                  BYTEA        NOT NULL,
+    -- the decoded value, pure values are only decoded when their type is known and the bytes
+    -- are a valid value of it
+    value                      JSONB,
+    is_decoded                 BOOLEAN      NOT NULL,
+    PRIMARY KEY (transaction_digest, command_index, argument_index)
+);
+
+CREATE INDEX transaction_inputs_checkpoint_sequence_number ON transaction_inputs (checkpoint_sequence_number);
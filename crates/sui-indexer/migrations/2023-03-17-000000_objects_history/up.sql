@@ -0,0 +1,7 @@
+-- Object versions are written to objects_history by the indexer when --index-object-history
+-- is set, including tombstones for deleted and wrapped objects, instead of on every write to
+-- objects.
+DROP TRIGGER IF EXISTS objects_history ON objects;
+DROP FUNCTION IF EXISTS objects_modified_func;
+
+CREATE INDEX objects_history_object_id_version ON objects_history (object_id, version);
@@ -0,0 +1,2 @@
+ALTER TABLE checkpoints DROP COLUMN validator_signature;
+ALTER TABLE checkpoints DROP COLUMN checkpoint_summary;
@@ -0,0 +1,5 @@
+-- BCS bytes of the checkpoint summary and of the aggregated signature of the validators that
+-- certified it, so that the certified checkpoint can be served to light clients.
+-- NULL for checkpoints indexed before this migration.
+ALTER TABLE checkpoints ADD COLUMN checkpoint_summary BYTEA;
+ALTER TABLE checkpoints ADD COLUMN validator_signature BYTEA;
@@ -0,0 +1,14 @@
+CREATE TABLE object_transactions (
+    object_id                  address       NOT NULL,
+    checkpoint_sequence_number BIGINT        NOT NULL,
+    -- position of the transaction in the checkpoint
+    transaction_index          BIGINT        NOT NULL,
+    transaction_digest         VARCHAR(255)  NOT NULL,
+    -- version of the object after the transaction
+    object_version             BIGINT        NOT NULL,
+    object_status              object_status NOT NULL,
+    -- pages of the transactions of an object are read off this index, however busy the object
+    PRIMARY KEY (object_id, checkpoint_sequence_number, transaction_index)
+);
+
+CREATE INDEX object_transactions_checkpoint_sequence_number ON object_transactions (checkpoint_sequence_number);
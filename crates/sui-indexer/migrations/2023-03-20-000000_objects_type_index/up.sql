@@ -0,0 +1,2 @@
+-- objects of a type are scanned in object id order off this index, see scan_objects_by_type
+CREATE INDEX objects_object_type ON objects (object_type, object_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS objects_type_unresolved;
+ALTER TABLE objects_history DROP COLUMN IF EXISTS type_unresolved;
+ALTER TABLE objects DROP COLUMN IF EXISTS type_unresolved;
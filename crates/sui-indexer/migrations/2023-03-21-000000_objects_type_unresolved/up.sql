@@ -0,0 +1,6 @@
+-- set on objects whose type could not be resolved when they were indexed, see --unresolved-type-policy
+ALTER TABLE objects ADD COLUMN type_unresolved BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE objects_history ADD COLUMN type_unresolved BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- objects to reprocess are found off this index
+CREATE INDEX objects_type_unresolved ON objects (object_id) WHERE type_unresolved;
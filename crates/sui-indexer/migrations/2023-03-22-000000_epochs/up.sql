@@ -0,0 +1,6 @@
+-- One row per epoch, written from the checkpoint that starts it: the genesis checkpoint, and the
+-- last checkpoint of the previous epoch.
+CREATE TABLE epochs (
+    epoch BIGINT PRIMARY KEY,
+    reference_gas_price BIGINT NOT NULL
+);
@@ -0,0 +1,6 @@
+-- The total supply of each coin type, read from the latest version of its treasury cap.
+CREATE TABLE coin_supply (
+    coin_type VARCHAR PRIMARY KEY,
+    total_supply BIGINT NOT NULL,
+    checkpoint BIGINT NOT NULL
+);
@@ -0,0 +1,6 @@
+-- The checkpoint ranges backfilled by indexers of their own, see `--checkpoint-range`. Their
+-- checkpoints are left out of the checkpoint indexers resume from until the range is removed.
+CREATE TABLE checkpoint_ranges (
+    range_start BIGINT PRIMARY KEY,
+    range_end BIGINT NOT NULL
+);
@@ -0,0 +1 @@
+DROP TABLE ptb_commands;
@@ -0,0 +1,20 @@
+-- The commands of programmable transactions and the results flowing between them, e.g. the
+-- transactions passing the coin of a SplitCoin to a MoveCall are those with a MoveCall whose
+-- input_commands hold the index of a SplitCoin of the same transaction.
+CREATE TABLE ptb_commands (
+    transaction_digest         VARCHAR(255) NOT NULL,
+    checkpoint_sequence_number BIGINT       NOT NULL,
+    -- position of the command in the transaction
+    command_index              INT          NOT NULL,
+    -- MoveCall, TransferObjects, SplitCoin, MergeCoins, Publish, MakeMoveVec or Upgrade
+    command_kind               VARCHAR      NOT NULL,
+    -- the arguments of the command in order, each of "GasCoin", {"Input": i}, {"Result": i} or
+    -- {"NestedResult": [i, j]}
+    arguments                  JSONB        NOT NULL,
+    -- positions of the commands whose results are arguments of this one, in ascending order
+    input_commands             INT[]        NOT NULL,
+    PRIMARY KEY (transaction_digest, command_index)
+);
+
+CREATE INDEX ptb_commands_checkpoint_sequence_number ON ptb_commands (checkpoint_sequence_number);
+CREATE INDEX ptb_commands_command_kind ON ptb_commands (command_kind);
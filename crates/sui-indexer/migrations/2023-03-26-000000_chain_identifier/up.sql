@@ -0,0 +1,6 @@
+-- The chain the db is indexed from, recorded on the first run so that later runs against a
+-- fullnode of another chain are refused. The single row has id 0.
+CREATE TABLE chain_identifier (
+    id SMALLINT PRIMARY KEY CHECK (id = 0),
+    identifier VARCHAR(255) NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS transactions_timestamp_ms;
+CREATE INDEX transactions_timestamp_ms ON transactions (timestamp_ms);
@@ -0,0 +1,5 @@
+-- transactions of a time window are paged in checkpoint order off this index, see
+-- query_transactions_by_time. The transactions of a checkpoint share its timestamp, so ties are
+-- broken by checkpoint and id.
+DROP INDEX IF EXISTS transactions_timestamp_ms;
+CREATE INDEX transactions_timestamp_ms ON transactions (timestamp_ms, checkpoint_sequence_number, id);
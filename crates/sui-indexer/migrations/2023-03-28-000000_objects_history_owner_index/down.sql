@@ -0,0 +1 @@
+DROP INDEX IF EXISTS objects_history_owner_address;
@@ -0,0 +1,2 @@
+-- the objects an address owned up to a checkpoint are read off this index, see get_owned_objects
+CREATE INDEX objects_history_owner_address ON objects_history (owner_address, checkpoint);
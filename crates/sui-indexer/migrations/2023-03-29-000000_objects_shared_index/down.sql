@@ -0,0 +1 @@
+DROP INDEX IF EXISTS objects_shared_object_type;
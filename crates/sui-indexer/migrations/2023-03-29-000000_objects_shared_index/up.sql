@@ -0,0 +1,2 @@
+-- the shared objects, optionally of a type, are read off this index, see get_shared_objects
+CREATE INDEX objects_shared_object_type ON objects (object_type, object_id) WHERE owner_type = 'shared';
@@ -0,0 +1 @@
+DROP TABLE lagging_handlers;
@@ -0,0 +1,6 @@
+-- The handlers left behind by a failure, with the latest checkpoint each has committed, see
+-- `--max-handler-lag`. A handler has no row while it keeps up with the checkpoints table.
+CREATE TABLE lagging_handlers (
+    handler VARCHAR(255) PRIMARY KEY,
+    checkpoint_sequence_number BIGINT NOT NULL
+);
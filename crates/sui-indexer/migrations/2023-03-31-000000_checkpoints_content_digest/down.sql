@@ -0,0 +1 @@
+ALTER TABLE checkpoints DROP COLUMN content_digest;
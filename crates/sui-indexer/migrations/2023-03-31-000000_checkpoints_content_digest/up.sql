@@ -0,0 +1,3 @@
+-- digest of the checkpoint contents, against which the fetched transactions are verified.
+-- NULL for checkpoints indexed before this migration.
+ALTER TABLE checkpoints ADD COLUMN content_digest VARCHAR(255);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS events_checkpoint_sequence_number;
+ALTER TABLE events DROP COLUMN checkpoint_sequence_number;
@@ -0,0 +1,10 @@
+-- events are paged in checkpoint order, see the checkpoint-based cursors of IndexerStore.
+-- Events indexed before this migration take the checkpoint of their transaction.
+ALTER TABLE events ADD COLUMN checkpoint_sequence_number BIGINT;
+UPDATE events
+SET checkpoint_sequence_number = transactions.checkpoint_sequence_number
+FROM transactions
+WHERE events.transaction_digest = transactions.transaction_digest;
+ALTER TABLE events ALTER COLUMN checkpoint_sequence_number SET NOT NULL;
+
+CREATE INDEX events_checkpoint_sequence_number ON events (checkpoint_sequence_number);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS packages_original_id;
+ALTER TABLE packages DROP COLUMN upgrade_cap_id;
+ALTER TABLE packages DROP COLUMN original_id;
@@ -0,0 +1,9 @@
+-- id of the first version of the package, equal to package_id unless this is an upgrade.
+-- Packages indexed before this migration predate upgrades and are their own original.
+ALTER TABLE packages ADD COLUMN original_id address;
+UPDATE packages SET original_id = package_id;
+ALTER TABLE packages ALTER COLUMN original_id SET NOT NULL;
+-- null for packages without an upgrade cap, e.g. the framework packages
+ALTER TABLE packages ADD COLUMN upgrade_cap_id address;
+
+CREATE INDEX packages_original_id ON packages (original_id);
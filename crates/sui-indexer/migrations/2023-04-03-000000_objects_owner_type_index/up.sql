@@ -0,0 +1,2 @@
+-- objects owned by an address are filtered by type off this index, see get_owned_objects
+CREATE INDEX objects_owner_object_type ON objects (owner_address, object_type);
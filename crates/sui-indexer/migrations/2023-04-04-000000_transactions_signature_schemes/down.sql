@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS transactions_signature_schemes;
+ALTER TABLE transactions DROP COLUMN multisig_schemes;
+ALTER TABLE transactions DROP COLUMN multisig_threshold;
+ALTER TABLE transactions DROP COLUMN signature_schemes;
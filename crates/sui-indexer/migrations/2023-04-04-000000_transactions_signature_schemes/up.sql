@@ -0,0 +1,9 @@
+-- signature scheme of each transaction signature, the sender's first.
+-- Empty for transactions indexed before this migration.
+ALTER TABLE transactions ADD COLUMN signature_schemes TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE transactions ALTER COLUMN signature_schemes DROP DEFAULT;
+-- threshold and public key schemes of the sender's multisig, if the sender signed with one
+ALTER TABLE transactions ADD COLUMN multisig_threshold INT;
+ALTER TABLE transactions ADD COLUMN multisig_schemes TEXT[];
+
+CREATE INDEX transactions_signature_schemes ON transactions USING GIN (signature_schemes);
@@ -22,6 +22,8 @@ use sui_types::event::EventID;
 use crate::errors::IndexerError;
 use crate::store::IndexerStore;
 
+const MAX_EVENT_PAGE_SIZE: usize = 1000;
+
 pub(crate) struct EventReadApi<S> {
     state: S,
     fullnode: HttpClient,
@@ -47,8 +49,22 @@ impl<S: IndexerStore> EventReadApi<S> {
         limit: Option<usize>,
         descending_order: Option<bool>,
     ) -> Result<EventPage, IndexerError> {
-        self.state
-            .get_events(query, cursor, limit, descending_order.unwrap_or_default())
+        // larger pages are cut down to the maximum, the cursor leads to the rest
+        let limit = limit.map_or(MAX_EVENT_PAGE_SIZE, |l| l.min(MAX_EVENT_PAGE_SIZE));
+        let indexer_cursor = cursor
+            .map(|id| self.state.get_event_cursor(id))
+            .transpose()?;
+        let (data, next_cursor) = self.state.get_events(
+            query,
+            indexer_cursor,
+            limit,
+            descending_order.unwrap_or_default(),
+        )?;
+        Ok(EventPage {
+            next_cursor: data.last().map(|e| e.id.clone()),
+            has_next_page: next_cursor.is_some(),
+            data,
+        })
     }
 }
 
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::errors::IndexerError;
-use crate::store::IndexerStore;
+use crate::store::{ConsistencyLevel, IndexerStore};
 use crate::types::SuiTransactionFullResponse;
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
@@ -96,27 +96,27 @@ impl<S: IndexerStore> ReadApi<S> {
             ));
         }
 
-        let digests_from_db = match query.filter {
+        let (digests_from_db, next_cursor_from_db) = match query.filter {
             None => {
-                let indexer_seq_number = self
-                    .state
-                    .get_transaction_sequence_by_digest(cursor_str, is_descending)?;
+                let indexer_cursor = cursor_str
+                    .map(|digest| self.state.get_transaction_cursor_by_digest(&digest))
+                    .transpose()?;
                 self.state
-                    .get_all_transaction_digest_page(indexer_seq_number, limit, is_descending)
+                    .get_all_transaction_digest_page(indexer_cursor, limit, is_descending)
             }
             Some(TransactionFilter::MoveFunction {
                 package,
                 module,
                 function,
             }) => {
-                let move_call_seq_number = self
-                    .state
-                    .get_move_call_sequence_by_digest(cursor_str, is_descending)?;
+                let move_call_cursor = cursor_str
+                    .map(|digest| self.state.get_move_call_cursor_by_digest(&digest))
+                    .transpose()?;
                 self.state.get_transaction_digest_page_by_move_call(
                     package.to_string(),
                     module,
                     function,
-                    move_call_seq_number,
+                    move_call_cursor,
                     limit,
                     is_descending,
                 )
@@ -125,44 +125,43 @@ impl<S: IndexerStore> ReadApi<S> {
             // SuiTransactionResponse, instead we should store the BCS
             // serialized transaction and retrive from there.
             // This is now blocked by the endpoint on FN side.
-            Some(TransactionFilter::InputObject(_input_obj_id)) => Ok(vec![]),
+            Some(TransactionFilter::InputObject(_input_obj_id)) => Ok((vec![], None)),
             Some(TransactionFilter::ChangedObject(mutated_obj_id)) => {
-                let indexer_seq_number = self
-                    .state
-                    .get_transaction_sequence_by_digest(cursor_str, is_descending)?;
+                let indexer_cursor = cursor_str
+                    .map(|digest| self.state.get_transaction_cursor_by_digest(&digest))
+                    .transpose()?;
                 self.state.get_transaction_digest_page_by_mutated_object(
                     mutated_obj_id.to_string(),
-                    indexer_seq_number,
-                    limit + 1,
+                    indexer_cursor,
+                    limit,
                     is_descending,
                 )
             }
             Some(TransactionFilter::FromAddress(sender_address)) => {
-                let indexer_seq_number = self
-                    .state
-                    .get_transaction_sequence_by_digest(cursor_str, is_descending)?;
+                let indexer_cursor = cursor_str
+                    .map(|digest| self.state.get_transaction_cursor_by_digest(&digest))
+                    .transpose()?;
                 self.state.get_transaction_digest_page_by_sender_address(
                     sender_address.to_string(),
-                    indexer_seq_number,
-                    limit + 1,
+                    indexer_cursor,
+                    limit,
                     is_descending,
                 )
             }
             Some(TransactionFilter::ToAddress(recipient_address)) => {
-                let recipient_seq_number = self
-                    .state
-                    .get_recipient_sequence_by_digest(cursor_str, is_descending)?;
+                let recipient_cursor = cursor_str
+                    .map(|digest| self.state.get_recipient_cursor_by_digest(&digest))
+                    .transpose()?;
                 self.state.get_transaction_digest_page_by_recipient_address(
                     recipient_address.to_string(),
-                    recipient_seq_number,
-                    limit + 1,
+                    recipient_cursor,
+                    limit,
                     is_descending,
                 )
             }
         }?;
 
-        // digests here are of size (limit + 1), where the last one is the cursor for the next page
-        let mut txn_digests = digests_from_db
+        let txn_digests = digests_from_db
             .iter()
             .map(|digest| {
                 let txn_digest: Result<TransactionDigest, _> = digest.clone().parse();
@@ -175,8 +174,7 @@ impl<S: IndexerStore> ReadApi<S> {
             })
             .collect::<Result<Vec<TransactionDigest>, IndexerError>>()?;
 
-        let has_next_page = txn_digests.len() > limit;
-        txn_digests.truncate(limit);
+        let has_next_page = next_cursor_from_db.is_some();
         let next_cursor = txn_digests.last().cloned().map_or(cursor, Some);
 
         Ok(Page {
@@ -194,7 +192,7 @@ impl<S: IndexerStore> ReadApi<S> {
         object_id: ObjectID,
         options: Option<SuiObjectDataOptions>,
     ) -> Result<SuiObjectResponse, IndexerError> {
-        let read = self.state.get_object(object_id, None)?;
+        let read = self.state.get_object(object_id, None, ConsistencyLevel::Latest)?;
         Ok((read, options.unwrap_or_default()).try_into()?)
     }
 
@@ -0,0 +1,127 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use clap::{ArgMatches, Command, ValueSource};
+use serde_json::Value;
+
+use crate::errors::IndexerError;
+
+/// Prepend to `args` the flags for the values of the `--config` file that `args` and the
+/// environment leave unset, and `--db-url` read from `--db-url-file` when `--db-url` is unset, so
+/// that clap parses and validates every source alike. From highest to lowest precedence, a flag is
+/// read from the command line, the environment, `--db-url-file` for `--db-url`, the config file,
+/// and finally its default value.
+pub(crate) fn merge_config_sources(
+    command: Command,
+    mut args: Vec<OsString>,
+) -> Result<Vec<OsString>, IndexerError> {
+    // Required flags may only be set by the config file, which this first pass ignores. Any other
+    // error, or a request for help, is reported by the final parse.
+    let Ok(matches) = command.clone().ignore_errors(true).try_get_matches_from(&args) else {
+        return Ok(args);
+    };
+
+    let mut file_values = match matches.value_of_os("config_file") {
+        Some(path) => read_config_file(Path::new(path))?,
+        None => BTreeMap::new(),
+    };
+    let mut merged = vec![];
+
+    if !is_set_explicitly(&matches, "db_url") {
+        let db_url_file = match matches.value_of_os("db_url_file") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => match file_values.get("db-url-file") {
+                Some(Value::String(path)) => Some(PathBuf::from(path)),
+                _ => None,
+            },
+        };
+        if let Some(path) = db_url_file {
+            let db_url = std::fs::read_to_string(&path).map_err(|e| {
+                IndexerError::ConfigError(format!(
+                    "Failed to read the db url from {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            file_values.remove("db-url");
+            merged.push(format!("--db-url={}", db_url.trim()).into());
+        }
+    }
+
+    for (key, value) in file_values {
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(key.as_str()) && arg.get_id() != "config_file")
+            .ok_or_else(|| {
+                IndexerError::ConfigError(format!("Unknown key `{}` in the config file", key))
+            })?;
+        if is_set_explicitly(&matches, arg.get_id()) {
+            continue;
+        }
+        let values = match value {
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            match value {
+                Value::Bool(set) if !arg.is_takes_value_set() => {
+                    if set {
+                        merged.push(format!("--{}", key).into());
+                    }
+                }
+                Value::String(value) => merged.push(format!("--{}={}", key, value).into()),
+                Value::Bool(_) | Value::Number(_) => {
+                    merged.push(format!("--{}={}", key, value).into())
+                }
+                Value::Null | Value::Array(_) | Value::Object(_) => {
+                    return Err(IndexerError::ConfigError(format!(
+                        "Unsupported value `{}` for key `{}` in the config file",
+                        value, key
+                    )))
+                }
+            }
+        }
+    }
+
+    // The merged flags go right after the binary name, before any subcommand.
+    let rest = args.split_off(args.len().min(1));
+    args.extend(merged);
+    args.extend(rest);
+    Ok(args)
+}
+
+fn is_set_explicitly(matches: &ArgMatches, id: &str) -> bool {
+    matches!(
+        matches.value_source(id),
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+    )
+}
+
+/// The values of a YAML or TOML config file, keyed by flag name. TOML files are told apart by
+/// their `.toml` extension.
+fn read_config_file(path: &Path) -> Result<BTreeMap<String, Value>, IndexerError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        IndexerError::ConfigError(format!(
+            "Failed to read config file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let is_toml = path.extension().map_or(false, |extension| extension == "toml");
+    let values = if is_toml {
+        toml::from_str(&contents).map_err(|e| e.to_string())
+    } else {
+        serde_yaml::from_str(&contents).map_err(|e| e.to_string())
+    };
+    values.map_err(|e| {
+        IndexerError::ConfigError(format!(
+            "Failed to parse config file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
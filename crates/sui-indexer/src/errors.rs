@@ -62,6 +62,42 @@ pub enum IndexerError {
 
     #[error("Invalid argument with error: `{0}`")]
     InvalidArgumentError(String),
+
+    #[error("Checkpoint contents do not match the checkpoint content digest: `{0}`")]
+    ContentDigestMismatch(String),
+
+    #[error("Checkpoint summary does not match the checkpoint digest: `{0}`")]
+    CheckpointDigestMismatch(String),
+
+    #[error("Checkpoint is not certified by the committee of its epoch: `{0}`")]
+    InvalidCheckpointSignature(String),
+
+    #[error("Indexer handler `{0}` is disabled, the data it indexes is not available")]
+    HandlerDisabled(String),
+
+    #[error("Indexer checkpoint ingestion stalled: `{0}`")]
+    IngestionStalled(String),
+
+    #[error("Indexer object history is disabled by --index-object-history=false")]
+    ObjectHistoryDisabled,
+
+    #[error("Indexer has not ingested the epoch data: `{0}`")]
+    EpochNotIndexed(String),
+
+    #[error("Indexer has not observed the treasury cap of coin type: `{0}`")]
+    TreasuryCapNotIndexed(String),
+
+    #[error("Indexer config error: `{0}`")]
+    ConfigError(String),
+
+    #[error("Indexer fullnode is on another chain than the indexed one: `{0}`")]
+    ChainIdentifierMismatch(String),
+
+    #[error("Indexer has not committed the checkpoint yet: `{0}`")]
+    CheckpointNotCommitted(String),
+
+    #[error("Indexer cannot prove the object inclusion, the data is not indexed: `{0}`")]
+    InclusionProofUnavailable(String),
 }
 
 impl IndexerError {
@@ -85,6 +121,18 @@ impl IndexerError {
             IndexerError::ObjectIdParseError(_) => "ObjectIdParseError".into(),
             IndexerError::SuiError(_) => "SuiError".into(),
             IndexerError::InvalidArgumentError(_) => "InvalidArgumentError".into(),
+            IndexerError::ContentDigestMismatch(_) => "ContentDigestMismatch".into(),
+            IndexerError::CheckpointDigestMismatch(_) => "CheckpointDigestMismatch".into(),
+            IndexerError::InvalidCheckpointSignature(_) => "InvalidCheckpointSignature".into(),
+            IndexerError::HandlerDisabled(_) => "HandlerDisabled".into(),
+            IndexerError::IngestionStalled(_) => "IngestionStalled".into(),
+            IndexerError::ObjectHistoryDisabled => "ObjectHistoryDisabled".into(),
+            IndexerError::EpochNotIndexed(_) => "EpochNotIndexed".into(),
+            IndexerError::TreasuryCapNotIndexed(_) => "TreasuryCapNotIndexed".into(),
+            IndexerError::ConfigError(_) => "ConfigError".into(),
+            IndexerError::ChainIdentifierMismatch(_) => "ChainIdentifierMismatch".into(),
+            IndexerError::CheckpointNotCommitted(_) => "CheckpointNotCommitted".into(),
+            IndexerError::InclusionProofUnavailable(_) => "InclusionProofUnavailable".into(),
         }
     }
 }
@@ -2,12 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::errors::IndexerError;
+use crate::handlers::committee_cache::CommitteeCache;
+use crate::handlers::health::LastError;
+use crate::handlers::package_cache::PackageCache;
+use crate::handlers::shutdown::ShutdownSignal;
+use crate::handlers::throughput::ThroughputWindow;
+use crate::handlers::watchdog::IngestionProgress;
 use crate::metrics::IndexerCheckpointHandlerMetrics;
 use crate::models::checkpoints::Checkpoint;
+use crate::models::coin_supply::CoinSupply;
+use crate::models::epochs::Epoch;
+use crate::models::events::Event;
 use crate::models::move_calls::MoveCall;
+use crate::models::object_transactions::ObjectTransaction;
 use crate::models::objects::{DeletedObject, Object, ObjectStatus};
+use crate::models::owners::OwnerType;
 use crate::models::packages::Package;
+use crate::models::ptb_commands::PtbCommand;
+use crate::models::raw_transactions::RawTransaction;
 use crate::models::recipients::Recipient;
+use crate::models::transaction_inputs::TransactionInput;
 use crate::models::transactions::Transaction;
 use crate::multi_get_full_transactions;
 use crate::store::{
@@ -15,32 +29,138 @@ use crate::store::{
     TransactionObjectChanges,
 };
 use crate::types::SuiTransactionFullResponse;
-use futures::future::join_all;
-use futures::FutureExt;
+use crate::{IndexerHandler, UnresolvedTypePolicy};
+use futures::future::{join_all, select, Either};
+use futures::{pin_mut, FutureExt, StreamExt};
+use move_core_types::language_storage::{StructTag, TypeTag};
 use mysten_metrics::spawn_monitored_task;
 use prometheus::Registry;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use sui_core::event_handler::EventHandler;
 use sui_json_rpc_types::{
-    OwnedObjectRef, SuiCommand, SuiGetPastObjectRequest, SuiObjectData, SuiObjectDataOptions,
-    SuiRawData, SuiTransactionDataAPI, SuiTransactionEffectsAPI, SuiTransactionKind,
+    Checkpoint as RpcCheckpoint, OwnedObjectRef, SuiCommand, SuiGetPastObjectRequest,
+    SuiObjectData, SuiObjectDataOptions, SuiObjectRef, SuiRawData, SuiTransaction,
+    SuiTransactionDataAPI, SuiTransactionEffects, SuiTransactionEffectsAPI, SuiTransactionKind,
 };
 use sui_sdk::error::Error;
 use sui_sdk::SuiClient;
-use sui_types::messages_checkpoint::CheckpointSequenceNumber;
-use sui_types::object::Owner;
+use sui_types::base_types::{ExecutionDigests, ObjectType, SequenceNumber, TransactionDigest};
+use sui_types::coin::TreasuryCap;
+use sui_types::message_envelope::Message;
+use sui_types::messages::{
+    Argument, CallArg, Command, SenderSignedData, TransactionDataAPI, TransactionEffects,
+    TransactionEffectsAPI, TransactionKind,
+};
+use sui_types::messages_checkpoint::{CheckpointContents, CheckpointSequenceNumber};
+use sui_types::move_package::UpgradeCap;
+use sui_types::object::{Object as SuiObject, Owner};
+use sui_types::sui_system_state::get_sui_system_state;
+use sui_types::SUI_SYSTEM_STATE_OBJECT_ID;
+use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
-use tracing::{error, info, warn};
+use tracing::{error, info, instrument, warn};
 
 const HANDLER_RETRY_INTERVAL_IN_SECS: u64 = 10;
+/// Most checkpoints a lagging handler commits between two checkpoints of the other handlers,
+/// unless it is too far behind them, see `catch_up_handlers`.
+const CATCH_UP_BATCH_SIZE: usize = 10;
 const MULTI_GET_CHUNK_SIZE: usize = 500;
+/// Window over which the ingestion throughput gauges are computed.
+const THROUGHPUT_WINDOW: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Where the checkpoint handler reads checkpoints from.
+pub enum CheckpointSource {
+    /// Follow the fullnode, optionally writing every checkpoint to `capture_dir` so that it can
+    /// be replayed with `Directory`.
+    Fullnode {
+        rpc_client: SuiClient,
+        capture_dir: Option<PathBuf>,
+    },
+    /// Replay the checkpoints captured in a directory, without a network, and stop after the
+    /// last one. Used to test the ingestion pipeline deterministically.
+    Directory(PathBuf),
+}
 
 pub struct CheckpointHandler<S> {
     state: S,
-    rpc_client: SuiClient,
+    source: CheckpointSource,
     event_handler: Arc<EventHandler>,
     metrics: IndexerCheckpointHandlerMetrics,
+    store_raw_bcs: bool,
+    package_cache: PackageCache,
+    enabled_handlers: BTreeSet<IndexerHandler>,
+    bulk_copy_threshold: u64,
+    progress: IngestionProgress,
+    last_error: LastError,
+    object_type_allowlist: Vec<String>,
+    /// Set when the validator signatures of checkpoints are verified
+    committee_cache: Option<CommitteeCache>,
+    unresolved_type_policy: UnresolvedTypePolicy,
+    /// Set when only the checkpoints of this inclusive range are indexed
+    checkpoint_range: Option<(u64, u64)>,
+    /// Number of checkpoints fetched concurrently ahead of the one being committed
+    prefetch_depth: usize,
+    shutdown: ShutdownSignal,
+    /// How long the checkpoints already fetched may take to commit once shutdown is requested
+    drain_timeout: Duration,
+    /// How many checkpoints a failing handler may fall behind the others, 0 when it may not
+    max_handler_lag: u64,
+    /// Set when the fetched transactions are checked against the checkpoint content digest
+    verify_contents: bool,
+}
+
+/// Why a checkpoint could not be decoded.
+#[derive(Debug)]
+enum DecodeFailure {
+    /// A step only this handler needs failed, the others can do without it
+    Handler(IndexerHandler, IndexerError),
+    /// The checkpoint itself could not be decoded
+    Checkpoint(IndexerError),
+}
+
+impl DecodeFailure {
+    fn of(handler: IndexerHandler) -> impl FnOnce(IndexerError) -> Self {
+        move |e| Self::Handler(handler, e)
+    }
+}
+
+impl From<IndexerError> for DecodeFailure {
+    fn from(e: IndexerError) -> Self {
+        Self::Checkpoint(e)
+    }
+}
+
+impl From<DecodeFailure> for IndexerError {
+    fn from(failure: DecodeFailure) -> Self {
+        match failure {
+            DecodeFailure::Handler(_, e) | DecodeFailure::Checkpoint(e) => e,
+        }
+    }
+}
+
+/// A handler committing the checkpoints the other handlers committed without it.
+struct LaggingHandler {
+    /// The latest checkpoint the handler committed
+    checkpoint: i64,
+    /// When the handler may be retried after failing
+    retry_at: Instant,
+}
+
+impl LaggingHandler {
+    fn new(checkpoint: i64) -> Self {
+        Self {
+            checkpoint,
+            retry_at: Instant::now(),
+        }
+    }
+
+    /// Hold off retrying the handler for `HANDLER_RETRY_INTERVAL_IN_SECS`.
+    fn retry_later(&mut self) {
+        self.retry_at = Instant::now() + Duration::from_secs(HANDLER_RETRY_INTERVAL_IN_SECS);
+    }
 }
 
 impl<S> CheckpointHandler<S>
@@ -49,22 +169,104 @@ where
 {
     pub fn new(
         state: S,
-        rpc_client: SuiClient,
+        source: CheckpointSource,
         event_handler: Arc<EventHandler>,
         prometheus_registry: &Registry,
+        store_raw_bcs: bool,
+        package_cache_size: usize,
+        enabled_handlers: BTreeSet<IndexerHandler>,
+        bulk_copy_threshold: u64,
+        progress: IngestionProgress,
+        last_error: LastError,
+        object_type_allowlist: Vec<String>,
+        verify_checkpoint_signatures: bool,
+        unresolved_type_policy: UnresolvedTypePolicy,
+        checkpoint_range: Option<(u64, u64)>,
+        prefetch_depth: usize,
     ) -> Self {
+        let rpc_client = match &source {
+            CheckpointSource::Fullnode { rpc_client, .. } => Some(rpc_client.clone()),
+            CheckpointSource::Directory(_) => None,
+        };
         Self {
             state,
-            rpc_client,
+            committee_cache: verify_checkpoint_signatures
+                .then(|| CommitteeCache::new(rpc_client.clone())),
+            package_cache: PackageCache::new(rpc_client, package_cache_size),
+            source,
             event_handler,
             metrics: IndexerCheckpointHandlerMetrics::new(prometheus_registry),
+            store_raw_bcs,
+            enabled_handlers,
+            bulk_copy_threshold,
+            progress,
+            last_error,
+            object_type_allowlist,
+            unresolved_type_policy,
+            checkpoint_range,
+            prefetch_depth: prefetch_depth.max(1),
+            shutdown: ShutdownSignal::default(),
+            drain_timeout: Duration::ZERO,
+            max_handler_lag: 0,
+            verify_contents: true,
         }
     }
 
+    /// Stop once `shutdown` is requested, after giving the checkpoints already fetched up to
+    /// `drain_timeout` to commit. Without it the handler runs until it runs out of checkpoints.
+    pub fn with_shutdown(mut self, shutdown: ShutdownSignal, drain_timeout: Duration) -> Self {
+        self.shutdown = shutdown;
+        self.drain_timeout = drain_timeout;
+        self
+    }
+
+    /// Let a handler that fails on a checkpoint fall up to `max_handler_lag` checkpoints behind
+    /// the others, which go on committing without it while it is retried. Without it, the
+    /// failure of a handler holds back every handler. Checkpoint ranges and replays ignore it.
+    pub fn with_max_handler_lag(mut self, max_handler_lag: u64) -> Self {
+        self.max_handler_lag = max_handler_lag;
+        self
+    }
+
+    /// Check the transactions of every checkpoint against its content digest before decoding it,
+    /// which is the default.
+    pub fn with_content_verification(mut self, verify_contents: bool) -> Self {
+        self.verify_contents = verify_contents;
+        self
+    }
+
+    fn is_enabled(&self, handler: IndexerHandler) -> bool {
+        self.enabled_handlers.contains(&handler)
+    }
+
+    /// Whether the raw BCS of transactions and effects has to be fetched along with them, to
+    /// verify the checkpoint contents, to be stored, or to decode programmable transactions.
+    fn needs_raw_bcs(&self) -> bool {
+        self.verify_contents
+            || self.store_raw_bcs
+            || self.is_enabled(IndexerHandler::Transactions)
+            || self.is_enabled(IndexerHandler::MoveCalls)
+    }
+
     pub fn spawn(self) -> JoinHandle<()> {
         spawn_monitored_task!(async move {
             let mut checkpoint_handler_exec_res = self.start().await;
             while let Err(e) = &checkpoint_handler_exec_res {
+                self.last_error.record(e);
+                if self.shutdown.is_requested() {
+                    error!(
+                        "Indexer checkpoint handler failed while shutting down with error: {:?}",
+                        e
+                    );
+                    return;
+                }
+                // Replays must not retry forever on bad input, fail the replay instead.
+                if let CheckpointSource::Directory(dir) = &self.source {
+                    panic!(
+                        "Failed to replay checkpoints from {:?} with error: {:?}",
+                        dir, e
+                    );
+                }
                 warn!(
                     "Indexer checkpoint handler failed with error: {:?}, retrying after {:?} secs...",
                     e, HANDLER_RETRY_INTERVAL_IN_SECS
@@ -80,43 +282,231 @@ where
 
     async fn start(&self) -> Result<(), IndexerError> {
         info!("Indexer checkpoint handler started...");
-        let mut next_cursor_sequence_number = self.state.get_latest_checkpoint_sequence_number()?;
-        if next_cursor_sequence_number > 0 {
+        let mut next_cursor_sequence_number = match self.checkpoint_range {
+            // Ranges are committed in any order, only the checkpoints of ours tell where we are
+            Some((first, last)) => {
+                info!("Indexing checkpoints {first} to {last}");
+                self.state.register_checkpoint_range(first, last)?;
+                self.state
+                    .get_latest_checkpoint_sequence_number_in_range(first, last)?
+                    .unwrap_or(first as i64 - 1)
+            }
+            None => self.state.get_latest_checkpoint_sequence_number()?,
+        };
+        let first = self.checkpoint_range.map_or(0, |(first, _)| first as i64);
+        if next_cursor_sequence_number > first {
             info!("Resuming from checkpoint {next_cursor_sequence_number}");
         }
         next_cursor_sequence_number += 1;
+        let last = self.checkpoint_range.map_or(u64::MAX, |(_, last)| last);
+        // The handlers lag behind the checkpoints of the indexer following the chain, the
+        // checkpoints of a range are committed with every handler.
+        let lagging = match self.checkpoint_range {
+            Some(_) => BTreeMap::new(),
+            None => self
+                .state
+                .get_lagging_handlers()?
+                .into_iter()
+                .filter(|(handler, _)| self.enabled_handlers.contains(handler))
+                .map(|(handler, checkpoint)| {
+                    info!("Handler {handler} resumes from checkpoint {checkpoint}");
+                    (handler, LaggingHandler::new(checkpoint))
+                })
+                .collect(),
+        };
 
-        loop {
-            self.metrics.total_checkpoint_requested.inc();
-            let request_guard = self.metrics.full_node_read_request_latency.start_timer();
+        // Checkpoints are fetched ahead while the current one is committed, see
+        // `prefetch_checkpoints`. The committer may still be committing after every checkpoint
+        // was fetched, while the fetcher is dropped as soon as the committer stops. Once
+        // shutdown is requested the fetcher stops, and the committer is left to drain.
+        self.metrics.prefetch_buffer_depth.set(0);
+        let (sender, receiver) = mpsc::channel(1);
+        let fetcher = self.prefetch_checkpoints(next_cursor_sequence_number as u64, last, sender);
+        let committer = self.commit_checkpoints(next_cursor_sequence_number, lagging, receiver);
+        pin_mut!(fetcher, committer);
+        let committer = match select(committer, fetcher).await {
+            Either::Left((result, _)) => return result,
+            Either::Right(((), committer)) => committer,
+        };
+        let shutdown = self.shutdown.requested();
+        pin_mut!(shutdown);
+        match select(committer, shutdown).await {
+            Either::Left((result, _)) => result,
+            Either::Right(((), committer)) => {
+                info!(
+                    "Shutting down, draining the checkpoints already fetched for up to {:?}",
+                    self.drain_timeout
+                );
+                tokio::time::timeout(self.drain_timeout, committer)
+                    .await
+                    .unwrap_or_else(|_| {
+                        // Each checkpoint is committed in a single db transaction, the one cut
+                        // off is indexed again by the next run.
+                        warn!(
+                            "Draining did not finish within {:?}, stopping without it",
+                            self.drain_timeout
+                        );
+                        Ok(())
+                    })
+            }
+        }
+    }
 
-            let checkpoint = self
-                .download_checkpoint_data(next_cursor_sequence_number as u64)
-                .await.map_err(|e| {
-                    error!(
-                        "Failed to download checkpoint data with checkpoint sequence number {} and error {:?}, retrying...",
-                        next_cursor_sequence_number, e
+    /// Fetch checkpoints `first` to `last` and send them in order, up to `prefetch_depth` of
+    /// them concurrently. No more are fetched while the committer is behind, so that fetched
+    /// checkpoints do not pile up in memory. Stops once the committer is gone or shutdown is
+    /// requested, or after sending an error or the end of a checkpoint directory.
+    async fn prefetch_checkpoints(
+        &self,
+        first: CheckpointSequenceNumber,
+        last: CheckpointSequenceNumber,
+        sender: mpsc::Sender<Result<Option<CheckpointData>, IndexerError>>,
+    ) {
+        let mut checkpoints = futures::stream::iter(first..=last)
+            .map(|seq| {
+                self.metrics.total_checkpoint_requested.inc();
+                self.metrics.prefetch_buffer_depth.inc();
+                async move {
+                    let request_guard = self.metrics.full_node_read_request_latency.start_timer();
+                    let checkpoint = self.fetch_checkpoint(seq).await;
+                    request_guard.stop_and_record();
+                    checkpoint
+                }
+            })
+            .buffered(self.prefetch_depth);
+        let fetch = async {
+            while let Some(checkpoint) = checkpoints.next().await {
+                let done = !matches!(checkpoint, Ok(Some(_)));
+                if sender.send(checkpoint).await.is_err() || done {
+                    return;
+                }
+            }
+        };
+        // Dropping the sender on shutdown tells the committer that no more checkpoints come
+        let shutdown = self.shutdown.requested();
+        pin_mut!(fetch, shutdown);
+        select(fetch, shutdown).await;
+    }
+
+    /// Index and commit the checkpoints sent by `prefetch_checkpoints`, starting with
+    /// `next_cursor_sequence_number`. The `lagging` handlers are left out until they catch up.
+    async fn commit_checkpoints(
+        &self,
+        mut next_cursor_sequence_number: i64,
+        mut lagging: BTreeMap<IndexerHandler, LaggingHandler>,
+        mut receiver: mpsc::Receiver<Result<Option<CheckpointData>, IndexerError>>,
+    ) -> Result<(), IndexerError> {
+        let mut fullnode_checkpoint = 0;
+        let mut throughput = ThroughputWindow::new(THROUGHPUT_WINDOW);
+        // The epochs before a range are ended by the indexers of the ranges before it, which
+        // may not have created the table partitions of the range's first epoch yet
+        let mut epoch_partitions_ready = self.checkpoint_range.is_none();
+        // Checkpoints and transactions committed since shutdown was requested
+        let (mut drained_checkpoints, mut drained_transactions) = (0, 0);
+        // Replays fail on the first error, and ranges commit every handler
+        let max_handler_lag = match (&self.source, self.checkpoint_range) {
+            (CheckpointSource::Fullnode { .. }, None) => self.max_handler_lag,
+            _ => 0,
+        };
+
+        loop {
+            // Only shutdown or a checkpoint range runs out of checkpoints to fetch
+            let Some(checkpoint) = receiver.recv().await else {
+                if self.shutdown.is_requested() {
+                    info!(
+                        "Drained {drained_checkpoints} checkpoints with {drained_transactions} \
+                        transactions, checkpoint {} is the last committed, stopping",
+                        next_cursor_sequence_number - 1
                     );
-                    e
-                })?;
-            request_guard.stop_and_record();
+                    return Ok(());
+                }
+                info!(
+                    "Checkpoint {} committed, the checkpoint range is done, stopping",
+                    next_cursor_sequence_number - 1
+                );
+                return Ok(());
+            };
+            self.metrics.prefetch_buffer_depth.dec();
+            let Some(mut checkpoint) = checkpoint? else {
+                info!(
+                    "No checkpoint {} to replay, stopping",
+                    next_cursor_sequence_number
+                );
+                return Ok(());
+            };
             self.metrics.total_checkpoint_received.inc();
 
-            // Index checkpoint data
+            // Index checkpoint data, without the handlers that fell behind. A handler failing on
+            // it falls behind too, if the lag allows it, for the others to go on without it.
             // TODO: Metrics
-            let (indexed_checkpoint, indexed_epoch) = self.index_checkpoint(&checkpoint)?;
+            self.catch_up_handlers(
+                &mut lagging,
+                next_cursor_sequence_number - 1,
+                max_handler_lag,
+            )
+            .await?;
+            let mut handlers: BTreeSet<IndexerHandler> = self
+                .enabled_handlers
+                .iter()
+                .filter(|handler| !lagging.contains_key(handler))
+                .copied()
+                .collect();
+            let (indexed_checkpoint, indexed_epoch) = loop {
+                match self.decode_checkpoint(&mut checkpoint, &handlers).await {
+                    Ok(indexed) => break indexed,
+                    Err(DecodeFailure::Handler(handler, e)) if max_handler_lag > 0 => {
+                        self.metrics.total_checkpoint_handler_error.inc();
+                        warn!(
+                            "Handler {handler} failed on checkpoint {} with error: {:?}, \
+                            committing it without the handler, which is retried after {:?} secs",
+                            next_cursor_sequence_number, e, HANDLER_RETRY_INTERVAL_IN_SECS
+                        );
+                        let committed = next_cursor_sequence_number - 1;
+                        self.state.mark_handler_lagging(handler, committed)?;
+                        let mut lagging_handler = LaggingHandler::new(committed);
+                        lagging_handler.retry_later();
+                        lagging.insert(handler, lagging_handler);
+                        handlers.remove(&handler);
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            };
+            if !epoch_partitions_ready {
+                if let Some(previous_epoch) = checkpoint.checkpoint.epoch.checked_sub(1) {
+                    self.state.persist_epoch(&TemporaryEpochStore {
+                        owner_index: vec![],
+                        epoch_id: previous_epoch,
+                    })?;
+                }
+                epoch_partitions_ready = true;
+            }
 
             // Write to DB
             let db_guard = self.metrics.db_write_request_latency.start_timer();
             let tx_count = indexed_checkpoint.transactions.len();
             let object_count = indexed_checkpoint.objects_changes.len();
-
-            self.state.persist_checkpoint(&indexed_checkpoint)?;
+            self.commit_checkpoint(&indexed_checkpoint, &mut fullnode_checkpoint)
+                .await?;
             info!(
                 "Checkpoint {} committed with {tx_count} transactions and {object_count} objects.",
                 next_cursor_sequence_number
             );
             self.metrics.total_checkpoint_processed.inc();
+            self.record_handler_progress(&lagging, next_cursor_sequence_number);
+            if self.shutdown.is_requested() {
+                drained_checkpoints += 1;
+                drained_transactions += tx_count;
+            }
+            self.progress.record();
+            self.last_error.clear();
+            let (checkpoints_per_second, transactions_per_second) =
+                throughput.record(std::time::Instant::now(), tx_count);
+            self.metrics
+                .checkpoints_per_second
+                .set(checkpoints_per_second);
+            self.metrics
+                .transactions_per_second
+                .set(transactions_per_second);
             db_guard.stop_and_record();
 
             // Process websocket subscription
@@ -135,13 +525,441 @@ where
         }
     }
 
+    /// Commit the checkpoints up to `committed` that the `lagging` handlers were left out of,
+    /// up to `CATCH_UP_BATCH_SIZE` of them per handler so that the other handlers are not held
+    /// back for long. A handler that fails is retried later, unless it is more than
+    /// `max_handler_lag` checkpoints behind: it is then caught up before anything else is
+    /// committed, and its failure fails the checkpoint handler. The handlers caught up with
+    /// `committed` are committed with the others again.
+    async fn catch_up_handlers(
+        &self,
+        lagging: &mut BTreeMap<IndexerHandler, LaggingHandler>,
+        committed: i64,
+        max_handler_lag: u64,
+    ) -> Result<(), IndexerError> {
+        for (handler, lagging_handler) in lagging.iter_mut() {
+            let blocking = committed - lagging_handler.checkpoint > max_handler_lag as i64;
+            let mut batch = 0;
+            while lagging_handler.checkpoint < committed
+                && (blocking
+                    || (batch < CATCH_UP_BATCH_SIZE && lagging_handler.retry_at <= Instant::now()))
+            {
+                let checkpoint = lagging_handler.checkpoint + 1;
+                match self.catch_up_handler(*handler, checkpoint).await {
+                    Ok(()) => {
+                        lagging_handler.checkpoint = checkpoint;
+                        batch += 1;
+                    }
+                    Err(e) if blocking => return Err(e),
+                    Err(e) => {
+                        self.metrics.total_checkpoint_handler_error.inc();
+                        warn!(
+                            "Handler {handler} failed again on checkpoint {checkpoint} with \
+                            error: {:?}, retrying after {:?} secs",
+                            e, HANDLER_RETRY_INTERVAL_IN_SECS
+                        );
+                        lagging_handler.retry_later();
+                    }
+                }
+            }
+        }
+
+        let caught_up: Vec<IndexerHandler> = lagging
+            .iter()
+            .filter(|(_, lagging_handler)| lagging_handler.checkpoint >= committed)
+            .map(|(handler, _)| *handler)
+            .collect();
+        for handler in caught_up {
+            self.state.mark_handler_caught_up(handler)?;
+            lagging.remove(&handler);
+            info!("Handler {handler} caught up with checkpoint {committed}");
+        }
+        Ok(())
+    }
+
+    /// Commit the rows of `handler` for checkpoint `seq`, which was committed without it.
+    #[instrument(name = "catch_up_handler", skip(self))]
+    async fn catch_up_handler(
+        &self,
+        handler: IndexerHandler,
+        seq: i64,
+    ) -> Result<(), IndexerError> {
+        let Some(mut checkpoint) = self.fetch_checkpoint(seq as u64).await? else {
+            return Err(IndexerError::UncategorizedError(anyhow::anyhow!(
+                "Checkpoint {} to catch up handler {} with is missing",
+                seq,
+                handler
+            )));
+        };
+        let (mut indexed_checkpoint, _) = self
+            .decode_checkpoint(&mut checkpoint, &BTreeSet::from([handler]))
+            .await?;
+        // The epoch was written along with the checkpoint
+        indexed_checkpoint.epoch = None;
+        self.state
+            .persist_handler_checkpoint(handler, &indexed_checkpoint)?;
+        Ok(())
+    }
+
+    fn record_handler_progress(
+        &self,
+        lagging: &BTreeMap<IndexerHandler, LaggingHandler>,
+        committed: i64,
+    ) {
+        for handler in &self.enabled_handlers {
+            let checkpoint = lagging
+                .get(handler)
+                .map_or(committed, |lagging_handler| lagging_handler.checkpoint);
+            let label = handler.to_string();
+            self.metrics
+                .handler_checkpoint
+                .with_label_values(&[&label])
+                .set(checkpoint);
+            self.metrics
+                .handler_lag
+                .with_label_values(&[&label])
+                .set(committed - checkpoint);
+        }
+    }
+
+    /// Read checkpoint `checkpoint` from the configured source, returns None once a checkpoint
+    /// directory has no more checkpoints to replay.
+    #[instrument(name = "fetch_checkpoint", skip(self))]
+    async fn fetch_checkpoint(
+        &self,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<Option<CheckpointData>, IndexerError> {
+        match &self.source {
+            CheckpointSource::Fullnode {
+                rpc_client,
+                capture_dir,
+            } => {
+                let data = self
+                    .download_checkpoint_data(rpc_client, checkpoint)
+                    .await.map_err(|e| {
+                        error!(
+                            "Failed to download checkpoint data with checkpoint sequence number {} and error {:?}, retrying...",
+                            checkpoint, e
+                        );
+                        e
+                    })?;
+                if let Some(capture_dir) = capture_dir {
+                    write_checkpoint_data(capture_dir, &data)?;
+                }
+                Ok(Some(data))
+            }
+            CheckpointSource::Directory(dir) => read_checkpoint_data(dir, checkpoint),
+        }
+    }
+
+    /// Verify and decode the checkpoint data, and turn it into the rows of `handlers` to write.
+    /// The failures of the steps that only some handler needs are blamed on that handler.
+    #[instrument(
+        name = "decode_checkpoint",
+        skip_all,
+        fields(checkpoint = data.checkpoint.sequence_number)
+    )]
+    async fn decode_checkpoint(
+        &self,
+        data: &mut CheckpointData,
+        handlers: &BTreeSet<IndexerHandler>,
+    ) -> Result<(TemporaryCheckpointStore, Option<TemporaryEpochStore>), DecodeFailure> {
+        // Make sure the fullnode served the contents the checkpoint commits to
+        if self.verify_contents {
+            verify_checkpoint_contents(data)?;
+        }
+        // Make sure the validators certified the checkpoint, rather than trusting the fullnode
+        if let Some(committee_cache) = &self.committee_cache {
+            committee_cache.verify_checkpoint(&data.checkpoint).await?;
+        }
+
+        // Decode events ourselves, against the package versions of this checkpoint
+        if handlers.contains(&IndexerHandler::Events)
+            || handlers.contains(&IndexerHandler::Objects)
+        {
+            self.package_cache
+                .observe_packages(data.changed_objects.iter().map(|(_, o)| o));
+        }
+        if handlers.contains(&IndexerHandler::Events) {
+            for tx in &mut data.transactions {
+                for event in &mut tx.events.data {
+                    event.parsed_json = self
+                        .package_cache
+                        .parse_event(event)
+                        .await
+                        .map_err(DecodeFailure::of(IndexerHandler::Events))?;
+                }
+            }
+        }
+        let unresolved = self
+            .resolve_object_types(data, handlers)
+            .await
+            .map_err(DecodeFailure::of(IndexerHandler::Objects))?;
+        let previous_cp = self.previous_checkpoint(&data.checkpoint).await?;
+        let (mut indexed_checkpoint, indexed_epoch) =
+            self.index_checkpoint(data, &previous_cp, handlers)?;
+        flag_unresolved_objects(&mut indexed_checkpoint, &unresolved);
+        if handlers.contains(&IndexerHandler::MoveCalls) {
+            indexed_checkpoint.transaction_inputs = self
+                .index_transaction_inputs(data)
+                .await
+                .map_err(DecodeFailure::of(IndexerHandler::MoveCalls))?;
+        }
+        Ok((indexed_checkpoint, indexed_epoch))
+    }
+
+    /// Write the indexed checkpoint to the store. `fullnode_checkpoint` is the last seen tip of
+    /// the fullnode, which decides between bulk and regular writes.
+    #[instrument(
+        name = "commit_checkpoint",
+        skip_all,
+        fields(checkpoint = indexed_checkpoint.checkpoint.sequence_number)
+    )]
+    async fn commit_checkpoint(
+        &self,
+        indexed_checkpoint: &TemporaryCheckpointStore,
+        fullnode_checkpoint: &mut u64,
+    ) -> Result<(), IndexerError> {
+        // Bulk load while far behind the fullnode, the tip is only refreshed once we get
+        // close to the last one seen.
+        let checkpoint = indexed_checkpoint.checkpoint.sequence_number as u64;
+        let behind = |tip: u64| tip.saturating_sub(checkpoint);
+        if let CheckpointSource::Fullnode { rpc_client, .. } = &self.source {
+            if self.bulk_copy_threshold > 0
+                && behind(*fullnode_checkpoint) < self.bulk_copy_threshold
+            {
+                *fullnode_checkpoint = get_latest_fullnode_checkpoint(rpc_client).await?;
+            }
+        }
+        if self.bulk_copy_threshold > 0 && behind(*fullnode_checkpoint) >= self.bulk_copy_threshold
+        {
+            self.state.persist_checkpoint_bulk(indexed_checkpoint)?;
+        } else {
+            self.state.persist_checkpoint(indexed_checkpoint)?;
+        }
+        self.record_rows_written(indexed_checkpoint);
+        Ok(())
+    }
+
+    /// Index a single transaction as part of its checkpoint, without writing anything, and
+    /// return the rows that would be written for it.
+    pub async fn debug_replay(
+        &self,
+        digest: TransactionDigest,
+    ) -> Result<TemporaryCheckpointStore, IndexerError> {
+        let CheckpointSource::Fullnode { rpc_client, .. } = &self.source else {
+            return Err(IndexerError::InvalidArgumentError(
+                "Replaying a transaction requires a fullnode, not a checkpoint directory".to_string(),
+            ));
+        };
+        let tx = multi_get_full_transactions(rpc_client.read_api(), vec![digest], false)
+            .await?
+            .pop()
+            .ok_or_else(|| {
+                IndexerError::FullNodeReadingError(format!("Transaction {digest} not found"))
+            })?;
+        let mut checkpoint = self
+            .download_checkpoint_data(rpc_client, tx.checkpoint)
+            .await?;
+        // Objects were fetched at the version written by their last transaction of the checkpoint
+        checkpoint.transactions.retain(|tx| tx.digest == digest);
+        checkpoint
+            .changed_objects
+            .retain(|(_, o)| o.previous_transaction == Some(digest));
+
+        if self.is_enabled(IndexerHandler::Events) || self.is_enabled(IndexerHandler::Objects) {
+            self.package_cache
+                .observe_packages(checkpoint.changed_objects.iter().map(|(_, o)| o));
+        }
+        if self.is_enabled(IndexerHandler::Events) {
+            for tx in &mut checkpoint.transactions {
+                for event in &mut tx.events.data {
+                    event.parsed_json = self.package_cache.parse_event(event).await?;
+                }
+            }
+        }
+        let unresolved = self
+            .resolve_object_types(&mut checkpoint, &self.enabled_handlers)
+            .await?;
+        let previous_cp = self.previous_checkpoint(&checkpoint.checkpoint).await?;
+        let (mut indexed_checkpoint, _) =
+            self.index_checkpoint(&checkpoint, &previous_cp, &self.enabled_handlers)?;
+        flag_unresolved_objects(&mut indexed_checkpoint, &unresolved);
+        if self.is_enabled(IndexerHandler::MoveCalls) {
+            indexed_checkpoint.transaction_inputs =
+                self.index_transaction_inputs(&checkpoint).await?;
+        }
+        Ok(indexed_checkpoint)
+    }
+
+    /// Check that the type of every changed object resolves against the packages we can read,
+    /// and apply the unresolved type policy to the objects whose type does not. Returns the ids
+    /// of the objects to flag with `type_unresolved`.
+    async fn resolve_object_types(
+        &self,
+        data: &mut CheckpointData,
+        handlers: &BTreeSet<IndexerHandler>,
+    ) -> Result<BTreeSet<String>, IndexerError> {
+        if !handlers.contains(&IndexerHandler::Objects) {
+            return Ok(BTreeSet::new());
+        }
+        // Objects of a checkpoint share few types, each is only resolved once.
+        let mut types = BTreeMap::<TypeTag, bool>::new();
+        let mut unresolved = BTreeSet::new();
+        for (_, o) in &data.changed_objects {
+            let Some(ObjectType::Struct(type_)) = &o.type_ else {
+                continue;
+            };
+            let type_ = TypeTag::from(type_.clone());
+            let resolved = match types.get(&type_) {
+                Some(resolved) => *resolved,
+                None => {
+                    let resolved = match self.package_cache.resolve_type(&type_).await {
+                        Ok(()) => true,
+                        Err(e) if self.unresolved_type_policy == UnresolvedTypePolicy::Fail => {
+                            return Err(e)
+                        }
+                        Err(e) => {
+                            warn!(
+                                "Type {} of object {} is unresolved: {:?}",
+                                type_, o.object_id, e
+                            );
+                            false
+                        }
+                    };
+                    types.insert(type_, resolved);
+                    resolved
+                }
+            };
+            if !resolved {
+                self.metrics.unresolved_types.inc();
+                unresolved.insert(o.object_id.to_string());
+            }
+        }
+        if self.unresolved_type_policy == UnresolvedTypePolicy::Skip {
+            data.changed_objects
+                .retain(|(_, o)| !unresolved.contains(&o.object_id.to_string()));
+            return Ok(BTreeSet::new());
+        }
+        Ok(unresolved)
+    }
+
+    fn record_rows_written(&self, data: &TemporaryCheckpointStore) {
+        let objects = data
+            .objects_changes
+            .iter()
+            .map(|changes| changes.mutated_objects.len() + changes.deleted_objects.len())
+            .sum::<usize>();
+        for (table, rows) in [
+            ("checkpoints", 1),
+            ("transactions", data.transactions.len()),
+            ("raw_transactions", data.raw_transactions.len()),
+            ("events", data.events.len()),
+            ("objects", objects),
+            ("addresses", data.addresses.len()),
+            ("packages", data.packages.len()),
+            ("move_calls", data.move_calls.len()),
+            ("object_transactions", data.object_transactions.len()),
+            ("transaction_inputs", data.transaction_inputs.len()),
+            ("ptb_commands", data.ptb_commands.len()),
+            ("recipients", data.recipients.len()),
+            ("epochs", data.epoch.iter().count()),
+            ("coin_supply", data.coin_supplies.len()),
+        ] {
+            self.metrics
+                .rows_written
+                .with_label_values(&[table])
+                .inc_by(rows as u64);
+        }
+    }
+
+    /// The row of the checkpoint before `checkpoint`, which its transaction counts are derived
+    /// from. The checkpoint before a range belongs to another range, which may not be indexed
+    /// yet, so it is rebuilt from the source instead of read from the store.
+    async fn previous_checkpoint(
+        &self,
+        checkpoint: &RpcCheckpoint,
+    ) -> Result<Checkpoint, IndexerError> {
+        if checkpoint.sequence_number == 0 {
+            return Ok(Checkpoint::default());
+        }
+        let previous = checkpoint.sequence_number - 1;
+        match self.checkpoint_range {
+            Some((first, _)) if checkpoint.sequence_number == first => {
+                self.rebuild_checkpoint(previous).await
+            }
+            _ => self.state.get_checkpoint(previous.into()),
+        }
+    }
+
+    /// Rebuild the row of checkpoint `seq` from the source, as far as the row of the next
+    /// checkpoint depends on it: its timestamp, its transaction totals and whether it ends its
+    /// epoch. The transactions of its epoch are counted from the last checkpoint of the previous
+    /// epoch, which is found by bisection as epochs never decrease.
+    async fn rebuild_checkpoint(
+        &self,
+        seq: CheckpointSequenceNumber,
+    ) -> Result<Checkpoint, IndexerError> {
+        let checkpoint = self.fetch_checkpoint_summary(seq).await?;
+        let mut row = Checkpoint::from(&checkpoint, &Checkpoint::default())?;
+
+        let (mut epoch_start, mut high) = (0, seq);
+        while epoch_start < high {
+            let mid = epoch_start + (high - epoch_start) / 2;
+            if self.fetch_checkpoint_summary(mid).await?.epoch < checkpoint.epoch {
+                epoch_start = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let previous_epochs_transactions = match epoch_start.checked_sub(1) {
+            Some(previous_epoch_end) => {
+                self.fetch_checkpoint_summary(previous_epoch_end)
+                    .await?
+                    .network_total_transactions
+            }
+            None => 0,
+        };
+        row.total_transactions_current_epoch =
+            (checkpoint.network_total_transactions - previous_epochs_transactions) as i64;
+        Ok(row)
+    }
+
+    /// Read only the summary of checkpoint `seq` from the configured source.
+    async fn fetch_checkpoint_summary(
+        &self,
+        seq: CheckpointSequenceNumber,
+    ) -> Result<RpcCheckpoint, IndexerError> {
+        match &self.source {
+            CheckpointSource::Fullnode { rpc_client, .. } => rpc_client
+                .read_api()
+                .get_checkpoint(seq.into())
+                .await
+                .map_err(|e| {
+                    IndexerError::FullNodeReadingError(format!(
+                        "Failed to get checkpoint with sequence number {} and error {:?}",
+                        seq, e
+                    ))
+                }),
+            CheckpointSource::Directory(dir) => read_checkpoint_data(dir, seq)?
+                .map(|data| data.checkpoint)
+                .ok_or_else(|| {
+                    IndexerError::UncategorizedError(anyhow::anyhow!(
+                        "Checkpoint {} is not in {:?}",
+                        seq,
+                        dir
+                    ))
+                }),
+        }
+    }
+
     /// Download all the data we need for one checkpoint.
     async fn download_checkpoint_data(
         &self,
+        rpc_client: &SuiClient,
         seq: CheckpointSequenceNumber,
     ) -> Result<CheckpointData, IndexerError> {
-        let mut checkpoint = self
-            .rpc_client
+        let mut checkpoint = rpc_client
             .read_api()
             .get_checkpoint(seq.into())
             .await
@@ -156,8 +974,7 @@ where
         // a checkpoint not generated yet.
         while checkpoint.is_err() {
             tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-            checkpoint = self
-                .rpc_client
+            checkpoint = rpc_client
                 .read_api()
                 .get_checkpoint(seq.into())
                 .await
@@ -171,9 +988,18 @@ where
         // unwrap here is safe because we checked for error above
         let checkpoint = checkpoint.unwrap();
 
-        let transactions = join_all(checkpoint.transactions.chunks(MULTI_GET_CHUNK_SIZE).map(
-            |digests| multi_get_full_transactions(self.rpc_client.read_api(), digests.to_vec()),
-        ))
+        let transactions = join_all(
+            checkpoint
+                .transactions
+                .chunks(MULTI_GET_CHUNK_SIZE)
+                .map(|digests| {
+                    multi_get_full_transactions(
+                        rpc_client.read_api(),
+                        digests.to_vec(),
+                        self.needs_raw_bcs(),
+                    )
+                }),
+        )
         .await
         .into_iter()
         .try_fold(vec![], |mut acc, chunk| {
@@ -201,7 +1027,7 @@ where
                 },
             );
 
-        let rpc = self.rpc_client.clone();
+        let rpc = rpc_client.clone();
         let changed_objects =
             join_all(object_changes.chunks(MULTI_GET_CHUNK_SIZE).map(|objects| {
                 let wanted_past_object_statuses: Vec<ObjectStatus> =
@@ -251,6 +1077,8 @@ where
     fn index_checkpoint(
         &self,
         data: &CheckpointData,
+        previous_cp: &Checkpoint,
+        handlers: &BTreeSet<IndexerHandler>,
     ) -> Result<(TemporaryCheckpointStore, Option<TemporaryEpochStore>), IndexerError> {
         let CheckpointData {
             checkpoint,
@@ -258,142 +1086,108 @@ where
             changed_objects,
         } = data;
 
-        let previous_cp = if checkpoint.sequence_number == 0 {
-            Checkpoint::default()
+        // Index transaction, addresses are derived from the indexed transactions. Transactions
+        // are stamped with the timestamp of their checkpoint, see `query_transactions_by_time`.
+        let db_transactions: Vec<Transaction> = if handlers.contains(&IndexerHandler::Transactions)
+            || handlers.contains(&IndexerHandler::Addresses)
+        {
+            transactions
+                .iter()
+                .map(|tx| {
+                    let mut transaction = Transaction::try_from(tx.clone())?;
+                    transaction.timestamp_ms = checkpoint.timestamp_ms as i64;
+                    Ok(transaction)
+                })
+                .collect::<Result<Vec<_>, IndexerError>>()?
         } else {
-            self.state
-                .get_checkpoint((checkpoint.sequence_number - 1).into())?
+            vec![]
         };
 
-        // Index transaction
-        let db_transactions = transactions
-            .iter()
-            .map(|tx| tx.clone().try_into())
-            .collect::<Result<Vec<_>, _>>()?;
+        // Index raw transaction bytes
+        let raw_transactions =
+            if self.store_raw_bcs && handlers.contains(&IndexerHandler::Transactions) {
+                transactions
+                    .iter()
+                    .map(RawTransaction::try_from)
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                vec![]
+            };
 
         // Index events
-        let events = transactions
-            .iter()
-            .flat_map(|tx| tx.events.data.iter().map(move |event| event.clone().into()))
-            .collect::<Vec<_>>();
+        let events = if handlers.contains(&IndexerHandler::Events) {
+            transactions
+                .iter()
+                .flat_map(|tx| {
+                    tx.events
+                        .data
+                        .iter()
+                        .map(|event| Event::new(checkpoint.sequence_number, event.clone()))
+                })
+                .collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
 
         // Index objects
-        let tx_objects = changed_objects
-            .iter()
-            // Unwrap safe here as we requested previous tx data in the request.
-            .fold(BTreeMap::<_, Vec<_>>::new(), |mut acc, (status, o)| {
-                if let Some(digest) = &o.previous_transaction {
-                    acc.entry(*digest).or_default().push((status, o));
-                }
-                acc
-            });
-
-        let objects_changes = transactions
-            .iter()
-            .map(|tx| {
-                let changed_objects = tx_objects
-                    .get(&tx.digest)
-                    .unwrap_or(&vec![])
-                    .iter()
-                    .map(|(status, o)| {
-                        Object::from(&checkpoint.epoch, &checkpoint.sequence_number, status, o)
-                    })
-                    .collect::<Vec<_>>();
-                let deleted = tx.effects.deleted().iter();
-                let deleted = deleted.map(|o| (ObjectStatus::Deleted, o));
-                let wrapped = tx.effects.wrapped().iter();
-                let wrapped = wrapped.map(|o| (ObjectStatus::Wrapped, o));
-                let unwrapped_then_deleted = tx.effects.unwrapped_then_deleted().iter();
-                let unwrapped_then_deleted =
-                    unwrapped_then_deleted.map(|o| (ObjectStatus::UnwrappedThenDeleted, o));
-                let all_deleted_objects = deleted
-                    .chain(wrapped)
-                    .chain(unwrapped_then_deleted)
-                    .map(|(status, oref)| {
-                        DeletedObject::from(
-                            &checkpoint.epoch,
-                            &checkpoint.sequence_number,
-                            oref,
-                            &tx.digest,
-                            status,
-                        )
-                    })
-                    .collect();
-                TransactionObjectChanges {
-                    mutated_objects: changed_objects,
-                    deleted_objects: all_deleted_objects,
-                }
-            })
-            .collect();
+        let objects_changes = if handlers.contains(&IndexerHandler::Objects) {
+            let objects_changes = index_objects(checkpoint, transactions, changed_objects);
+            self.filter_allowed_objects(objects_changes)?
+        } else {
+            vec![]
+        };
 
         // Index addresses
-        let addresses = db_transactions
-            .iter()
-            .map(|tx: &Transaction| tx.into())
-            .collect();
+        let addresses = if handlers.contains(&IndexerHandler::Addresses) {
+            db_transactions
+                .iter()
+                .map(|tx: &Transaction| tx.into())
+                .collect()
+        } else {
+            vec![]
+        };
+        let db_transactions = if handlers.contains(&IndexerHandler::Transactions) {
+            db_transactions
+        } else {
+            vec![]
+        };
 
         // Index packages
-        let packages = Self::index_packages(transactions, changed_objects)?;
+        let packages = if handlers.contains(&IndexerHandler::Packages) {
+            self.index_packages(transactions, changed_objects)?
+        } else {
+            vec![]
+        };
 
-        let move_calls: Vec<MoveCall> = transactions
-            .iter()
-            .map(|t| {
-                let tx = t.transaction.data.transaction();
-                (
-                    tx.clone(),
-                    t.digest,
-                    checkpoint.sequence_number,
-                    checkpoint.epoch,
-                    t.transaction.data.sender(),
-                )
-            })
-            .filter_map(
-                |(tx_kind, txn_digest, checkpoint_seq, epoch, sender)| match tx_kind {
-                    SuiTransactionKind::ProgrammableTransaction(pt) => Some(
-                        pt.commands
-                            .into_iter()
-                            .filter_map(move |command| match command {
-                                SuiCommand::MoveCall(m) => Some(MoveCall {
-                                    id: None,
-                                    transaction_digest: txn_digest.to_string(),
-                                    checkpoint_sequence_number: checkpoint_seq as i64,
-                                    epoch: epoch as i64,
-                                    sender: sender.to_string(),
-                                    move_package: m.package.to_string(),
-                                    move_module: m.module,
-                                    move_function: m.function,
-                                }),
-                                _ => None,
-                            }),
-                    ),
+        let move_calls = if handlers.contains(&IndexerHandler::MoveCalls) {
+            index_move_calls(checkpoint, transactions)
+        } else {
+            vec![]
+        };
 
-                    _ => None,
-                },
-            )
-            .flatten()
-            .collect();
+        let recipients = if handlers.contains(&IndexerHandler::Recipients) {
+            index_recipients(checkpoint, transactions)
+        } else {
+            vec![]
+        };
 
-        let recipients: Vec<Recipient> = transactions
-            .iter()
-            .flat_map(|tx| {
-                let created = tx.effects.created().iter();
-                let mutated = tx.effects.mutated().iter();
-                let unwrapped = tx.effects.unwrapped().iter();
-                created
-                    .chain(mutated)
-                    .chain(unwrapped)
-                    .filter_map(|obj_ref| match obj_ref.owner {
-                        Owner::AddressOwner(address) => Some(Recipient {
-                            id: None,
-                            transaction_digest: tx.effects.transaction_digest().to_string(),
-                            checkpoint_sequence_number: checkpoint.sequence_number as i64,
-                            epoch: checkpoint.epoch as i64,
-                            recipient: address.to_string(),
-                        }),
-                        _ => None,
-                    })
-            })
-            .collect();
+        let object_transactions = if handlers.contains(&IndexerHandler::Transactions) {
+            index_object_transactions(checkpoint, transactions)
+        } else {
+            vec![]
+        };
+
+        let ptb_commands = if handlers.contains(&IndexerHandler::Transactions) {
+            index_ptb_commands(checkpoint, transactions)?
+        } else {
+            vec![]
+        };
+
+        let coin_supplies = if handlers.contains(&IndexerHandler::Objects) {
+            index_coin_supplies(checkpoint, changed_objects)?
+        } else {
+            vec![]
+        };
 
         // Index epoch
         // TODO: Aggregate all object owner changes into owner index at epoch change.
@@ -408,20 +1202,97 @@ where
 
         Ok((
             TemporaryCheckpointStore {
-                checkpoint: Checkpoint::from(checkpoint, &previous_cp)?,
+                checkpoint: Checkpoint::from(checkpoint, previous_cp)?,
                 transactions: db_transactions,
+                raw_transactions,
                 events,
                 objects_changes,
                 addresses,
                 packages,
                 move_calls,
+                object_transactions,
+                // Decoding needs the package cache, see `index_transaction_inputs`
+                transaction_inputs: vec![],
+                ptb_commands,
                 recipients,
+                epoch: index_epoch(checkpoint, changed_objects)?,
+                coin_supplies,
             },
             epoch_index,
         ))
     }
 
+    /// Decode the inputs passed to the move calls of the checkpoint's transactions against the
+    /// parameter types of the called functions. Inputs that cannot be decoded are kept as raw
+    /// bytes.
+    async fn index_transaction_inputs(
+        &self,
+        data: &CheckpointData,
+    ) -> Result<Vec<TransactionInput>, IndexerError> {
+        let mut transaction_inputs = vec![];
+        for tx in &data.transactions {
+            let sender_signed_data: SenderSignedData = bcs::from_bytes(&tx.raw_transaction)
+                .map_err(|e| {
+                    IndexerError::SerdeError(format!(
+                        "Failed to deserialize raw transaction {:?} with error: {:?}",
+                        tx.digest, e
+                    ))
+                })?;
+            let kind = sender_signed_data.transaction_data().kind();
+            let TransactionKind::ProgrammableTransaction(pt) = kind else {
+                continue;
+            };
+            for (command_index, command) in pt.commands.iter().enumerate() {
+                let Command::MoveCall(call) = command else {
+                    continue;
+                };
+                let parameter_types = self.package_cache.get_parameter_types(call).await?;
+                for (argument_index, argument) in call.arguments.iter().enumerate() {
+                    // Only inputs have a value of their own, results are not known before
+                    // execution.
+                    let Argument::Input(input_index) = argument else {
+                        continue;
+                    };
+                    let Some(input) = pt.inputs.get(*input_index as usize) else {
+                        continue;
+                    };
+                    let parameter_type = parameter_types
+                        .as_ref()
+                        .and_then(|types| types.get(argument_index));
+                    let (raw_value, value) = match input {
+                        CallArg::Pure(bytes) => {
+                            let value = match parameter_type {
+                                Some(type_) => {
+                                    self.package_cache.parse_pure_value(type_, bytes).await?
+                                }
+                                None => None,
+                            };
+                            (bytes.clone(), value)
+                        }
+                        CallArg::Object(object_arg) => {
+                            let id = object_arg.id();
+                            (id.as_ref().to_vec(), Some(serde_json::json!(id)))
+                        }
+                    };
+                    transaction_inputs.push(TransactionInput {
+                        transaction_digest: tx.digest.to_string(),
+                        checkpoint_sequence_number: data.checkpoint.sequence_number as i64,
+                        command_index: command_index as i32,
+                        argument_index: argument_index as i32,
+                        input_index: *input_index as i32,
+                        parameter_type: parameter_type.map(|t| t.to_string()),
+                        raw_value,
+                        is_decoded: value.is_some(),
+                        value,
+                    });
+                }
+            }
+        }
+        Ok(transaction_inputs)
+    }
+
     fn index_packages(
+        &self,
         transactions: &[SuiTransactionFullResponse],
         changed_objects: &[(ObjectStatus, SuiObjectData)],
     ) -> Result<Vec<Package>, IndexerError> {
@@ -440,16 +1311,559 @@ where
             })
             .collect::<BTreeMap<_, _>>();
 
-        transactions
+        // A publish creates the package's upgrade cap and an upgrade mutates it, either way the
+        // cap was last touched by the transaction that produced the package.
+        let upgrade_caps = changed_objects
             .iter()
-            .flat_map(|tx| {
-                tx.effects.created().iter().map(|oref| {
-                    object_map
-                        .get(&oref.reference.object_id)
-                        .map(|o| Package::try_from(*tx.transaction.data.sender(), o))
+            .filter_map(|(_, o)| match (&o.type_, o.previous_transaction) {
+                (Some(ObjectType::Struct(t)), Some(digest))
+                    if StructTag::from(t.clone()) == UpgradeCap::type_() =>
+                {
+                    Some((digest, o.object_id))
+                }
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut packages: Vec<Package> = vec![];
+        for tx in transactions {
+            let upgraded_from = match tx.transaction.data.transaction() {
+                SuiTransactionKind::ProgrammableTransaction(pt) => {
+                    pt.commands.iter().find_map(|command| match command {
+                        SuiCommand::Upgrade(_, _, current_package_id, _) => {
+                            Some(current_package_id.to_string())
+                        }
+                        _ => None,
+                    })
+                }
+                _ => None,
+            };
+            for oref in tx.effects.created() {
+                let Some(package) = object_map.get(&oref.reference.object_id) else {
+                    continue;
+                };
+                let original_id = match &upgraded_from {
+                    // An upgrade joins the lineage of the package it replaces, which may have been
+                    // published earlier in this same checkpoint.
+                    Some(previous_id) => {
+                        match packages.iter().find(|p| &p.package_id == previous_id) {
+                            Some(previous) => previous.original_id.clone(),
+                            None => self.state.get_package(previous_id)?.original_id,
+                        }
+                    }
+                    None => package.id.to_string(),
+                };
+                packages.push(Package::try_from(
+                    *tx.transaction.data.sender(),
+                    package,
+                    original_id,
+                    upgrade_caps.get(&tx.digest).copied(),
+                )?);
+            }
+        }
+        Ok(packages)
+    }
+
+    /// With an object type allowlist, keep only the changes to objects of the allowed types, to
+    /// the objects they own, such as their dynamic fields, and to objects already indexed.
+    fn filter_allowed_objects(
+        &self,
+        objects_changes: Vec<TransactionObjectChanges>,
+    ) -> Result<Vec<TransactionObjectChanges>, IndexerError> {
+        if self.object_type_allowlist.is_empty() {
+            return Ok(objects_changes);
+        }
+        let mutated = || objects_changes.iter().flat_map(|c| &c.mutated_objects);
+        let mut allowed: BTreeSet<String> = mutated()
+            .filter(|o| is_allowed_type(&self.object_type_allowlist, &o.object_type))
+            .map(|o| o.object_id.clone())
+            .collect();
+
+        // The other objects are kept if they or their owner were indexed before.
+        let mut candidates = BTreeSet::new();
+        for o in mutated().filter(|o| !allowed.contains(&o.object_id)) {
+            candidates.insert(o.object_id.clone());
+            if let (OwnerType::ObjectOwner, Some(owner)) = (&o.owner_type, &o.owner_address) {
+                candidates.insert(owner.clone());
+            }
+        }
+        for o in objects_changes.iter().flat_map(|c| &c.deleted_objects) {
+            candidates.insert(o.object_id.clone());
+        }
+        candidates.retain(|id| !allowed.contains(id));
+        if !candidates.is_empty() {
+            let candidates: Vec<String> = candidates.into_iter().collect();
+            allowed.extend(self.state.get_indexed_object_ids(&candidates)?);
+        }
+
+        // Objects owned by kept objects are kept too, down to the dynamic fields of dynamic
+        // fields created in this checkpoint.
+        loop {
+            let owned: Vec<String> = mutated()
+                .filter(|o| matches!(o.owner_type, OwnerType::ObjectOwner))
+                .filter(|o| !allowed.contains(&o.object_id))
+                .filter(|o| matches!(&o.owner_address, Some(owner) if allowed.contains(owner)))
+                .map(|o| o.object_id.clone())
+                .collect();
+            if owned.is_empty() {
+                break;
+            }
+            allowed.extend(owned);
+        }
+
+        Ok(objects_changes
+            .into_iter()
+            .map(|changes| TransactionObjectChanges {
+                mutated_objects: changes
+                    .mutated_objects
+                    .into_iter()
+                    .filter(|o| allowed.contains(&o.object_id))
+                    .collect(),
+                deleted_objects: changes
+                    .deleted_objects
+                    .into_iter()
+                    .filter(|o| allowed.contains(&o.object_id))
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+/// Whether an object type is in the allowlist. An entry matches the types it is a prefix of up
+/// to a `::` or type parameters, ex `0x2::coin` and `0x2::coin::Coin` both match
+/// `0x2::coin::Coin<0x2::sui::SUI>`.
+fn is_allowed_type(allowlist: &[String], object_type: &str) -> bool {
+    allowlist.iter().any(|entry| {
+        matches!(
+            object_type.strip_prefix(entry.as_str()),
+            Some(rest) if rest.is_empty() || rest.starts_with("::") || rest.starts_with('<')
+        )
+    })
+}
+
+/// Flag the objects among `unresolved` for them to be reprocessed once their type resolves.
+fn flag_unresolved_objects(data: &mut TemporaryCheckpointStore, unresolved: &BTreeSet<String>) {
+    if unresolved.is_empty() {
+        return;
+    }
+    for changes in &mut data.objects_changes {
+        for object in &mut changes.mutated_objects {
+            object.type_unresolved = unresolved.contains(&object.object_id);
+        }
+    }
+}
+
+fn index_objects(
+    checkpoint: &RpcCheckpoint,
+    transactions: &[SuiTransactionFullResponse],
+    changed_objects: &[(ObjectStatus, SuiObjectData)],
+) -> Vec<TransactionObjectChanges> {
+    let tx_objects = changed_objects
+        .iter()
+        // Unwrap safe here as we requested previous tx data in the request.
+        .fold(BTreeMap::<_, Vec<_>>::new(), |mut acc, (status, o)| {
+            if let Some(digest) = &o.previous_transaction {
+                acc.entry(*digest).or_default().push((status, o));
+            }
+            acc
+        });
+
+    transactions
+        .iter()
+        .map(|tx| {
+            let changed_objects = tx_objects
+                .get(&tx.digest)
+                .unwrap_or(&vec![])
+                .iter()
+                .map(|(status, o)| {
+                    Object::from(&checkpoint.epoch, &checkpoint.sequence_number, status, o)
                 })
+                .collect::<Vec<_>>();
+            let deleted = tx.effects.deleted().iter();
+            let deleted = deleted.map(|o| (ObjectStatus::Deleted, o));
+            let wrapped = tx.effects.wrapped().iter();
+            let wrapped = wrapped.map(|o| (ObjectStatus::Wrapped, o));
+            let unwrapped_then_deleted = tx.effects.unwrapped_then_deleted().iter();
+            let unwrapped_then_deleted =
+                unwrapped_then_deleted.map(|o| (ObjectStatus::UnwrappedThenDeleted, o));
+            let all_deleted_objects = deleted
+                .chain(wrapped)
+                .chain(unwrapped_then_deleted)
+                .map(|(status, oref)| {
+                    DeletedObject::from(
+                        &checkpoint.epoch,
+                        &checkpoint.sequence_number,
+                        oref,
+                        &tx.digest,
+                        status,
+                    )
+                })
+                .collect();
+            TransactionObjectChanges {
+                mutated_objects: changed_objects,
+                deleted_objects: all_deleted_objects,
+            }
+        })
+        .collect()
+}
+
+fn index_move_calls(
+    checkpoint: &RpcCheckpoint,
+    transactions: &[SuiTransactionFullResponse],
+) -> Vec<MoveCall> {
+    transactions
+        .iter()
+        .map(|t| {
+            let tx = t.transaction.data.transaction();
+            (
+                tx.clone(),
+                t.digest,
+                checkpoint.sequence_number,
+                checkpoint.epoch,
+                t.transaction.data.sender(),
+            )
+        })
+        .filter_map(
+            |(tx_kind, txn_digest, checkpoint_seq, epoch, sender)| match tx_kind {
+                SuiTransactionKind::ProgrammableTransaction(pt) => Some(
+                    pt.commands
+                        .into_iter()
+                        .filter_map(move |command| match command {
+                            SuiCommand::MoveCall(m) => Some(MoveCall {
+                                id: None,
+                                transaction_digest: txn_digest.to_string(),
+                                checkpoint_sequence_number: checkpoint_seq as i64,
+                                epoch: epoch as i64,
+                                sender: sender.to_string(),
+                                move_package: m.package.to_string(),
+                                move_module: m.module,
+                                move_function: m.function,
+                            }),
+                            _ => None,
+                        }),
+                ),
+
+                _ => None,
+            },
+        )
+        .flatten()
+        .collect()
+}
+
+fn index_recipients(
+    checkpoint: &RpcCheckpoint,
+    transactions: &[SuiTransactionFullResponse],
+) -> Vec<Recipient> {
+    transactions
+        .iter()
+        .flat_map(|tx| {
+            let created = tx.effects.created().iter();
+            let mutated = tx.effects.mutated().iter();
+            let unwrapped = tx.effects.unwrapped().iter();
+            created
+                .chain(mutated)
+                .chain(unwrapped)
+                .filter_map(|obj_ref| match obj_ref.owner {
+                    Owner::AddressOwner(address) => Some(Recipient {
+                        id: None,
+                        transaction_digest: tx.effects.transaction_digest().to_string(),
+                        checkpoint_sequence_number: checkpoint.sequence_number as i64,
+                        epoch: checkpoint.epoch as i64,
+                        recipient: address.to_string(),
+                    }),
+                    _ => None,
+                })
+        })
+        .collect()
+}
+
+/// The commands of the checkpoint's programmable transactions, each with the commands whose
+/// results it takes.
+fn index_ptb_commands(
+    checkpoint: &RpcCheckpoint,
+    transactions: &[SuiTransactionFullResponse],
+) -> Result<Vec<PtbCommand>, IndexerError> {
+    let mut ptb_commands = vec![];
+    for tx in transactions {
+        let sender_signed_data: SenderSignedData =
+            bcs::from_bytes(&tx.raw_transaction).map_err(|e| {
+                IndexerError::SerdeError(format!(
+                    "Failed to deserialize raw transaction {:?} with error: {:?}",
+                    tx.digest, e
+                ))
+            })?;
+        let kind = sender_signed_data.transaction_data().kind();
+        let TransactionKind::ProgrammableTransaction(pt) = kind else {
+            continue;
+        };
+        for (command_index, command) in pt.commands.iter().enumerate() {
+            ptb_commands.push(PtbCommand::new(
+                tx.digest.to_string(),
+                checkpoint.sequence_number as i64,
+                command_index,
+                command,
+            )?);
+        }
+    }
+    Ok(ptb_commands)
+}
+
+/// Every object touched by the effects of the checkpoint's transactions, with the position of
+/// the transaction in the checkpoint to order the transactions of an object.
+fn index_object_transactions(
+    checkpoint: &RpcCheckpoint,
+    transactions: &[SuiTransactionFullResponse],
+) -> Vec<ObjectTransaction> {
+    transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(transaction_index, tx)| {
+            let effects = &tx.effects;
+            let created = effects.created().iter();
+            let created = created.map(|o| (&o.reference, ObjectStatus::Created));
+            let mutated = effects.mutated().iter();
+            let mutated = mutated.map(|o| (&o.reference, ObjectStatus::Mutated));
+            let unwrapped = effects.unwrapped().iter();
+            let unwrapped = unwrapped.map(|o| (&o.reference, ObjectStatus::Unwrapped));
+            let deleted = effects.deleted().iter();
+            let deleted = deleted.map(|o| (o, ObjectStatus::Deleted));
+            let wrapped = effects.wrapped().iter();
+            let wrapped = wrapped.map(|o| (o, ObjectStatus::Wrapped));
+            let unwrapped_then_deleted = effects.unwrapped_then_deleted().iter();
+            let unwrapped_then_deleted =
+                unwrapped_then_deleted.map(|o| (o, ObjectStatus::UnwrappedThenDeleted));
+            created
+                .chain(mutated)
+                .chain(unwrapped)
+                .chain(deleted)
+                .chain(wrapped)
+                .chain(unwrapped_then_deleted)
+                .map(
+                    move |(oref, status): (&SuiObjectRef, ObjectStatus)| ObjectTransaction {
+                        object_id: oref.object_id.to_string(),
+                        checkpoint_sequence_number: checkpoint.sequence_number as i64,
+                        transaction_index: transaction_index as i64,
+                        transaction_digest: tx.digest.to_string(),
+                        object_version: oref.version.value() as i64,
+                        object_status: status,
+                    },
+                )
+        })
+        .collect()
+}
+
+/// The epoch started by the checkpoint, read from the system state objects it changed: the
+/// genesis checkpoint starts epoch 0, and the last checkpoint of an epoch starts the next one.
+fn index_epoch(
+    checkpoint: &RpcCheckpoint,
+    changed_objects: &[(ObjectStatus, SuiObjectData)],
+) -> Result<Option<Epoch>, IndexerError> {
+    if checkpoint.sequence_number != 0 && checkpoint.end_of_epoch_data.is_none() {
+        return Ok(None);
+    }
+    // The system state wrapper and its inner state, which is a dynamic field of the wrapper
+    let system_state_owner = Owner::ObjectOwner(SUI_SYSTEM_STATE_OBJECT_ID.into());
+    let mut system_state_objects = changed_objects
+        .iter()
+        .filter(|(status, o)| {
+            matches!(status, ObjectStatus::Created | ObjectStatus::Mutated)
+                && (o.object_id == SUI_SYSTEM_STATE_OBJECT_ID
+                    || o.owner == Some(system_state_owner))
+        })
+        .map(|(_, o)| o.clone().try_into())
+        .collect::<Result<Vec<SuiObject>, _>>()?;
+    if system_state_objects.is_empty() {
+        warn!(
+            "Checkpoint {} starts an epoch but changes no system state object",
+            checkpoint.sequence_number
+        );
+        return Ok(None);
+    }
+    // Latest versions first, for them to be found before versions changed earlier in the
+    // checkpoint
+    system_state_objects.sort_by_key(|o| std::cmp::Reverse(o.version()));
+    let system_state = get_sui_system_state(&system_state_objects.as_slice())?;
+    Ok(Some(Epoch::from(&system_state)))
+}
+
+/// The total supply of each coin type whose treasury cap was created, mutated by a mint or a burn,
+/// or unwrapped in the checkpoint, read from the latest version of the cap.
+fn index_coin_supplies(
+    checkpoint: &RpcCheckpoint,
+    changed_objects: &[(ObjectStatus, SuiObjectData)],
+) -> Result<Vec<CoinSupply>, IndexerError> {
+    let mut supplies: BTreeMap<String, (SequenceNumber, u64)> = BTreeMap::new();
+    for (status, o) in changed_objects {
+        if !matches!(
+            status,
+            ObjectStatus::Created | ObjectStatus::Mutated | ObjectStatus::Unwrapped
+        ) {
+            continue;
+        }
+        let Some(SuiRawData::MoveObject(move_object)) = &o.bcs else {
+            continue;
+        };
+        if !TreasuryCap::is_treasury_cap(&move_object.type_) {
+            continue;
+        }
+        let Some(coin_type) = move_object.type_.type_params.first() else {
+            continue;
+        };
+        let treasury_cap = TreasuryCap::from_bcs_bytes(&move_object.bcs_bytes)?;
+        let supply = (o.version, treasury_cap.total_supply.value);
+        // A checkpoint can change the same cap more than once, the latest version wins
+        supplies
+            .entry(coin_type.to_string())
+            .and_modify(|latest| {
+                if supply.0 > latest.0 {
+                    *latest = supply;
+                }
             })
-            .flatten()
-            .collect()
+            .or_insert(supply);
+    }
+    Ok(supplies
+        .into_iter()
+        .map(|(coin_type, (_, total_supply))| CoinSupply {
+            coin_type,
+            total_supply: total_supply as i64,
+            checkpoint: checkpoint.sequence_number as i64,
+        })
+        .collect())
+}
+
+async fn get_latest_fullnode_checkpoint(rpc_client: &SuiClient) -> Result<u64, IndexerError> {
+    rpc_client
+        .read_api()
+        .get_latest_checkpoint_sequence_number()
+        .await
+        .map_err(|e| {
+            IndexerError::FullNodeReadingError(format!(
+                "Failed to get latest checkpoint sequence number with error {:?}",
+                e
+            ))
+        })
+}
+
+fn checkpoint_data_path(dir: &Path, seq: CheckpointSequenceNumber) -> PathBuf {
+    dir.join(format!("{seq}.json"))
+}
+
+/// Write the data of a checkpoint to `<dir>/<sequence number>.json`.
+fn write_checkpoint_data(dir: &Path, data: &CheckpointData) -> Result<(), IndexerError> {
+    let path = checkpoint_data_path(dir, data.checkpoint.sequence_number);
+    let json = serde_json::to_vec(data).map_err(|e| {
+        IndexerError::SerdeError(format!(
+            "Failed to serialize checkpoint {} with error {:?}",
+            data.checkpoint.sequence_number, e
+        ))
+    })?;
+    std::fs::write(&path, json).map_err(|e| {
+        IndexerError::UncategorizedError(anyhow::anyhow!(
+            "Failed to write checkpoint to {:?} with error {:?}",
+            path,
+            e
+        ))
+    })
+}
+
+/// Read the data of a checkpoint written by `write_checkpoint_data`, `None` if there is no
+/// such checkpoint in `dir`.
+fn read_checkpoint_data(
+    dir: &Path,
+    seq: CheckpointSequenceNumber,
+) -> Result<Option<CheckpointData>, IndexerError> {
+    let path = checkpoint_data_path(dir, seq);
+    let json = match std::fs::read(&path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(IndexerError::UncategorizedError(anyhow::anyhow!(
+                "Failed to read checkpoint from {:?} with error {:?}",
+                path,
+                e
+            )))
+        }
+    };
+    serde_json::from_slice(&json).map(Some).map_err(|e| {
+        IndexerError::SerdeError(format!(
+            "Failed to deserialize checkpoint from {:?} with error {:?}",
+            path, e
+        ))
+    })
+}
+
+/// Recompute the checkpoint contents from the fetched transactions and effects and check
+/// that they hash to the content digest in the checkpoint summary. The JSON responses that
+/// get indexed are checked against the raw BCS bytes as well, so that a fullnode cannot serve
+/// tampered contents alongside untouched raw data.
+fn verify_checkpoint_contents(data: &CheckpointData) -> Result<(), IndexerError> {
+    let CheckpointData {
+        checkpoint,
+        transactions,
+        ..
+    } = data;
+    let seq = checkpoint.sequence_number;
+
+    let transactions_by_digest: HashMap<_, _> =
+        transactions.iter().map(|tx| (tx.digest, tx)).collect();
+    let mut execution_digests = Vec::with_capacity(checkpoint.transactions.len());
+    let mut user_signatures = Vec::with_capacity(checkpoint.transactions.len());
+    for digest in &checkpoint.transactions {
+        let tx = transactions_by_digest.get(digest).ok_or_else(|| {
+            IndexerError::ContentDigestMismatch(format!(
+                "transaction {:?} of checkpoint {} was not fetched",
+                digest, seq
+            ))
+        })?;
+        let sender_signed_data: SenderSignedData =
+            bcs::from_bytes(&tx.raw_transaction).map_err(|e| {
+                IndexerError::SerdeError(format!(
+                    "Failed to deserialize raw transaction {:?} with error: {:?}",
+                    digest, e
+                ))
+            })?;
+        let effects: TransactionEffects = bcs::from_bytes(&tx.raw_effects).map_err(|e| {
+            IndexerError::SerdeError(format!(
+                "Failed to deserialize raw effects of transaction {:?} with error: {:?}",
+                digest, e
+            ))
+        })?;
+
+        if sender_signed_data.digest() != *digest || effects.transaction_digest() != digest {
+            return Err(IndexerError::ContentDigestMismatch(format!(
+                "raw data of transaction {:?} in checkpoint {} belongs to another transaction",
+                digest, seq
+            )));
+        }
+        if SuiTransaction::try_from(sender_signed_data.clone())? != tx.transaction
+            || SuiTransactionEffects::try_from(effects.clone())? != tx.effects
+        {
+            return Err(IndexerError::ContentDigestMismatch(format!(
+                "transaction {:?} in checkpoint {} does not match its raw data",
+                digest, seq
+            )));
+        }
+
+        execution_digests.push(ExecutionDigests::new(*digest, effects.digest()));
+        // system transactions are not user signed and are checkpointed without signatures
+        if sender_signed_data.intent_message().value.is_system_tx() {
+            user_signatures.push(vec![]);
+        } else {
+            user_signatures.push(sender_signed_data.tx_signatures().to_vec());
+        }
+    }
+
+    let contents = CheckpointContents::new_with_causally_ordered_transactions_and_signatures(
+        execution_digests,
+        user_signatures,
+    );
+    if contents.digest() != &checkpoint.content_digest {
+        return Err(IndexerError::ContentDigestMismatch(format!(
+            "checkpoint {} has content digest {:?} but its contents hash to {:?}",
+            seq,
+            checkpoint.content_digest,
+            contents.digest()
+        )));
     }
+    Ok(())
 }
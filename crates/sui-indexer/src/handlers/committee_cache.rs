@@ -0,0 +1,109 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use sui_json_rpc_types::Checkpoint as RpcCheckpoint;
+use sui_sdk::SuiClient;
+use sui_types::base_types::AuthorityName;
+use sui_types::committee::{Committee, EpochId, StakeUnit};
+use sui_types::crypto::AuthorityPublicKey;
+
+use crate::errors::IndexerError;
+use crate::models::checkpoints::rpc_certified_summary;
+
+/// Committees of the epochs indexed so far, to verify the validator signatures of checkpoints.
+/// The committee of an epoch is taken from the last checkpoint of the previous epoch once that
+/// checkpoint is verified, so only the committee of the first epoch indexed by the process is
+/// fetched from the fullnode and trusted as is. Without a fullnode, the replayed checkpoints
+/// must start at an epoch whose committee is known.
+pub struct CommitteeCache {
+    rpc_client: Option<SuiClient>,
+    committees: Mutex<HashMap<EpochId, Arc<Committee>>>,
+}
+
+impl CommitteeCache {
+    pub fn new(rpc_client: Option<SuiClient>) -> Self {
+        Self {
+            rpc_client,
+            committees: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Verify that the checkpoint is certified by a quorum of the committee of its epoch. A
+    /// verified end of epoch checkpoint also provides the committee of the next epoch.
+    pub async fn verify_checkpoint(&self, checkpoint: &RpcCheckpoint) -> Result<(), IndexerError> {
+        let committee = self.get_committee(checkpoint.epoch).await?;
+        rpc_certified_summary(checkpoint)?
+            .verify_signature(&committee)
+            .map_err(|e| {
+                IndexerError::InvalidCheckpointSignature(format!(
+                    "checkpoint {} of epoch {} failed verification with error: {:?}",
+                    checkpoint.sequence_number, checkpoint.epoch, e
+                ))
+            })?;
+        if let Some(end_of_epoch_data) = &checkpoint.end_of_epoch_data {
+            let next_epoch = checkpoint.epoch + 1;
+            let next_committee =
+                new_committee(next_epoch, &end_of_epoch_data.next_epoch_committee)?;
+            self.committees
+                .lock()
+                .unwrap()
+                .insert(next_epoch, Arc::new(next_committee));
+        }
+        Ok(())
+    }
+
+    async fn get_committee(&self, epoch: EpochId) -> Result<Arc<Committee>, IndexerError> {
+        if let Some(committee) = self.committees.lock().unwrap().get(&epoch) {
+            return Ok(committee.clone());
+        }
+        let Some(rpc_client) = &self.rpc_client else {
+            return Err(IndexerError::InvalidCheckpointSignature(format!(
+                "the committee of epoch {} is unknown without a fullnode",
+                epoch
+            )));
+        };
+        let committee_info = rpc_client
+            .governance_api()
+            .get_committee_info(Some(epoch))
+            .await
+            .map_err(|e| {
+                IndexerError::FullNodeReadingError(format!(
+                    "Failed to get committee of epoch {} with error {:?}",
+                    epoch, e
+                ))
+            })?;
+        let committee = Arc::new(new_committee(epoch, &committee_info.validators)?);
+        self.committees
+            .lock()
+            .unwrap()
+            .insert(epoch, committee.clone());
+        Ok(committee)
+    }
+}
+
+/// `Committee::new` panics on committees it cannot verify with, which fullnodes must not be
+/// able to trigger.
+fn new_committee(
+    epoch: EpochId,
+    validators: &[(AuthorityName, StakeUnit)],
+) -> Result<Committee, IndexerError> {
+    if validators.iter().all(|(_, stake)| *stake == 0) {
+        return Err(IndexerError::InvalidCheckpointSignature(format!(
+            "the committee of epoch {} has no stake",
+            epoch
+        )));
+    }
+    for (name, _) in validators {
+        AuthorityPublicKey::try_from(*name).map_err(|e| {
+            IndexerError::InvalidCheckpointSignature(format!(
+                "the committee of epoch {} has an invalid public key {:?}: {:?}",
+                epoch, name, e
+            ))
+        })?;
+    }
+    let voting_rights: BTreeMap<_, _> = validators.iter().copied().collect();
+    Ok(Committee::new(epoch, voting_rights))
+}
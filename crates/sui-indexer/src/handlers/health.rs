@@ -0,0 +1,87 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Extension, Router};
+use prometheus::Registry;
+use tracing::error;
+
+use crate::errors::IndexerError;
+use crate::metrics::IndexerHealthMetrics;
+
+pub const HEALTH_ROUTE: &str = "/health";
+
+/// The last error that stopped checkpoint ingestion, cleared once a checkpoint is committed
+/// again. Exposed as the `indexer_last_error` gauge and in the body of `/health`.
+#[derive(Clone)]
+pub struct LastError {
+    error: Arc<Mutex<Option<(String, String)>>>,
+    metrics: IndexerHealthMetrics,
+}
+
+impl LastError {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            error: Arc::new(Mutex::new(None)),
+            metrics: IndexerHealthMetrics::new(registry),
+        }
+    }
+
+    pub fn record(&self, error: &IndexerError) {
+        let kind = error.name();
+        let mut last_error = self.error.lock().unwrap();
+        if let Some((last_kind, _)) = last_error.take() {
+            self.metrics
+                .last_error
+                .with_label_values(&[&last_kind])
+                .set(0);
+        }
+        self.metrics.last_error.with_label_values(&[&kind]).set(1);
+        *last_error = Some((kind, error.to_string()));
+    }
+
+    pub fn clear(&self) {
+        if let Some((kind, _)) = self.error.lock().unwrap().take() {
+            self.metrics.last_error.with_label_values(&[&kind]).set(0);
+        }
+    }
+
+    /// Kind and message of the last error, None if ingestion is healthy.
+    pub fn get(&self) -> Option<(String, String)> {
+        self.error.lock().unwrap().clone()
+    }
+}
+
+/// Serve `/health` on `addr`, which answers 200 while ingestion is healthy, and 503 with the
+/// kind and message of the last error otherwise.
+pub fn start_health_server(addr: SocketAddr, last_error: LastError) {
+    let app = Router::new()
+        .route(HEALTH_ROUTE, get(health))
+        .layer(Extension(last_error));
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::Server::bind(&addr)
+            .serve(app.into_make_service())
+            .await
+        {
+            error!(
+                "Indexer health server on {} failed with error: {:?}",
+                addr, e
+            );
+        }
+    });
+}
+
+async fn health(Extension(last_error): Extension<LastError>) -> (StatusCode, String) {
+    match last_error.get() {
+        None => (StatusCode::OK, "OK".to_string()),
+        Some((kind, message)) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("{}: {}", kind, message),
+        ),
+    }
+}
@@ -2,3 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod checkpoint_handler;
+pub mod committee_cache;
+pub mod health;
+pub mod package_cache;
+pub mod shutdown;
+pub mod throughput;
+pub mod watchdog;
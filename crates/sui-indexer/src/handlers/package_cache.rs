@@ -0,0 +1,318 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use lru::LruCache;
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::{SignatureToken, StructHandleIndex};
+use move_binary_format::CompiledModule;
+use move_bytecode_utils::layout::TypeLayoutBuilder;
+use move_bytecode_utils::module_cache::GetModule;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use move_core_types::value::MoveValue;
+use serde_json::Value;
+use sui_json_rpc_types::{SuiEvent, SuiMoveValue, SuiObjectData, SuiObjectDataOptions, SuiRawData};
+use sui_sdk::SuiClient;
+use sui_types::base_types::{ObjectID, SequenceNumber};
+use sui_types::event::Event;
+use sui_types::messages::ProgrammableMoveCall;
+
+use crate::errors::IndexerError;
+
+type PackageModules = Arc<BTreeMap<String, Vec<u8>>>;
+
+/// Module bytecode of the packages needed to decode Move values, keyed by package id and
+/// version so that a package upgraded in place (e.g. the framework) never decodes values against
+/// stale bytecode. Packages are fetched from the fullnode the first time they are needed, unless
+/// they were published in an indexed checkpoint, and the least recently used ones are evicted
+/// once `capacity` packages are cached. Without a fullnode, only the packages of indexed
+/// checkpoints are available.
+pub struct PackageCache {
+    rpc_client: Option<SuiClient>,
+    packages: Mutex<LruCache<(ObjectID, SequenceNumber), PackageModules>>,
+    /// Latest version of each package seen in the checkpoints indexed so far.
+    versions: Mutex<HashMap<ObjectID, SequenceNumber>>,
+}
+
+impl PackageCache {
+    pub fn new(rpc_client: Option<SuiClient>, capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity.max(1)).unwrap();
+        Self {
+            rpc_client,
+            packages: Mutex::new(LruCache::new(capacity)),
+            versions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record the packages published or upgraded among `objects`, so that values of their types
+    /// are decoded against these versions from now on.
+    pub fn observe_packages<'a>(&self, objects: impl IntoIterator<Item = &'a SuiObjectData>) {
+        let mut versions = self.versions.lock().unwrap();
+        let mut packages = self.packages.lock().unwrap();
+        for o in objects {
+            if let Some(SuiRawData::Package(p)) = &o.bcs {
+                let version = versions.entry(p.id).or_insert(p.version);
+                *version = (*version).max(p.version);
+                packages.put((p.id, p.version), Arc::new(p.module_map.clone()));
+            }
+        }
+    }
+
+    /// Decode the BCS contents of `event` into JSON, fetching any package its type depends on.
+    pub async fn parse_event(&self, event: &SuiEvent) -> Result<Value, IndexerError> {
+        self.with_packages(|view| {
+            let move_event = Event {
+                package_id: event.package_id,
+                transaction_module: event.transaction_module.clone(),
+                sender: event.sender,
+                type_: event.type_.clone(),
+                contents: event.bcs.clone(),
+            };
+            SuiEvent::try_from(
+                move_event,
+                event.id.tx_digest,
+                event.id.event_seq,
+                event.timestamp_ms,
+                view,
+            )
+        })
+        .await?
+        .map(|parsed| parsed.parsed_json)
+        .map_err(|e| {
+            IndexerError::SerdeError(format!(
+                "Failed to decode event {:?} of type {} with error {:?}",
+                event.id, event.type_, e
+            ))
+        })
+    }
+
+    /// Resolve the parameter types of the function called by `call`, with the call's type
+    /// arguments substituted. Returns None if the function cannot be found in the package.
+    pub async fn get_parameter_types(
+        &self,
+        call: &ProgrammableMoveCall,
+    ) -> Result<Option<Vec<TypeTag>>, IndexerError> {
+        let package = self.get_package(call.package).await?;
+        let Some(bytes) = package.get(call.module.as_str()) else {
+            return Ok(None);
+        };
+        let module = CompiledModule::deserialize(bytes).map_err(|e| {
+            IndexerError::SerdeError(format!(
+                "Failed to deserialize module {}::{} with error {:?}",
+                call.package, call.module, e
+            ))
+        })?;
+        let Some(function) = module.function_defs.iter().find(|f| {
+            module.identifier_at(module.function_handle_at(f.function).name)
+                == call.function.as_ident_str()
+        }) else {
+            return Ok(None);
+        };
+        let handle = module.function_handle_at(function.function);
+        Ok(module
+            .signature_at(handle.parameters)
+            .0
+            .iter()
+            .map(|token| type_tag(&module, &call.type_arguments, token))
+            .collect())
+    }
+
+    /// Decode the BCS bytes of a pure value of type `type_` into JSON, fetching any package the
+    /// type depends on. Returns None if the bytes are not a valid value of that type.
+    pub async fn parse_pure_value(
+        &self,
+        type_: &TypeTag,
+        bytes: &[u8],
+    ) -> Result<Option<Value>, IndexerError> {
+        let layout = self
+            .with_packages(|view| TypeLayoutBuilder::build_with_types(type_, view))
+            .await?;
+        Ok(layout.ok().and_then(|layout| {
+            MoveValue::simple_deserialize(bytes, &layout)
+                .ok()
+                .map(|value| SuiMoveValue::from(value).to_json_value())
+        }))
+    }
+
+    /// Check that the layout of values of type `type_` can be built, fetching any package the
+    /// type depends on.
+    pub async fn resolve_type(&self, type_: &TypeTag) -> Result<(), IndexerError> {
+        self.with_packages(|view| TypeLayoutBuilder::build_with_types(type_, view))
+            .await?
+            .map(|_| ())
+            .map_err(|e| {
+                IndexerError::SerdeError(format!(
+                    "Failed to build the layout of type {} with error {:?}",
+                    type_, e
+                ))
+            })
+    }
+
+    /// Run `f` against the packages fetched so far, and as long as it fails on packages it
+    /// couldn't find, fetch them and run it again. Returns the outcome of the last run.
+    async fn with_packages<T, E>(
+        &self,
+        f: impl Fn(&PackageView<'_>) -> Result<T, E>,
+    ) -> Result<Result<T, E>, IndexerError> {
+        let mut packages = HashMap::new();
+        loop {
+            let view = PackageView {
+                packages: &packages,
+                missing: RefCell::default(),
+            };
+            let result = f(&view);
+            let missing = view.missing.into_inner();
+            match result {
+                // The layout spans packages we haven't loaded yet, fetch them and try again.
+                Err(_) if !missing.is_empty() => {
+                    for package_id in missing {
+                        packages.insert(package_id, self.get_package(package_id).await?);
+                    }
+                }
+                result => return Ok(result),
+            }
+        }
+    }
+
+    async fn get_package(&self, package_id: ObjectID) -> Result<PackageModules, IndexerError> {
+        let version = self.versions.lock().unwrap().get(&package_id).copied();
+        if let Some(version) = version {
+            if let Some(package) = self.packages.lock().unwrap().get(&(package_id, version)) {
+                return Ok(package.clone());
+            }
+        }
+
+        let Some(rpc_client) = &self.rpc_client else {
+            return Err(IndexerError::FullNodeReadingError(format!(
+                "Package {} at version {:?} is not in the indexed checkpoints, and there is no \
+                fullnode to fetch it from",
+                package_id, version
+            )));
+        };
+        let read_api = rpc_client.read_api();
+        let options = SuiObjectDataOptions::bcs_lossless();
+        let response = match version {
+            Some(version) => read_api
+                .try_get_parsed_past_object(package_id, version, options)
+                .await
+                .map(|resp| resp.into_object()),
+            // We haven't seen this package being published, it predates the checkpoints indexed
+            // by this process, so the latest version is the one in effect.
+            None => read_api
+                .get_object_with_options(package_id, options)
+                .await
+                .map(|resp| resp.into_object()),
+        };
+        let object = response
+            .map_err(|e| {
+                IndexerError::FullNodeReadingError(format!(
+                    "Failed to get package {} at version {:?} with error {:?}",
+                    package_id, version, e
+                ))
+            })?
+            .map_err(|e| {
+                IndexerError::FullNodeReadingError(format!(
+                    "Package {} at version {:?} is not available: {:?}",
+                    package_id, version, e
+                ))
+            })?;
+        let Some(SuiRawData::Package(package)) = object.bcs else {
+            return Err(IndexerError::FullNodeReadingError(format!(
+                "Object {} is not a package",
+                package_id
+            )));
+        };
+
+        let modules = Arc::new(package.module_map);
+        self.versions
+            .lock()
+            .unwrap()
+            .entry(package_id)
+            .or_insert(package.version);
+        self.packages
+            .lock()
+            .unwrap()
+            .put((package_id, package.version), modules.clone());
+        Ok(modules)
+    }
+}
+
+/// Resolves modules from a fixed set of packages, recording the packages that were asked for but
+/// are not in the set.
+struct PackageView<'a> {
+    packages: &'a HashMap<ObjectID, PackageModules>,
+    missing: RefCell<BTreeSet<ObjectID>>,
+}
+
+impl GetModule for PackageView<'_> {
+    type Error = anyhow::Error;
+    type Item = CompiledModule;
+
+    fn get_module_by_id(&self, id: &ModuleId) -> Result<Option<CompiledModule>, Self::Error> {
+        let package_id = ObjectID::from(*id.address());
+        let Some(package) = self.packages.get(&package_id) else {
+            self.missing.borrow_mut().insert(package_id);
+            return Ok(None);
+        };
+        package
+            .get(id.name().as_str())
+            .map(|bytes| CompiledModule::deserialize(bytes))
+            .transpose()
+            .map_err(|e| anyhow!("Failed to deserialize module {}: {:?}", id, e))
+    }
+}
+
+/// The type of a function parameter, references are resolved to the type they point to. Returns
+/// None if the type refers to a type argument the call does not provide.
+fn type_tag(
+    module: &CompiledModule,
+    type_args: &[TypeTag],
+    token: &SignatureToken,
+) -> Option<TypeTag> {
+    Some(match token {
+        SignatureToken::Bool => TypeTag::Bool,
+        SignatureToken::U8 => TypeTag::U8,
+        SignatureToken::U16 => TypeTag::U16,
+        SignatureToken::U32 => TypeTag::U32,
+        SignatureToken::U64 => TypeTag::U64,
+        SignatureToken::U128 => TypeTag::U128,
+        SignatureToken::U256 => TypeTag::U256,
+        SignatureToken::Address => TypeTag::Address,
+        SignatureToken::Signer => TypeTag::Signer,
+        SignatureToken::Vector(inner) => {
+            TypeTag::Vector(Box::new(type_tag(module, type_args, inner)?))
+        }
+        SignatureToken::Struct(idx) => TypeTag::Struct(Box::new(struct_tag(module, *idx, vec![]))),
+        SignatureToken::StructInstantiation(idx, targs) => {
+            let type_params = targs
+                .iter()
+                .map(|t| type_tag(module, type_args, t))
+                .collect::<Option<_>>()?;
+            TypeTag::Struct(Box::new(struct_tag(module, *idx, type_params)))
+        }
+        SignatureToken::TypeParameter(idx) => type_args.get(*idx as usize)?.clone(),
+        SignatureToken::Reference(inner) | SignatureToken::MutableReference(inner) => {
+            type_tag(module, type_args, inner)?
+        }
+    })
+}
+
+fn struct_tag(
+    module: &CompiledModule,
+    idx: StructHandleIndex,
+    type_params: Vec<TypeTag>,
+) -> StructTag {
+    let struct_handle = module.struct_handle_at(idx);
+    let module_handle = module.module_handle_at(struct_handle.module);
+    StructTag {
+        address: *module.address_identifier_at(module_handle.address),
+        module: module.identifier_at(module_handle.name).to_owned(),
+        name: module.identifier_at(struct_handle.name).to_owned(),
+        type_params,
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use mysten_metrics::spawn_monitored_task;
+use tokio::signal;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Set once the indexer is asked to stop. The checkpoint handler then fetches no more
+/// checkpoints, and commits the ones it already fetched before stopping, see
+/// `--shutdown-drain-timeout-secs`.
+#[derive(Clone)]
+pub struct ShutdownSignal {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for ShutdownSignal {
+    fn default() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+}
+
+impl ShutdownSignal {
+    /// A signal requested when the process receives SIGTERM or SIGINT. Handling them replaces
+    /// their default of exiting right away, so a second one exits without waiting for the drain.
+    pub fn from_os_signals() -> Self {
+        let shutdown = Self::default();
+        let requested = shutdown.clone();
+        spawn_monitored_task!(async move {
+            wait_for_os_signal().await;
+            info!("Received a shutdown signal, stopping the indexer...");
+            requested.request();
+            wait_for_os_signal().await;
+            warn!("Received a second shutdown signal, exiting without draining");
+            std::process::exit(1);
+        });
+        shutdown
+    }
+
+    pub fn request(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolves once shutdown is requested, right away if it already was.
+    pub async fn requested(&self) {
+        let mut receiver = self.sender.subscribe();
+        while !*receiver.borrow_and_update() {
+            // Only fails once the sender is dropped, which we hold on to
+            if receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+async fn wait_for_os_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Rolling ingestion rate of the checkpoint handler, over the checkpoints committed in the last
+/// `window`.
+pub struct ThroughputWindow {
+    window: Duration,
+    /// Commit time and transaction count of each checkpoint in the window, oldest first.
+    commits: VecDeque<(Instant, usize)>,
+}
+
+impl ThroughputWindow {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            commits: VecDeque::new(),
+        }
+    }
+
+    /// Record a committed checkpoint and return the checkpoints and transactions per second
+    /// committed since the oldest checkpoint still in the window.
+    pub fn record(&mut self, now: Instant, transactions: usize) -> (f64, f64) {
+        self.commits.push_back((now, transactions));
+        while let Some((oldest, _)) = self.commits.front() {
+            if now.duration_since(*oldest) <= self.window {
+                break;
+            }
+            self.commits.pop_front();
+        }
+
+        let (oldest, _) = self.commits[0];
+        let elapsed = now.duration_since(oldest).as_secs_f64();
+        if elapsed == 0.0 {
+            return (0.0, 0.0);
+        }
+        // The oldest commit only marks the start of the window, its checkpoint was ingested
+        // before it.
+        let checkpoints = self.commits.len() - 1;
+        let transactions: usize = self.commits.iter().skip(1).map(|(_, tx)| tx).sum();
+        (checkpoints as f64 / elapsed, transactions as f64 / elapsed)
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinHandle;
+use tracing::error;
+
+use crate::errors::IndexerError;
+use crate::metrics::IndexerWatchdogMetrics;
+
+const MIN_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time of the last checkpoint committed by the checkpoint handler.
+#[derive(Clone)]
+pub struct IngestionProgress {
+    last_commit: Arc<Mutex<Instant>>,
+}
+
+impl Default for IngestionProgress {
+    fn default() -> Self {
+        Self {
+            last_commit: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+}
+
+impl IngestionProgress {
+    pub fn record(&self) {
+        *self.last_commit.lock().unwrap() = Instant::now();
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.last_commit.lock().unwrap().elapsed()
+    }
+}
+
+/// Watches checkpoint ingestion for stalls, e.g. an await on the fullnode that never resolves,
+/// which would otherwise leave a process that looks healthy but indexes nothing.
+pub struct Watchdog {
+    progress: IngestionProgress,
+    timeout: Duration,
+    abort_on_stall: bool,
+    metrics: IndexerWatchdogMetrics,
+}
+
+impl Watchdog {
+    pub fn new(
+        progress: IngestionProgress,
+        timeout: Duration,
+        abort_on_stall: bool,
+        metrics: IndexerWatchdogMetrics,
+    ) -> Self {
+        Self {
+            progress,
+            timeout,
+            abort_on_stall,
+            metrics,
+        }
+    }
+
+    /// Wait for the `ingestion` task to finish. If it commits no checkpoint for longer than the
+    /// timeout, the process is aborted for an orchestrator to restart it, or the task is stopped
+    /// and `IndexerError::IngestionStalled` is returned for the caller to start a new one.
+    pub async fn watch(&self, mut ingestion: JoinHandle<()>) -> Result<(), IndexerError> {
+        self.progress.record();
+        let mut interval = tokio::time::interval((self.timeout / 10).max(MIN_CHECK_INTERVAL));
+        loop {
+            tokio::select! {
+                result = &mut ingestion => {
+                    result.expect("Indexer main should not run into errors.");
+                    return Ok(());
+                }
+                _ = interval.tick() => {
+                    let elapsed = self.progress.elapsed();
+                    if elapsed < self.timeout {
+                        continue;
+                    }
+                    self.metrics.stall_detected.inc();
+                    error!(
+                        "Indexer checkpoint ingestion made no progress for {:?}, {}",
+                        elapsed,
+                        if self.abort_on_stall { "aborting" } else { "restarting ingestion" }
+                    );
+                    if self.abort_on_stall {
+                        std::process::abort();
+                    }
+                    ingestion.abort();
+                    return Err(IndexerError::IngestionStalled(format!(
+                        "no checkpoint committed for {:?}",
+                        elapsed
+                    )));
+                }
+            }
+        }
+    }
+}
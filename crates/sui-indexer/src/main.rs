@@ -1,19 +1,52 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use clap::Parser;
+use prometheus::Registry;
 use sui_indexer::errors::IndexerError;
+use sui_indexer::migrations::{migration_status, run_pending_migrations};
 use sui_indexer::store::PgIndexerStore;
-use sui_indexer::{new_pg_connection_pool, Indexer, IndexerConfig};
+use sui_indexer::{
+    get_pg_pool_connection, new_pg_connection_pool, Indexer, IndexerCommand, IndexerConfig,
+    MigrationsCommand,
+};
 use sui_node::metrics::start_prometheus_server;
+use tracing::{info, warn};
 
 #[tokio::main]
 async fn main() -> Result<(), IndexerError> {
-    let _guard = telemetry_subscribers::TelemetryConfig::new()
-        .with_env()
-        .init();
+    let indexer_config = IndexerConfig::load()?;
 
-    let indexer_config = IndexerConfig::parse();
+    let mut telemetry_config = telemetry_subscribers::TelemetryConfig::new().with_env();
+    if let Some(endpoint) = &indexer_config.otlp_endpoint {
+        telemetry_config = telemetry_config.with_otlp_endpoint(endpoint, "sui-indexer");
+    }
+    let _guard = telemetry_config.init();
+    if indexer_config.danger_accept_invalid_fullnode_certs {
+        warn!(
+            "DANGER: --danger-accept-invalid-fullnode-certs is set, the TLS certificate of the \
+            fullnode at {} is NOT verified and anyone able to intercept the connection can feed \
+            the indexer made-up data. Only use it in development.",
+            indexer_config.rpc_client_url
+        );
+    }
+    if let Some(IndexerCommand::DebugReplay { digest }) = indexer_config.command {
+        // Only reads from the db, and does not serve metrics so that it can run next to the
+        // indexer.
+        let pg_connection_pool = new_pg_connection_pool(&indexer_config.db_url).await?;
+        let store = PgIndexerStore::new(pg_connection_pool)
+            .with_enabled_handlers(indexer_config.enabled_handlers())
+            .with_object_history(indexer_config.index_object_history);
+        let rows =
+            Indexer::debug_replay(&indexer_config, &Registry::default(), store, digest).await?;
+        println!("{:#?}", rows);
+        return Ok(());
+    }
+    if let Some(IndexerCommand::Migrations {
+        command: MigrationsCommand::Status { apply },
+    }) = indexer_config.command
+    {
+        return migrations_status(&indexer_config, apply).await;
+    }
     let registry_service = start_prometheus_server(
         // NOTE: this parses the input host addr and port number for socket addr,
         // so unwrap() is safe here.
@@ -27,7 +60,55 @@ async fn main() -> Result<(), IndexerError> {
 
     let registry = registry_service.default_registry();
     let pg_connection_pool = new_pg_connection_pool(&indexer_config.db_url).await?;
-    let store = PgIndexerStore::new(pg_connection_pool);
+    let store = PgIndexerStore::new(pg_connection_pool)
+        .with_enabled_handlers(indexer_config.enabled_handlers())
+        .with_object_history(indexer_config.index_object_history)
+        .with_plain_insert_tables(indexer_config.plain_insert_tables.iter().copied().collect())
+        .with_deadlock_retries(indexer_config.db_deadlock_retries, &registry)
+        .with_bulk_copy(&indexer_config.db_url);
 
-    Indexer::start(&indexer_config, &registry, store).await
+    // Held until the indexer exits
+    let _stream_lock = match indexer_config.checkpoint_stream() {
+        Some(stream) => match store.try_lock_stream(&stream)? {
+            Some(lock) => Some(lock),
+            None => {
+                warn!("Another indexer is writing checkpoint stream {stream} to the db, exiting");
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    Indexer::start(&indexer_config, &registry, store).await?;
+    // The connection pool is closed, and the stream lock released, as the process exits
+    info!("Sui indexer stopped");
+    Ok(())
+}
+
+/// Print the applied and pending migrations of the db, and run the pending ones if `apply` is
+/// set. Exits with an error if any migration is left pending.
+async fn migrations_status(config: &IndexerConfig, apply: bool) -> Result<(), IndexerError> {
+    let pg_connection_pool = new_pg_connection_pool(&config.db_url).await?;
+    let mut conn = get_pg_pool_connection(&pg_connection_pool)?;
+    let status = migration_status(&mut conn)?;
+    for migration in &status.applied {
+        println!("applied {migration}");
+    }
+    for migration in &status.pending {
+        println!("pending {migration}");
+    }
+    if status.pending.is_empty() {
+        println!("Schema is up to date");
+        return Ok(());
+    }
+    if !apply {
+        eprintln!(
+            "{} migrations are pending, run with --apply to run them",
+            status.pending.len()
+        );
+        std::process::exit(1);
+    }
+    for version in run_pending_migrations(&mut conn)? {
+        println!("ran {version}");
+    }
+    Ok(())
 }
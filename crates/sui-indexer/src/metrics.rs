@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use prometheus::{
-    register_histogram_with_registry, register_int_counter_with_registry, Histogram, IntCounter,
-    Registry,
+    register_gauge_with_registry, register_histogram_with_registry,
+    register_int_counter_vec_with_registry, register_int_counter_with_registry,
+    register_int_gauge_vec_with_registry, register_int_gauge_with_registry, Gauge, Histogram,
+    IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Registry,
 };
 
 /// Prometheus metrics for sui-indexer.
@@ -255,6 +257,13 @@ pub struct IndexerCheckpointHandlerMetrics {
     pub db_write_request_latency: Histogram,
     pub full_node_read_request_latency: Histogram,
     pub subscription_process_latency: Histogram,
+    pub rows_written: IntCounterVec,
+    pub checkpoints_per_second: Gauge,
+    pub transactions_per_second: Gauge,
+    pub unresolved_types: IntCounter,
+    pub prefetch_buffer_depth: IntGauge,
+    pub handler_checkpoint: IntGaugeVec,
+    pub handler_lag: IntGaugeVec,
 }
 
 impl IndexerCheckpointHandlerMetrics {
@@ -305,6 +314,105 @@ impl IndexerCheckpointHandlerMetrics {
                 registry,
             )
             .unwrap(),
+            rows_written: register_int_counter_vec_with_registry!(
+                "indexer_rows_written_total",
+                "Total number of rows written to the db, by table",
+                &["table"],
+                registry,
+            )
+            .unwrap(),
+            checkpoints_per_second: register_gauge_with_registry!(
+                "indexer_checkpoints_per_second",
+                "Checkpoints committed per second over the last minute",
+                registry,
+            )
+            .unwrap(),
+            transactions_per_second: register_gauge_with_registry!(
+                "indexer_transactions_per_second",
+                "Transactions committed per second over the last minute",
+                registry,
+            )
+            .unwrap(),
+            unresolved_types: register_int_counter_with_registry!(
+                "indexer_unresolved_types_total",
+                "Total number of indexed objects whose type could not be resolved",
+                registry,
+            )
+            .unwrap(),
+            prefetch_buffer_depth: register_int_gauge_with_registry!(
+                "indexer_prefetch_buffer_depth",
+                "Number of checkpoints being fetched or fetched ahead of the one being committed",
+                registry,
+            )
+            .unwrap(),
+            handler_checkpoint: register_int_gauge_vec_with_registry!(
+                "indexer_handler_checkpoint",
+                "Latest checkpoint committed by each enabled handler",
+                &["handler"],
+                registry,
+            )
+            .unwrap(),
+            handler_lag: register_int_gauge_vec_with_registry!(
+                "indexer_handler_lag",
+                "Number of checkpoints each handler is behind the latest committed checkpoint",
+                &["handler"],
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+pub struct IndexerWatchdogMetrics {
+    pub stall_detected: IntCounter,
+}
+
+impl IndexerWatchdogMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            stall_detected: register_int_counter_with_registry!(
+                "indexer_stall_detected_total",
+                "Total number of checkpoint ingestion stalls detected by the watchdog",
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexerHealthMetrics {
+    pub last_error: IntGaugeVec,
+}
+
+impl IndexerHealthMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            last_error: register_int_gauge_vec_with_registry!(
+                "indexer_last_error",
+                "Set to 1 for the kind of the last error that stopped checkpoint ingestion",
+                &["kind"],
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexerStoreMetrics {
+    pub db_deadlock_retries: IntCounter,
+}
+
+impl IndexerStoreMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            db_deadlock_retries: register_int_counter_with_registry!(
+                "indexer_db_deadlock_retries_total",
+                "Total number of commits re-run after a serialization failure or a deadlock",
+                registry,
+            )
+            .unwrap(),
         }
     }
 }
@@ -0,0 +1,90 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use diesel::migration::MigrationSource;
+use diesel::pg::Pg;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+use crate::errors::IndexerError;
+use crate::PgPoolConnection;
+
+/// The migrations of the schema this binary reads and writes.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// A migration of `MIGRATIONS`, or one applied to the db that this binary does not know of.
+#[derive(Debug, Clone)]
+pub struct MigrationInfo {
+    pub version: String,
+    /// Name of the migration directory, `None` for unknown migrations.
+    pub name: Option<String>,
+}
+
+impl std::fmt::Display for MigrationInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} ({})", self.version, name),
+            None => write!(f, "{} (unknown to this binary)", self.version),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub applied: Vec<MigrationInfo>,
+    pub pending: Vec<MigrationInfo>,
+}
+
+pub fn migration_status(conn: &mut PgPoolConnection) -> Result<MigrationStatus, IndexerError> {
+    let known: Vec<MigrationInfo> = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+        .map_err(|e| {
+            IndexerError::UncategorizedError(anyhow::anyhow!(
+                "Failed loading embedded migrations with error: {:?}",
+                e
+            ))
+        })?
+        .iter()
+        .map(|m| MigrationInfo {
+            version: m.name().version().to_string(),
+            name: Some(m.name().to_string()),
+        })
+        .collect();
+    let mut applied_versions = conn.applied_migrations().map_err(|e| {
+        IndexerError::PostgresReadError(format!(
+            "Failed reading applied migrations with error: {:?}",
+            e
+        ))
+    })?;
+    applied_versions.sort();
+
+    let applied = applied_versions
+        .iter()
+        .map(|version| {
+            let version = version.to_string();
+            known
+                .iter()
+                .find(|m| m.version == version)
+                .cloned()
+                .unwrap_or(MigrationInfo {
+                    version,
+                    name: None,
+                })
+        })
+        .collect::<Vec<_>>();
+    let pending = known
+        .into_iter()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+        .collect();
+    Ok(MigrationStatus { applied, pending })
+}
+
+/// Run the pending migrations, and return the versions that were applied.
+pub fn run_pending_migrations(conn: &mut PgPoolConnection) -> Result<Vec<String>, IndexerError> {
+    conn.run_pending_migrations(MIGRATIONS)
+        .map(|versions| versions.iter().map(|v| v.to_string()).collect())
+        .map_err(|e| {
+            IndexerError::PostgresWriteError(format!(
+                "Failed running pending migrations with error: {:?}",
+                e
+            ))
+        })
+}
@@ -6,9 +6,13 @@ use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use sui_json_rpc_types::Checkpoint as RpcCheckpoint;
 use sui_types::base_types::TransactionDigest;
-use sui_types::digests::CheckpointDigest;
+use sui_types::crypto::AuthorityStrongQuorumSignInfo;
+use sui_types::digests::{CheckpointContentsDigest, CheckpointDigest};
 use sui_types::gas::GasCostSummary;
-use sui_types::messages_checkpoint::EndOfEpochData;
+use sui_types::message_envelope::Message;
+use sui_types::messages_checkpoint::{
+    CertifiedCheckpointSummary, CheckpointSummary, EndOfEpochData,
+};
 
 use crate::schema::checkpoints;
 use crate::schema::checkpoints::end_of_epoch_data;
@@ -34,11 +38,21 @@ pub struct Checkpoint {
     pub timestamp_ms: i64,
     pub timestamp_ms_str: NaiveDateTime,
     pub checkpoint_tps: f32,
+    pub checkpoint_summary: Option<Vec<u8>>,
+    pub validator_signature: Option<Vec<u8>>,
+    pub content_digest: Option<String>,
 }
 
 impl TryFrom<Checkpoint> for RpcCheckpoint {
     type Error = IndexerError;
     fn try_from(checkpoint: Checkpoint) -> Result<Self, Self::Error> {
+        let certified_summary = checkpoint.certified_summary()?.ok_or_else(|| {
+            IndexerError::PostgresReadError(format!(
+                "Checkpoint {} was indexed without its validator signature",
+                checkpoint.sequence_number
+            ))
+        })?;
+        let (summary, validator_signature) = certified_summary.into_data_and_sig();
         let parsed_digest = checkpoint
             .checkpoint_digest
             .parse::<CheckpointDigest>()
@@ -48,6 +62,19 @@ impl TryFrom<Checkpoint> for RpcCheckpoint {
                     checkpoint.checkpoint_digest, e
                 ))
             })?;
+        // checkpoints indexed before the content digest was stored fall back to the signed summary
+        let parsed_content_digest = match &checkpoint.content_digest {
+            Some(content_digest) => content_digest
+                .parse::<CheckpointContentsDigest>()
+                .map_err(|e| {
+                    IndexerError::SerdeError(format!(
+                        "Failed to decode checkpoint content digest: {:?} with err: {:?}",
+                        content_digest, e
+                    ))
+                })?,
+            None => summary.content_digest,
+        };
+        let parsed_txn_digests = checkpoint.transaction_digests()?;
 
         let parsed_previous_digest = checkpoint
             .previous_checkpoint_digest
@@ -60,20 +87,6 @@ impl TryFrom<Checkpoint> for RpcCheckpoint {
                 })
             })
             .transpose()?;
-        let parsed_txn_digests: Vec<TransactionDigest> = checkpoint
-            .transactions
-            .into_iter()
-            .filter_map(|txn| {
-                txn.map(|txn| {
-                    txn.parse().map_err(|e| {
-                        IndexerError::SerdeError(format!(
-                            "Failed to decode transaction digest: {:?} with err: {:?}",
-                            txn, e
-                        ))
-                    })
-                })
-            })
-            .collect::<Result<Vec<TransactionDigest>, IndexerError>>()?;
 
         let data: Option<EndOfEpochData> =
             if let Some(end_of_epoch_data_str) = checkpoint.end_of_epoch_data {
@@ -91,6 +104,7 @@ impl TryFrom<Checkpoint> for RpcCheckpoint {
             epoch: checkpoint.epoch as u64,
             sequence_number: checkpoint.sequence_number as u64,
             digest: parsed_digest,
+            content_digest: parsed_content_digest,
             previous_digest: parsed_previous_digest,
             end_of_epoch_data: data,
             epoch_rolling_gas_cost_summary: GasCostSummary {
@@ -101,7 +115,8 @@ impl TryFrom<Checkpoint> for RpcCheckpoint {
             network_total_transactions: checkpoint.total_transactions_from_genesis as u64,
             timestamp_ms: checkpoint.timestamp_ms as u64,
             transactions: parsed_txn_digests,
-            checkpoint_commitments: vec![],
+            checkpoint_commitments: summary.checkpoint_commitments,
+            validator_signature: (&validator_signature).into(),
         })
     }
 }
@@ -174,10 +189,12 @@ impl Checkpoint {
             .iter()
             .map(|t| Some(t.base58_encode()))
             .collect();
+        let (checkpoint_summary, validator_signature) = certified_summary_bytes(rpc_checkpoint)?;
 
         Ok(Checkpoint {
             sequence_number: rpc_checkpoint.sequence_number as i64,
             checkpoint_digest: rpc_checkpoint.digest.base58_encode(),
+            content_digest: Some(rpc_checkpoint.content_digest.base58_encode()),
             epoch: rpc_checkpoint.epoch as i64,
             transactions: checkpoint_transactions,
             previous_checkpoint_digest: rpc_checkpoint.previous_digest.map(|d| d.base58_encode()),
@@ -203,6 +220,108 @@ impl Checkpoint {
             )
             .unwrap(),
             checkpoint_tps: tps,
+            checkpoint_summary: Some(checkpoint_summary),
+            validator_signature: Some(validator_signature),
         })
     }
+
+    /// The digests of the checkpoint's transactions, in the order they were executed in.
+    pub fn transaction_digests(&self) -> Result<Vec<TransactionDigest>, IndexerError> {
+        self.transactions
+            .iter()
+            .flatten()
+            .map(|txn| {
+                txn.parse().map_err(|e| {
+                    IndexerError::SerdeError(format!(
+                        "Failed to decode transaction digest: {:?} with err: {:?}",
+                        txn, e
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// The summary of the checkpoint along with the aggregated signature of the validators that
+    /// certified it, `None` for checkpoints indexed before signatures were stored.
+    pub fn certified_summary(&self) -> Result<Option<CertifiedCheckpointSummary>, IndexerError> {
+        let (summary, signature) = match (&self.checkpoint_summary, &self.validator_signature) {
+            (Some(summary), Some(signature)) => (summary, signature),
+            _ => return Ok(None),
+        };
+        let summary: CheckpointSummary = bcs::from_bytes(summary).map_err(|e| {
+            IndexerError::SerdeError(format!(
+                "Failed to deserialize summary of checkpoint {} with error: {:?}",
+                self.sequence_number, e
+            ))
+        })?;
+        let signature: AuthorityStrongQuorumSignInfo = bcs::from_bytes(signature).map_err(|e| {
+            IndexerError::SerdeError(format!(
+                "Failed to deserialize validator signature of checkpoint {} with error: {:?}",
+                self.sequence_number, e
+            ))
+        })?;
+        Ok(Some(CertifiedCheckpointSummary::new_from_data_and_sig(
+            summary, signature,
+        )))
+    }
+}
+
+/// Rebuilds the summary the validators signed from the RPC checkpoint, along with their
+/// signature. The rebuilt summary is checked against the checkpoint digest, as it is only useful
+/// to verify the signature or to light clients if it is exactly the signed one.
+pub fn rpc_certified_summary(
+    rpc_checkpoint: &RpcCheckpoint,
+) -> Result<CertifiedCheckpointSummary, IndexerError> {
+    let summary = CheckpointSummary {
+        epoch: rpc_checkpoint.epoch,
+        sequence_number: rpc_checkpoint.sequence_number,
+        network_total_transactions: rpc_checkpoint.network_total_transactions,
+        content_digest: rpc_checkpoint.content_digest,
+        previous_digest: rpc_checkpoint.previous_digest,
+        epoch_rolling_gas_cost_summary: rpc_checkpoint.epoch_rolling_gas_cost_summary.clone(),
+        timestamp_ms: rpc_checkpoint.timestamp_ms,
+        checkpoint_commitments: rpc_checkpoint.checkpoint_commitments.clone(),
+        end_of_epoch_data: rpc_checkpoint.end_of_epoch_data.clone(),
+        // not exposed by the fullnode, no protocol version populates it yet
+        version_specific_data: vec![],
+    };
+    if summary.digest() != rpc_checkpoint.digest {
+        return Err(IndexerError::CheckpointDigestMismatch(format!(
+            "summary rebuilt for checkpoint {} has digest {:?}, expected {:?}",
+            rpc_checkpoint.sequence_number,
+            summary.digest(),
+            rpc_checkpoint.digest
+        )));
+    }
+    let signature = AuthorityStrongQuorumSignInfo::try_from(&rpc_checkpoint.validator_signature)
+        .map_err(|e| {
+            IndexerError::SerdeError(format!(
+                "Failed to decode validator signature of checkpoint {} with error: {:?}",
+                rpc_checkpoint.sequence_number, e
+            ))
+        })?;
+    Ok(CertifiedCheckpointSummary::new_from_data_and_sig(
+        summary, signature,
+    ))
+}
+
+/// The BCS bytes of the summary and of the signature of the checkpoint, see
+/// `rpc_certified_summary`.
+fn certified_summary_bytes(
+    rpc_checkpoint: &RpcCheckpoint,
+) -> Result<(Vec<u8>, Vec<u8>), IndexerError> {
+    let certified_summary = rpc_certified_summary(rpc_checkpoint)?;
+    let summary = bcs::to_bytes(certified_summary.data()).map_err(|e| {
+        IndexerError::SerdeError(format!(
+            "Failed to serialize summary of checkpoint {} with error: {:?}",
+            rpc_checkpoint.sequence_number, e
+        ))
+    })?;
+    let signature = bcs::to_bytes(certified_summary.auth_sig()).map_err(|e| {
+        IndexerError::SerdeError(format!(
+            "Failed to serialize validator signature of checkpoint {} with error: {:?}",
+            rpc_checkpoint.sequence_number, e
+        ))
+    })?;
+    Ok((summary, signature))
 }
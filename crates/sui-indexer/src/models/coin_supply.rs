@@ -0,0 +1,15 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::coin_supply;
+
+use diesel::prelude::*;
+
+/// The total supply of a coin type, as of the latest change to its treasury cap.
+#[derive(Queryable, Insertable, Debug, Clone)]
+#[diesel(table_name = coin_supply)]
+pub struct CoinSupply {
+    pub coin_type: String,
+    pub total_supply: i64,
+    pub checkpoint: i64,
+}
@@ -0,0 +1,25 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::epochs;
+
+use diesel::prelude::*;
+
+use sui_types::sui_system_state::{SuiSystemState, SuiSystemStateTrait};
+
+/// An epoch, read from the system state at its start.
+#[derive(Queryable, Insertable, Debug, Clone)]
+#[diesel(table_name = epochs)]
+pub struct Epoch {
+    pub epoch: i64,
+    pub reference_gas_price: i64,
+}
+
+impl From<&SuiSystemState> for Epoch {
+    fn from(system_state: &SuiSystemState) -> Self {
+        Self {
+            epoch: system_state.epoch() as i64,
+            reference_gas_price: system_state.reference_gas_price() as i64,
+        }
+    }
+}
@@ -26,10 +26,11 @@ pub struct Event {
     pub event_time_ms: Option<i64>,
     pub parsed_json: Value,
     pub event_bcs: Vec<u8>,
+    pub checkpoint_sequence_number: i64,
 }
 
-impl From<SuiEvent> for Event {
-    fn from(se: SuiEvent) -> Self {
+impl Event {
+    pub fn new(checkpoint_sequence_number: u64, se: SuiEvent) -> Self {
         Self {
             id: None,
             transaction_digest: se.id.tx_digest.base58_encode(),
@@ -41,6 +42,7 @@ impl From<SuiEvent> for Event {
             event_time_ms: se.timestamp_ms.map(|t| t as i64),
             parsed_json: se.parsed_json,
             event_bcs: se.bcs,
+            checkpoint_sequence_number: checkpoint_sequence_number as i64,
         }
     }
 }
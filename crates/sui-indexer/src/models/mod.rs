@@ -3,11 +3,17 @@
 
 pub mod addresses;
 pub mod checkpoints;
+pub mod coin_supply;
+pub mod epochs;
 pub mod error_logs;
 pub mod events;
 pub mod move_calls;
+pub mod object_transactions;
 pub mod objects;
 pub mod owners;
 pub mod packages;
+pub mod ptb_commands;
+pub mod raw_transactions;
 pub mod recipients;
+pub mod transaction_inputs;
 pub mod transactions;
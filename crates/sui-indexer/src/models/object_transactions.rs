@@ -0,0 +1,19 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::models::objects::ObjectStatus;
+use crate::schema::object_transactions;
+use diesel::prelude::*;
+
+/// A transaction whose effects touched an object, with the status and version of the object
+/// after it. Deleted and wrapped objects are recorded at the version of their tombstone.
+#[derive(Queryable, Insertable, Debug, Clone)]
+#[diesel(table_name = object_transactions)]
+pub struct ObjectTransaction {
+    pub object_id: String,
+    pub checkpoint_sequence_number: i64,
+    pub transaction_index: i64,
+    pub transaction_digest: String,
+    pub object_version: i64,
+    pub object_status: ObjectStatus,
+}
@@ -3,8 +3,8 @@
 
 use crate::errors::IndexerError;
 use crate::models::owners::OwnerType;
-use crate::schema::objects;
 use crate::schema::sql_types::BcsBytes;
+use crate::schema::{objects, objects_history};
 use diesel::deserialize::FromSql;
 use diesel::pg::{Pg, PgValue};
 use diesel::prelude::*;
@@ -13,6 +13,7 @@ use diesel::sql_types::{Bytea, Nullable, Record, VarChar};
 use diesel::SqlType;
 use diesel_derive_enum::DbEnum;
 use move_bytecode_utils::module_cache::GetModule;
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use sui_json_rpc_types::{SuiObjectData, SuiObjectRef, SuiRawData};
 use sui_types::base_types::{EpochId, ObjectID, ObjectRef, ObjectType, SequenceNumber, SuiAddress};
@@ -42,7 +43,66 @@ pub struct Object {
     pub has_public_transfer: bool,
     pub storage_rebate: i64,
     pub bcs: Vec<NamedBcsBytes>,
+    /// Set when the type of the object could not be resolved as it was indexed, so that its
+    /// contents are only available as raw BCS until it is reprocessed.
+    pub type_unresolved: bool,
 }
+
+/// A version of an object in `objects_history`. Deleted and wrapped versions are tombstones
+/// without owner nor contents.
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = objects_history)]
+pub struct ObjectHistory {
+    pub epoch: i64,
+    pub checkpoint: i64,
+    pub object_id: String,
+    pub version: i64,
+    pub object_digest: String,
+    pub owner_type: OwnerType,
+    pub owner_address: Option<String>,
+    pub initial_shared_version: Option<i64>,
+    pub previous_transaction: String,
+    pub object_type: String,
+    pub object_status: ObjectStatus,
+    pub has_public_transfer: bool,
+    pub storage_rebate: i64,
+    pub bcs: Vec<NamedBcsBytes>,
+    pub type_unresolved: bool,
+}
+
+impl From<Object> for ObjectHistory {
+    fn from(o: Object) -> Self {
+        ObjectHistory {
+            epoch: o.epoch,
+            checkpoint: o.checkpoint,
+            object_id: o.object_id,
+            version: o.version,
+            object_digest: o.object_digest,
+            owner_type: o.owner_type,
+            owner_address: o.owner_address,
+            initial_shared_version: o.initial_shared_version,
+            previous_transaction: o.previous_transaction,
+            object_type: o.object_type,
+            object_status: o.object_status,
+            has_public_transfer: o.has_public_transfer,
+            storage_rebate: o.storage_rebate,
+            bcs: o.bcs,
+            type_unresolved: o.type_unresolved,
+        }
+    }
+}
+
+/// The transaction that produced the current version of an object, as tracked by the
+/// `previous_transaction` column of the objects table.
+#[derive(Queryable, Debug, Clone)]
+pub struct LastModifyingTransaction {
+    pub transaction_digest: String,
+    // checkpoint seq number of the transaction.
+    pub checkpoint: i64,
+    // the version of the object the transaction produced.
+    pub version: i64,
+}
+
 #[derive(SqlType, Debug, Clone)]
 #[diesel(sql_type = crate::schema::sql_types::BcsBytes)]
 pub struct NamedBcsBytes(pub String, pub Vec<u8>);
@@ -93,11 +153,12 @@ impl From<DeletedObject> for Object {
             has_public_transfer: o.has_public_transfer,
             storage_rebate: 0,
             bcs: vec![],
+            type_unresolved: false,
         }
     }
 }
 
-#[derive(DbEnum, Debug, Clone, Copy)]
+#[derive(DbEnum, Debug, Clone, Copy, Serialize, Deserialize)]
 #[ExistingTypePath = "crate::schema::sql_types::ObjectStatus"]
 pub enum ObjectStatus {
     Created,
@@ -155,6 +216,7 @@ impl Object {
             has_public_transfer,
             storage_rebate: o.storage_rebate.unwrap_or_default() as i64,
             bcs,
+            type_unresolved: false,
         }
     }
 
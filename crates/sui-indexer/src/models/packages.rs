@@ -8,8 +8,10 @@ use diesel::prelude::*;
 
 use crate::models::objects::NamedBcsBytes;
 use sui_json_rpc_types::SuiRawMovePackage;
-use sui_types::base_types::SuiAddress;
+use sui_types::base_types::{ObjectID, SuiAddress};
 
+/// A published package. Every upgrade of a package is published at a new id, `original_id`
+/// ties all of them back to the first version so that the lineage can be followed.
 #[derive(Queryable, Insertable, Debug, Identifiable)]
 #[diesel(table_name = packages, primary_key(package_id, version))]
 pub struct Package {
@@ -17,10 +19,17 @@ pub struct Package {
     pub version: i64,
     pub author: String,
     pub data: Vec<NamedBcsBytes>,
+    pub original_id: String,
+    pub upgrade_cap_id: Option<String>,
 }
 
 impl Package {
-    pub fn try_from(sender: SuiAddress, package: &SuiRawMovePackage) -> Result<Self, IndexerError> {
+    pub fn try_from(
+        sender: SuiAddress,
+        package: &SuiRawMovePackage,
+        original_id: String,
+        upgrade_cap_id: Option<ObjectID>,
+    ) -> Result<Self, IndexerError> {
         Ok(Self {
             package_id: package.id.to_string(),
             version: package.version.value() as i64,
@@ -31,6 +40,8 @@ impl Package {
                 .into_iter()
                 .map(|(k, v)| NamedBcsBytes(k, v))
                 .collect(),
+            original_id,
+            upgrade_cap_id: upgrade_cap_id.map(|id| id.to_string()),
         })
     }
 }
@@ -0,0 +1,69 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::errors::IndexerError;
+use crate::schema::ptb_commands;
+use diesel::prelude::*;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use sui_types::messages::{Argument, Command};
+
+/// A command of a programmable transaction, with its arguments and the earlier commands of the
+/// transaction whose results it takes.
+#[derive(Queryable, Insertable, Debug, Clone)]
+#[diesel(table_name = ptb_commands)]
+pub struct PtbCommand {
+    pub transaction_digest: String,
+    pub checkpoint_sequence_number: i64,
+    pub command_index: i32,
+    pub command_kind: String,
+    pub arguments: Value,
+    pub input_commands: Vec<Option<i32>>,
+}
+
+impl PtbCommand {
+    pub fn new(
+        transaction_digest: String,
+        checkpoint_sequence_number: i64,
+        command_index: usize,
+        command: &Command,
+    ) -> Result<Self, IndexerError> {
+        let (command_kind, arguments) = match command {
+            Command::MoveCall(call) => ("MoveCall", call.arguments.clone()),
+            Command::TransferObjects(objects, recipient) => {
+                let mut arguments = objects.clone();
+                arguments.push(*recipient);
+                ("TransferObjects", arguments)
+            }
+            Command::SplitCoin(coin, amount) => ("SplitCoin", vec![*coin, *amount]),
+            Command::MergeCoins(coin, coins) => {
+                let mut arguments = vec![*coin];
+                arguments.extend(coins);
+                ("MergeCoins", arguments)
+            }
+            Command::Publish(_) => ("Publish", vec![]),
+            Command::MakeMoveVec(_, elements) => ("MakeMoveVec", elements.clone()),
+            Command::Upgrade(_, _, _, ticket) => ("Upgrade", vec![*ticket]),
+        };
+        let input_commands: BTreeSet<_> = arguments
+            .iter()
+            .filter_map(|argument| match argument {
+                Argument::Result(i) | Argument::NestedResult(i, _) => Some(*i as i32),
+                Argument::GasCoin | Argument::Input(_) => None,
+            })
+            .collect();
+        Ok(Self {
+            transaction_digest,
+            checkpoint_sequence_number,
+            command_index: command_index as i32,
+            command_kind: command_kind.to_string(),
+            arguments: serde_json::to_value(&arguments).map_err(|e| {
+                IndexerError::SerdeError(format!(
+                    "Failed to serialize the arguments of command {}: {:?}",
+                    command_index, e
+                ))
+            })?,
+            input_commands: input_commands.into_iter().map(Some).collect(),
+        })
+    }
+}
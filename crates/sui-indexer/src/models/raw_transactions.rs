@@ -0,0 +1,88 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::errors::IndexerError;
+use crate::schema::raw_transactions;
+use crate::types::SuiTransactionFullResponse;
+use diesel::prelude::*;
+use sui_types::base_types::ExecutionDigests;
+use sui_types::message_envelope::Message;
+use sui_types::messages::{SenderSignedData, TransactionDataAPI, TransactionEffects};
+use sui_types::messages_checkpoint::CheckpointContents;
+
+/// Raw BCS bytes of a transaction and its effects, kept so that the indexed data
+/// can be re-verified against the signatures and digests without the fullnode.
+#[derive(Queryable, Insertable, Debug, Clone, Default)]
+pub struct RawTransaction {
+    pub transaction_digest: String,
+    pub checkpoint_sequence_number: i64,
+    pub raw_transaction: Vec<u8>,
+    pub raw_effects: Vec<u8>,
+}
+
+impl TryFrom<&SuiTransactionFullResponse> for RawTransaction {
+    type Error = IndexerError;
+
+    fn try_from(tx_resp: &SuiTransactionFullResponse) -> Result<Self, Self::Error> {
+        if tx_resp.raw_transaction.is_empty() || tx_resp.raw_effects.is_empty() {
+            return Err(IndexerError::InsertableParsingError(format!(
+                "Raw BCS bytes are missing in transaction response of digest {:?}",
+                tx_resp.digest
+            )));
+        }
+        Ok(RawTransaction {
+            transaction_digest: tx_resp.digest.to_string(),
+            checkpoint_sequence_number: tx_resp.checkpoint as i64,
+            raw_transaction: tx_resp.raw_transaction.clone(),
+            raw_effects: tx_resp.raw_effects.clone(),
+        })
+    }
+}
+
+impl RawTransaction {
+    pub fn sender_signed_data(&self) -> Result<SenderSignedData, IndexerError> {
+        bcs::from_bytes(&self.raw_transaction).map_err(|e| {
+            IndexerError::SerdeError(format!(
+                "Failed to deserialize raw transaction {:?} with error: {:?}",
+                self.transaction_digest, e
+            ))
+        })
+    }
+
+    pub fn effects(&self) -> Result<TransactionEffects, IndexerError> {
+        bcs::from_bytes(&self.raw_effects).map_err(|e| {
+            IndexerError::SerdeError(format!(
+                "Failed to deserialize raw effects of transaction {:?} with error: {:?}",
+                self.transaction_digest, e
+            ))
+        })
+    }
+}
+
+/// Rebuild the contents of a checkpoint from the raw data of its transactions, given in the
+/// order of the checkpoint.
+pub fn checkpoint_contents(
+    raw_transactions: &[RawTransaction],
+) -> Result<CheckpointContents, IndexerError> {
+    let mut execution_digests = Vec::with_capacity(raw_transactions.len());
+    let mut user_signatures = Vec::with_capacity(raw_transactions.len());
+    for raw_transaction in raw_transactions {
+        let sender_signed_data = raw_transaction.sender_signed_data()?;
+        let effects = raw_transaction.effects()?;
+        execution_digests.push(ExecutionDigests::new(
+            sender_signed_data.digest(),
+            effects.digest(),
+        ));
+        // system transactions are not user signed and are checkpointed without signatures
+        if sender_signed_data.intent_message().value.is_system_tx() {
+            user_signatures.push(vec![]);
+        } else {
+            user_signatures.push(sender_signed_data.tx_signatures().to_vec());
+        }
+    }
+    let contents = CheckpointContents::new_with_causally_ordered_transactions_and_signatures(
+        execution_digests,
+        user_signatures,
+    );
+    Ok(contents)
+}
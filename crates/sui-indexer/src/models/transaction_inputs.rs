@@ -0,0 +1,23 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::transaction_inputs;
+use diesel::prelude::*;
+use serde_json::Value;
+
+/// An input of a transaction passed as argument to one of its move calls, decoded against the
+/// type of the function parameter it is passed to. Arguments that cannot be decoded are kept
+/// as raw bytes, with `is_decoded` unset.
+#[derive(Queryable, Insertable, Debug, Clone)]
+#[diesel(table_name = transaction_inputs)]
+pub struct TransactionInput {
+    pub transaction_digest: String,
+    pub checkpoint_sequence_number: i64,
+    pub command_index: i32,
+    pub argument_index: i32,
+    pub input_index: i32,
+    pub parameter_type: Option<String>,
+    pub raw_value: Vec<u8>,
+    pub value: Option<Value>,
+    pub is_decoded: bool,
+}
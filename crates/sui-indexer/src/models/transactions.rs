@@ -10,6 +10,8 @@ use sui_json_rpc_types::{
     OwnedObjectRef, SuiObjectRef, SuiTransaction, SuiTransactionDataAPI, SuiTransactionEffects,
     SuiTransactionEffectsAPI,
 };
+use sui_types::crypto::{SignatureScheme, SuiSignature};
+use sui_types::signature::GenericSignature;
 
 use crate::errors::IndexerError;
 use crate::schema::transactions::transaction_digest;
@@ -45,6 +47,9 @@ pub struct Transaction {
     pub transaction_content: String,
     pub transaction_effects_content: String,
     pub confirmed_local_execution: Option<bool>,
+    pub signature_schemes: Vec<Option<String>>,
+    pub multisig_threshold: Option<i32>,
+    pub multisig_schemes: Option<Vec<Option<String>>>,
 }
 
 pub fn commit_transactions(
@@ -96,6 +101,25 @@ impl TryFrom<SuiTransactionFullResponse> for Transaction {
             ))
         })?;
 
+        let signature_schemes: Vec<String> = tx_resp
+            .transaction
+            .tx_signatures
+            .iter()
+            .map(|signature| signature_scheme(signature).to_string())
+            .collect();
+        // Signatures are in the order of the signers, the sender's comes first
+        let sender_multisig = match tx_resp.transaction.tx_signatures.first() {
+            Some(GenericSignature::MultiSig(multisig)) => Some(&multisig.multisig_pk),
+            _ => None,
+        };
+        let multisig_threshold = sender_multisig.map(|pk| *pk.threshold() as i32);
+        let multisig_schemes = sender_multisig.map(|pk| {
+            pk.pubkeys()
+                .iter()
+                .map(|(pk, _)| Some(pk.scheme().to_string()))
+                .collect()
+        });
+
         let effects = tx_resp.effects;
         let transaction_data = tx_resp.transaction.data;
         // canonical txn digest string is Base58 encoded
@@ -189,6 +213,9 @@ impl TryFrom<SuiTransactionFullResponse> for Transaction {
             transaction_content: txn_json,
             transaction_effects_content: txn_effect_json,
             confirmed_local_execution: tx_resp.confirmed_local_execution,
+            signature_schemes: vec_string_to_vec_opt_string(signature_schemes),
+            multisig_threshold,
+            multisig_schemes,
         })
     }
 }
@@ -219,7 +246,10 @@ impl TryInto<SuiTransactionFullResponse> for Transaction {
                 ))
             })?,
             transaction,
+            // raw BCS bytes are stored in the raw_transactions table when enabled
+            raw_transaction: vec![],
             effects,
+            raw_effects: vec![],
             confirmed_local_execution: self.confirmed_local_execution,
             timestamp_ms: self.timestamp_ms as u64,
             checkpoint: self.checkpoint_sequence_number as u64,
@@ -239,6 +269,13 @@ fn obj_ref_to_obj_id_string(obj_ref: &SuiObjectRef) -> String {
     obj_ref.object_id.to_string()
 }
 
+fn signature_scheme(signature: &GenericSignature) -> SignatureScheme {
+    match signature {
+        GenericSignature::MultiSig(_) => SignatureScheme::MultiSig,
+        GenericSignature::Signature(signature) => signature.scheme(),
+    }
+}
+
 fn vec_string_to_vec_opt_string(v: Vec<String>) -> Vec<Option<String>> {
     v.into_iter().map(Some).collect::<Vec<Option<String>>>()
 }
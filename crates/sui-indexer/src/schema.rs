@@ -44,6 +44,38 @@ diesel::table! {
         timestamp_ms -> Int8,
         timestamp_ms_str -> Timestamp,
         checkpoint_tps -> Float4,
+        checkpoint_summary -> Nullable<Bytea>,
+        validator_signature -> Nullable<Bytea>,
+        content_digest -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    chain_identifier (id) {
+        id -> Int2,
+        identifier -> Varchar,
+    }
+}
+
+diesel::table! {
+    checkpoint_ranges (range_start) {
+        range_start -> Int8,
+        range_end -> Int8,
+    }
+}
+
+diesel::table! {
+    coin_supply (coin_type) {
+        coin_type -> Varchar,
+        total_supply -> Int8,
+        checkpoint -> Int8,
+    }
+}
+
+diesel::table! {
+    epochs (epoch) {
+        epoch -> Int8,
+        reference_gas_price -> Int8,
     }
 }
 
@@ -68,6 +100,14 @@ diesel::table! {
         event_time_ms -> Nullable<Int8>,
         parsed_json -> Jsonb,
         event_bcs -> Bytea,
+        checkpoint_sequence_number -> Int8,
+    }
+}
+
+diesel::table! {
+    lagging_handlers (handler) {
+        handler -> Varchar,
+        checkpoint_sequence_number -> Int8,
     }
 }
 
@@ -84,6 +124,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ObjectStatus;
+
+    object_transactions (object_id, checkpoint_sequence_number, transaction_index) {
+        object_id -> Varchar,
+        checkpoint_sequence_number -> Int8,
+        transaction_index -> Int8,
+        transaction_digest -> Varchar,
+        object_version -> Int8,
+        object_status -> ObjectStatus,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::OwnerType;
@@ -105,6 +159,7 @@ diesel::table! {
         has_public_transfer -> Bool,
         storage_rebate -> Int8,
         bcs -> Array<Nullable<BcsBytes>>,
+        type_unresolved -> Bool,
     }
 }
 
@@ -129,6 +184,7 @@ diesel::table! {
         has_public_transfer -> Bool,
         storage_rebate -> Int8,
         bcs -> Array<Nullable<BcsBytes>>,
+        type_unresolved -> Bool,
     }
 }
 
@@ -177,6 +233,28 @@ diesel::table! {
         version -> Int8,
         author -> Varchar,
         data -> Array<Nullable<BcsBytes>>,
+        original_id -> Varchar,
+        upgrade_cap_id -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    ptb_commands (transaction_digest, command_index) {
+        transaction_digest -> Varchar,
+        checkpoint_sequence_number -> Int8,
+        command_index -> Int4,
+        command_kind -> Varchar,
+        arguments -> Jsonb,
+        input_commands -> Array<Nullable<Int4>>,
+    }
+}
+
+diesel::table! {
+    raw_transactions (transaction_digest) {
+        transaction_digest -> Varchar,
+        checkpoint_sequence_number -> Int8,
+        raw_transaction -> Bytea,
+        raw_effects -> Bytea,
     }
 }
 
@@ -190,6 +268,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    transaction_inputs (transaction_digest, command_index, argument_index) {
+        transaction_digest -> Varchar,
+        checkpoint_sequence_number -> Int8,
+        command_index -> Int4,
+        argument_index -> Int4,
+        input_index -> Int4,
+        parameter_type -> Nullable<Text>,
+        raw_value -> Bytea,
+        value -> Nullable<Jsonb>,
+        is_decoded -> Bool,
+    }
+}
+
 diesel::table! {
     transactions (id) {
         id -> Int8,
@@ -217,20 +309,32 @@ diesel::table! {
         transaction_content -> Text,
         transaction_effects_content -> Text,
         confirmed_local_execution -> Nullable<Bool>,
+        signature_schemes -> Array<Nullable<Text>>,
+        multisig_threshold -> Nullable<Int4>,
+        multisig_schemes -> Nullable<Array<Nullable<Text>>>,
     }
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
     addresses,
+    chain_identifier,
+    checkpoint_ranges,
     checkpoints,
+    coin_supply,
+    epochs,
     error_logs,
     events,
+    lagging_handlers,
     move_calls,
+    object_transactions,
     objects,
     objects_history,
     owner,
     owner_history,
     packages,
+    ptb_commands,
+    raw_transactions,
     recipients,
+    transaction_inputs,
     transactions,
 );
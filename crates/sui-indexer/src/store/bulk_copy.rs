@@ -0,0 +1,209 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Arc, Mutex};
+
+use postgres::binary_copy::BinaryCopyInWriter;
+use postgres::types::{ToSql, Type};
+use postgres::{Client, NoTls};
+
+use crate::errors::IndexerError;
+use crate::models::events::Event;
+use crate::models::transactions::Transaction;
+
+const COPY_TRANSACTIONS: &str = "COPY transactions (transaction_digest, sender, recipients, \
+    checkpoint_sequence_number, timestamp_ms, transaction_kind, created, mutated, deleted, \
+    unwrapped, wrapped, move_calls, gas_object_id, gas_object_sequence, gas_object_digest, \
+    gas_budget, total_gas_cost, computation_cost, storage_cost, storage_rebate, gas_price, \
+    transaction_content, transaction_effects_content, confirmed_local_execution, \
+    signature_schemes, multisig_threshold, multisig_schemes) FROM STDIN BINARY";
+
+const COPY_EVENTS: &str = "COPY events (transaction_digest, event_sequence, sender, package, \
+    module, event_type, event_time_ms, parsed_json, event_bcs, checkpoint_sequence_number) \
+    FROM STDIN BINARY";
+
+/// Loads transactions and events with a binary `COPY`, which diesel 2.0 does not support, over
+/// connections of its own made with the `postgres` crate. The `id` columns are left out for
+/// their sequences to fill in, as they are on `INSERT`.
+#[derive(Clone)]
+pub(crate) struct BulkCopy {
+    db_url: String,
+    /// Idle connections, a load takes one or opens a new one, so concurrent loads do not wait
+    /// on each other
+    clients: Arc<Mutex<Vec<Client>>>,
+}
+
+impl BulkCopy {
+    pub(crate) fn new(db_url: String) -> Self {
+        Self {
+            db_url,
+            clients: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Replace the transactions and events of checkpoint `checkpoint_sequence_number` with
+    /// `transactions` and `events`, in a single database transaction. The checkpoint has not been
+    /// committed yet, as checkpoints are committed last, so the rows it already has are leftovers
+    /// of an interrupted commit. Rows of other checkpoints are kept, they may belong to another
+    /// checkpoint range.
+    pub(crate) fn load(
+        &self,
+        checkpoint_sequence_number: i64,
+        transactions: &[Transaction],
+        events: &[Event],
+    ) -> Result<(), IndexerError> {
+        // The blocking client runs a runtime of its own, which cannot be entered from the
+        // runtime of the caller.
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let mut client = self.take_client()?;
+                    copy(&mut client, checkpoint_sequence_number, transactions, events).map_err(
+                        |e| {
+                            IndexerError::PostgresWriteError(format!(
+                                "Failed copying transactions and events of checkpoint {} to PostgresDB with error: {:?}",
+                                checkpoint_sequence_number, e
+                            ))
+                        },
+                    )?;
+                    self.clients.lock().unwrap().push(client);
+                    Ok(())
+                })
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+    }
+
+    fn take_client(&self) -> Result<Client, IndexerError> {
+        let idle = self.clients.lock().unwrap().pop();
+        match idle {
+            Some(client) if !client.is_closed() => Ok(client),
+            _ => Client::connect(&self.db_url, NoTls).map_err(|e| {
+                IndexerError::PgPoolConnectionError(format!(
+                    "Failed to connect to PostgresDB for bulk loading with error: {:?}",
+                    e
+                ))
+            }),
+        }
+    }
+}
+
+fn copy(
+    client: &mut Client,
+    checkpoint_sequence_number: i64,
+    transactions: &[Transaction],
+    events: &[Event],
+) -> Result<(), postgres::Error> {
+    let mut db_transaction = client.transaction()?;
+    db_transaction.execute(
+        "DELETE FROM transactions WHERE checkpoint_sequence_number = $1",
+        &[&checkpoint_sequence_number],
+    )?;
+    db_transaction.execute(
+        "DELETE FROM events WHERE checkpoint_sequence_number = $1",
+        &[&checkpoint_sequence_number],
+    )?;
+
+    let text_array = Type::TEXT_ARRAY;
+    let sink = db_transaction.copy_in(COPY_TRANSACTIONS)?;
+    let mut writer = BinaryCopyInWriter::new(
+        sink,
+        &[
+            Type::VARCHAR,
+            Type::VARCHAR,
+            text_array.clone(),
+            Type::INT8,
+            Type::INT8,
+            Type::TEXT,
+            text_array.clone(),
+            text_array.clone(),
+            text_array.clone(),
+            text_array.clone(),
+            text_array.clone(),
+            text_array.clone(),
+            Type::VARCHAR,
+            Type::INT8,
+            Type::VARCHAR,
+            Type::INT8,
+            Type::INT8,
+            Type::INT8,
+            Type::INT8,
+            Type::INT8,
+            Type::INT8,
+            Type::TEXT,
+            Type::TEXT,
+            Type::BOOL,
+            text_array.clone(),
+            Type::INT4,
+            text_array,
+        ],
+    );
+    for t in transactions {
+        let row: [&(dyn ToSql + Sync); 27] = [
+            &t.transaction_digest,
+            &t.sender,
+            &t.recipients,
+            &t.checkpoint_sequence_number,
+            &t.timestamp_ms,
+            &t.transaction_kind,
+            &t.created,
+            &t.mutated,
+            &t.deleted,
+            &t.unwrapped,
+            &t.wrapped,
+            &t.move_calls,
+            &t.gas_object_id,
+            &t.gas_object_sequence,
+            &t.gas_object_digest,
+            &t.gas_budget,
+            &t.total_gas_cost,
+            &t.computation_cost,
+            &t.storage_cost,
+            &t.storage_rebate,
+            &t.gas_price,
+            &t.transaction_content,
+            &t.transaction_effects_content,
+            &t.confirmed_local_execution,
+            &t.signature_schemes,
+            &t.multisig_threshold,
+            &t.multisig_schemes,
+        ];
+        writer.write(&row)?;
+    }
+    writer.finish()?;
+
+    let sink = db_transaction.copy_in(COPY_EVENTS)?;
+    let mut writer = BinaryCopyInWriter::new(
+        sink,
+        &[
+            Type::VARCHAR,
+            Type::INT8,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::TEXT,
+            Type::TEXT,
+            Type::INT8,
+            Type::JSONB,
+            Type::BYTEA,
+            Type::INT8,
+        ],
+    );
+    for e in events {
+        let row: [&(dyn ToSql + Sync); 10] = [
+            &e.transaction_digest,
+            &e.event_sequence,
+            &e.sender,
+            &e.package,
+            &e.module,
+            &e.event_type,
+            &e.event_time_ms,
+            &e.parsed_json,
+            &e.event_bcs,
+            &e.checkpoint_sequence_number,
+        ];
+        writer.write(&row)?;
+    }
+    writer.finish()?;
+
+    db_transaction.commit()
+}
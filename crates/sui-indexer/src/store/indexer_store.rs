@@ -4,111 +4,307 @@
 use crate::errors::IndexerError;
 use crate::models::addresses::Address;
 use crate::models::checkpoints::Checkpoint;
+use crate::models::coin_supply::CoinSupply;
+use crate::models::epochs::Epoch;
 use crate::models::events::Event;
 use crate::models::move_calls::MoveCall;
-use crate::models::objects::{DeletedObject, Object, ObjectStatus};
+use crate::models::object_transactions::ObjectTransaction;
+use crate::models::objects::{DeletedObject, LastModifyingTransaction, Object, ObjectStatus};
 use crate::models::owners::ObjectOwner;
 use crate::models::packages::Package;
+use crate::models::ptb_commands::PtbCommand;
+use crate::models::raw_transactions::RawTransaction;
 use crate::models::recipients::Recipient;
+use crate::models::transaction_inputs::TransactionInput;
 use crate::models::transactions::Transaction;
 use crate::types::SuiTransactionFullResponse;
+use crate::IndexerHandler;
 use async_trait::async_trait;
+use futures::stream::BoxStream;
+use move_core_types::language_storage::StructTag;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use sui_json_rpc_types::{
-    Checkpoint as RpcCheckpoint, CheckpointId, EventFilter, EventPage, SuiObjectData,
+    Checkpoint as RpcCheckpoint, CheckpointId, EventFilter, SuiEvent, SuiObjectData,
 };
-use sui_types::base_types::{ObjectID, SequenceNumber};
+use sui_types::base_types::{
+    ExecutionDigests, ObjectID, ObjectRef, SequenceNumber, SuiAddress, TransactionDigest,
+};
+use sui_types::committee::{Committee, EpochId};
+use sui_types::crypto::SignatureScheme;
 use sui_types::event::EventID;
-use sui_types::object::ObjectRead;
+use sui_types::gas::GasCostSummary;
+use sui_types::message_envelope::Message;
+use sui_types::messages::{TransactionEffects, TransactionEffectsAPI};
+use sui_types::messages_checkpoint::{
+    CertifiedCheckpointSummary, CheckpointContents, CheckpointSequenceNumber,
+};
+use sui_types::object::{Object as SuiObject, ObjectRead};
+
+/// Ownership chains deeper than this are not followed by `IndexerStore::get_object_ancestry`
+pub const MAX_OBJECT_ANCESTRY_DEPTH: usize = 64;
 
 #[async_trait]
 pub trait IndexerStore {
     type ModuleCache;
 
+    /// The latest indexed checkpoint, which indexing resumes after, or -1 if there is none. The
+    /// checkpoints of registered ranges are left out, as they are indexed out of order.
     fn get_latest_checkpoint_sequence_number(&self) -> Result<i64, IndexerError>;
+    /// The latest indexed checkpoint between `first` and `last`, both included.
+    fn get_latest_checkpoint_sequence_number_in_range(
+        &self,
+        first: CheckpointSequenceNumber,
+        last: CheckpointSequenceNumber,
+    ) -> Result<Option<i64>, IndexerError>;
+    /// Register the range of checkpoints between `first` and `last`, both included, as indexed by
+    /// an indexer of its own, see `--checkpoint-range`.
+    fn register_checkpoint_range(
+        &self,
+        first: CheckpointSequenceNumber,
+        last: CheckpointSequenceNumber,
+    ) -> Result<(), IndexerError>;
+    /// Record `chain_id` as the chain the store is indexed from unless one is recorded already,
+    /// and return the recorded one.
+    fn persist_chain_identifier(&self, chain_id: &str) -> Result<String, IndexerError>;
     fn get_checkpoint(&self, id: CheckpointId) -> Result<Checkpoint, IndexerError>;
+    /// The summary of the checkpoint along with the aggregated signature of the validators that
+    /// certified it, for light clients to verify the checkpoint against the committee.
+    fn get_checkpoint_summary(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<CertifiedCheckpointSummary, IndexerError>;
+    /// The digests of the transactions of the checkpoint, in the canonical order in which they
+    /// were executed.
+    fn get_checkpoint_transaction_digests(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<Vec<TransactionDigest>, IndexerError>;
+    /// The reference gas price of the latest ingested epoch.
+    fn get_reference_gas_price(&self) -> Result<u64, IndexerError>;
+    fn get_reference_gas_price_at_epoch(&self, epoch: EpochId) -> Result<u64, IndexerError>;
+    /// The total supply of the coin type, as recorded by the latest indexed version of its
+    /// treasury cap.
+    fn get_total_supply(&self, coin_type: &str) -> Result<u64, IndexerError>;
 
     fn get_event(&self, id: EventID) -> Result<Event, IndexerError>;
+    fn get_event_cursor(&self, id: EventID) -> Result<Cursor, IndexerError>;
     fn get_events(
         &self,
         query: EventFilter,
-        cursor: Option<EventID>,
-        limit: Option<usize>,
+        cursor: Option<Cursor>,
+        limit: usize,
         descending_order: bool,
-    ) -> Result<EventPage, IndexerError>;
+    ) -> Result<(Vec<SuiEvent>, Option<Cursor>), IndexerError>;
 
+    /// An object at `version` if given, at its latest version otherwise, as of `consistency`.
+    /// A version written after the checkpoint of `ConsistencyLevel::AtCheckpoint` reads as not
+    /// existing.
     fn get_object(
         &self,
         object_id: ObjectID,
         version: Option<SequenceNumber>,
+        consistency: ConsistencyLevel,
     ) -> Result<ObjectRead, IndexerError>;
+    /// An object at a past version, only available when object history is indexed. Deleted
+    /// versions read as deleted.
+    fn get_object_at_version(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> Result<ObjectRead, IndexerError>;
+    /// The indexed versions of an object in ascending order, starting after `cursor`, only
+    /// available when object history is indexed
+    fn get_object_versions(
+        &self,
+        object_id: ObjectID,
+        cursor: Option<SequenceNumber>,
+        limit: usize,
+    ) -> Result<(Vec<Object>, Option<SequenceNumber>), IndexerError>;
+    /// The ids among `object_ids` of the objects in the objects table
+    fn get_indexed_object_ids(
+        &self,
+        object_ids: &[String],
+    ) -> Result<BTreeSet<String>, IndexerError>;
+    /// The transaction that created or last modified the latest version of an object, deleted
+    /// and wrapped objects included
+    fn get_last_modifying_transaction(
+        &self,
+        object_id: ObjectID,
+    ) -> Result<LastModifyingTransaction, IndexerError>;
+    /// The ownership chain of an object: the object itself, then the object owning it and so on,
+    /// up to the first object not owned by another one, i.e. owned by an address, shared or
+    /// immutable. The value of a dynamic object field is owned by its field, which is owned by
+    /// the parent, so both appear in the chain. The chain also ends at a wrapped or deleted
+    /// object, as the object wrapping it is not indexed, and before an owner missing from the
+    /// index. Empty if the object itself is not indexed. Fails on a cycle or on a chain longer
+    /// than `MAX_OBJECT_ANCESTRY_DEPTH`.
+    fn get_object_ancestry(&self, object_id: ObjectID) -> Result<Vec<ObjectRead>, IndexerError>;
+    /// The version of an object written by the checkpoint, along with the proof that the
+    /// checkpoint certified by the validators wrote it, see `ObjectInclusionProof`. If several
+    /// transactions of the checkpoint wrote the object, the proof is for the last one. Fails with
+    /// `IndexerError::InclusionProofUnavailable` for checkpoints indexed without their validator
+    /// signature or the raw data of their transactions, and for object versions no longer
+    /// indexed, i.e. overwritten since when object history is not indexed.
+    fn get_object_inclusion_proof(
+        &self,
+        object_id: ObjectID,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<ObjectInclusionProof, IndexerError>;
+    /// Every object of type `object_type`, deleted and wrapped objects excluded, in batches of at
+    /// most `batch_size` ordered by object id. A maintenance primitive for migrations and exports
+    /// rather than a query to serve: each batch is read in its own short transaction, starting
+    /// after the last object of the previous batch, so a scan over millions of objects neither
+    /// holds a long transaction nor more than a batch in memory. The position only moves on when
+    /// a batch is yielded, so dropping the stream at any point loses nothing. The stream ends
+    /// after the first error.
+    fn scan_objects_by_type(
+        &self,
+        object_type: StructTag,
+        batch_size: usize,
+    ) -> BoxStream<'static, Result<Vec<Object>, IndexerError>>;
+    /// At most `limit` dynamic fields, deleted and wrapped ones excluded, whose parent object is
+    /// absent from the objects table, ordered by object id. A diagnostic to validate the index
+    /// after incidents rather than a query to serve, it scans the dynamic fields. Note that the
+    /// fields of a parent that never was an object of its own, such as a `Table` wrapped in
+    /// another object, are returned too, so each result is a lead to check rather than a bug.
+    fn find_orphaned_dynamic_fields(&self, limit: usize) -> Result<Vec<Object>, IndexerError>;
 
     fn get_total_transaction_number(&self) -> Result<i64, IndexerError>;
 
     // TODO: combine all get_transaction* methods
     fn get_transaction_by_digest(&self, txn_digest: &str) -> Result<Transaction, IndexerError>;
+    fn get_raw_transaction(&self, txn_digest: &str) -> Result<RawTransaction, IndexerError>;
+    /// The inputs passed to the move calls of a transaction, ordered by command and argument
+    fn get_transaction_inputs(
+        &self,
+        txn_digest: &str,
+    ) -> Result<Vec<TransactionInput>, IndexerError>;
+    /// The commands of a programmable transaction ordered by index, each with its arguments and
+    /// the commands whose results it takes
+    fn get_ptb_structure(&self, txn_digest: &str) -> Result<Vec<PtbCommand>, IndexerError>;
     fn multi_get_transactions_by_digests(
         &self,
         txn_digests: &[String],
     ) -> Result<Vec<Transaction>, IndexerError>;
+    fn get_gas_breakdown(&self, txn_digest: &str) -> Result<GasCostSummary, IndexerError>;
+    /// Gas costs summed over all transactions of checkpoints `start_checkpoint` to
+    /// `end_checkpoint`, both inclusive
+    fn get_gas_totals(
+        &self,
+        start_checkpoint: i64,
+        end_checkpoint: i64,
+    ) -> Result<GasCostSummary, IndexerError>;
 
+    // All paginated methods below take an exclusive cursor, start from the beginning (or the
+    // end, if descending) when it is `None`, and return the page together with the cursor of
+    // its last item if there are more items to read.
     fn get_all_transaction_digest_page(
         &self,
-        start_sequence: Option<i64>,
+        cursor: Option<Cursor>,
         limit: usize,
         is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError>;
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError>;
 
     fn get_transaction_digest_page_by_mutated_object(
         &self,
         object_id: String,
-        start_sequence: Option<i64>,
+        cursor: Option<Cursor>,
         limit: usize,
         is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError>;
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError>;
+
+    /// Page through the transactions whose effects created, mutated, unwrapped, wrapped or
+    /// deleted an object, in checkpoint order only. Read off a per-object index, so that paging
+    /// through busy objects such as shared counters does not scan the transactions table.
+    fn get_transactions_for_object(
+        &self,
+        object_id: ObjectID,
+        cursor: Option<Cursor>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError>;
 
     fn get_transaction_digest_page_by_sender_address(
         &self,
         sender_address: String,
-        start_sequence: Option<i64>,
+        cursor: Option<Cursor>,
+        limit: usize,
+        is_descending: bool,
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError>;
+
+    /// Page through the transactions with a signature of the given scheme, see
+    /// `get_all_transaction_digest_page`. Multisig signers are found with
+    /// `SignatureScheme::MultiSig`, whatever the schemes of their public keys.
+    fn query_transactions_by_signature_scheme(
+        &self,
+        scheme: SignatureScheme,
+        cursor: Option<Cursor>,
         limit: usize,
         is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError>;
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError>;
+
+    /// Page through the transactions of checkpoints with a timestamp from `start_ms` inclusive
+    /// to `end_ms` exclusive, in checkpoint order only. The transactions of a checkpoint share
+    /// its timestamp, the cursor tells them apart.
+    fn query_transactions_by_time(
+        &self,
+        start_ms: i64,
+        end_ms: i64,
+        cursor: Option<Cursor>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError>;
 
     fn get_transaction_digest_page_by_recipient_address(
         &self,
         recipient_address: String,
-        start_sequence: Option<i64>,
+        cursor: Option<Cursor>,
         limit: usize,
         is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError>;
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError>;
+
+    /// Latest version of every object owned by `owner` as of `consistency`, optionally only
+    /// those of type `type_filter`. Paged by object id, so the cursor is the id of the last
+    /// object of a page.
+    fn get_owned_objects(
+        &self,
+        owner: SuiAddress,
+        type_filter: Option<StructTag>,
+        cursor: Option<ObjectID>,
+        limit: usize,
+        consistency: ConsistencyLevel,
+    ) -> Result<(Vec<ObjectRead>, Option<ObjectID>), IndexerError>;
+
+    /// Latest version of every shared object that was not deleted, optionally only those of type
+    /// `type_filter`. The version a shared object was shared at, which transactions take it as an
+    /// argument with, is in its `Owner::Shared`. Paged by object id, so the cursor is the id of the
+    /// last object of a page.
+    fn get_shared_objects(
+        &self,
+        type_filter: Option<StructTag>,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<(Vec<ObjectRead>, Option<ObjectID>), IndexerError>;
 
     fn get_transaction_digest_page_by_move_call(
         &self,
         package: String,
         module: Option<String>,
         function: Option<String>,
-        start_sequence: Option<i64>,
+        cursor: Option<Cursor>,
         limit: usize,
         is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError>;
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError>;
 
-    fn get_transaction_sequence_by_digest(
-        &self,
-        txn_digest: Option<String>,
-        is_descending: bool,
-    ) -> Result<Option<i64>, IndexerError>;
-
-    fn get_move_call_sequence_by_digest(
-        &self,
-        txn_digest: Option<String>,
-        is_descending: bool,
-    ) -> Result<Option<i64>, IndexerError>;
+    // The cursors of a transaction differ per table, as each table is paginated on its own rows.
+    fn get_transaction_cursor_by_digest(&self, txn_digest: &str) -> Result<Cursor, IndexerError>;
+    fn get_move_call_cursor_by_digest(&self, txn_digest: &str) -> Result<Cursor, IndexerError>;
+    fn get_recipient_cursor_by_digest(&self, txn_digest: &str) -> Result<Cursor, IndexerError>;
 
-    fn get_recipient_sequence_by_digest(
-        &self,
-        txn_digest: Option<String>,
-        is_descending: bool,
-    ) -> Result<Option<i64>, IndexerError>;
+    /// Latest version of the package published at `package_id`
+    fn get_package(&self, package_id: &str) -> Result<Package, IndexerError>;
+    /// All versions of a package, i.e. the original publish and every upgrade of it, ordered by
+    /// version
+    fn get_package_versions(&self, original_id: &str) -> Result<Vec<Package>, IndexerError>;
 
     fn read_transactions(
         &self,
@@ -117,6 +313,33 @@ pub trait IndexerStore {
     ) -> Result<Vec<Transaction>, IndexerError>;
 
     fn persist_checkpoint(&self, data: &TemporaryCheckpointStore) -> Result<usize, IndexerError>;
+    /// Same as `persist_checkpoint`, but allowed to use a faster path that skips conflict handling
+    /// where possible. Only used while backfilling, far behind the tip of the chain.
+    fn persist_checkpoint_bulk(
+        &self,
+        data: &TemporaryCheckpointStore,
+    ) -> Result<usize, IndexerError>;
+    /// The handlers left behind by a failure, with the latest checkpoint each has committed, see
+    /// `--max-handler-lag`. The handlers not in it are caught up with the latest checkpoint.
+    fn get_lagging_handlers(&self) -> Result<BTreeMap<IndexerHandler, i64>, IndexerError>;
+    /// Record that `handler` has committed up to `checkpoint` only, before the checkpoints after
+    /// it are committed without it.
+    fn mark_handler_lagging(
+        &self,
+        handler: IndexerHandler,
+        checkpoint: i64,
+    ) -> Result<(), IndexerError>;
+    /// Record that `handler` caught up, for the checkpoints after its last one to be committed
+    /// with it again.
+    fn mark_handler_caught_up(&self, handler: IndexerHandler) -> Result<(), IndexerError>;
+    /// Write the rows of a checkpoint already committed without `handler`, which are the rows of
+    /// `handler` only, and advance its lagging checkpoint to it. The checkpoint and its epoch
+    /// are not written again.
+    fn persist_handler_checkpoint(
+        &self,
+        handler: IndexerHandler,
+        data: &TemporaryCheckpointStore,
+    ) -> Result<usize, IndexerError>;
     fn persist_epoch(&self, data: &TemporaryEpochStore) -> Result<(), IndexerError>;
 
     fn log_errors(&self, errors: Vec<IndexerError>) -> Result<(), IndexerError>;
@@ -124,7 +347,103 @@ pub trait IndexerStore {
     fn module_cache(&self) -> &Self::ModuleCache;
 }
 
-#[derive(Clone, Debug)]
+/// The point in time object reads are served at. Reads at a checkpoint see the same objects
+/// however far the indexer has moved on since, so that a client can page through a consistent
+/// snapshot or read its own writes once their checkpoint is committed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConsistencyLevel {
+    /// The latest indexed version of each object
+    #[default]
+    Latest,
+    /// The latest version of each object written at or before the checkpoint. Only available
+    /// when object history is indexed, and fails with `IndexerError::CheckpointNotCommitted`
+    /// until the checkpoint is committed.
+    AtCheckpoint(CheckpointSequenceNumber),
+}
+
+/// Position of an item in a paginated store query. Items are ordered by the checkpoint that
+/// created them first and by their index within the table second, so a cursor keeps pointing
+/// at the same place when rows of newer checkpoints are inserted while a client is paging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cursor {
+    checkpoint: i64,
+    index: i64,
+}
+
+impl Cursor {
+    pub fn new(checkpoint: i64, index: i64) -> Self {
+        Self { checkpoint, index }
+    }
+
+    pub fn checkpoint(&self) -> i64 {
+        self.checkpoint
+    }
+
+    pub fn index(&self) -> i64 {
+        self.index
+    }
+}
+
+/// The proof that a checkpoint wrote a version of an object, for clients to read the object
+/// from an indexer they do not trust. Each part commits to the next: the summary signed by the
+/// validators to the contents through their digest, the contents to the effects of the
+/// transaction that wrote the object through their digest, and the effects to the object
+/// through its reference. Checkpoints commit to their contents with a digest of the whole
+/// contents rather than with a Merkle root, so the proof carries all of them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObjectInclusionProof {
+    pub checkpoint_summary: CertifiedCheckpointSummary,
+    pub checkpoint_contents: CheckpointContents,
+    pub transaction_effects: TransactionEffects,
+    pub object: SuiObject,
+}
+
+impl ObjectInclusionProof {
+    /// Verify the proof against the committee of the epoch of the checkpoint, returning the
+    /// reference of the proven object version.
+    pub fn verify(&self, committee: &Committee) -> Result<ObjectRef, IndexerError> {
+        let summary = self.checkpoint_summary.data();
+        self.checkpoint_summary
+            .verify_with_contents(committee, Some(&self.checkpoint_contents))
+            .map_err(|e| {
+                IndexerError::InvalidCheckpointSignature(format!(
+                    "checkpoint {} of epoch {} failed verification with error: {:?}",
+                    summary.sequence_number, summary.epoch, e
+                ))
+            })?;
+        let execution_digests = ExecutionDigests::new(
+            *self.transaction_effects.transaction_digest(),
+            self.transaction_effects.digest(),
+        );
+        if !self
+            .checkpoint_contents
+            .iter()
+            .any(|digests| *digests == execution_digests)
+        {
+            return Err(IndexerError::ContentDigestMismatch(format!(
+                "effects of transaction {:?} are not in the contents of checkpoint {}",
+                execution_digests.transaction, summary.sequence_number
+            )));
+        }
+        let object_ref = self.object.compute_object_reference();
+        if !self
+            .transaction_effects
+            .all_changed_objects()
+            .into_iter()
+            .any(|(changed, _, _)| *changed == object_ref)
+        {
+            return Err(IndexerError::ContentDigestMismatch(format!(
+                "object {:?} was not written by transaction {:?}",
+                object_ref, execution_digests.transaction
+            )));
+        }
+        Ok(object_ref)
+    }
+}
+
+/// Everything fetched from the fullnode to index one checkpoint. Serialized as JSON to capture
+/// checkpoints for replay, see `--checkpoint-dir`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CheckpointData {
     pub checkpoint: RpcCheckpoint,
     pub transactions: Vec<SuiTransactionFullResponse>,
@@ -132,15 +451,24 @@ pub struct CheckpointData {
 }
 
 // Per checkpoint indexing
+#[derive(Debug)]
 pub struct TemporaryCheckpointStore {
     pub checkpoint: Checkpoint,
     pub transactions: Vec<Transaction>,
+    pub raw_transactions: Vec<RawTransaction>,
     pub events: Vec<Event>,
     pub objects_changes: Vec<TransactionObjectChanges>,
     pub addresses: Vec<Address>,
     pub packages: Vec<Package>,
     pub move_calls: Vec<MoveCall>,
+    pub object_transactions: Vec<ObjectTransaction>,
+    pub transaction_inputs: Vec<TransactionInput>,
+    pub ptb_commands: Vec<PtbCommand>,
     pub recipients: Vec<Recipient>,
+    /// Set when the checkpoint starts an epoch
+    pub epoch: Option<Epoch>,
+    /// One row per coin type whose treasury cap changed in the checkpoint
+    pub coin_supplies: Vec<CoinSupply>,
 }
 
 #[derive(Debug)]
@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub use indexer_store::*;
-pub use pg_indexer_store::PgIndexerStore;
+pub use pg_indexer_store::{PgIndexerStore, StreamLock};
 
+mod bulk_copy;
 mod indexer_store;
 mod module_resolver;
 mod pg_indexer_store;
@@ -1,46 +1,102 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
+use clap::ValueEnum;
 use diesel::dsl::{count, max};
-use diesel::sql_types::VarChar;
+use diesel::pg::{Pg, PgConnection};
+use diesel::query_builder::{BoxedSqlQuery, SqlQuery};
+use diesel::result::DatabaseErrorKind;
+use diesel::sql_types::{BigInt, Bool, Text, VarChar};
 use diesel::upsert::excluded;
-use diesel::{ExpressionMethods, PgArrayExpressionMethods};
-use diesel::{OptionalExtension, QueryableByName};
+use diesel::{BoolExpressionMethods, ExpressionMethods, PgArrayExpressionMethods};
+use diesel::{OptionalExtension, QueryResult, QueryableByName};
 use diesel::{QueryDsl, RunQueryDsl};
+use futures::stream::{self, BoxStream, StreamExt};
 use move_bytecode_utils::module_cache::SyncModuleCache;
-use tracing::{error, info};
+use move_core_types::language_storage::StructTag;
+use prometheus::Registry;
+use tracing::{error, info, warn};
 
-use sui_json_rpc_types::{CheckpointId, EventFilter, EventPage, SuiEvent};
-use sui_types::base_types::{ObjectID, SequenceNumber};
+use sui_json_rpc_types::{CheckpointId, EventFilter, SuiEvent};
+use sui_types::base_types::{ObjectID, SequenceNumber, SuiAddress, TransactionDigest};
 use sui_types::committee::EpochId;
+use sui_types::crypto::SignatureScheme;
 use sui_types::event::EventID;
-use sui_types::object::ObjectRead;
+use sui_types::gas::GasCostSummary;
+use sui_types::messages::TransactionEffectsAPI;
+use sui_types::messages_checkpoint::{CertifiedCheckpointSummary, CheckpointSequenceNumber};
+use sui_types::object::{Object as SuiObject, ObjectRead};
+use sui_types::parse_sui_type_tag;
 
 use crate::errors::IndexerError;
+use crate::metrics::IndexerStoreMetrics;
 use crate::models::checkpoints::Checkpoint;
+use crate::models::coin_supply::CoinSupply;
+use crate::models::epochs::Epoch;
 use crate::models::error_logs::commit_error_logs;
 use crate::models::events::Event;
-use crate::models::objects::Object;
+use crate::models::objects::{LastModifyingTransaction, Object, ObjectHistory, ObjectStatus};
+use crate::models::owners::OwnerType;
+use crate::models::packages::Package;
+use crate::models::ptb_commands::PtbCommand;
+use crate::models::raw_transactions::{checkpoint_contents, RawTransaction};
+use crate::models::transaction_inputs::TransactionInput;
 use crate::models::transactions::Transaction;
 use crate::schema::{
-    addresses, checkpoints, checkpoints::dsl as checkpoints_dsl, events, move_calls,
-    move_calls::dsl as move_calls_dsl, objects, objects::dsl as objects_dsl, objects_history,
-    packages, recipients, recipients::dsl as recipients_dsl, transactions,
+    addresses, chain_identifier, checkpoint_ranges, checkpoints,
+    checkpoints::dsl as checkpoints_dsl, coin_supply, coin_supply::dsl as coin_supply_dsl, epochs,
+    epochs::dsl as epochs_dsl, events, lagging_handlers, move_calls,
+    move_calls::dsl as move_calls_dsl, object_transactions,
+    object_transactions::dsl as object_transactions_dsl, objects, objects::dsl as objects_dsl,
+    objects_history, packages, packages::dsl as packages_dsl, ptb_commands,
+    ptb_commands::dsl as ptb_commands_dsl, raw_transactions,
+    raw_transactions::dsl as raw_transactions_dsl, recipients, recipients::dsl as recipients_dsl,
+    transaction_inputs, transaction_inputs::dsl as transaction_inputs_dsl, transactions,
     transactions::dsl as transactions_dsl,
 };
+use crate::store::bulk_copy::BulkCopy;
 use crate::store::indexer_store::TemporaryCheckpointStore;
 use crate::store::module_resolver::IndexerModuleResolver;
-use crate::store::{IndexerStore, TemporaryEpochStore};
-use crate::{get_pg_pool_connection, PgConnectionPool};
+use crate::store::{
+    ConsistencyLevel, Cursor, IndexerStore, ObjectInclusionProof, TemporaryEpochStore,
+    MAX_OBJECT_ANCESTRY_DEPTH,
+};
+use crate::{
+    get_pg_pool_connection, AppendOnlyTable, IndexerHandler, PgConnectionPool, PgPoolConnection,
+};
 
-const MAX_EVENT_PAGE_SIZE: usize = 1000;
 const PG_COMMIT_CHUNK_SIZE: usize = 1000;
+/// Backoff before the first retry of a commit, doubled for every further retry up to
+/// `MAX_COMMIT_RETRY_BACKOFF`
+const COMMIT_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+const MAX_COMMIT_RETRY_BACKOFF: Duration = Duration::from_secs(5);
+
+const GET_GAS_TOTALS_SQL: &str = r#"
+SELECT COALESCE(SUM(computation_cost), 0)::BIGINT AS computation_cost,
+       COALESCE(SUM(storage_cost), 0)::BIGINT     AS storage_cost,
+       COALESCE(SUM(storage_rebate), 0)::BIGINT   AS storage_rebate
+FROM transactions
+WHERE checkpoint_sequence_number BETWEEN $1 AND $2;
+"#;
+
+// The underscore of dynamic_field is escaped, it matches any character in a LIKE pattern.
+const FIND_ORPHANED_DYNAMIC_FIELDS_SQL: &str = r#"
+SELECT child.object_id
+FROM objects child
+WHERE child.owner_type = 'object_owner'
+  AND child.object_type LIKE '0x2::dynamic\_field::Field<%'
+  AND child.object_status NOT IN ('deleted', 'wrapped', 'unwrapped_then_deleted')
+  AND NOT EXISTS (SELECT 1 FROM objects parent WHERE parent.object_id = child.owner_address)
+ORDER BY child.object_id
+LIMIT $1;
+"#;
 
 const GET_PARTITION_SQL: &str = r#"
 SELECT parent.relname                           AS table_name,
@@ -54,11 +110,111 @@ WHERE parent.relkind = 'p'
 GROUP BY table_name;
 "#;
 
+/// The advisory lock of a logical stream of checkpoints, see `PgIndexerStore::try_lock_stream`.
+pub struct StreamLock {
+    pg_pool_conn: PgPoolConnection,
+    stream: String,
+}
+
+impl Drop for StreamLock {
+    fn drop(&mut self) {
+        // The connection goes back to the pool, so the lock would outlive the guard otherwise
+        if let Err(e) = diesel::sql_query("SELECT pg_advisory_unlock(hashtextextended($1, 0))")
+            .bind::<Text, _>(&self.stream)
+            .execute(&mut self.pg_pool_conn)
+        {
+            error!(
+                "Failed releasing the advisory lock of stream {} with error {:?}",
+                self.stream, e
+            );
+        }
+    }
+}
+
+/// Retries of the commits of the ingestion path that failed because of concurrent transactions
+#[derive(Clone, Default)]
+struct CommitRetries {
+    max_retries: u32,
+    metrics: Option<IndexerStoreMetrics>,
+}
+
+impl CommitRetries {
+    /// Run `commit` in a serializable read-write transaction, and re-run the whole transaction
+    /// with exponential backoff while it fails on a serialization failure or a deadlock.
+    fn run<T>(
+        &self,
+        pg_pool_conn: &mut PgPoolConnection,
+        mut commit: impl FnMut(&mut PgConnection) -> QueryResult<T>,
+    ) -> QueryResult<T> {
+        let mut retries = 0;
+        loop {
+            match pg_pool_conn
+                .build_transaction()
+                .serializable()
+                .read_write()
+                .run(&mut commit)
+            {
+                Err(e) if retries < self.max_retries && is_serialization_failure(&e) => {
+                    let backoff = COMMIT_RETRY_BACKOFF
+                        .saturating_mul(2u32.saturating_pow(retries))
+                        .min(MAX_COMMIT_RETRY_BACKOFF);
+                    retries += 1;
+                    if let Some(metrics) = &self.metrics {
+                        metrics.db_deadlock_retries.inc();
+                    }
+                    warn!(
+                        "Retrying commit in {:?}, attempt {} of {}, after error: {:?}",
+                        backoff, retries, self.max_retries, e
+                    );
+                    std::thread::sleep(backoff);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Serialization failures (SQLSTATE 40001) and deadlocks (40P01) only abort a transaction
+/// because of concurrent ones, so it can succeed when re-run. Diesel classifies errors by their
+/// SQLSTATE but has no kind for deadlocks, and does not expose the SQLSTATE of the errors it
+/// leaves unclassified. Those are all re-run rather than telling deadlocks apart by their
+/// message, which is translated according to `lc_messages`. Any other error among them fails
+/// again and is returned once the retries run out.
+fn is_serialization_failure(error: &diesel::result::Error) -> bool {
+    matches!(
+        error,
+        diesel::result::Error::DatabaseError(
+            DatabaseErrorKind::SerializationFailure | DatabaseErrorKind::Unknown,
+            _
+        )
+    )
+}
+
+/// Write `values` to the append-only `table` with a plain `INSERT` if `plain_insert` is set, and
+/// otherwise skip the rows that were already written.
+macro_rules! insert_append_only {
+    ($conn:expr, $plain_insert:expr, $table:expr, $values:expr) => {
+        if $plain_insert {
+            diesel::insert_into($table).values($values).execute($conn)
+        } else {
+            diesel::insert_into($table)
+                .values($values)
+                .on_conflict_do_nothing()
+                .execute($conn)
+        }
+    };
+}
+
 #[derive(Clone)]
 pub struct PgIndexerStore {
     cp: PgConnectionPool,
     partition_manager: PartitionManager,
     pub module_cache: Arc<SyncModuleCache<IndexerModuleResolver>>,
+    enabled_handlers: BTreeSet<IndexerHandler>,
+    object_history: bool,
+    plain_insert_tables: BTreeSet<AppendOnlyTable>,
+    commit_retries: CommitRetries,
+    bulk_copy: Option<BulkCopy>,
 }
 
 impl PgIndexerStore {
@@ -68,404 +224,1834 @@ impl PgIndexerStore {
             cp: cp.clone(),
             partition_manager: PartitionManager::new(cp).unwrap(),
             module_cache,
+            enabled_handlers: IndexerHandler::value_variants().iter().copied().collect(),
+            object_history: true,
+            plain_insert_tables: BTreeSet::new(),
+            commit_retries: CommitRetries::default(),
+            bulk_copy: None,
         }
     }
-}
 
-#[async_trait]
-impl IndexerStore for PgIndexerStore {
-    type ModuleCache = SyncModuleCache<IndexerModuleResolver>;
+    /// Only serve reads from the tables written by `enabled_handlers`, reads of the other tables
+    /// fail with `IndexerError::HandlerDisabled` instead of returning stale or missing data.
+    pub fn with_enabled_handlers(mut self, enabled_handlers: BTreeSet<IndexerHandler>) -> Self {
+        self.enabled_handlers = enabled_handlers;
+        self
+    }
+
+    /// Write every object version to `objects_history` and serve reads of past versions from it,
+    /// which is on by default.
+    pub fn with_object_history(mut self, object_history: bool) -> Self {
+        self.object_history = object_history;
+        self
+    }
+
+    /// Write `plain_insert_tables` with a plain `INSERT` instead of skipping the rows that
+    /// conflict with existing ones, which saves resolving conflicts on every write. Writing a row
+    /// of these tables twice then fails the commit, so a checkpoint whose rows were partly written
+    /// before a crash cannot be indexed again without removing them first.
+    pub fn with_plain_insert_tables(
+        mut self,
+        plain_insert_tables: BTreeSet<AppendOnlyTable>,
+    ) -> Self {
+        self.plain_insert_tables = plain_insert_tables;
+        self
+    }
+
+    /// Re-run a commit up to `max_retries` times when it fails on a serialization failure or a
+    /// deadlock with a concurrent writer, such as another backfill worker, counting the retries
+    /// in `registry`.
+    pub fn with_deadlock_retries(mut self, max_retries: u32, registry: &Registry) -> Self {
+        self.commit_retries = CommitRetries {
+            max_retries,
+            metrics: Some(IndexerStoreMetrics::new(registry)),
+        };
+        self
+    }
+
+    /// Bulk load transactions and events with a binary `COPY`, over connections to `db_url` of
+    /// its own made without TLS. Without it, bulk writes are regular writes.
+    pub fn with_bulk_copy(mut self, db_url: &str) -> Self {
+        self.bulk_copy = Some(BulkCopy::new(db_url.to_string()));
+        self
+    }
+
+    /// Take the advisory lock of the logical stream of checkpoints `stream`, which is held until
+    /// the returned guard is dropped, or return None if another session holds it. The lock is
+    /// held by a connection of its own, and is released by the db if that connection is lost.
+    pub fn try_lock_stream(&self, stream: &str) -> Result<Option<StreamLock>, IndexerError> {
+        #[derive(QueryableByName)]
+        struct AdvisoryLock {
+            #[diesel(sql_type = Bool)]
+            locked: bool,
+        }
 
-    fn get_latest_checkpoint_sequence_number(&self) -> Result<i64, IndexerError> {
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-        pg_pool_conn
+        let lock = diesel::sql_query(
+            "SELECT pg_try_advisory_lock(hashtextextended($1, 0)) AS locked",
+        )
+        .bind::<Text, _>(stream)
+        .get_result::<AdvisoryLock>(&mut pg_pool_conn)
+        .map_err(|e| {
+            IndexerError::PostgresReadError(format!(
+                "Failed taking the advisory lock of stream {} with error {:?}",
+                stream, e
+            ))
+        })?;
+        Ok(lock.locked.then(|| StreamLock {
+            pg_pool_conn,
+            stream: stream.to_string(),
+        }))
+    }
+
+    fn ensure_object_history(&self) -> Result<(), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Objects)?;
+        if self.object_history {
+            Ok(())
+        } else {
+            Err(IndexerError::ObjectHistoryDisabled)
+        }
+    }
+
+    /// Reads at a checkpoint are served off the object history, and only once the checkpoint
+    /// is committed: the object versions of a later checkpoint may not all be written yet. While
+    /// the objects handler lags, that is only up to the checkpoint it committed, even though the
+    /// later checkpoints are committed by the other handlers.
+    fn ensure_checkpoint_committed(
+        &self,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<i64, IndexerError> {
+        self.ensure_object_history()?;
+        let mut latest = self.get_latest_checkpoint_sequence_number()?;
+        if let Some(objects_checkpoint) = self.get_lagging_handlers()?.get(&IndexerHandler::Objects)
+        {
+            latest = latest.min(*objects_checkpoint);
+        }
+        match i64::try_from(checkpoint) {
+            Ok(checkpoint) if checkpoint <= latest => Ok(checkpoint),
+            _ => Err(IndexerError::CheckpointNotCommitted(format!(
+                "{}, the objects are committed up to checkpoint {}",
+                checkpoint, latest
+            ))),
+        }
+    }
+
+    fn get_object_at_checkpoint(
+        &self,
+        object_id: ObjectID,
+        version: Option<SequenceNumber>,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<ObjectRead, IndexerError> {
+        let checkpoint = self.ensure_checkpoint_committed(checkpoint)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let object = pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                checkpoints_dsl::checkpoints
-                    .select(max(checkpoints::sequence_number))
-                    .first::<Option<i64>>(conn)
-                    // -1 to differentiate between no checkpoints and the first checkpoint
-                    .map(|o| o.unwrap_or(-1))
+                let mut boxed_query = objects_history::dsl::objects_history
+                    .filter(objects_history::object_id.eq(object_id.to_string()))
+                    .filter(objects_history::checkpoint.le(checkpoint))
+                    .into_boxed();
+                if let Some(version) = version {
+                    boxed_query =
+                        boxed_query.filter(objects_history::version.eq(version.value() as i64));
+                }
+                boxed_query
+                    .order(objects_history::version.desc())
+                    .first::<Object>(conn)
+                    .optional()
             })
             .map_err(|e| {
                 IndexerError::PostgresReadError(format!(
-                    "Failed reading latest checkpoint sequence number in PostgresDB with error {:?}",
-                    e
+                    "Failed reading object with id {} and version {:?} at checkpoint {} and err: {:?}",
+                    object_id, version, checkpoint, e
                 ))
-            })
+            })?;
+
+        match object {
+            None => Ok(ObjectRead::NotExists(object_id)),
+            Some(o) => o.try_into_object_read(&self.module_cache),
+        }
     }
 
-    fn get_checkpoint(&self, id: CheckpointId) -> Result<Checkpoint, IndexerError> {
+    fn plain_insert(&self, table: AppendOnlyTable) -> bool {
+        self.plain_insert_tables.contains(&table)
+    }
+
+    fn ensure_enabled(&self, handler: IndexerHandler) -> Result<(), IndexerError> {
+        if self.enabled_handlers.contains(&handler) {
+            Ok(())
+        } else {
+            Err(IndexerError::HandlerDisabled(handler.to_string()))
+        }
+    }
+
+    /// A batch of `scan_objects_by_type`, the objects of a type with ids greater than `cursor`
+    fn read_objects_by_type(
+        &self,
+        object_type: &str,
+        cursor: Option<&str>,
+        batch_size: usize,
+    ) -> Result<Vec<Object>, IndexerError> {
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
         pg_pool_conn
             .build_transaction()
             .read_only()
-            .run(|conn| match id {
-                CheckpointId::SequenceNumber(seq) => checkpoints_dsl::checkpoints
-                    .filter(checkpoints::sequence_number.eq(seq as i64))
-                    .limit(1)
-                    .first::<Checkpoint>(conn),
-                CheckpointId::Digest(digest) => checkpoints_dsl::checkpoints
-                    .filter(checkpoints::checkpoint_digest.eq(digest.base58_encode()))
-                    .limit(1)
-                    .first::<Checkpoint>(conn),
+            .run(|conn| {
+                let mut boxed_query = objects_dsl::objects
+                    .filter(objects_dsl::object_type.eq(object_type))
+                    .filter(objects_dsl::object_status.ne_all(vec![
+                        ObjectStatus::Deleted,
+                        ObjectStatus::Wrapped,
+                        ObjectStatus::UnwrappedThenDeleted,
+                    ]))
+                    .into_boxed();
+                if let Some(cursor) = cursor {
+                    boxed_query = boxed_query.filter(objects_dsl::object_id.gt(cursor));
+                }
+                boxed_query
+                    .order(objects_dsl::object_id.asc())
+                    .limit(batch_size as i64)
+                    .load::<Object>(conn)
             })
             .map_err(|e| {
                 IndexerError::PostgresReadError(format!(
-                    "Failed reading previous checkpoint in PostgresDB with error {:?}",
-                    e
+                    "Failed scanning objects with type {} and cursor {:?} and batch size {} and err: {:?}",
+                    object_type, cursor, batch_size, e
                 ))
             })
     }
 
-    fn get_event(&self, id: EventID) -> Result<Event, IndexerError> {
+    /// Write the rows of the checkpoint, then the checkpoint itself, or only advance the lagging
+    /// checkpoint of `handler` when the rows are those of a handler catching up.
+    fn persist_checkpoint_data(
+        &self,
+        data: &TemporaryCheckpointStore,
+        bulk: bool,
+        handler: Option<IndexerHandler>,
+    ) -> Result<usize, IndexerError> {
+        let TemporaryCheckpointStore {
+            checkpoint,
+            transactions,
+            raw_transactions,
+            events,
+            objects_changes,
+            addresses,
+            packages,
+            move_calls,
+            object_transactions,
+            transaction_inputs,
+            ptb_commands,
+            recipients, // TODO: store raw object
+            epoch,
+            coin_supplies,
+        } = data;
+
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-        pg_pool_conn
-            .build_transaction()
-            .read_only()
-            .run(|conn| {
-                events::table
-                    .filter(events::dsl::transaction_digest.eq(id.tx_digest.base58_encode()))
-                    .filter(events::dsl::event_sequence.eq(id.event_seq as i64))
-                    .first::<Event>(conn)
+        if let Some(bulk_copy) = self.bulk_copy.as_ref().filter(|_| bulk) {
+            bulk_copy.load(checkpoint.sequence_number, transactions, events)?;
+        } else {
+            // Commit indexed transactions
+            for transaction_chunk in transactions.chunks(PG_COMMIT_CHUNK_SIZE) {
+                self.commit_retries
+                    .run(&mut pg_pool_conn, |conn| {
+                        insert_append_only!(
+                            conn,
+                            self.plain_insert(AppendOnlyTable::Transactions),
+                            transactions::table,
+                            transaction_chunk
+                        )
+                    })
+                    .map_err(|e| {
+                        IndexerError::PostgresWriteError(format!(
+                            "Failed writing transactions to PostgresDB with error: {:?}",
+                            e
+                        ))
+                    })?;
+            }
+
+            // Commit indexed events
+            for event_chunk in events.chunks(PG_COMMIT_CHUNK_SIZE) {
+                self.commit_retries
+                    .run(&mut pg_pool_conn, |conn| {
+                        insert_append_only!(
+                            conn,
+                            self.plain_insert(AppendOnlyTable::Events),
+                            events::table,
+                            event_chunk
+                        )
+                    })
+                    .map_err(|e| {
+                        IndexerError::PostgresWriteError(format!(
+                            "Failed writing events to PostgresDB with error: {:?}",
+                            e
+                        ))
+                    })?;
+            }
+        }
+
+        // Commit raw transaction bytes
+        for raw_transaction_chunk in raw_transactions.chunks(PG_COMMIT_CHUNK_SIZE) {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    insert_append_only!(
+                        conn,
+                        self.plain_insert(AppendOnlyTable::RawTransactions),
+                        raw_transactions::table,
+                        raw_transaction_chunk
+                    )
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing raw transactions to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Commit indexed objects
+        for changes in objects_changes {
+            for mutated_object_change_chunk in changes.mutated_objects.chunks(PG_COMMIT_CHUNK_SIZE)
+            {
+                self.commit_retries
+                    .run(&mut pg_pool_conn, |conn| {
+                        let newer_objects = newer_objects(conn, mutated_object_change_chunk)?;
+                        diesel::insert_into(objects::table)
+                            .values(newer_objects)
+                            .on_conflict(objects::object_id)
+                            .do_update()
+                            .set((
+                                objects::epoch.eq(excluded(objects::epoch)),
+                                objects::checkpoint.eq(excluded(objects::checkpoint)),
+                                objects::version.eq(excluded(objects::version)),
+                                objects::object_digest.eq(excluded(objects::object_digest)),
+                                objects::owner_type.eq(excluded(objects::owner_type)),
+                                objects::owner_address.eq(excluded(objects::owner_address)),
+                                objects::initial_shared_version
+                                    .eq(excluded(objects::initial_shared_version)),
+                                objects::previous_transaction
+                                    .eq(excluded(objects::previous_transaction)),
+                                objects::object_status.eq(excluded(objects::object_status)),
+                                objects::type_unresolved.eq(excluded(objects::type_unresolved)),
+                            ))
+                            .execute(conn)
+                    })
+                    .map_err(|e| {
+                        IndexerError::PostgresWriteError(format!(
+                            "Failed writing updated objects to PostgresDB with chunk: {:?} error: {:?}",
+                            mutated_object_change_chunk, e
+                        ))
+                    })?;
+            }
+
+            let deleted_objects: Vec<Object> = changes
+                .deleted_objects
+                .iter()
+                .map(|deleted_object| deleted_object.clone().into())
+                .collect();
+            for deleted_object_change_chunk in deleted_objects.chunks(PG_COMMIT_CHUNK_SIZE) {
+                self.commit_retries
+                    .run(&mut pg_pool_conn, |conn| {
+                        let newer_objects = newer_objects(conn, deleted_object_change_chunk)?;
+                        diesel::insert_into(objects::table)
+                            .values(newer_objects)
+                            .on_conflict(objects::object_id)
+                            .do_update()
+                            .set((
+                                objects::epoch.eq(excluded(objects::epoch)),
+                                objects::checkpoint.eq(excluded(objects::checkpoint)),
+                                objects::version.eq(excluded(objects::version)),
+                                objects::previous_transaction
+                                    .eq(excluded(objects::previous_transaction)),
+                                objects::object_status.eq(excluded(objects::object_status)),
+                            ))
+                            .execute(conn)
+                    })
+                    .map_err(|e| {
+                        IndexerError::PostgresWriteError(format!(
+                            "Failed writing deleted objects to PostgresDB with chunk: {:?} error: {:?}",
+                            deleted_object_change_chunk, e
+                        ))
+                    })?;
+            }
+
+            if !self.object_history {
+                continue;
+            }
+            // Deleted and wrapped objects are kept as tombstones, so that the history records the
+            // version they left the store at instead of ending at their previous version.
+            let object_versions: Vec<ObjectHistory> = changes
+                .mutated_objects
+                .iter()
+                .cloned()
+                .chain(deleted_objects)
+                .map(ObjectHistory::from)
+                .collect();
+            for object_versions_chunk in object_versions.chunks(PG_COMMIT_CHUNK_SIZE) {
+                self.commit_retries
+                    .run(&mut pg_pool_conn, |conn| {
+                        insert_append_only!(
+                            conn,
+                            self.plain_insert(AppendOnlyTable::ObjectsHistory),
+                            objects_history::table,
+                            object_versions_chunk
+                        )
+                    })
+                    .map_err(|e| {
+                        IndexerError::PostgresWriteError(format!(
+                            "Failed writing object history to PostgresDB with error: {:?}",
+                            e
+                        ))
+                    })?;
+            }
+        }
+
+        // Commit indexed addresses
+        for addresses_chunk in addresses.chunks(PG_COMMIT_CHUNK_SIZE) {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    diesel::insert_into(addresses::table)
+                        .values(addresses_chunk)
+                        .on_conflict(addresses::account_address)
+                        .do_nothing()
+                        .execute(conn)
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing addresses to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Commit indexed packages
+        for packages_chunk in packages.chunks(PG_COMMIT_CHUNK_SIZE) {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    insert_append_only!(
+                        conn,
+                        self.plain_insert(AppendOnlyTable::Packages),
+                        packages::table,
+                        packages_chunk
+                    )
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing packages to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Commit indexed move calls
+        for move_calls_chunk in move_calls.chunks(PG_COMMIT_CHUNK_SIZE) {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    insert_append_only!(
+                        conn,
+                        self.plain_insert(AppendOnlyTable::MoveCalls),
+                        move_calls::table,
+                        move_calls_chunk
+                    )
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing move_calls to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Commit indexed object transactions
+        for object_transactions_chunk in object_transactions.chunks(PG_COMMIT_CHUNK_SIZE) {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    insert_append_only!(
+                        conn,
+                        self.plain_insert(AppendOnlyTable::ObjectTransactions),
+                        object_transactions::table,
+                        object_transactions_chunk
+                    )
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing object_transactions to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Commit indexed move call inputs
+        for transaction_inputs_chunk in transaction_inputs.chunks(PG_COMMIT_CHUNK_SIZE) {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    insert_append_only!(
+                        conn,
+                        self.plain_insert(AppendOnlyTable::TransactionInputs),
+                        transaction_inputs::table,
+                        transaction_inputs_chunk
+                    )
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing transaction_inputs to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Commit indexed programmable transaction commands
+        for ptb_commands_chunk in ptb_commands.chunks(PG_COMMIT_CHUNK_SIZE) {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    insert_append_only!(
+                        conn,
+                        self.plain_insert(AppendOnlyTable::PtbCommands),
+                        ptb_commands::table,
+                        ptb_commands_chunk
+                    )
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing ptb_commands to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Commit indexed recipients
+        for recipients_chunk in recipients.chunks(PG_COMMIT_CHUNK_SIZE) {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    insert_append_only!(
+                        conn,
+                        self.plain_insert(AppendOnlyTable::Recipients),
+                        recipients::table,
+                        recipients_chunk
+                    )
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing recipients to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Commit the epoch started by the checkpoint
+        if let Some(epoch) = epoch {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    diesel::insert_into(epochs::table)
+                        .values(epoch)
+                        .on_conflict_do_nothing()
+                        .execute(conn)
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing epoch to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Commit the supplies of the coin types whose treasury cap changed
+        if !coin_supplies.is_empty() {
+            self.commit_retries
+                .run(&mut pg_pool_conn, |conn| {
+                    // Checkpoint ranges are committed in any order, keep the latest supplies
+                    let checkpoints: HashMap<String, i64> = coin_supply::table
+                        .select((coin_supply::coin_type, coin_supply::checkpoint))
+                        .filter(coin_supply::coin_type.eq_any(
+                            coin_supplies.iter().map(|supply| &supply.coin_type),
+                        ))
+                        .load::<(String, i64)>(conn)?
+                        .into_iter()
+                        .collect();
+                    let newer_supplies: Vec<&CoinSupply> = coin_supplies
+                        .iter()
+                        .filter(|supply| {
+                            checkpoints
+                                .get(&supply.coin_type)
+                                .map_or(true, |checkpoint| *checkpoint <= supply.checkpoint)
+                        })
+                        .collect();
+                    diesel::insert_into(coin_supply::table)
+                        .values(newer_supplies)
+                        .on_conflict(coin_supply::coin_type)
+                        .do_update()
+                        .set((
+                            coin_supply::total_supply.eq(excluded(coin_supply::total_supply)),
+                            coin_supply::checkpoint.eq(excluded(coin_supply::checkpoint)),
+                        ))
+                        .execute(conn)
+                })
+                .map_err(|e| {
+                    IndexerError::PostgresWriteError(format!(
+                        "Failed writing coin supplies to PostgresDB with error: {:?}",
+                        e
+                    ))
+                })?;
+        }
+
+        if let Some(handler) = handler {
+            self.mark_handler_lagging(handler, checkpoint.sequence_number)?;
+            return Ok(1);
+        }
+
+        // Commit indexed checkpoint last, so that if the checkpoint is committed,
+        // all related data have been committed as well.
+        self.commit_retries
+            .run(&mut pg_pool_conn, |conn| {
+                diesel::insert_into(checkpoints::table)
+                    .values(checkpoint)
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresWriteError(format!(
+                    "Failed writing checkpoint to PostgresDB with error: {:?}",
+                    e
+                ))
+            })
+    }
+}
+
+#[async_trait]
+impl IndexerStore for PgIndexerStore {
+    type ModuleCache = SyncModuleCache<IndexerModuleResolver>;
+
+    fn get_latest_checkpoint_sequence_number(&self) -> Result<i64, IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                let ranges: Vec<(i64, i64)> = checkpoint_ranges::table
+                    .select((checkpoint_ranges::range_start, checkpoint_ranges::range_end))
+                    .load(conn)?;
+                // Skip the ranges the latest checkpoints are in, one index lookup per range
+                let mut below = i64::MAX;
+                loop {
+                    let latest = checkpoints_dsl::checkpoints
+                        .select(max(checkpoints::sequence_number))
+                        .filter(checkpoints::sequence_number.lt(below))
+                        .first::<Option<i64>>(conn)?;
+                    let range = latest.and_then(|seq| {
+                        ranges
+                            .iter()
+                            .find(|(start, end)| (*start..=*end).contains(&seq))
+                    });
+                    match range {
+                        Some((start, _)) => below = *start,
+                        // -1 to differentiate between no checkpoints and the first checkpoint
+                        None => return Ok::<_, diesel::result::Error>(latest.unwrap_or(-1)),
+                    }
+                }
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading latest checkpoint sequence number in PostgresDB with error {:?}",
+                    e
+                ))
+            })
+    }
+
+    fn get_latest_checkpoint_sequence_number_in_range(
+        &self,
+        first: CheckpointSequenceNumber,
+        last: CheckpointSequenceNumber,
+    ) -> Result<Option<i64>, IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                checkpoints_dsl::checkpoints
+                    .select(max(checkpoints::sequence_number))
+                    .filter(checkpoints::sequence_number.between(first as i64, last as i64))
+                    .first::<Option<i64>>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading latest checkpoint sequence number between {} and {} in PostgresDB with error {:?}",
+                    first, last, e
+                ))
+            })
+    }
+
+    fn register_checkpoint_range(
+        &self,
+        first: CheckpointSequenceNumber,
+        last: CheckpointSequenceNumber,
+    ) -> Result<(), IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        self.commit_retries
+            .run(&mut pg_pool_conn, |conn| {
+                diesel::insert_into(checkpoint_ranges::table)
+                    .values((
+                        checkpoint_ranges::range_start.eq(first as i64),
+                        checkpoint_ranges::range_end.eq(last as i64),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresWriteError(format!(
+                    "Failed writing checkpoint range {}-{} to PostgresDB with error: {:?}",
+                    first, last, e
+                ))
+            })?;
+        Ok(())
+    }
+
+    fn persist_chain_identifier(&self, chain_id: &str) -> Result<String, IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        self.commit_retries
+            .run(&mut pg_pool_conn, |conn| {
+                diesel::insert_into(chain_identifier::table)
+                    .values((
+                        chain_identifier::id.eq(0),
+                        chain_identifier::identifier.eq(chain_id),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+                chain_identifier::table
+                    .select(chain_identifier::identifier)
+                    .first::<String>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresWriteError(format!(
+                    "Failed writing chain identifier {} to PostgresDB with error: {:?}",
+                    chain_id, e
+                ))
+            })
+    }
+
+    fn get_checkpoint(&self, id: CheckpointId) -> Result<Checkpoint, IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| match id {
+                CheckpointId::SequenceNumber(seq) => checkpoints_dsl::checkpoints
+                    .filter(checkpoints::sequence_number.eq(seq as i64))
+                    .limit(1)
+                    .first::<Checkpoint>(conn),
+                CheckpointId::Digest(digest) => checkpoints_dsl::checkpoints
+                    .filter(checkpoints::checkpoint_digest.eq(digest.base58_encode()))
+                    .limit(1)
+                    .first::<Checkpoint>(conn),
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading previous checkpoint in PostgresDB with error {:?}",
+                    e
+                ))
+            })
+    }
+
+    fn get_checkpoint_summary(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<CertifiedCheckpointSummary, IndexerError> {
+        self.get_checkpoint(CheckpointId::SequenceNumber(sequence_number))?
+            .certified_summary()?
+            .ok_or_else(|| {
+                IndexerError::PostgresReadError(format!(
+                    "Checkpoint {} was indexed without its validator signature",
+                    sequence_number
+                ))
+            })
+    }
+
+    fn get_checkpoint_transaction_digests(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<Vec<TransactionDigest>, IndexerError> {
+        // The checkpoint lists its transactions in the order of the checkpoint contents
+        self.get_checkpoint(CheckpointId::SequenceNumber(sequence_number))?
+            .transaction_digests()
+    }
+
+    fn get_reference_gas_price(&self) -> Result<u64, IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let epoch = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                epochs_dsl::epochs
+                    .order(epochs::epoch.desc())
+                    .first::<Epoch>(conn)
+                    .optional()
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading latest epoch in PostgresDB with error {:?}",
+                    e
+                ))
+            })?
+            .ok_or_else(|| {
+                IndexerError::EpochNotIndexed("no epoch has been ingested yet".to_string())
+            })?;
+        Ok(epoch.reference_gas_price as u64)
+    }
+
+    fn get_reference_gas_price_at_epoch(&self, epoch: EpochId) -> Result<u64, IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let indexed_epoch = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                epochs_dsl::epochs
+                    .filter(epochs::epoch.eq(epoch as i64))
+                    .first::<Epoch>(conn)
+                    .optional()
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading epoch {} in PostgresDB with error {:?}",
+                    epoch, e
+                ))
+            })?
+            .ok_or_else(|| IndexerError::EpochNotIndexed(format!("epoch {}", epoch)))?;
+        Ok(indexed_epoch.reference_gas_price as u64)
+    }
+
+    fn get_total_supply(&self, coin_type: &str) -> Result<u64, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Objects)?;
+        // Coin types are stored in their canonical form, e.g. with full length addresses
+        let coin_type = parse_sui_type_tag(coin_type)
+            .map_err(|e| {
+                IndexerError::InvalidArgumentError(format!(
+                    "Invalid coin type {}: {:?}",
+                    coin_type, e
+                ))
+            })?
+            .to_string();
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let supply = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                coin_supply_dsl::coin_supply
+                    .filter(coin_supply::coin_type.eq(&coin_type))
+                    .first::<CoinSupply>(conn)
+                    .optional()
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading supply of coin type {} in PostgresDB with error {:?}",
+                    coin_type, e
+                ))
+            })?
+            .ok_or(IndexerError::TreasuryCapNotIndexed(coin_type))?;
+        Ok(supply.total_supply as u64)
+    }
+
+    fn get_event(&self, id: EventID) -> Result<Event, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Events)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                events::table
+                    .filter(events::dsl::transaction_digest.eq(id.tx_digest.base58_encode()))
+                    .filter(events::dsl::event_sequence.eq(id.event_seq as i64))
+                    .first::<Event>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading event in PostgresDB with error {:?}",
+                    e
+                ))
+            })
+    }
+
+    fn get_event_cursor(&self, id: EventID) -> Result<Cursor, IndexerError> {
+        let event = self.get_event(id)?;
+        let event_id = event
+            .id
+            .ok_or_else(|| IndexerError::PostgresReadError("Event ID is None".to_string()))?;
+        Ok(Cursor::new(event.checkpoint_sequence_number, event_id))
+    }
+
+    fn get_events(
+        &self,
+        query: EventFilter,
+        cursor: Option<Cursor>,
+        limit: usize,
+        descending_order: bool,
+    ) -> Result<(Vec<SuiEvent>, Option<Cursor>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Events)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let mut boxed_query = events::table.into_boxed();
+        match query {
+            EventFilter::All(..) => {}
+            EventFilter::Transaction(digest) => {
+                boxed_query =
+                    boxed_query.filter(events::dsl::transaction_digest.eq(digest.base58_encode()));
+            }
+            EventFilter::MoveModule { package, module } => {
+                boxed_query = boxed_query
+                    .filter(events::dsl::package.eq(package.to_string()))
+                    .filter(events::dsl::module.eq(module.to_string()));
+            }
+            EventFilter::MoveEventType(struct_name) => {
+                boxed_query =
+                    boxed_query.filter(events::dsl::event_type.eq(struct_name.to_string()));
+            }
+            EventFilter::Sender(sender) => {
+                boxed_query = boxed_query.filter(events::dsl::sender.eq(sender.to_string()));
+            }
+            EventFilter::TimeRange {
+                start_time,
+                end_time,
+            } => {
+                boxed_query = boxed_query
+                    .filter(events::dsl::event_time_ms.ge(start_time as i64))
+                    .filter(events::dsl::event_time_ms.lt(end_time as i64));
+            }
+            // TODO: Implement EventFilter to SQL
+            _ => {
+                return Err(IndexerError::NotImplementedError(format!(
+                    "Filter type [{query:?}] not supported by the Indexer."
+                )))
+            }
+        }
+
+        let events_vec: Vec<Event> = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                if let Some(cursor) = cursor {
+                    if descending_order {
+                        boxed_query = boxed_query.filter(
+                            events::checkpoint_sequence_number
+                                .lt(cursor.checkpoint())
+                                .or(events::checkpoint_sequence_number
+                                    .eq(cursor.checkpoint())
+                                    .and(events::id.lt(cursor.index()))),
+                        );
+                    } else {
+                        boxed_query = boxed_query.filter(
+                            events::checkpoint_sequence_number
+                                .gt(cursor.checkpoint())
+                                .or(events::checkpoint_sequence_number
+                                    .eq(cursor.checkpoint())
+                                    .and(events::id.gt(cursor.index()))),
+                        );
+                    }
+                }
+                if descending_order {
+                    boxed_query = boxed_query
+                        .order((events::checkpoint_sequence_number.desc(), events::id.desc()));
+                } else {
+                    boxed_query = boxed_query
+                        .order((events::checkpoint_sequence_number.asc(), events::id.asc()));
+                }
+                // fetch one more item to tell if there is next page
+                boxed_query.limit(limit as i64 + 1).load(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading events in PostgresDB with error {:?}",
+                    e
+                ))
+            })?;
+
+        let events_with_cursor = events_vec
+            .into_iter()
+            .map(|event| {
+                let cursor = Cursor::new(event.checkpoint_sequence_number, event.id.unwrap_or(0));
+                Ok((event.try_into()?, cursor))
+            })
+            .collect::<Result<Vec<(SuiEvent, Cursor)>, IndexerError>>()?;
+        Ok(into_page(events_with_cursor, limit))
+    }
+
+    fn get_total_transaction_number(&self) -> Result<i64, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                transactions_dsl::transactions
+                    .select(count(transactions_dsl::id))
+                    .first::<i64>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading total transaction number with err: {:?}",
+                    e
+                ))
+            })
+    }
+
+    fn get_transaction_by_digest(&self, txn_digest: &str) -> Result<Transaction, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                transactions_dsl::transactions
+                    .filter(transactions_dsl::transaction_digest.eq(txn_digest))
+                    .first::<Transaction>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transaction with digest {} and err: {:?}",
+                    txn_digest, e
+                ))
+            })
+    }
+
+    fn get_raw_transaction(&self, txn_digest: &str) -> Result<RawTransaction, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                raw_transactions_dsl::raw_transactions
+                    .filter(raw_transactions_dsl::transaction_digest.eq(txn_digest))
+                    .first::<RawTransaction>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading raw transaction with digest {} and err: {:?}",
+                    txn_digest, e
+                ))
+            })
+    }
+
+    fn get_transaction_inputs(
+        &self,
+        txn_digest: &str,
+    ) -> Result<Vec<TransactionInput>, IndexerError> {
+        self.ensure_enabled(IndexerHandler::MoveCalls)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                transaction_inputs_dsl::transaction_inputs
+                    .filter(transaction_inputs_dsl::transaction_digest.eq(txn_digest))
+                    .order((
+                        transaction_inputs_dsl::command_index.asc(),
+                        transaction_inputs_dsl::argument_index.asc(),
+                    ))
+                    .load::<TransactionInput>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transaction inputs with digest {} and err: {:?}",
+                    txn_digest, e
+                ))
+            })
+    }
+
+    fn get_ptb_structure(&self, txn_digest: &str) -> Result<Vec<PtbCommand>, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                ptb_commands_dsl::ptb_commands
+                    .filter(ptb_commands_dsl::transaction_digest.eq(txn_digest))
+                    .order(ptb_commands_dsl::command_index.asc())
+                    .load::<PtbCommand>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading ptb commands with digest {} and err: {:?}",
+                    txn_digest, e
+                ))
+            })
+    }
+
+    fn get_package(&self, package_id: &str) -> Result<Package, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Packages)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                packages_dsl::packages
+                    .filter(packages_dsl::package_id.eq(package_id))
+                    .order(packages_dsl::version.desc())
+                    .first::<Package>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading package with id {} and err: {:?}",
+                    package_id, e
+                ))
+            })
+    }
+
+    fn get_package_versions(&self, original_id: &str) -> Result<Vec<Package>, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Packages)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                packages_dsl::packages
+                    .filter(packages_dsl::original_id.eq(original_id))
+                    .order(packages_dsl::version.asc())
+                    .load::<Package>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading versions of package with original id {} and err: {:?}",
+                    original_id, e
+                ))
+            })
+    }
+
+    fn multi_get_transactions_by_digests(
+        &self,
+        txn_digests: &[String],
+    ) -> Result<Vec<Transaction>, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                transactions_dsl::transactions
+                    .filter(transactions_dsl::transaction_digest.eq_any(txn_digests))
+                    .load::<Transaction>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transactions with digests {:?} and err: {:?}",
+                    txn_digests, e
+                ))
+            })
+    }
+
+    fn get_gas_breakdown(&self, txn_digest: &str) -> Result<GasCostSummary, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                transactions_dsl::transactions
+                    .filter(transactions_dsl::transaction_digest.eq(txn_digest))
+                    .select((
+                        transactions_dsl::computation_cost,
+                        transactions_dsl::storage_cost,
+                        transactions_dsl::storage_rebate,
+                    ))
+                    .first::<(i64, i64, i64)>(conn)
+            })
+            .map(|(computation_cost, storage_cost, storage_rebate)| {
+                GasCostSummary::new(
+                    computation_cost as u64,
+                    storage_cost as u64,
+                    storage_rebate as u64,
+                )
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading gas breakdown with digest {} and err: {:?}",
+                    txn_digest, e
+                ))
+            })
+    }
+
+    fn get_gas_totals(
+        &self,
+        start_checkpoint: i64,
+        end_checkpoint: i64,
+    ) -> Result<GasCostSummary, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+
+        #[derive(QueryableByName, Debug, Clone)]
+        struct GasTotals {
+            #[diesel(sql_type = BigInt)]
+            computation_cost: i64,
+            #[diesel(sql_type = BigInt)]
+            storage_cost: i64,
+            #[diesel(sql_type = BigInt)]
+            storage_rebate: i64,
+        }
+
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                diesel::sql_query(GET_GAS_TOTALS_SQL)
+                    .bind::<BigInt, _>(start_checkpoint)
+                    .bind::<BigInt, _>(end_checkpoint)
+                    .get_result::<GasTotals>(conn)
+            })
+            .map(|totals| {
+                GasCostSummary::new(
+                    totals.computation_cost as u64,
+                    totals.storage_cost as u64,
+                    totals.storage_rebate as u64,
+                )
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading gas totals of checkpoints {} to {} and err: {:?}",
+                    start_checkpoint, end_checkpoint, e
+                ))
+            })
+    }
+
+    fn get_transaction_cursor_by_digest(&self, txn_digest: &str) -> Result<Cursor, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                transactions_dsl::transactions
+                    .filter(transactions_dsl::transaction_digest.eq(txn_digest))
+                    .select((
+                        transactions_dsl::checkpoint_sequence_number,
+                        transactions_dsl::id,
+                    ))
+                    .first::<(i64, i64)>(conn)
+            })
+            .map(|(checkpoint, id)| Cursor::new(checkpoint, id))
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transaction cursor with digest {} and err: {:?}",
+                    txn_digest, e
+                ))
+            })
+    }
+
+    fn get_object(
+        &self,
+        object_id: ObjectID,
+        version: Option<SequenceNumber>,
+        consistency: ConsistencyLevel,
+    ) -> Result<ObjectRead, IndexerError> {
+        if let ConsistencyLevel::AtCheckpoint(checkpoint) = consistency {
+            return self.get_object_at_checkpoint(object_id, version, checkpoint);
+        }
+        if let Some(version) = version {
+            return self.get_object_at_version(object_id, version);
+        }
+        self.ensure_enabled(IndexerHandler::Objects)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let object = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                objects_dsl::objects
+                    .filter(objects_dsl::object_id.eq(object_id.to_string()))
+                    .first::<Object>(conn)
+                    .optional()
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading object with id {} and err: {:?}",
+                    object_id, e
+                ))
+            })?;
+
+        match object {
+            None => Ok(ObjectRead::NotExists(object_id)),
+            Some(o) => o.try_into_object_read(&self.module_cache),
+        }
+    }
+
+    fn get_object_at_version(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> Result<ObjectRead, IndexerError> {
+        self.ensure_object_history()?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let object = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                objects_history::dsl::objects_history
+                    .filter(objects_history::object_id.eq(object_id.to_string()))
+                    .filter(objects_history::version.eq(version.value() as i64))
+                    .first::<Object>(conn)
+                    .optional()
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading object with id {} at version {} and err: {:?}",
+                    object_id, version, e
+                ))
+            })?;
+
+        match object {
+            None => Ok(ObjectRead::NotExists(object_id)),
+            Some(o) => o.try_into_object_read(&self.module_cache),
+        }
+    }
+
+    fn get_object_versions(
+        &self,
+        object_id: ObjectID,
+        cursor: Option<SequenceNumber>,
+        limit: usize,
+    ) -> Result<(Vec<Object>, Option<SequenceNumber>), IndexerError> {
+        self.ensure_object_history()?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let versions = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                let mut boxed_query = objects_history::dsl::objects_history
+                    .filter(objects_history::object_id.eq(object_id.to_string()))
+                    .into_boxed();
+                if let Some(cursor) = cursor {
+                    boxed_query =
+                        boxed_query.filter(objects_history::version.gt(cursor.value() as i64));
+                }
+                // fetch one more item to tell if there is next page
+                boxed_query
+                    .order(objects_history::version.asc())
+                    .limit(limit as i64 + 1)
+                    .load::<Object>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading versions of object with id {} and err: {:?}",
+                    object_id, e
+                ))
+            })?;
+
+        let versions_with_cursor = versions
+            .into_iter()
+            .map(|o| {
+                let cursor = SequenceNumber::from_u64(o.version as u64);
+                (o, cursor)
+            })
+            .collect();
+        Ok(into_page(versions_with_cursor, limit))
+    }
+
+    fn get_indexed_object_ids(
+        &self,
+        object_ids: &[String],
+    ) -> Result<BTreeSet<String>, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Objects)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                objects_dsl::objects
+                    .filter(objects_dsl::object_id.eq_any(object_ids))
+                    .select(objects_dsl::object_id)
+                    .load::<String>(conn)
+            })
+            .map(|ids| ids.into_iter().collect())
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading indexed object ids with err: {:?}",
+                    e
+                ))
+            })
+    }
+
+    fn get_last_modifying_transaction(
+        &self,
+        object_id: ObjectID,
+    ) -> Result<LastModifyingTransaction, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Objects)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                objects_dsl::objects
+                    .filter(objects_dsl::object_id.eq(object_id.to_string()))
+                    .select((
+                        objects_dsl::previous_transaction,
+                        objects_dsl::checkpoint,
+                        objects_dsl::version,
+                    ))
+                    .first::<LastModifyingTransaction>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading last modifying transaction of object {} and err: {:?}",
+                    object_id, e
+                ))
+            })
+    }
+
+    fn get_object_ancestry(&self, object_id: ObjectID) -> Result<Vec<ObjectRead>, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Objects)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        // Walk the whole chain in one transaction, so that it is not torn by a concurrent commit.
+        let ancestry = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                let mut ancestry: Vec<Object> = vec![];
+                let mut next = Some(object_id.to_string());
+                while let Some(id) = next.take() {
+                    if ancestry.iter().any(|o| o.object_id == id) {
+                        return Err(IndexerError::UncategorizedError(anyhow!(
+                            "Ownership chain of object {} has a cycle at object {}",
+                            object_id,
+                            id
+                        )));
+                    }
+                    if ancestry.len() == MAX_OBJECT_ANCESTRY_DEPTH {
+                        return Err(IndexerError::UncategorizedError(anyhow!(
+                            "Ownership chain of object {} is longer than {} objects",
+                            object_id,
+                            MAX_OBJECT_ANCESTRY_DEPTH
+                        )));
+                    }
+                    let object = objects_dsl::objects
+                        .filter(objects_dsl::object_id.eq(id.as_str()))
+                        .first::<Object>(conn)
+                        .optional()
+                        .map_err(|e| {
+                            IndexerError::PostgresReadError(format!(
+                                "Failed reading object {} in the ownership chain of object {} and err: {:?}",
+                                id, object_id, e
+                            ))
+                        })?;
+                    let Some(object) = object else {
+                        break;
+                    };
+                    // Rows of deleted and wrapped objects are kept with their last owner.
+                    let live = !matches!(
+                        object.object_status,
+                        ObjectStatus::Deleted
+                            | ObjectStatus::Wrapped
+                            | ObjectStatus::UnwrappedThenDeleted
+                    );
+                    if live && matches!(object.owner_type, OwnerType::ObjectOwner) {
+                        next = object.owner_address.clone();
+                    }
+                    ancestry.push(object);
+                }
+                Ok(ancestry)
+            })?;
+
+        ancestry
+            .into_iter()
+            .map(|o| o.try_into_object_read(&self.module_cache))
+            .collect()
+    }
+
+    fn get_object_inclusion_proof(
+        &self,
+        object_id: ObjectID,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<ObjectInclusionProof, IndexerError> {
+        // The raw transactions are indexed by the transactions handler
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        self.ensure_enabled(IndexerHandler::Objects)?;
+        let unavailable = |reason: String| {
+            IndexerError::InclusionProofUnavailable(format!(
+                "object {} at checkpoint {}: {}",
+                object_id, checkpoint, reason
+            ))
+        };
+
+        let indexed_checkpoint = self.get_checkpoint(CheckpointId::SequenceNumber(checkpoint))?;
+        let checkpoint_summary = indexed_checkpoint
+            .certified_summary()?
+            .ok_or_else(|| {
+                unavailable("the checkpoint was indexed without its validator signature".into())
+            })?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let mut raw_transactions_by_digest: HashMap<String, RawTransaction> = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                raw_transactions_dsl::raw_transactions
+                    .filter(raw_transactions::checkpoint_sequence_number.eq(checkpoint as i64))
+                    .load::<RawTransaction>(conn)
             })
             .map_err(|e| {
                 IndexerError::PostgresReadError(format!(
-                    "Failed reading event in PostgresDB with error {:?}",
-                    e
+                    "Failed reading raw transactions of checkpoint {} and err: {:?}",
+                    checkpoint, e
                 ))
+            })?
+            .into_iter()
+            .map(|tx| (tx.transaction_digest.clone(), tx))
+            .collect();
+        // The contents list the transactions in the order of the checkpoint
+        let raw_transactions = indexed_checkpoint
+            .transaction_digests()?
+            .into_iter()
+            .map(|digest| {
+                let raw_transaction = raw_transactions_by_digest.remove(&digest.to_string());
+                raw_transaction.ok_or_else(|| {
+                    unavailable(format!("the raw data of transaction {} is not indexed", digest))
+                })
             })
-    }
-
-    fn get_events(
-        &self,
-        query: EventFilter,
-        cursor: Option<EventID>,
-        limit: Option<usize>,
-        descending_order: bool,
-    ) -> Result<EventPage, IndexerError> {
-        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-        let mut boxed_query = events::table.into_boxed();
-        match query {
-            EventFilter::All(..) => {}
-            EventFilter::Transaction(digest) => {
-                boxed_query =
-                    boxed_query.filter(events::dsl::transaction_digest.eq(digest.base58_encode()));
-            }
-            EventFilter::MoveModule { package, module } => {
-                boxed_query = boxed_query
-                    .filter(events::dsl::package.eq(package.to_string()))
-                    .filter(events::dsl::module.eq(module.to_string()));
-            }
-            EventFilter::MoveEventType(struct_name) => {
-                boxed_query =
-                    boxed_query.filter(events::dsl::event_type.eq(struct_name.to_string()));
-            }
-            EventFilter::Sender(sender) => {
-                boxed_query = boxed_query.filter(events::dsl::sender.eq(sender.to_string()));
-            }
-            EventFilter::TimeRange {
-                start_time,
-                end_time,
-            } => {
-                boxed_query = boxed_query
-                    .filter(events::dsl::event_time_ms.ge(start_time as i64))
-                    .filter(events::dsl::event_time_ms.lt(end_time as i64));
-            }
-            // TODO: Implement EventFilter to SQL
-            _ => {
-                return Err(IndexerError::NotImplementedError(format!(
-                    "Filter type [{query:?}] not supported by the Indexer."
-                )))
-            }
+            .collect::<Result<Vec<_>, _>>()?;
+        let checkpoint_contents = checkpoint_contents(&raw_transactions)?;
+        if checkpoint_contents.digest() != &checkpoint_summary.data().content_digest {
+            return Err(IndexerError::ContentDigestMismatch(format!(
+                "checkpoint {} has content digest {:?} but its indexed contents hash to {:?}",
+                checkpoint,
+                checkpoint_summary.data().content_digest,
+                checkpoint_contents.digest()
+            )));
         }
 
-        let mut page_limit = limit.unwrap_or(MAX_EVENT_PAGE_SIZE);
-        if page_limit > MAX_EVENT_PAGE_SIZE {
-            Err(IndexerError::InvalidArgumentError(format!(
-                "Limit {} exceeds the maximum page size {}",
-                page_limit, MAX_EVENT_PAGE_SIZE
-            )))?;
+        let mut written = None;
+        for raw_transaction in raw_transactions.iter().rev() {
+            let effects = raw_transaction.effects()?;
+            let object_ref = effects
+                .all_changed_objects()
+                .into_iter()
+                .find(|(object_ref, _, _)| object_ref.0 == object_id)
+                .map(|(object_ref, _, _)| *object_ref);
+            if let Some(object_ref) = object_ref {
+                written = Some((effects, object_ref));
+                break;
+            }
         }
-        // fetch one more item to tell if there is next page
-        page_limit += 1;
+        let Some((transaction_effects, object_ref)) = written else {
+            return Err(IndexerError::InvalidArgumentError(format!(
+                "object {} was not created, mutated or unwrapped in checkpoint {}",
+                object_id, checkpoint
+            )));
+        };
 
-        let pg_cursor = cursor
-            .map(|c| {
-                self.get_event(c)?
-                    .id
-                    .ok_or_else(|| IndexerError::PostgresReadError("Event ID is None".to_string()))
-            })
-            .transpose()?;
-        let events_vec: Vec<Event> = pg_pool_conn
+        // The version is in the objects table until it is overwritten, and in the object history
+        // if that is indexed
+        let version = object_ref.1.value() as i64;
+        let object_history = self.object_history;
+        let object = pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                if let Some(pg_cursor) = pg_cursor {
-                    if descending_order {
-                        boxed_query = boxed_query.filter(events::dsl::id.lt(pg_cursor));
-                    } else {
-                        boxed_query = boxed_query.filter(events::dsl::id.gt(pg_cursor));
-                    }
-                }
-                if descending_order {
-                    boxed_query = boxed_query.order(events::id.desc());
-                } else {
-                    boxed_query = boxed_query.order(events::id.asc());
+                let latest = objects_dsl::objects
+                    .filter(objects_dsl::object_id.eq(object_id.to_string()))
+                    .filter(objects_dsl::version.eq(version))
+                    .first::<Object>(conn)
+                    .optional()?;
+                if latest.is_some() || !object_history {
+                    return Ok(latest);
                 }
-                boxed_query.load(conn)
+                objects_history::dsl::objects_history
+                    .filter(objects_history::object_id.eq(object_id.to_string()))
+                    .filter(objects_history::version.eq(version))
+                    .first::<Object>(conn)
+                    .optional()
             })
             .map_err(|e| {
                 IndexerError::PostgresReadError(format!(
-                    "Failed reading events in PostgresDB with error {:?}",
-                    e
+                    "Failed reading object with id {} at version {} and err: {:?}",
+                    object_id, version, e
+                ))
+            })?
+            .ok_or_else(|| {
+                unavailable(format!(
+                    "version {} of the object is no longer indexed",
+                    object_ref.1
                 ))
             })?;
+        let object: SuiObject = object.try_into()?;
+        if object.compute_object_reference() != object_ref {
+            return Err(unavailable(format!(
+                "the indexed version {} of the object does not hash to {:?}",
+                object_ref.1, object_ref.2
+            )));
+        }
 
-        let mut sui_event_vec = events_vec
-            .into_iter()
-            .map(|event| event.try_into())
-            .collect::<Result<Vec<SuiEvent>, _>>()?;
-        // reset to original limit for checking and truncating
-        page_limit -= 1;
-        let has_next_page = sui_event_vec.len() > page_limit;
-        sui_event_vec.truncate(page_limit);
-        let next_cursor = sui_event_vec.last().map(|e| e.id.clone());
-        Ok(EventPage {
-            data: sui_event_vec,
-            next_cursor,
-            has_next_page,
+        Ok(ObjectInclusionProof {
+            checkpoint_summary,
+            checkpoint_contents,
+            transaction_effects,
+            object,
         })
     }
 
-    fn get_total_transaction_number(&self) -> Result<i64, IndexerError> {
+    fn scan_objects_by_type(
+        &self,
+        object_type: StructTag,
+        batch_size: usize,
+    ) -> BoxStream<'static, Result<Vec<Object>, IndexerError>> {
+        if let Err(e) = self.ensure_enabled(IndexerHandler::Objects) {
+            return stream::once(async move { Err(e) }).boxed();
+        }
+        let store = self.clone();
+        let object_type = object_type.to_string();
+        let batch_size = batch_size.max(1);
+        // The state is the id of the last object yielded, or `None` once the scan is over.
+        stream::unfold(Some(None), move |cursor: Option<Option<String>>| {
+            let store = store.clone();
+            let object_type = object_type.clone();
+            async move {
+                let cursor = cursor?;
+                // Read off the runtime, if the stream is dropped meanwhile the batch is discarded.
+                let batch = tokio::task::spawn_blocking(move || {
+                    store.read_objects_by_type(&object_type, cursor.as_deref(), batch_size)
+                })
+                .await
+                .map_err(|e| IndexerError::UncategorizedError(anyhow!(e)))
+                .and_then(|batch| batch);
+                match batch {
+                    Ok(batch) if batch.is_empty() => None,
+                    Ok(batch) => {
+                        // A short batch is the last one.
+                        let next = (batch.len() == batch_size)
+                            .then(|| batch.last().map(|o| o.object_id.clone()));
+                        Some((Ok(batch), next))
+                    }
+                    Err(e) => Some((Err(e), None)),
+                }
+            }
+        })
+        .boxed()
+    }
+
+    fn find_orphaned_dynamic_fields(&self, limit: usize) -> Result<Vec<Object>, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Objects)?;
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+
+        #[derive(QueryableByName, Debug, Clone)]
+        struct OrphanedObject {
+            #[diesel(sql_type = VarChar)]
+            object_id: String,
+        }
+
         pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                transactions_dsl::transactions
-                    .select(count(transactions_dsl::id))
-                    .first::<i64>(conn)
+                let orphans: Vec<String> = diesel::sql_query(FIND_ORPHANED_DYNAMIC_FIELDS_SQL)
+                    .bind::<BigInt, _>(limit.min(i64::MAX as usize) as i64)
+                    .load::<OrphanedObject>(conn)?
+                    .into_iter()
+                    .map(|orphan| orphan.object_id)
+                    .collect();
+                objects_dsl::objects
+                    .filter(objects_dsl::object_id.eq_any(orphans))
+                    .order(objects_dsl::object_id.asc())
+                    .load::<Object>(conn)
             })
             .map_err(|e| {
                 IndexerError::PostgresReadError(format!(
-                    "Failed reading total transaction number with err: {:?}",
-                    e
+                    "Failed finding orphaned dynamic fields with limit {} and err: {:?}",
+                    limit, e
                 ))
             })
     }
 
-    fn get_transaction_by_digest(&self, txn_digest: &str) -> Result<Transaction, IndexerError> {
+    fn get_move_call_cursor_by_digest(&self, txn_digest: &str) -> Result<Cursor, IndexerError> {
+        self.ensure_enabled(IndexerHandler::MoveCalls)?;
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
         pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                transactions_dsl::transactions
-                    .filter(transactions_dsl::transaction_digest.eq(txn_digest))
-                    .first::<Transaction>(conn)
+                move_calls_dsl::move_calls
+                    .filter(move_calls_dsl::transaction_digest.eq(txn_digest))
+                    .select((
+                        max(move_calls_dsl::checkpoint_sequence_number),
+                        max(move_calls_dsl::id),
+                    ))
+                    .first::<(Option<i64>, Option<i64>)>(conn)
             })
             .map_err(|e| {
                 IndexerError::PostgresReadError(format!(
-                    "Failed reading transaction with digest {} and err: {:?}",
+                    "Failed reading move call cursor with digest {} and err: {:?}",
                     txn_digest, e
                 ))
             })
+            .and_then(|cursor| grouped_cursor(cursor, "move call", txn_digest))
     }
 
-    fn multi_get_transactions_by_digests(
-        &self,
-        txn_digests: &[String],
-    ) -> Result<Vec<Transaction>, IndexerError> {
+    fn get_recipient_cursor_by_digest(&self, txn_digest: &str) -> Result<Cursor, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Recipients)?;
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
         pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                transactions_dsl::transactions
-                    .filter(transactions_dsl::transaction_digest.eq_any(txn_digests))
-                    .load::<Transaction>(conn)
+                recipients_dsl::recipients
+                    .filter(recipients_dsl::transaction_digest.eq(txn_digest))
+                    .select((
+                        max(recipients_dsl::checkpoint_sequence_number),
+                        max(recipients_dsl::id),
+                    ))
+                    .first::<(Option<i64>, Option<i64>)>(conn)
             })
             .map_err(|e| {
                 IndexerError::PostgresReadError(format!(
-                    "Failed reading transactions with digests {:?} and err: {:?}",
-                    txn_digests, e
+                    "Failed reading recipient cursor with digest {} and err: {:?}",
+                    txn_digest, e
                 ))
             })
+            .and_then(|cursor| grouped_cursor(cursor, "recipient", txn_digest))
     }
 
-    fn get_transaction_sequence_by_digest(
+    fn get_all_transaction_digest_page(
         &self,
-        txn_digest: Option<String>,
+        cursor: Option<Cursor>,
+        limit: usize,
         is_descending: bool,
-    ) -> Result<Option<i64>, IndexerError> {
-        txn_digest
-            .map(|digest| {
-                let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-                pg_pool_conn
-                    .build_transaction()
-                    .read_only()
-                    .run(|conn| {
-                        let mut boxed_query = transactions_dsl::transactions
-                            .filter(transactions_dsl::transaction_digest.eq(digest.clone()))
-                            .select(transactions_dsl::id)
-                            .into_boxed();
-                        if is_descending {
-                            boxed_query = boxed_query.order(transactions_dsl::id.desc());
-                        } else {
-                            boxed_query = boxed_query.order(transactions_dsl::id.asc());
-                        }
-                        boxed_query.first::<i64>(conn)
-                    })
-                    .map_err(|e| {
-                        IndexerError::PostgresReadError(format!(
-                            "Failed reading transaction sequence with digest {} and err: {:?}",
-                            digest, e
-                        ))
-                    })
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                let boxed_query = transactions_dsl::transactions.into_boxed();
+                load_transaction_digest_page(conn, boxed_query, cursor, limit, is_descending)
+            })
+            .map(|rows| into_page(rows, limit))
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading all transaction digests with cursor {:?} and limit {} and err: {:?}",
+                    cursor, limit, e
+                ))
             })
-            .transpose()
     }
 
-    fn get_object(
+    fn get_owned_objects(
         &self,
-        object_id: ObjectID,
-        version: Option<SequenceNumber>,
-    ) -> Result<ObjectRead, IndexerError> {
+        owner: SuiAddress,
+        type_filter: Option<StructTag>,
+        cursor: Option<ObjectID>,
+        limit: usize,
+        consistency: ConsistencyLevel,
+    ) -> Result<(Vec<ObjectRead>, Option<ObjectID>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Objects)?;
+        let checkpoint = match consistency {
+            ConsistencyLevel::Latest => None,
+            ConsistencyLevel::AtCheckpoint(checkpoint) => {
+                Some(self.ensure_checkpoint_committed(checkpoint)?)
+            }
+        };
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-        let object = pg_pool_conn
+        let objects = pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                if let Some(version) = version {
-                    objects_history::dsl::objects_history
-                        .filter(objects_history::object_id.eq(object_id.to_string()))
-                        .filter(objects_history::version.eq(version.value() as i64))
-                        .get_result(conn)
-                        .optional()
-                } else {
-                    objects_dsl::objects
-                        .filter(objects_dsl::object_id.eq(object_id.to_string()))
-                        .first::<Object>(conn)
-                        .optional()
+                if let Some(checkpoint) = checkpoint {
+                    return load_owned_objects_at_checkpoint(
+                        conn,
+                        owner,
+                        type_filter.as_ref(),
+                        cursor,
+                        limit,
+                        checkpoint,
+                    );
+                }
+                // Rows of deleted and wrapped objects are kept with their last owner.
+                let mut boxed_query = objects_dsl::objects
+                    .filter(objects_dsl::owner_type.eq(OwnerType::AddressOwner))
+                    .filter(objects_dsl::owner_address.eq(owner.to_string()))
+                    .filter(objects_dsl::object_status.ne_all(vec![
+                        ObjectStatus::Deleted,
+                        ObjectStatus::Wrapped,
+                        ObjectStatus::UnwrappedThenDeleted,
+                    ]))
+                    .into_boxed();
+                if let Some(type_filter) = &type_filter {
+                    boxed_query =
+                        boxed_query.filter(objects_dsl::object_type.eq(type_filter.to_string()));
+                }
+                if let Some(cursor) = cursor {
+                    boxed_query = boxed_query.filter(objects_dsl::object_id.gt(cursor.to_string()));
                 }
+                boxed_query
+                    .order(objects_dsl::object_id.asc())
+                    .limit(limit as i64 + 1)
+                    .load::<Object>(conn)
             })
             .map_err(|e| {
                 IndexerError::PostgresReadError(format!(
-                    "Failed reading object with id {} and err: {:?}",
-                    object_id, e
+                    "Failed reading objects owned by {} with type {:?} and cursor {:?} and limit {} at {:?} and err: {:?}",
+                    owner, type_filter, cursor, limit, consistency, e
                 ))
             })?;
 
-        match object {
-            None => Ok(ObjectRead::NotExists(object_id)),
-            Some(o) => o.try_into_object_read(&self.module_cache),
-        }
-    }
-
-    fn get_move_call_sequence_by_digest(
-        &self,
-        txn_digest: Option<String>,
-        is_descending: bool,
-    ) -> Result<Option<i64>, IndexerError> {
-        txn_digest
-            .map(|digest| {
-                let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-                pg_pool_conn
-                    .build_transaction()
-                    .read_only()
-                    .run(|conn| {
-                        let mut boxed_query = move_calls_dsl::move_calls
-                            .filter(move_calls_dsl::transaction_digest.eq(digest.clone()))
-                            .into_boxed();
-                        if is_descending {
-                            boxed_query = boxed_query.order(move_calls_dsl::id.desc());
-                        } else {
-                            boxed_query = boxed_query.order(move_calls_dsl::id.asc());
-                        }
-                        boxed_query.select(move_calls_dsl::id).first::<i64>(conn)
-                    })
-                    .map_err(|e| {
-                        IndexerError::PostgresReadError(format!(
-                            "Failed reading move call sequence with digest {} and err: {:?}",
-                            digest, e
-                        ))
-                    })
-            })
-            .transpose()
-    }
-
-    fn get_recipient_sequence_by_digest(
-        &self,
-        txn_digest: Option<String>,
-        is_descending: bool,
-    ) -> Result<Option<i64>, IndexerError> {
-        txn_digest
-            .map(|txn_digest| {
-                let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-                pg_pool_conn
-                    .build_transaction()
-                    .read_only()
-                    .run(|conn| {
-                        let mut boxed_query = recipients_dsl::recipients
-                            .filter(recipients_dsl::transaction_digest.eq(&txn_digest))
-                            .into_boxed();
-                        if is_descending {
-                            boxed_query = boxed_query.order(recipients_dsl::id.desc());
-                        } else {
-                            boxed_query = boxed_query.order(recipients_dsl::id.asc());
-                        }
-                        boxed_query.select(recipients_dsl::id).first::<i64>(conn)
-                    })
-                    .map_err(|e| {
-                        IndexerError::PostgresReadError(format!(
-                            "Failed reading recipients sequence with digest {} and err: {:?}",
-                            txn_digest, e
-                        ))
-                    })
+        let objects = objects
+            .into_iter()
+            .map(|o| {
+                let object_id = ObjectID::from_str(&o.object_id)?;
+                Ok::<_, IndexerError>((o, object_id))
             })
-            .transpose()
+            .collect::<Result<Vec<_>, _>>()?;
+        let (objects, next_cursor) = into_page(objects, limit);
+        let objects = objects
+            .into_iter()
+            .map(|o| o.try_into_object_read(&self.module_cache))
+            .collect::<Result<_, _>>()?;
+        Ok((objects, next_cursor))
     }
 
-    fn get_all_transaction_digest_page(
+    fn get_shared_objects(
         &self,
-        start_sequence: Option<i64>,
+        type_filter: Option<StructTag>,
+        cursor: Option<ObjectID>,
         limit: usize,
-        is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
+    ) -> Result<(Vec<ObjectRead>, Option<ObjectID>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Objects)?;
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-        pg_pool_conn
+        let objects = pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                let mut boxed_query = transactions_dsl::transactions.into_boxed();
-                if is_descending {
-                    boxed_query = boxed_query.order(transactions_dsl::id.desc());
-                } else {
-                    boxed_query = boxed_query.order(transactions_dsl::id.asc());
+                // Rows of deleted objects are kept with their last owner.
+                let mut boxed_query = objects_dsl::objects
+                    .filter(objects_dsl::owner_type.eq(OwnerType::Shared))
+                    .filter(objects_dsl::object_status.ne_all(vec![
+                        ObjectStatus::Deleted,
+                        ObjectStatus::Wrapped,
+                        ObjectStatus::UnwrappedThenDeleted,
+                    ]))
+                    .into_boxed();
+                if let Some(type_filter) = &type_filter {
+                    boxed_query =
+                        boxed_query.filter(objects_dsl::object_type.eq(type_filter.to_string()));
                 }
-
-                if is_descending {
-                    boxed_query
-                        .order(transactions_dsl::id.desc())
-                        .limit((limit + 1) as i64)
-                        .select(transactions_dsl::transaction_digest)
-                        .load::<String>(conn)
-                } else {
-                    boxed_query
-                        .order(transactions_dsl::id.asc())
-                        .limit((limit + 1) as i64)
-                        .select(transactions_dsl::transaction_digest)
-                        .load::<String>(conn)
+                if let Some(cursor) = cursor {
+                    boxed_query = boxed_query.filter(objects_dsl::object_id.gt(cursor.to_string()));
                 }
-            }).map_err(|e| {
-            IndexerError::PostgresReadError(format!(
-                "Failed reading all transaction digests with start_sequence {:?} and limit {} and err: {:?}",
-                start_sequence, limit, e
-            ))
-        })
+                boxed_query
+                    .order(objects_dsl::object_id.asc())
+                    .limit(limit as i64 + 1)
+                    .load::<Object>(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading shared objects with type {:?} and cursor {:?} and limit {} and err: {:?}",
+                    type_filter, cursor, limit, e
+                ))
+            })?;
+
+        let objects = objects
+            .into_iter()
+            .map(|o| {
+                let object_id = ObjectID::from_str(&o.object_id)?;
+                Ok::<_, IndexerError>((o, object_id))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (objects, next_cursor) = into_page(objects, limit);
+        let objects = objects
+            .into_iter()
+            .map(|o| o.try_into_object_read(&self.module_cache))
+            .collect::<Result<_, _>>()?;
+        Ok((objects, next_cursor))
     }
 
     fn get_transaction_digest_page_by_move_call(
@@ -473,190 +2059,283 @@ impl IndexerStore for PgIndexerStore {
         package_name: String,
         module_name: Option<String>,
         function_name: Option<String>,
-        start_sequence: Option<i64>,
+        cursor: Option<Cursor>,
         limit: usize,
         is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::MoveCalls)?;
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
         pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                let mut builder = move_calls_dsl::move_calls.filter(move_calls_dsl::move_package.eq(package_name.clone()))
-                    .group_by(move_calls_dsl::transaction_digest)
-                    .select((move_calls_dsl::transaction_digest, max(move_calls_dsl::id)))
-                    .into_boxed();
+                let mut query = diesel::sql_query(
+                    "SELECT transaction_digest, \
+                        MAX(checkpoint_sequence_number) AS checkpoint_sequence_number, \
+                        MAX(id) AS max_id \
+                    FROM move_calls WHERE move_package = ",
+                )
+                .into_boxed()
+                .bind::<Text, _>(package_name.clone());
                 if let Some(module_name) = module_name.clone() {
-                    builder = builder.filter(move_calls_dsl::move_module.eq(module_name));
+                    query = query.sql(" AND move_module = ").bind::<Text, _>(module_name);
                 }
                 if let Some(function_name) = function_name.clone() {
-                    builder = builder.filter(move_calls_dsl::move_function.eq(function_name));
-                }
-                if let Some(start_sequence) = start_sequence {
-                    if is_descending {
-                        builder = builder.filter(move_calls_dsl::id.lt(start_sequence));
-                    } else {
-                        builder = builder.filter(move_calls_dsl::id.gt(start_sequence));
-                    }
-                }
-
-                if is_descending {
-                    builder.order(move_calls_dsl::id.desc())
-                        .limit(limit as i64)
-                        .load::<(String, Option<i64>)>(conn)
-                } else {
-                    builder.order(move_calls_dsl::id.asc())
-                        .limit(limit as i64)
-                        .load::<(String, Option<i64>)>(conn)
+                    query = query.sql(" AND move_function = ").bind::<Text, _>(function_name);
                 }
-            }).map(|v| v.into_iter().map(|(digest, _)| digest).collect()).map_err(|e| {
-            IndexerError::PostgresReadError(format!(
-                "Failed reading transaction digests with package_name {} module_name {:?} and function_name {:?} and start_sequence {:?} and limit {} and err: {:?}",
-                package_name, module_name, function_name, start_sequence, limit, e
-            ))
-        })
+                load_grouped_digest_page(conn, query, cursor, limit, is_descending)
+            })
+            .map(|rows| into_page(rows, limit))
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transaction digests with package_name {} module_name {:?} and function_name {:?} and cursor {:?} and limit {} and err: {:?}",
+                    package_name, module_name, function_name, cursor, limit, e
+                ))
+            })
     }
 
     fn get_transaction_digest_page_by_mutated_object(
         &self,
         object_id: String,
-        start_sequence: Option<i64>,
+        cursor: Option<Cursor>,
         limit: usize,
         is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
         pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                let mut boxed_query = transactions_dsl::transactions
+                let boxed_query = transactions_dsl::transactions
                     .filter(transactions_dsl::mutated.contains(vec![Some(object_id.clone())]))
                     .or_filter(transactions_dsl::created.contains(vec![Some(object_id.clone())]))
                     .or_filter(transactions_dsl::unwrapped.contains(vec![Some(object_id.clone())]))
                     .into_boxed();
-                if let Some(start_sequence) = start_sequence {
-                    if is_descending {
-                        boxed_query = boxed_query
-                            .filter(transactions_dsl::id.lt(start_sequence));
-                    } else {
-                        boxed_query = boxed_query
-                            .filter(transactions_dsl::id.gt(start_sequence));
-                    }
-                }
-                if is_descending {
-                    boxed_query
-                        .order(transactions_dsl::id.desc())
-                        .limit(limit as i64)
-                        .select(transactions_dsl::transaction_digest)
-                        .load::<String>(conn)
-                } else {
-                    boxed_query
-                        .order(transactions_dsl::id.asc())
-                        .limit(limit as i64)
-                        .select(transactions_dsl::transaction_digest)
-                        .load::<String>(conn)
+                load_transaction_digest_page(conn, boxed_query, cursor, limit, is_descending)
+            })
+            .map(|rows| into_page(rows, limit))
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transaction digests by mutated object id {} with cursor {:?} and limit {} and err: {:?}",
+                    object_id, cursor, limit, e
+                ))
+            })
+    }
+
+    fn get_transactions_for_object(
+        &self,
+        object_id: ObjectID,
+        cursor: Option<Cursor>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                let mut boxed_query = object_transactions_dsl::object_transactions
+                    .filter(object_transactions_dsl::object_id.eq(object_id.to_string()))
+                    .into_boxed();
+                if let Some(cursor) = cursor {
+                    // The lower bound on the checkpoint alone lets the scan start at the cursor
+                    // in the primary key, instead of at the first transaction of the object.
+                    boxed_query = boxed_query
+                        .filter(
+                            object_transactions_dsl::checkpoint_sequence_number
+                                .ge(cursor.checkpoint()),
+                        )
+                        .filter(
+                            object_transactions_dsl::checkpoint_sequence_number
+                                .gt(cursor.checkpoint())
+                                .or(object_transactions_dsl::transaction_index
+                                    .gt(cursor.index())),
+                        );
                 }
-            }).map_err(|e| {
-            IndexerError::PostgresReadError(format!(
-                "Failed reading transaction digests by mutated object id {} with start_sequence {:?} and limit {} and err: {:?}",
-                object_id, start_sequence, limit, e
-            ))
-        })
+                // fetch one more item to tell if there is next page
+                boxed_query
+                    .order((
+                        object_transactions_dsl::checkpoint_sequence_number.asc(),
+                        object_transactions_dsl::transaction_index.asc(),
+                    ))
+                    .limit(limit as i64 + 1)
+                    .select((
+                        object_transactions_dsl::transaction_digest,
+                        object_transactions_dsl::checkpoint_sequence_number,
+                        object_transactions_dsl::transaction_index,
+                    ))
+                    .load::<(String, i64, i64)>(conn)
+            })
+            .map(|rows| {
+                let rows = rows
+                    .into_iter()
+                    .map(|(digest, checkpoint, index)| (digest, Cursor::new(checkpoint, index)))
+                    .collect();
+                into_page(rows, limit)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transactions of object {} with cursor {:?} and limit {} and err: {:?}",
+                    object_id, cursor, limit, e
+                ))
+            })
     }
 
     fn get_transaction_digest_page_by_sender_address(
         &self,
         sender_address: String,
-        start_sequence: Option<i64>,
+        cursor: Option<Cursor>,
         limit: usize,
         is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
         pg_pool_conn
             .build_transaction()
             .read_only()
             .run(|conn| {
-                    let mut boxed_query = transactions_dsl::transactions
-                        .filter(transactions_dsl::sender.eq(sender_address.clone()))
-                        .into_boxed();
-                    if let Some(start_sequence) = start_sequence {
-                        if is_descending {
-                            boxed_query = boxed_query
-                                .filter(transactions_dsl::id.lt(start_sequence));
-                        } else {
-                            boxed_query = boxed_query
-                                .filter(transactions_dsl::id.gt(start_sequence));
-                        }
-                    }
+                let boxed_query = transactions_dsl::transactions
+                    .filter(transactions_dsl::sender.eq(sender_address.clone()))
+                    .into_boxed();
+                load_transaction_digest_page(conn, boxed_query, cursor, limit, is_descending)
+            })
+            .map(|rows| into_page(rows, limit))
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transaction digests by sender address {} with cursor {:?} and limit {} and err: {:?}",
+                    sender_address, cursor, limit, e
+                ))
+            })
+    }
 
-                    if is_descending {
-                        boxed_query
-                            .order(transactions_dsl::id.desc())
-                            .limit(limit as i64)
-                            .select(transactions_dsl::transaction_digest)
-                            .load::<String>(conn)
-                    } else {
-                        boxed_query
-                            .order(transactions_dsl::id.asc())
-                            .limit(limit as i64)
-                            .select(transactions_dsl::transaction_digest)
-                            .load::<String>(conn)
+    fn query_transactions_by_signature_scheme(
+        &self,
+        scheme: SignatureScheme,
+        cursor: Option<Cursor>,
+        limit: usize,
+        is_descending: bool,
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                let boxed_query = transactions_dsl::transactions
+                    .filter(
+                        transactions_dsl::signature_schemes
+                            .contains(vec![Some(scheme.to_string())]),
+                    )
+                    .into_boxed();
+                load_transaction_digest_page(conn, boxed_query, cursor, limit, is_descending)
+            })
+            .map(|rows| into_page(rows, limit))
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transaction digests by signature scheme {} with cursor {:?} and limit {} and err: {:?}",
+                    scheme, cursor, limit, e
+                ))
+            })
+    }
+
+    fn query_transactions_by_time(
+        &self,
+        start_ms: i64,
+        end_ms: i64,
+        cursor: Option<Cursor>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                let mut boxed_query = transactions_dsl::transactions
+                    .filter(transactions_dsl::timestamp_ms.ge(start_ms))
+                    .filter(transactions_dsl::timestamp_ms.lt(end_ms))
+                    .into_boxed();
+                if let Some(cursor) = cursor {
+                    // Timestamps never decrease from a checkpoint to the next, so the index scan
+                    // can start at the timestamp of the cursor rather than at `start_ms`.
+                    let cursor_timestamp_ms = transactions_dsl::transactions
+                        .filter(transactions_dsl::id.eq(cursor.index()))
+                        .select(transactions_dsl::timestamp_ms)
+                        .first::<i64>(conn)
+                        .optional()?;
+                    if let Some(cursor_timestamp_ms) = cursor_timestamp_ms {
+                        boxed_query = boxed_query
+                            .filter(transactions_dsl::timestamp_ms.ge(cursor_timestamp_ms));
                     }
-            }).map_err(|e| {
-            IndexerError::PostgresReadError(format!(
-                "Failed reading transaction digests by sender address {} with start_sequence {:?} and limit {} and err: {:?}",
-                sender_address, start_sequence, limit, e
-            ))
-        })
+                    boxed_query = boxed_query.filter(
+                        transactions_dsl::checkpoint_sequence_number
+                            .gt(cursor.checkpoint())
+                            .or(transactions_dsl::checkpoint_sequence_number
+                                .eq(cursor.checkpoint())
+                                .and(transactions_dsl::id.gt(cursor.index()))),
+                    );
+                }
+                // fetch one more item to tell if there is next page
+                boxed_query
+                    .order((
+                        transactions_dsl::timestamp_ms.asc(),
+                        transactions_dsl::checkpoint_sequence_number.asc(),
+                        transactions_dsl::id.asc(),
+                    ))
+                    .limit(limit as i64 + 1)
+                    .select((
+                        transactions_dsl::transaction_digest,
+                        transactions_dsl::checkpoint_sequence_number,
+                        transactions_dsl::id,
+                    ))
+                    .load::<(String, i64, i64)>(conn)
+            })
+            .map(|rows| {
+                let rows = rows
+                    .into_iter()
+                    .map(|(digest, checkpoint, id)| (digest, Cursor::new(checkpoint, id)))
+                    .collect();
+                into_page(rows, limit)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transaction digests from {} to {} ms with cursor {:?} and limit {} and err: {:?}",
+                    start_ms, end_ms, cursor, limit, e
+                ))
+            })
     }
 
     fn get_transaction_digest_page_by_recipient_address(
         &self,
         recipient_address: String,
-        start_sequence: Option<i64>,
+        cursor: Option<Cursor>,
         limit: usize,
         is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
-        #[derive(QueryableByName, Debug, Clone)]
-        struct TempDigestTable {
-            #[diesel(sql_type = VarChar)]
-            digest_name: String,
-        }
-
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        self.ensure_enabled(IndexerHandler::Recipients)?;
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-        Ok(pg_pool_conn.build_transaction()
+        pg_pool_conn
+            .build_transaction()
             .read_only()
             .run(|conn| {
-                let sql_query = format!(
-                    "SELECT transaction_digest as digest_name FROM (
-                        SELECT transaction_digest, max(id) AS max_id 
-                        FROM recipients WHERE recipient = '{}' {} GROUP BY transaction_digest ORDER BY max_id {} LIMIT {}
-                    ) AS t",
-                    recipient_address.clone(),
-                    if let Some(start_sequence) = start_sequence {
-                        if is_descending {
-                            format!("AND id < {}", start_sequence)
-                        } else {
-                            format!("AND id > {}", start_sequence)
-                        }
-                    } else {
-                        "".to_string()
-                    },
-                    if is_descending {
-                        "DESC"
-                    } else {
-                        "ASC"
-                    },
-                    limit
-                );
-                diesel::sql_query(sql_query).load(conn)
+                let query = diesel::sql_query(
+                    "SELECT transaction_digest, \
+                        MAX(checkpoint_sequence_number) AS checkpoint_sequence_number, \
+                        MAX(id) AS max_id \
+                    FROM recipients WHERE recipient = ",
+                )
+                .into_boxed()
+                .bind::<VarChar, _>(recipient_address.clone());
+                load_grouped_digest_page(conn, query, cursor, limit, is_descending)
             })
+            .map(|rows| into_page(rows, limit))
             .map_err(|e| {
-            IndexerError::PostgresReadError(format!(
-                "Failed reading transaction digests by recipient address {} with start_sequence {:?} and limit {} and err: {:?}",
-                recipient_address, start_sequence, limit, e
-            ))
-        })?.into_iter().map(|table: TempDigestTable| table.digest_name ).collect())
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading transaction digests by recipient address {} with cursor {:?} and limit {} and err: {:?}",
+                    recipient_address, cursor, limit, e
+                ))
+            })
     }
 
     fn read_transactions(
@@ -664,6 +2343,7 @@ impl IndexerStore for PgIndexerStore {
         last_processed_id: i64,
         limit: usize,
     ) -> Result<Vec<Transaction>, IndexerError> {
+        self.ensure_enabled(IndexerHandler::Transactions)?;
         let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
         pg_pool_conn
             .build_transaction()
@@ -683,224 +2363,97 @@ impl IndexerStore for PgIndexerStore {
     }
 
     fn persist_checkpoint(&self, data: &TemporaryCheckpointStore) -> Result<usize, IndexerError> {
-        let TemporaryCheckpointStore {
-            checkpoint,
-            transactions,
-            events,
-            objects_changes,
-            addresses,
-            packages,
-            move_calls,
-            recipients, // TODO: store raw object
-        } = data;
-
-        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
-        // Commit indexed transactions
-        for transaction_chunk in transactions.chunks(PG_COMMIT_CHUNK_SIZE) {
-            pg_pool_conn
-                .build_transaction()
-                .serializable()
-                .read_write()
-                .run(|conn| {
-                    diesel::insert_into(transactions::table)
-                        .values(transaction_chunk)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                })
-                .map_err(|e| {
-                    IndexerError::PostgresWriteError(format!(
-                        "Failed writing transactions to PostgresDB with error: {:?}",
-                        e
-                    ))
-                })?;
-        }
-
-        // Commit indexed events
-        for event_chunk in events.chunks(PG_COMMIT_CHUNK_SIZE) {
-            pg_pool_conn
-                .build_transaction()
-                .serializable()
-                .read_write()
-                .run(|conn| {
-                    diesel::insert_into(events::table)
-                        .values(event_chunk)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                })
-                .map_err(|e| {
-                    IndexerError::PostgresWriteError(format!(
-                        "Failed writing events to PostgresDB with error: {:?}",
-                        e
-                    ))
-                })?;
-        }
-
-        // Commit indexed objects
-        for changes in objects_changes {
-            for mutated_object_change_chunk in changes.mutated_objects.chunks(PG_COMMIT_CHUNK_SIZE)
-            {
-                pg_pool_conn
-                    .build_transaction()
-                    .serializable()
-                    .read_write()
-                    .run(|conn| {
-                        diesel::insert_into(objects::table)
-                            .values(mutated_object_change_chunk)
-                            .on_conflict(objects::object_id)
-                            .do_update()
-                            .set((
-                                objects::epoch.eq(excluded(objects::epoch)),
-                                objects::checkpoint.eq(excluded(objects::checkpoint)),
-                                objects::version.eq(excluded(objects::version)),
-                                objects::object_digest.eq(excluded(objects::object_digest)),
-                                objects::owner_address.eq(excluded(objects::owner_address)),
-                                objects::previous_transaction
-                                    .eq(excluded(objects::previous_transaction)),
-                                objects::object_status.eq(excluded(objects::object_status)),
-                            ))
-                            .execute(conn)
-                    })
-                    .map_err(|e| {
-                        IndexerError::PostgresWriteError(format!(
-                            "Failed writing updated objects to PostgresDB with chunk: {:?} error: {:?}",
-                            mutated_object_change_chunk, e
-                        ))
-                    })?;
-            }
-
-            let deleted_objects: Vec<Object> = changes
-                .deleted_objects
-                .iter()
-                .map(|deleted_object| deleted_object.clone().into())
-                .collect();
-            for deleted_object_change_chunk in deleted_objects.chunks(PG_COMMIT_CHUNK_SIZE) {
-                pg_pool_conn
-                    .build_transaction()
-                    .serializable()
-                    .read_write()
-                    .run(|conn| {
-                        diesel::insert_into(objects::table)
-                            .values(deleted_object_change_chunk)
-                            .on_conflict(objects::object_id)
-                            .do_update()
-                            .set((
-                                objects::epoch.eq(excluded(objects::epoch)),
-                                objects::checkpoint.eq(excluded(objects::checkpoint)),
-                                objects::version.eq(excluded(objects::version)),
-                                objects::previous_transaction
-                                    .eq(excluded(objects::previous_transaction)),
-                                objects::object_status.eq(excluded(objects::object_status)),
-                            ))
-                            .execute(conn)
-                    })
-                    .map_err(|e| {
-                        IndexerError::PostgresWriteError(format!(
-                            "Failed writing deleted objects to PostgresDB with chunk: {:?} error: {:?}",
-                            deleted_object_change_chunk, e
-                        ))
-                    })?;
-            }
-        }
-
-        // Commit indexed addresses
-        for addresses_chunk in addresses.chunks(PG_COMMIT_CHUNK_SIZE) {
-            pg_pool_conn
-                .build_transaction()
-                .serializable()
-                .read_write()
-                .run(|conn| {
-                    diesel::insert_into(addresses::table)
-                        .values(addresses_chunk)
-                        .on_conflict(addresses::account_address)
-                        .do_nothing()
-                        .execute(conn)
-                })
-                .map_err(|e| {
-                    IndexerError::PostgresWriteError(format!(
-                        "Failed writing addresses to PostgresDB with error: {:?}",
-                        e
-                    ))
-                })?;
-        }
+        self.persist_checkpoint_data(data, false, None)
+    }
 
-        // Commit indexed packages
-        for packages_chunk in packages.chunks(PG_COMMIT_CHUNK_SIZE) {
-            pg_pool_conn
-                .build_transaction()
-                .serializable()
-                .read_write()
-                .run(|conn| {
-                    diesel::insert_into(packages::table)
-                        .values(packages_chunk)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                })
-                .map_err(|e| {
-                    IndexerError::PostgresWriteError(format!(
-                        "Failed writing packages to PostgresDB with error: {:?}",
-                        e
-                    ))
-                })?;
-        }
+    fn persist_checkpoint_bulk(
+        &self,
+        data: &TemporaryCheckpointStore,
+    ) -> Result<usize, IndexerError> {
+        self.persist_checkpoint_data(data, true, None)
+    }
 
-        // Commit indexed move calls
-        for move_calls_chunk in move_calls.chunks(PG_COMMIT_CHUNK_SIZE) {
-            pg_pool_conn
-                .build_transaction()
-                .serializable()
-                .read_write()
-                .run(|conn| {
-                    diesel::insert_into(move_calls::table)
-                        .values(move_calls_chunk)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                })
-                .map_err(|e| {
-                    IndexerError::PostgresWriteError(format!(
-                        "Failed writing move_calls to PostgresDB with error: {:?}",
-                        e
+    fn get_lagging_handlers(&self) -> Result<BTreeMap<IndexerHandler, i64>, IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        let rows: Vec<(String, i64)> = pg_pool_conn
+            .build_transaction()
+            .read_only()
+            .run(|conn| {
+                lagging_handlers::table
+                    .select((
+                        lagging_handlers::handler,
+                        lagging_handlers::checkpoint_sequence_number,
                     ))
-                })?;
-        }
+                    .load(conn)
+            })
+            .map_err(|e| {
+                IndexerError::PostgresReadError(format!(
+                    "Failed reading lagging handlers with error: {:?}",
+                    e
+                ))
+            })?;
+        // Rows of handlers that no longer exist are left alone
+        Ok(rows
+            .into_iter()
+            .filter_map(|(name, checkpoint)| {
+                IndexerHandler::value_variants()
+                    .iter()
+                    .find(|handler| handler.to_string() == name)
+                    .map(|handler| (*handler, checkpoint))
+            })
+            .collect())
+    }
 
-        // Commit indexed recipients
-        for recipients_chunk in recipients.chunks(PG_COMMIT_CHUNK_SIZE) {
-            pg_pool_conn
-                .build_transaction()
-                .serializable()
-                .read_write()
-                .run(|conn| {
-                    diesel::insert_into(recipients::table)
-                        .values(recipients_chunk)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                })
-                .map_err(|e| {
-                    IndexerError::PostgresWriteError(format!(
-                        "Failed writing recipients to PostgresDB with error: {:?}",
-                        e
+    fn mark_handler_lagging(
+        &self,
+        handler: IndexerHandler,
+        checkpoint: i64,
+    ) -> Result<(), IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        self.commit_retries
+            .run(&mut pg_pool_conn, |conn| {
+                diesel::insert_into(lagging_handlers::table)
+                    .values((
+                        lagging_handlers::handler.eq(handler.to_string()),
+                        lagging_handlers::checkpoint_sequence_number.eq(checkpoint),
                     ))
-                })?;
-        }
-
-        // Commit indexed checkpoint last, so that if the checkpoint is committed,
-        // all related data have been committed as well.
-        pg_pool_conn
-            .build_transaction()
-            .serializable()
-            .read_write()
-            .run(|conn| {
-                diesel::insert_into(checkpoints::table)
-                    .values(checkpoint)
-                    .on_conflict_do_nothing()
+                    .on_conflict(lagging_handlers::handler)
+                    .do_update()
+                    .set(lagging_handlers::checkpoint_sequence_number.eq(checkpoint))
                     .execute(conn)
             })
             .map_err(|e| {
                 IndexerError::PostgresWriteError(format!(
-                    "Failed writing checkpoint to PostgresDB with error: {:?}",
-                    e
+                    "Failed writing checkpoint {} of lagging handler {} to PostgresDB with error: {:?}",
+                    checkpoint, handler, e
                 ))
+            })?;
+        Ok(())
+    }
+
+    fn mark_handler_caught_up(&self, handler: IndexerHandler) -> Result<(), IndexerError> {
+        let mut pg_pool_conn = get_pg_pool_connection(&self.cp)?;
+        self.commit_retries
+            .run(&mut pg_pool_conn, |conn| {
+                diesel::delete(lagging_handlers::table)
+                    .filter(lagging_handlers::handler.eq(handler.to_string()))
+                    .execute(conn)
             })
+            .map_err(|e| {
+                IndexerError::PostgresWriteError(format!(
+                    "Failed removing lagging handler {} from PostgresDB with error: {:?}",
+                    handler, e
+                ))
+            })?;
+        Ok(())
+    }
+
+    fn persist_handler_checkpoint(
+        &self,
+        handler: IndexerHandler,
+        data: &TemporaryCheckpointStore,
+    ) -> Result<usize, IndexerError> {
+        self.persist_checkpoint_data(data, false, Some(handler))
     }
 
     fn persist_epoch(&self, data: &TemporaryEpochStore) -> Result<(), IndexerError> {
@@ -924,6 +2477,224 @@ impl IndexerStore for PgIndexerStore {
     }
 }
 
+/// The objects of `changes` that are not older than their row in `objects`, as checkpoint ranges
+/// are committed in any order. Read in the transaction of the write, for a concurrent write of the
+/// same objects to fail it on a serialization failure.
+fn newer_objects<'a>(
+    conn: &mut PgConnection,
+    changes: &'a [Object],
+) -> QueryResult<Vec<&'a Object>> {
+    let versions: HashMap<String, i64> = objects::table
+        .select((objects::object_id, objects::version))
+        .filter(objects::object_id.eq_any(changes.iter().map(|o| &o.object_id)))
+        .load::<(String, i64)>(conn)?
+        .into_iter()
+        .collect();
+    Ok(changes
+        .iter()
+        .filter(|o| {
+            versions
+                .get(&o.object_id)
+                .map_or(true, |version| *version <= o.version)
+        })
+        .collect())
+}
+
+/// Split off the extra item fetched beyond `limit`; if there was one, the cursor of the
+/// last item of the page is returned to continue from.
+fn into_page<T, C: Copy>(mut items: Vec<(T, C)>, limit: usize) -> (Vec<T>, Option<C>) {
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items.last().map(|(_, cursor)| *cursor)
+    } else {
+        None
+    };
+    (
+        items.into_iter().map(|(item, _)| item).collect(),
+        next_cursor,
+    )
+}
+
+fn grouped_cursor(
+    cursor: (Option<i64>, Option<i64>),
+    table: &str,
+    txn_digest: &str,
+) -> Result<Cursor, IndexerError> {
+    match cursor {
+        (Some(checkpoint), Some(index)) => Ok(Cursor::new(checkpoint, index)),
+        _ => Err(IndexerError::PostgresReadError(format!(
+            "Failed reading {} cursor with digest {}: no such rows",
+            table, txn_digest
+        ))),
+    }
+}
+
+/// Page through transactions in (checkpoint, id) order, starting after `cursor`.
+/// The objects `owner` owned as of `checkpoint` after `cursor` in object id order, at most
+/// `limit + 1` of them. The latest version up to the checkpoint of each object `owner` ever
+/// owned up to then is read in batches, keeping those still owned by `owner`.
+fn load_owned_objects_at_checkpoint(
+    conn: &mut PgConnection,
+    owner: SuiAddress,
+    type_filter: Option<&StructTag>,
+    cursor: Option<ObjectID>,
+    limit: usize,
+    checkpoint: i64,
+) -> QueryResult<Vec<Object>> {
+    let owner = owner.to_string();
+    let type_filter = type_filter.map(|type_filter| type_filter.to_string());
+    // Object ids are 0x prefixed, so any of them is after the empty string.
+    let mut after = cursor.map(|cursor| cursor.to_string()).unwrap_or_default();
+    let mut owned = vec![];
+    while owned.len() <= limit {
+        let ever_owned = objects_history::table
+            .filter(objects_history::owner_address.eq(owner.clone()))
+            .filter(objects_history::checkpoint.le(checkpoint))
+            .select(objects_history::object_id);
+        let batch = objects_history::table
+            .filter(objects_history::object_id.eq_any(ever_owned))
+            .filter(objects_history::object_id.gt(after.clone()))
+            .filter(objects_history::checkpoint.le(checkpoint))
+            .distinct_on(objects_history::object_id)
+            .order((objects_history::object_id.asc(), objects_history::version.desc()))
+            .limit(limit as i64 + 1)
+            .load::<Object>(conn)?;
+        let exhausted = batch.len() <= limit;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        after = last.object_id.clone();
+        // Rows of deleted and wrapped objects are kept with their last owner.
+        owned.extend(batch.into_iter().filter(|o| {
+            matches!(o.owner_type, OwnerType::AddressOwner)
+                && o.owner_address.as_deref() == Some(owner.as_str())
+                && !matches!(
+                    o.object_status,
+                    ObjectStatus::Deleted
+                        | ObjectStatus::Wrapped
+                        | ObjectStatus::UnwrappedThenDeleted
+                )
+                && type_filter.as_ref().map_or(true, |t| &o.object_type == t)
+        }));
+        if exhausted {
+            break;
+        }
+    }
+    owned.truncate(limit + 1);
+    Ok(owned)
+}
+
+fn load_transaction_digest_page(
+    conn: &mut PgConnection,
+    mut boxed_query: transactions::BoxedQuery<'_, Pg>,
+    cursor: Option<Cursor>,
+    limit: usize,
+    is_descending: bool,
+) -> QueryResult<Vec<(String, Cursor)>> {
+    if let Some(cursor) = cursor {
+        if is_descending {
+            boxed_query = boxed_query.filter(
+                transactions_dsl::checkpoint_sequence_number
+                    .lt(cursor.checkpoint())
+                    .or(transactions_dsl::checkpoint_sequence_number
+                        .eq(cursor.checkpoint())
+                        .and(transactions_dsl::id.lt(cursor.index()))),
+            );
+        } else {
+            boxed_query = boxed_query.filter(
+                transactions_dsl::checkpoint_sequence_number
+                    .gt(cursor.checkpoint())
+                    .or(transactions_dsl::checkpoint_sequence_number
+                        .eq(cursor.checkpoint())
+                        .and(transactions_dsl::id.gt(cursor.index()))),
+            );
+        }
+    }
+    if is_descending {
+        boxed_query = boxed_query.order((
+            transactions_dsl::checkpoint_sequence_number.desc(),
+            transactions_dsl::id.desc(),
+        ));
+    } else {
+        boxed_query = boxed_query.order((
+            transactions_dsl::checkpoint_sequence_number.asc(),
+            transactions_dsl::id.asc(),
+        ));
+    }
+    // fetch one more item to tell if there is next page
+    boxed_query
+        .limit(limit as i64 + 1)
+        .select((
+            transactions_dsl::transaction_digest,
+            transactions_dsl::checkpoint_sequence_number,
+            transactions_dsl::id,
+        ))
+        .load::<(String, i64, i64)>(conn)
+        .map(|rows| {
+            rows.into_iter()
+                .map(|(digest, checkpoint, id)| (digest, Cursor::new(checkpoint, id)))
+                .collect()
+        })
+}
+
+#[derive(QueryableByName, Debug, Clone)]
+struct GroupedDigest {
+    #[diesel(sql_type = VarChar)]
+    transaction_digest: String,
+    #[diesel(sql_type = BigInt)]
+    checkpoint_sequence_number: i64,
+    #[diesel(sql_type = BigInt)]
+    max_id: i64,
+}
+
+/// Page through tables with multiple rows per transaction, like move_calls and recipients.
+/// Rows are grouped by transaction and each transaction is positioned by its latest row, so
+/// the cursor is compared against the whole group rather than against individual rows.
+/// `query` selects the grouped columns and carries the WHERE clause of the filter.
+fn load_grouped_digest_page(
+    conn: &mut PgConnection,
+    mut query: BoxedSqlQuery<'_, Pg, SqlQuery>,
+    cursor: Option<Cursor>,
+    limit: usize,
+    is_descending: bool,
+) -> QueryResult<Vec<(String, Cursor)>> {
+    let (cmp, order) = if is_descending {
+        ("<", "DESC")
+    } else {
+        (">", "ASC")
+    };
+    query = query.sql(" GROUP BY transaction_digest");
+    if let Some(cursor) = cursor {
+        query = query
+            .sql(format!(
+                " HAVING (MAX(checkpoint_sequence_number), MAX(id)) {} (",
+                cmp
+            ))
+            .bind::<BigInt, _>(cursor.checkpoint())
+            .sql(", ")
+            .bind::<BigInt, _>(cursor.index())
+            .sql(")");
+    }
+    // fetch one more item to tell if there is next page
+    query
+        .sql(format!(
+            " ORDER BY checkpoint_sequence_number {}, max_id {} LIMIT ",
+            order, order
+        ))
+        .bind::<BigInt, _>(limit as i64 + 1)
+        .load::<GroupedDigest>(conn)
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| {
+                    (
+                        row.transaction_digest,
+                        Cursor::new(row.checkpoint_sequence_number, row.max_id),
+                    )
+                })
+                .collect()
+        })
+}
+
 #[derive(Clone)]
 struct PartitionManager {
     cp: PgConnectionPool,
@@ -950,9 +2721,13 @@ impl PartitionManager {
             .run(|conn| {
                 let mut updated_table = vec![];
                 for (table, last_partition) in &tables {
-                    if last_partition < &next_epoch_id {
-                        let sql = format!("CREATE TABLE {table}_partition_{next_epoch_id} PARTITION OF {table} FOR VALUES FROM ({next_epoch_id}) TO ({});", next_epoch_id+1);
+                    // Checkpoint ranges indexed out of order can skip epochs, and several
+                    // indexers can create the same partition
+                    for epoch_id in last_partition + 1..=next_epoch_id {
+                        let sql = format!("CREATE TABLE IF NOT EXISTS {table}_partition_{epoch_id} PARTITION OF {table} FOR VALUES FROM ({epoch_id}) TO ({});", epoch_id+1);
                         diesel::sql_query(sql).execute(conn)?;
+                    }
+                    if last_partition < &next_epoch_id {
                         updated_table.push(table);
                     }
                 }
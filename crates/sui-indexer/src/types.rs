@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use serde::{Deserialize, Serialize};
 use sui_json_rpc_types::{
     BalanceChange, ObjectChange, SuiTransaction, SuiTransactionEffects, SuiTransactionEvents,
     SuiTransactionResponse,
@@ -8,12 +9,16 @@ use sui_json_rpc_types::{
 use sui_types::digests::TransactionDigest;
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SuiTransactionFullResponse {
     pub digest: TransactionDigest,
     /// Transaction input data
     pub transaction: SuiTransaction,
+    /// BCS encoded SenderSignedData, empty unless raw input was requested
+    pub raw_transaction: Vec<u8>,
     pub effects: SuiTransactionEffects,
+    /// BCS encoded TransactionEffects, empty unless raw effects were requested
+    pub raw_effects: Vec<u8>,
     pub events: SuiTransactionEvents,
     pub object_changes: Option<Vec<ObjectChange>>,
     pub balance_changes: Option<Vec<BalanceChange>>,
@@ -29,7 +34,9 @@ impl TryFrom<SuiTransactionResponse> for SuiTransactionFullResponse {
         let SuiTransactionResponse {
             digest,
             transaction,
+            raw_transaction,
             effects,
+            raw_effects,
             events,
             object_changes,
             balance_changes,
@@ -80,7 +87,9 @@ impl TryFrom<SuiTransactionResponse> for SuiTransactionFullResponse {
         Ok(SuiTransactionFullResponse {
             digest,
             transaction,
+            raw_transaction,
             effects,
+            raw_effects,
             events,
             object_changes,
             balance_changes,
@@ -96,7 +105,9 @@ impl From<SuiTransactionFullResponse> for SuiTransactionResponse {
         let SuiTransactionFullResponse {
             digest,
             transaction,
+            raw_transaction,
             effects,
+            raw_effects,
             events,
             object_changes,
             balance_changes,
@@ -108,7 +119,9 @@ impl From<SuiTransactionFullResponse> for SuiTransactionResponse {
         SuiTransactionResponse {
             digest,
             transaction: Some(transaction),
+            raw_transaction,
             effects: Some(effects),
+            raw_effects,
             events: Some(events),
             object_changes,
             balance_changes,
@@ -1,17 +1,32 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use clap::Parser;
+use futures::stream::BoxStream;
+use move_core_types::language_storage::StructTag;
 use prometheus::Registry;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use sui_indexer::errors::IndexerError;
 use sui_indexer::models::checkpoints::Checkpoint;
-use sui_indexer::models::objects::Object;
+use sui_indexer::models::objects::{LastModifyingTransaction, Object};
+use sui_indexer::models::packages::Package;
+use sui_indexer::models::ptb_commands::PtbCommand;
+use sui_indexer::models::raw_transactions::RawTransaction;
+use sui_indexer::models::transaction_inputs::TransactionInput;
 use sui_indexer::models::transactions::Transaction;
-use sui_indexer::store::{IndexerStore, TemporaryCheckpointStore, TemporaryEpochStore};
-use sui_indexer::{Indexer, IndexerConfig};
+use sui_indexer::store::{
+    ConsistencyLevel, Cursor, IndexerStore, ObjectInclusionProof, TemporaryCheckpointStore,
+    TemporaryEpochStore,
+};
+use sui_indexer::{Indexer, IndexerConfig, IndexerHandler};
 use sui_json_rpc_types::{CheckpointId, EventFilter};
-use sui_types::base_types::{ObjectID, SequenceNumber};
+use sui_types::base_types::{ObjectID, SequenceNumber, SuiAddress, TransactionDigest};
+use sui_types::committee::EpochId;
+use sui_types::crypto::SignatureScheme;
+use sui_types::gas::GasCostSummary;
+use sui_types::messages_checkpoint::{CertifiedCheckpointSummary, CheckpointSequenceNumber};
 use sui_types::object::ObjectRead;
 use test_utils::network::TestClusterBuilder;
 
@@ -36,6 +51,171 @@ async fn test_genesis() {
 
     // TODO: add more test
     assert!(!store.tables.read().unwrap().objects.is_empty());
+    assert!(store.tables.read().unwrap().chain_identifier.is_some());
+}
+
+#[tokio::test]
+async fn test_chain_identifier_mismatch() {
+    let test_cluster = TestClusterBuilder::new().build().await.unwrap();
+    let mut config = IndexerConfig::default();
+    config.rpc_client_url = test_cluster.rpc_url().to_string();
+    // Out of the way of the indexer of test_genesis
+    config.rpc_server_port = 0;
+
+    // The fullnode is not on the expected chain
+    config.expected_chain_id = Some("00000000".to_string());
+    let store = InMemoryIndexerStore::new();
+    let result = Indexer::start(&config, &Registry::default(), store.clone()).await;
+    assert!(matches!(result, Err(IndexerError::ChainIdentifierMismatch(_))));
+    assert!(store.tables.read().unwrap().chain_identifier.is_none());
+
+    // The db indexes another chain
+    config.expected_chain_id = None;
+    store.persist_chain_identifier("00000000").unwrap();
+    let result = Indexer::start(&config, &Registry::default(), store).await;
+    assert!(matches!(result, Err(IndexerError::ChainIdentifierMismatch(_))));
+}
+
+#[test]
+fn test_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("indexer.yaml");
+    std::fs::write(
+        &config_path,
+        "db-url: postgres://file\n\
+         rpc-client-url: http://file:9000\n\
+         package-cache-size: 10\n\
+         store-raw-bcs: true\n\
+         rpc-client-compression: false\n\
+         enabled-handlers: [objects, events]\n",
+    )
+    .unwrap();
+    let config_arg = config_path.to_str().unwrap();
+
+    // Flags given on the command line take precedence over the file.
+    let config = IndexerConfig::load_from([
+        "sui-indexer",
+        "--config",
+        config_arg,
+        "--rpc-client-url",
+        "http://cli:9000",
+    ])
+    .unwrap();
+    assert_eq!(config.db_url, "postgres://file");
+    assert_eq!(config.rpc_client_url, "http://cli:9000");
+    assert_eq!(config.package_cache_size, 10);
+    assert!(config.store_raw_bcs);
+    assert!(!config.rpc_client_compression);
+    assert_eq!(
+        config.enabled_handlers,
+        vec![IndexerHandler::Objects, IndexerHandler::Events]
+    );
+    // Flags in neither keep their default.
+    assert_eq!(config.bulk_copy_threshold, 1000);
+    assert!(!config.danger_accept_invalid_fullnode_certs);
+
+    // The db url file takes precedence over the config file.
+    let db_url_path = dir.path().join("db_url");
+    std::fs::write(&db_url_path, "postgres://secret\n").unwrap();
+    let config = IndexerConfig::load_from([
+        "sui-indexer",
+        "--config",
+        config_arg,
+        "--db-url-file",
+        db_url_path.to_str().unwrap(),
+    ])
+    .unwrap();
+    assert_eq!(config.db_url, "postgres://secret");
+    assert_eq!(config.rpc_client_url, "http://file:9000");
+
+    let toml_path = dir.path().join("indexer.toml");
+    std::fs::write(
+        &toml_path,
+        "db-url = \"postgres://toml\"\nrpc-client-url = \"http://toml:9000\"\n",
+    )
+    .unwrap();
+    let config =
+        IndexerConfig::load_from(["sui-indexer", "--config", toml_path.to_str().unwrap()]).unwrap();
+    assert_eq!(config.db_url, "postgres://toml");
+
+    std::fs::write(&config_path, "no-such-flag: 1\n").unwrap();
+    assert!(matches!(
+        IndexerConfig::load_from(["sui-indexer", "--config", config_arg, "--db-url", "x"]),
+        Err(IndexerError::ConfigError(_))
+    ));
+}
+
+#[test]
+fn test_checkpoint_range() {
+    let config = IndexerConfig::load_from([
+        "sui-indexer",
+        "--db-url",
+        "x",
+        "--rpc-client-url",
+        "x",
+        "--checkpoint-range",
+        "1000-1999",
+    ])
+    .unwrap();
+    assert_eq!(config.checkpoint_range, Some((1000, 1999)));
+
+    for range in ["1999-1000", "1000", "a-b"] {
+        assert!(IndexerConfig::try_parse_from([
+            "sui-indexer",
+            "--db-url",
+            "x",
+            "--rpc-client-url",
+            "x",
+            "--checkpoint-range",
+            range,
+        ])
+        .is_err());
+    }
+}
+
+#[test]
+fn test_fullnode_headers() {
+    let dir = tempfile::tempdir().unwrap();
+    let tenant_path = dir.path().join("tenant");
+    std::fs::write(&tenant_path, "acme\n").unwrap();
+    std::env::set_var("TEST_FULLNODE_HEADERS_API_KEY", "secret");
+    let tenant_header = format!("x-tenant=file:{}", tenant_path.display());
+    let args = |headers: &[&str]| {
+        let mut args = vec!["sui-indexer", "--db-url", "x", "--rpc-client-url", "x"];
+        for header in headers {
+            args.extend(["--fullnode-header", *header]);
+        }
+        IndexerConfig::load_from(args)
+    };
+
+    let config = args(&[
+        "x-api-key=env:TEST_FULLNODE_HEADERS_API_KEY",
+        &tenant_header,
+        "x-trace=a=b",
+    ])
+    .unwrap();
+    let headers = config.fullnode_headers().unwrap();
+    assert_eq!(headers.len(), 3);
+    assert_eq!(headers["x-api-key"], "secret");
+    assert!(headers["x-api-key"].is_sensitive());
+    assert_eq!(headers["x-tenant"], "acme");
+    assert_eq!(headers["x-trace"], "a=b");
+
+    assert!(matches!(
+        args(&["x-api-key=env:TEST_FULLNODE_HEADERS_UNSET"]),
+        Err(IndexerError::ConfigError(_))
+    ));
+    assert!(matches!(args(&["not a header=x"]), Err(IndexerError::ConfigError(_))));
+    assert!(IndexerConfig::try_parse_from([
+        "sui-indexer",
+        "--db-url",
+        "x",
+        "--rpc-client-url",
+        "x",
+        "--fullnode-header",
+        "x-api-key",
+    ])
+    .is_err());
 }
 
 #[derive(Clone)]
@@ -55,6 +235,7 @@ impl InMemoryIndexerStore {
 struct Tables {
     pub objects: Vec<Object>,
     pub checkpoints: Vec<Checkpoint>,
+    pub chain_identifier: Option<String>,
 }
 
 impl IndexerStore for InMemoryIndexerStore {
@@ -64,6 +245,30 @@ impl IndexerStore for InMemoryIndexerStore {
         Ok(self.tables.read().unwrap().checkpoints.len() as i64 - 1)
     }
 
+    fn get_latest_checkpoint_sequence_number_in_range(
+        &self,
+        _first: CheckpointSequenceNumber,
+        _last: CheckpointSequenceNumber,
+    ) -> Result<Option<i64>, IndexerError> {
+        todo!()
+    }
+
+    fn register_checkpoint_range(
+        &self,
+        _first: CheckpointSequenceNumber,
+        _last: CheckpointSequenceNumber,
+    ) -> Result<(), IndexerError> {
+        todo!()
+    }
+
+    fn persist_chain_identifier(&self, chain_id: &str) -> Result<String, IndexerError> {
+        let mut tables = self.tables.write().unwrap();
+        Ok(tables
+            .chain_identifier
+            .get_or_insert_with(|| chain_id.to_string())
+            .clone())
+    }
+
     fn get_checkpoint(&self, id: CheckpointId) -> Result<Checkpoint, IndexerError> {
         Ok(match id {
             CheckpointId::SequenceNumber(seq) => {
@@ -81,6 +286,32 @@ impl IndexerStore for InMemoryIndexerStore {
         })
     }
 
+    fn get_checkpoint_summary(
+        &self,
+        _sequence_number: CheckpointSequenceNumber,
+    ) -> Result<CertifiedCheckpointSummary, IndexerError> {
+        todo!()
+    }
+
+    fn get_checkpoint_transaction_digests(
+        &self,
+        _sequence_number: CheckpointSequenceNumber,
+    ) -> Result<Vec<TransactionDigest>, IndexerError> {
+        todo!()
+    }
+
+    fn get_reference_gas_price(&self) -> Result<u64, IndexerError> {
+        todo!()
+    }
+
+    fn get_reference_gas_price_at_epoch(&self, _epoch: EpochId) -> Result<u64, IndexerError> {
+        todo!()
+    }
+
+    fn get_total_supply(&self, _coin_type: &str) -> Result<u64, IndexerError> {
+        todo!()
+    }
+
     fn get_event(
         &self,
         _id: sui_types::event::EventID,
@@ -88,13 +319,17 @@ impl IndexerStore for InMemoryIndexerStore {
         todo!()
     }
 
+    fn get_event_cursor(&self, _id: sui_types::event::EventID) -> Result<Cursor, IndexerError> {
+        todo!()
+    }
+
     fn get_events(
         &self,
         _query: EventFilter,
-        _cursor: Option<sui_types::event::EventID>,
-        _limit: Option<usize>,
+        _cursor: Option<Cursor>,
+        _limit: usize,
         _descending_order: bool,
-    ) -> Result<sui_json_rpc_types::EventPage, IndexerError> {
+    ) -> Result<(Vec<sui_json_rpc_types::SuiEvent>, Option<Cursor>), IndexerError> {
         todo!()
     }
 
@@ -106,56 +341,68 @@ impl IndexerStore for InMemoryIndexerStore {
         todo!()
     }
 
-    fn get_transaction_sequence_by_digest(
-        &self,
-        _txn_digest: Option<String>,
-        _is_descending: bool,
-    ) -> Result<Option<i64>, IndexerError> {
+    fn get_raw_transaction(&self, _txn_digest: &str) -> Result<RawTransaction, IndexerError> {
         todo!()
     }
 
-    fn get_recipient_sequence_by_digest(
+    fn get_transaction_inputs(
         &self,
-        _txn_digest: Option<String>,
-        _is_descending: bool,
-    ) -> Result<Option<i64>, IndexerError> {
+        _txn_digest: &str,
+    ) -> Result<Vec<TransactionInput>, IndexerError> {
         todo!()
     }
 
-    fn get_move_call_sequence_by_digest(
-        &self,
-        _txn_digest: Option<String>,
-        _is_descending: bool,
-    ) -> Result<Option<i64>, IndexerError> {
+    fn get_ptb_structure(&self, _txn_digest: &str) -> Result<Vec<PtbCommand>, IndexerError> {
+        todo!()
+    }
+
+    fn get_transaction_cursor_by_digest(&self, _txn_digest: &str) -> Result<Cursor, IndexerError> {
+        todo!()
+    }
+
+    fn get_recipient_cursor_by_digest(&self, _txn_digest: &str) -> Result<Cursor, IndexerError> {
+        todo!()
+    }
+
+    fn get_move_call_cursor_by_digest(&self, _txn_digest: &str) -> Result<Cursor, IndexerError> {
         todo!()
     }
 
     fn get_all_transaction_digest_page(
         &self,
-        _start_sequence: Option<i64>,
+        _cursor: Option<Cursor>,
         _limit: usize,
         _is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
         todo!()
     }
 
     fn get_transaction_digest_page_by_mutated_object(
         &self,
         _object_id: String,
-        _start_sequence: Option<i64>,
+        _cursor: Option<Cursor>,
         _limit: usize,
         _is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        todo!()
+    }
+
+    fn get_transactions_for_object(
+        &self,
+        _object_id: ObjectID,
+        _cursor: Option<Cursor>,
+        _limit: usize,
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
         todo!()
     }
 
     fn get_transaction_digest_page_by_sender_address(
         &self,
         _sender_address: String,
-        _start_sequence: Option<i64>,
+        _cursor: Option<Cursor>,
         _limit: usize,
         _is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
         todo!()
     }
 
@@ -164,20 +411,40 @@ impl IndexerStore for InMemoryIndexerStore {
         _package: String,
         _module: Option<String>,
         _function: Option<String>,
-        _start_sequence: Option<i64>,
+        _cursor: Option<Cursor>,
+        _limit: usize,
+        _is_descending: bool,
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        todo!()
+    }
+
+    fn query_transactions_by_signature_scheme(
+        &self,
+        _scheme: SignatureScheme,
+        _cursor: Option<Cursor>,
         _limit: usize,
         _is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
+        todo!()
+    }
+
+    fn query_transactions_by_time(
+        &self,
+        _start_ms: i64,
+        _end_ms: i64,
+        _cursor: Option<Cursor>,
+        _limit: usize,
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
         todo!()
     }
 
     fn get_transaction_digest_page_by_recipient_address(
         &self,
         _recipient_address: String,
-        _start_sequence: Option<i64>,
+        _cursor: Option<Cursor>,
         _limit: usize,
         _is_descending: bool,
-    ) -> Result<Vec<String>, IndexerError> {
+    ) -> Result<(Vec<String>, Option<Cursor>), IndexerError> {
         todo!()
     }
 
@@ -188,6 +455,14 @@ impl IndexerStore for InMemoryIndexerStore {
         todo!()
     }
 
+    fn get_package(&self, _package_id: &str) -> Result<Package, IndexerError> {
+        todo!()
+    }
+
+    fn get_package_versions(&self, _original_id: &str) -> Result<Vec<Package>, IndexerError> {
+        todo!()
+    }
+
     fn read_transactions(
         &self,
         _last_processed_id: i64,
@@ -200,10 +475,98 @@ impl IndexerStore for InMemoryIndexerStore {
         &self,
         _object_id: ObjectID,
         _version: Option<SequenceNumber>,
+        _consistency: ConsistencyLevel,
     ) -> Result<ObjectRead, IndexerError> {
         todo!();
     }
 
+    fn get_object_at_version(
+        &self,
+        _object_id: ObjectID,
+        _version: SequenceNumber,
+    ) -> Result<ObjectRead, IndexerError> {
+        todo!()
+    }
+
+    fn get_object_versions(
+        &self,
+        _object_id: ObjectID,
+        _cursor: Option<SequenceNumber>,
+        _limit: usize,
+    ) -> Result<(Vec<Object>, Option<SequenceNumber>), IndexerError> {
+        todo!()
+    }
+
+    fn get_indexed_object_ids(
+        &self,
+        _object_ids: &[String],
+    ) -> Result<BTreeSet<String>, IndexerError> {
+        todo!()
+    }
+
+    fn get_last_modifying_transaction(
+        &self,
+        _object_id: ObjectID,
+    ) -> Result<LastModifyingTransaction, IndexerError> {
+        todo!()
+    }
+
+    fn get_object_ancestry(&self, _object_id: ObjectID) -> Result<Vec<ObjectRead>, IndexerError> {
+        todo!()
+    }
+
+    fn get_object_inclusion_proof(
+        &self,
+        _object_id: ObjectID,
+        _checkpoint: CheckpointSequenceNumber,
+    ) -> Result<ObjectInclusionProof, IndexerError> {
+        todo!()
+    }
+
+    fn scan_objects_by_type(
+        &self,
+        _object_type: StructTag,
+        _batch_size: usize,
+    ) -> BoxStream<'static, Result<Vec<Object>, IndexerError>> {
+        todo!()
+    }
+
+    fn find_orphaned_dynamic_fields(&self, _limit: usize) -> Result<Vec<Object>, IndexerError> {
+        todo!()
+    }
+
+    fn get_gas_breakdown(&self, _txn_digest: &str) -> Result<GasCostSummary, IndexerError> {
+        todo!()
+    }
+
+    fn get_gas_totals(
+        &self,
+        _start_checkpoint: i64,
+        _end_checkpoint: i64,
+    ) -> Result<GasCostSummary, IndexerError> {
+        todo!()
+    }
+
+    fn get_owned_objects(
+        &self,
+        _owner: SuiAddress,
+        _type_filter: Option<StructTag>,
+        _cursor: Option<ObjectID>,
+        _limit: usize,
+        _consistency: ConsistencyLevel,
+    ) -> Result<(Vec<ObjectRead>, Option<ObjectID>), IndexerError> {
+        todo!()
+    }
+
+    fn get_shared_objects(
+        &self,
+        _type_filter: Option<StructTag>,
+        _cursor: Option<ObjectID>,
+        _limit: usize,
+    ) -> Result<(Vec<ObjectRead>, Option<ObjectID>), IndexerError> {
+        todo!()
+    }
+
     fn persist_checkpoint(&self, data: &TemporaryCheckpointStore) -> Result<usize, IndexerError> {
         let TemporaryCheckpointStore {
             objects_changes,
@@ -219,6 +582,37 @@ impl IndexerStore for InMemoryIndexerStore {
         Ok(0)
     }
 
+    fn persist_checkpoint_bulk(
+        &self,
+        data: &TemporaryCheckpointStore,
+    ) -> Result<usize, IndexerError> {
+        self.persist_checkpoint(data)
+    }
+
+    fn get_lagging_handlers(&self) -> Result<BTreeMap<IndexerHandler, i64>, IndexerError> {
+        Ok(BTreeMap::new())
+    }
+
+    fn mark_handler_lagging(
+        &self,
+        _handler: IndexerHandler,
+        _checkpoint: i64,
+    ) -> Result<(), IndexerError> {
+        todo!()
+    }
+
+    fn mark_handler_caught_up(&self, _handler: IndexerHandler) -> Result<(), IndexerError> {
+        todo!()
+    }
+
+    fn persist_handler_checkpoint(
+        &self,
+        _handler: IndexerHandler,
+        _data: &TemporaryCheckpointStore,
+    ) -> Result<usize, IndexerError> {
+        todo!()
+    }
+
     fn persist_epoch(&self, _data: &TemporaryEpochStore) -> Result<(), IndexerError> {
         todo!()
     }
@@ -4,29 +4,48 @@
 // integration test with standalone postgresql database
 #[cfg(feature = "pg_integration")]
 mod pg_integration {
+    use clap::ValueEnum;
     use diesel::migration::MigrationSource;
+    use fastcrypto::encoding::{Encoding, Hex};
     use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+    use futures::TryStreamExt;
     use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
     use prometheus::Registry;
+    use std::collections::{BTreeMap, BTreeSet};
     use std::env;
     use std::str::FromStr;
+    use std::time::Duration;
     use sui_config::SUI_KEYSTORE_FILENAME;
     use sui_indexer::errors::IndexerError;
-    use sui_indexer::store::{IndexerStore, PgIndexerStore};
-    use sui_indexer::{new_pg_connection_pool, Indexer, IndexerConfig, PgPoolConnection};
-    use sui_json_rpc::api::{ReadApiClient, TransactionBuilderClient, WriteApiClient};
+    use sui_indexer::store::{
+        ConsistencyLevel, IndexerStore, PgIndexerStore, TemporaryCheckpointStore,
+    };
+    use sui_indexer::{
+        new_pg_connection_pool, AppendOnlyTable, Indexer, IndexerConfig, IndexerHandler,
+        PgPoolConnection, ShutdownSignal,
+    };
+    use sui_json_rpc::api::{
+        GovernanceReadApiClient, ReadApiClient, TransactionBuilderClient, WriteApiClient,
+    };
     use sui_json_rpc_types::{
-        SuiMoveObject, SuiObjectDataOptions, SuiObjectResponse, SuiParsedMoveObject,
-        SuiTransactionResponseOptions, SuiTransactionResponseQuery, TransactionBytes,
+        BigInt, CheckpointId, SuiMoveObject, SuiObjectDataOptions, SuiObjectResponse,
+        SuiParsedMoveObject, SuiTransactionResponseOptions, SuiTransactionResponseQuery,
+        TransactionBytes,
     };
     use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
     use sui_types::base_types::ObjectID;
+    use sui_types::clock::Clock;
+    use sui_types::crypto::SignatureScheme;
     use sui_types::digests::TransactionDigest;
+    use sui_types::gas::GasCostSummary;
     use sui_types::gas_coin::GasCoin;
     use sui_types::messages::ExecuteTransactionRequestType;
-    use sui_types::object::ObjectFormatOptions;
+    use sui_types::object::{ObjectFormatOptions, ObjectRead, Owner};
     use sui_types::query::TransactionFilter;
     use sui_types::utils::to_sender_signed_transaction;
+    use sui_types::{
+        SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION, SUI_SYSTEM_STATE_OBJECT_ID,
+    };
     use test_utils::network::{TestCluster, TestClusterBuilder};
     use tokio::task::JoinHandle;
     const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
@@ -165,6 +184,540 @@ mod pg_integration {
             tx_response.digest,
             tx_mutation_query_response.data.last().unwrap().digest,
         );
+
+        // query txn with the signature scheme of the sender
+        let digest = tx_response.digest.base58_encode();
+        let (ed25519_txs, _) = store.query_transactions_by_signature_scheme(
+            SignatureScheme::ED25519,
+            None,
+            100,
+            false,
+        )?;
+        assert!(ed25519_txs.contains(&digest));
+        let (multisig_txs, _) = store.query_transactions_by_signature_scheme(
+            SignatureScheme::MultiSig,
+            None,
+            100,
+            false,
+        )?;
+        assert!(multisig_txs.is_empty());
+        let tx = store.get_transaction_by_digest(&digest)?;
+        assert_eq!(tx.signature_schemes, vec![Some("ed25519".to_string())]);
+        assert_eq!(tx.multisig_threshold, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_inputs() -> Result<(), anyhow::Error> {
+        let (test_cluster, indexer_rpc_client, store, _handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+        let address = test_cluster.get_address_0();
+        let coins = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(address, None, None, None)
+            .await?
+            .data;
+
+        // pay::split_vec(coin, amounts, ctx)
+        let transaction_bytes: TransactionBytes = indexer_rpc_client
+            .split_coin(
+                address,
+                coins[0].coin_object_id,
+                vec![1000, 2000],
+                Some(coins[1].coin_object_id),
+                2000,
+            )
+            .await?;
+        let keystore_path = test_cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+        let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+        let tx =
+            to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(&address)?);
+        let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+        let tx_response = indexer_rpc_client
+            .execute_transaction(
+                tx_bytes,
+                signatures,
+                Some(SuiTransactionResponseOptions::new()),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?;
+        let digest = tx_response.digest.base58_encode();
+        wait_until_transaction_synced(&store, &digest).await;
+
+        let inputs = store.get_transaction_inputs(&digest)?;
+        assert_eq!(inputs.len(), 2);
+        let (coin, amounts) = (&inputs[0], &inputs[1]);
+        assert_eq!(coin.argument_index, 0);
+        assert!(coin.is_decoded);
+        assert_eq!(coin.value, Some(serde_json::json!(coins[0].coin_object_id)));
+        assert_eq!(amounts.argument_index, 1);
+        assert_eq!(amounts.parameter_type.as_deref(), Some("vector<u64>"));
+        assert!(amounts.is_decoded);
+        assert_eq!(amounts.value, Some(serde_json::json!(["1000", "2000"])));
+        assert_eq!(amounts.raw_value, bcs::to_bytes(&vec![1000u64, 2000])?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ptb_structure() -> Result<(), anyhow::Error> {
+        let (test_cluster, indexer_rpc_client, store, _handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+        let address = test_cluster.get_address_0();
+        let coins = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(address, None, None, None)
+            .await?
+            .data;
+
+        // Split an amount off the coin and transfer the new coin
+        let transaction_bytes: TransactionBytes = indexer_rpc_client
+            .pay(
+                address,
+                vec![coins[0].coin_object_id],
+                vec![test_cluster.get_address_1()],
+                vec![BigInt::from(1000)],
+                Some(coins[1].coin_object_id),
+                2000,
+            )
+            .await?;
+        let keystore_path = test_cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+        let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+        let tx =
+            to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(&address)?);
+        let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+        let tx_response = indexer_rpc_client
+            .execute_transaction(
+                tx_bytes,
+                signatures,
+                Some(SuiTransactionResponseOptions::new()),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?;
+        let digest = tx_response.digest.base58_encode();
+        wait_until_transaction_synced(&store, &digest).await;
+
+        let commands = store.get_ptb_structure(&digest)?;
+        assert_eq!(commands.len(), 2);
+        let (split, transfer) = (&commands[0], &commands[1]);
+        assert_eq!(split.command_index, 0);
+        assert_eq!(split.command_kind, "SplitCoin");
+        assert_eq!(
+            split.arguments,
+            serde_json::json!([{ "Input": 0 }, { "Input": 2 }])
+        );
+        assert!(split.input_commands.is_empty());
+        assert_eq!(transfer.command_index, 1);
+        assert_eq!(transfer.command_kind, "TransferObjects");
+        assert_eq!(
+            transfer.arguments,
+            serde_json::json!([{ "Result": 0 }, { "Input": 1 }])
+        );
+        assert_eq!(transfer.input_commands, vec![Some(0)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_history() -> Result<(), anyhow::Error> {
+        let (test_cluster, indexer_rpc_client, store, _handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+        let address = test_cluster.get_address_0();
+        let coins = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(address, None, None, None)
+            .await?
+            .data;
+        let coin = &coins[0];
+
+        let transaction_bytes: TransactionBytes = indexer_rpc_client
+            .split_coin(
+                address,
+                coin.coin_object_id,
+                vec![1000],
+                Some(coins[1].coin_object_id),
+                2000,
+            )
+            .await?;
+        let keystore_path = test_cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+        let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+        let tx =
+            to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(&address)?);
+        let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+        let tx_response = indexer_rpc_client
+            .execute_transaction(
+                tx_bytes,
+                signatures,
+                Some(SuiTransactionResponseOptions::new()),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?;
+        wait_until_transaction_synced(&store, &tx_response.digest.base58_encode()).await;
+
+        // The split coin keeps its genesis version next to the one the split produced.
+        let (versions, next_cursor) = store.get_object_versions(coin.coin_object_id, None, 10)?;
+        assert!(next_cursor.is_none());
+        let versions: Vec<_> = versions.iter().map(|o| o.version as u64).collect();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0], coin.version.value());
+        assert!(versions[1] > versions[0]);
+
+        let (first_page, next_cursor) = store.get_object_versions(coin.coin_object_id, None, 1)?;
+        assert_eq!(first_page.len(), 1);
+        let (second_page, _) = store.get_object_versions(coin.coin_object_id, next_cursor, 1)?;
+        assert_eq!(second_page[0].version as u64, versions[1]);
+
+        let past_coin = store
+            .get_object_at_version(coin.coin_object_id, coin.version)?
+            .into_object()?;
+        assert_eq!(past_coin.version(), coin.version);
+        assert_eq!(GasCoin::try_from(&past_coin)?.value(), coin.balance);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_past_versions_served_by_default() -> Result<(), anyhow::Error> {
+        // Neither the config nor the store turn object history on explicitly.
+        assert!(IndexerConfig::default().index_object_history);
+        let (test_cluster, indexer_rpc_client, store, _handle) =
+            start_test_cluster_with_store(|store| store).await;
+        wait_until_next_checkpoint(&store).await;
+        let (sender, recipient) = (test_cluster.get_address_0(), test_cluster.get_address_1());
+        let coins = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(sender, None, None, None)
+            .await?
+            .data;
+        let coin = &coins[0];
+
+        let transaction_bytes: TransactionBytes = indexer_rpc_client
+            .transfer_object(
+                sender,
+                coin.coin_object_id,
+                Some(coins[1].coin_object_id),
+                2000,
+                recipient,
+            )
+            .await?;
+        let keystore_path = test_cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+        let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+        let tx =
+            to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(&sender)?);
+        let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+        let tx_response = indexer_rpc_client
+            .execute_transaction(
+                tx_bytes,
+                signatures,
+                Some(SuiTransactionResponseOptions::new()),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?;
+        wait_until_transaction_synced(&store, &tx_response.digest.base58_encode()).await;
+
+        let past_coin = store
+            .get_object(
+                coin.coin_object_id,
+                Some(coin.version),
+                ConsistencyLevel::Latest,
+            )?
+            .into_object()?;
+        assert_eq!(past_coin.version(), coin.version);
+        assert_eq!(past_coin.owner, Owner::AddressOwner(sender));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reads_at_checkpoint()-> Result<(), anyhow::Error> {
+        let (test_cluster, indexer_rpc_client, store, _handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+        let (sender, recipient) = (test_cluster.get_address_0(), test_cluster.get_address_1());
+        let coins = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(sender, None, None, None)
+            .await?
+            .data;
+        let coin = &coins[0];
+        let before = store.get_latest_checkpoint_sequence_number()? as u64;
+
+        let transaction_bytes: TransactionBytes = indexer_rpc_client
+            .transfer_object(
+                sender,
+                coin.coin_object_id,
+                Some(coins[1].coin_object_id),
+                2000,
+                recipient,
+            )
+            .await?;
+        let keystore_path = test_cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+        let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+        let tx =
+            to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(&sender)?);
+        let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+        let tx_response = indexer_rpc_client
+            .execute_transaction(
+                tx_bytes,
+                signatures,
+                Some(SuiTransactionResponseOptions::new()),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?;
+        wait_until_transaction_synced(&store, &tx_response.digest.base58_encode()).await;
+
+        let owned_ids = |owner, consistency| -> Result<Vec<ObjectID>, anyhow::Error> {
+            let (owned, _) = store.get_owned_objects(owner, None, None, usize::MAX, consistency)?;
+            owned
+                .into_iter()
+                .map(|o| Ok::<_, anyhow::Error>(o.into_object()?.id()))
+                .collect()
+        };
+        // The latest reads see the transfer, the reads at the checkpoint before it do not.
+        let latest = ConsistencyLevel::Latest;
+        let at_before = ConsistencyLevel::AtCheckpoint(before);
+        assert!(owned_ids(recipient, latest)?.contains(&coin.coin_object_id));
+        assert!(!owned_ids(sender, latest)?.contains(&coin.coin_object_id));
+        assert!(owned_ids(sender, at_before)?.contains(&coin.coin_object_id));
+        assert!(!owned_ids(recipient, at_before)?.contains(&coin.coin_object_id));
+
+        let past_coin = store
+            .get_object(coin.coin_object_id, None, at_before)?
+            .into_object()?;
+        assert_eq!(past_coin.version(), coin.version);
+        let latest_coin = store
+            .get_object(coin.coin_object_id, None, latest)?
+            .into_object()?;
+        assert!(latest_coin.version() > coin.version);
+        // The version written after the checkpoint does not exist as of the checkpoint.
+        assert!(matches!(
+            store.get_object(coin.coin_object_id, Some(latest_coin.version()), at_before)?,
+            ObjectRead::NotExists(_)
+        ));
+
+        let latest_committed = store.get_latest_checkpoint_sequence_number()? as u64;
+        assert!(matches!(
+            store.get_object(
+                coin.coin_object_id,
+                None,
+                ConsistencyLevel::AtCheckpoint(latest_committed + 1000)
+            ),
+            Err(IndexerError::CheckpointNotCommitted(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_plain_insert_tables() -> Result<(), anyhow::Error> {
+        let (test_cluster, indexer_rpc_client, store, _handle) =
+            start_test_cluster_with_store(|store| {
+                store.with_plain_insert_tables(
+                    AppendOnlyTable::value_variants().iter().copied().collect(),
+                )
+            })
+            .await;
+        wait_until_next_checkpoint(&store).await;
+        let (sender, recipient) = (test_cluster.get_address_0(), test_cluster.get_address_1());
+        let coins = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(sender, None, None, None)
+            .await?
+            .data;
+
+        let transaction_bytes: TransactionBytes = indexer_rpc_client
+            .transfer_object(
+                sender,
+                coins[0].coin_object_id,
+                Some(coins[1].coin_object_id),
+                2000,
+                recipient,
+            )
+            .await?;
+        let keystore_path = test_cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+        let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+        let tx =
+            to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(&sender)?);
+        let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+        let tx_response = indexer_rpc_client
+            .execute_transaction(
+                tx_bytes,
+                signatures,
+                Some(SuiTransactionResponseOptions::new()),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?;
+        let digest = tx_response.digest.base58_encode();
+        wait_until_transaction_synced(&store, &digest).await;
+
+        // Every checkpoint is written once, so the plain inserts index it as upserts would.
+        let (object_txs, _) = store.get_transactions_for_object(coins[0].coin_object_id, None, 10)?;
+        assert!(object_txs.contains(&digest));
+        let coin = store
+            .get_object(coins[0].coin_object_id, None, ConsistencyLevel::Latest)?
+            .into_object()?;
+        assert_eq!(coin.owner, Owner::AddressOwner(recipient));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bulk_copy() {
+        use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+        use sui_indexer::models::events::Event;
+        use sui_indexer::models::transactions::Transaction;
+        use sui_indexer::schema::{events, transactions};
+
+        let shutdown = ShutdownSignal::default();
+        let (_test_cluster, _, store, handle) = start_test_cluster_with_shutdown(
+            |store| store.with_bulk_copy(&database_url()),
+            shutdown.clone(),
+        )
+        .await;
+        wait_until_next_checkpoint(&store).await;
+        shutdown.request();
+        handle.await.unwrap().unwrap();
+
+        let mut conn = new_pg_connection_pool(&database_url())
+            .await
+            .unwrap()
+            .get()
+            .unwrap();
+        let mut genesis_rows = || {
+            let transactions: Vec<Transaction> = transactions::table
+                .filter(transactions::checkpoint_sequence_number.eq(0))
+                .order(transactions::transaction_digest)
+                .load(&mut conn)
+                .unwrap();
+            let events: Vec<Event> = events::table
+                .filter(events::checkpoint_sequence_number.eq(0))
+                .order((events::transaction_digest, events::event_sequence))
+                .load(&mut conn)
+                .unwrap();
+            (transactions, events)
+        };
+        let (transactions, events) = genesis_rows();
+        assert!(!transactions.is_empty());
+        assert!(!events.is_empty());
+
+        let data = TemporaryCheckpointStore {
+            checkpoint: store.get_checkpoint(CheckpointId::SequenceNumber(0)).unwrap(),
+            transactions: transactions.clone(),
+            raw_transactions: vec![],
+            events: events.clone(),
+            objects_changes: vec![],
+            addresses: vec![],
+            packages: vec![],
+            move_calls: vec![],
+            object_transactions: vec![],
+            transaction_inputs: vec![],
+            ptb_commands: vec![],
+            recipients: vec![],
+            epoch: None,
+            coin_supplies: vec![],
+        };
+        // Copying a checkpoint again replaces its rows instead of failing on them
+        store.persist_checkpoint_bulk(&data).unwrap();
+        store.persist_checkpoint_bulk(&data).unwrap();
+
+        let (copied_transactions, copied_events) = genesis_rows();
+        assert_eq!(copied_transactions.len(), transactions.len());
+        for (copied, transaction) in copied_transactions.iter().zip(&transactions) {
+            assert_eq!(copied.transaction_digest, transaction.transaction_digest);
+            assert_eq!(copied.recipients, transaction.recipients);
+            assert_eq!(copied.transaction_content, transaction.transaction_content);
+            assert_eq!(copied.signature_schemes, transaction.signature_schemes);
+            assert_eq!(copied.multisig_schemes, transaction.multisig_schemes);
+        }
+        assert_eq!(copied_events.len(), events.len());
+        for (copied, event) in copied_events.iter().zip(&events) {
+            assert_eq!(copied.transaction_digest, event.transaction_digest);
+            assert_eq!(copied.parsed_json, event.parsed_json);
+            assert_eq!(copied.event_bcs, event.event_bcs);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transactions_for_object() -> Result<(), anyhow::Error> {
+        let (test_cluster, indexer_rpc_client, store, _handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+        let address = test_cluster.get_address_0();
+        let coins = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(address, None, None, None)
+            .await?
+            .data;
+        let coin = &coins[0];
+
+        let transaction_bytes: TransactionBytes = indexer_rpc_client
+            .split_coin(
+                address,
+                coin.coin_object_id,
+                vec![1000],
+                Some(coins[1].coin_object_id),
+                2000,
+            )
+            .await?;
+        let keystore_path = test_cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME);
+        let keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
+        let tx =
+            to_sender_signed_transaction(transaction_bytes.to_data()?, keystore.get_key(&address)?);
+        let (tx_bytes, signatures) = tx.to_tx_bytes_and_signatures();
+        let tx_response = indexer_rpc_client
+            .execute_transaction(
+                tx_bytes,
+                signatures,
+                Some(SuiTransactionResponseOptions::new()),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?;
+        let digest = tx_response.digest.base58_encode();
+        wait_until_transaction_synced(&store, &digest).await;
+
+        // The genesis transaction created the coin, the split mutated it.
+        let (digests, next_cursor) =
+            store.get_transactions_for_object(coin.coin_object_id, None, 10)?;
+        assert!(next_cursor.is_none());
+        assert_eq!(
+            digests,
+            vec![coin.previous_transaction.base58_encode(), digest.clone()]
+        );
+
+        let (first_page, next_cursor) =
+            store.get_transactions_for_object(coin.coin_object_id, None, 1)?;
+        assert_eq!(first_page, digests[..1]);
+        assert!(next_cursor.is_some());
+        let (second_page, next_cursor) =
+            store.get_transactions_for_object(coin.coin_object_id, next_cursor, 1)?;
+        assert_eq!(second_page, vec![digest]);
+        assert!(next_cursor.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_ancestry() -> Result<(), anyhow::Error> {
+        let (test_cluster, _, store, _handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+        let address = test_cluster.get_address_0();
+        let coins = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(address, None, None, None)
+            .await?
+            .data;
+        let coin = &coins[0];
+
+        // An address owned coin is the root of its own chain.
+        let ancestry = store.get_object_ancestry(coin.coin_object_id)?;
+        assert_eq!(ancestry.len(), 1);
+        let ObjectRead::Exists(object_ref, object, _) = &ancestry[0] else {
+            panic!("Expected the coin to exist");
+        };
+        assert_eq!(object_ref.0, coin.coin_object_id);
+        assert_eq!(object.owner, Owner::AddressOwner(address));
+
+        // An object that is not indexed has no chain.
+        assert!(store.get_object_ancestry(ObjectID::random())?.is_empty());
         Ok(())
     }
 
@@ -182,7 +735,11 @@ mod pg_integration {
         wait_until_next_checkpoint(&store).await;
 
         let coin_object = store
-            .get_object(coins[0].coin_object_id, Some(coins[0].version))
+            .get_object(
+                coins[0].coin_object_id,
+                Some(coins[0].version),
+                ConsistencyLevel::Latest,
+            )
             .unwrap()
             .into_object()
             .unwrap();
@@ -205,33 +762,677 @@ mod pg_integration {
         drop(handle);
     }
 
-    async fn start_test_cluster() -> (
-        TestCluster,
-        HttpClient,
-        PgIndexerStore,
-        JoinHandle<Result<(), IndexerError>>,
-    ) {
-        let pg_host = env::var("POSTGRES_HOST").unwrap_or_else(|_| "localhost".into());
-        let pg_port = env::var("POSTGRES_PORT").unwrap_or_else(|_| "32771".into());
-        let pw = env::var("POSTGRES_PASSWORD").unwrap_or_else(|_| "postgrespw".into());
-        let db_url = format!("postgres://postgres:{pw}@{pg_host}:{pg_port}");
-        let pg_connection_pool = new_pg_connection_pool(&db_url).await.unwrap();
-
+    #[tokio::test]
+    async fn test_last_modifying_transaction() {
+        let (test_cluster, _, store, handle) = start_test_cluster().await;
+        let coin = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(test_cluster.get_address_0(), None, None, None)
+            .await
+            .unwrap()
+            .data[0]
+            .clone();
+        wait_until_next_checkpoint(&store).await;
+
+        let last = store
+            .get_last_modifying_transaction(coin.coin_object_id)
+            .unwrap();
+        assert_eq!(
+            last.transaction_digest,
+            coin.previous_transaction.base58_encode()
+        );
+        assert_eq!(last.version, coin.version.value() as i64);
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_object_inclusion_proof() {
+        let (test_cluster, _, store, handle) = start_test_cluster().await;
+        let coin = test_cluster
+            .sui_client()
+            .coin_read_api()
+            .get_coins(test_cluster.get_address_0(), None, None, None)
+            .await
+            .unwrap()
+            .data[0]
+            .clone();
+        wait_until_next_checkpoint(&store).await;
+
+        // The proof of the checkpoint that wrote the coin verifies against the genesis committee
+        let checkpoint = store
+            .get_last_modifying_transaction(coin.coin_object_id)
+            .unwrap()
+            .checkpoint as u64;
+        let proof = store
+            .get_object_inclusion_proof(coin.coin_object_id, checkpoint)
+            .unwrap();
+        let committee = test_cluster.swarm.config().committee();
+        assert_eq!(proof.verify(&committee).unwrap(), coin.object_ref());
+
+        // A tampered object no longer matches the effects
+        let mut tampered = proof.clone();
+        tampered.object.previous_transaction = TransactionDigest::random();
+        assert!(matches!(
+            tampered.verify(&committee),
+            Err(IndexerError::ContentDigestMismatch(_))
+        ));
+
+        // The coin is not written by a later checkpoint
+        let latest = store.get_latest_checkpoint_sequence_number().unwrap() as u64;
+        if latest > checkpoint {
+            assert!(matches!(
+                store.get_object_inclusion_proof(coin.coin_object_id, latest),
+                Err(IndexerError::InvalidArgumentError(_))
+            ));
+        }
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_gas_totals() {
+        let (_test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        let checkpoint = store.get_checkpoint(0.into()).unwrap();
+        let mut expected = GasCostSummary::default();
+        for tx in checkpoint.transactions {
+            let gas = store.get_gas_breakdown(&tx.unwrap()).unwrap();
+            expected.computation_cost += gas.computation_cost;
+            expected.storage_cost += gas.storage_cost;
+            expected.storage_rebate += gas.storage_rebate;
+        }
+        assert_eq!(store.get_gas_totals(0, 0).unwrap(), expected);
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_summary() {
+        let (test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        // The stored summary and signature verify against the genesis committee.
+        let committee = test_cluster.swarm.config().committee();
+        let summary = store.get_checkpoint_summary(0).unwrap();
+        summary.verify_with_contents(&committee, None).unwrap();
+        let checkpoint = store.get_checkpoint(0.into()).unwrap();
+        assert_eq!(
+            summary.digest().base58_encode(),
+            checkpoint.checkpoint_digest
+        );
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_transaction_digests() {
+        let (test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        let checkpoint = test_cluster
+            .rpc_client()
+            .get_checkpoint(0.into())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_checkpoint_transaction_digests(0).unwrap(),
+            checkpoint.transactions
+        );
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_transactions_by_time() {
+        let (_test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        // Transactions are stamped with the timestamp of their checkpoint.
+        let checkpoint = store.get_checkpoint(0.into()).unwrap();
+        let digests: Vec<String> = store
+            .get_checkpoint_transaction_digests(0)
+            .unwrap()
+            .iter()
+            .map(|digest| digest.base58_encode())
+            .collect();
+        for digest in &digests {
+            let transaction = store.get_transaction_by_digest(digest).unwrap();
+            assert_eq!(transaction.timestamp_ms, checkpoint.timestamp_ms);
+        }
+
+        // Page one transaction at a time through the window of the checkpoint, each transaction
+        // sharing its timestamp is read exactly once.
+        let (start_ms, end_ms) = (checkpoint.timestamp_ms, checkpoint.timestamp_ms + 1);
+        let mut read = vec![];
+        let mut cursor = None;
+        loop {
+            let (page, next_cursor) = store
+                .query_transactions_by_time(start_ms, end_ms, cursor, 1)
+                .unwrap();
+            read.extend(page);
+            cursor = next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        let (all, _) = store
+            .query_transactions_by_time(start_ms, end_ms, None, read.len() + 1)
+            .unwrap();
+        assert_eq!(read, all);
+        assert!(digests.iter().all(|digest| read.contains(digest)));
+
+        // The end of the window is exclusive.
+        let (empty, cursor) = store
+            .query_transactions_by_time(start_ms, start_ms, None, 10)
+            .unwrap();
+        assert!(empty.is_empty());
+        assert!(cursor.is_none());
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_latest_checkpoint_in_range() {
+        let (_test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        assert_eq!(
+            store
+                .get_latest_checkpoint_sequence_number_in_range(0, 0)
+                .unwrap(),
+            Some(0)
+        );
+        let latest = store.get_latest_checkpoint_sequence_number().unwrap() as u64;
+        assert_eq!(
+            store
+                .get_latest_checkpoint_sequence_number_in_range(latest + 1000, latest + 2000)
+                .unwrap(),
+            None
+        );
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_lagging_handlers() {
+        let (_test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+        assert!(store.get_lagging_handlers().unwrap().is_empty());
+
+        store
+            .mark_handler_lagging(IndexerHandler::Events, 0)
+            .unwrap();
+        assert_eq!(
+            store.get_lagging_handlers().unwrap(),
+            BTreeMap::from([(IndexerHandler::Events, 0)])
+        );
+
+        // Catching up advances the handler, but does not write the checkpoint
+        let latest = store.get_latest_checkpoint_sequence_number().unwrap();
+        let mut checkpoint = store
+            .get_checkpoint(CheckpointId::SequenceNumber(latest as u64))
+            .unwrap();
+        checkpoint.sequence_number = latest + 1000;
+        let data = TemporaryCheckpointStore {
+            checkpoint,
+            transactions: vec![],
+            raw_transactions: vec![],
+            events: vec![],
+            objects_changes: vec![],
+            addresses: vec![],
+            packages: vec![],
+            move_calls: vec![],
+            object_transactions: vec![],
+            transaction_inputs: vec![],
+            ptb_commands: vec![],
+            recipients: vec![],
+            epoch: None,
+            coin_supplies: vec![],
+        };
+        store
+            .persist_handler_checkpoint(IndexerHandler::Events, &data)
+            .unwrap();
+        assert_eq!(
+            store.get_lagging_handlers().unwrap(),
+            BTreeMap::from([(IndexerHandler::Events, latest + 1000)])
+        );
+        assert!(store
+            .get_checkpoint(CheckpointId::SequenceNumber(latest as u64 + 1000))
+            .is_err());
+
+        store
+            .mark_handler_caught_up(IndexerHandler::Events)
+            .unwrap();
+        assert!(store.get_lagging_handlers().unwrap().is_empty());
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_reads_at_checkpoint_with_lagging_objects_handler() {
+        let shutdown = ShutdownSignal::default();
+        let (test_cluster, _, store, handle) =
+            start_test_cluster_with_shutdown(|store| store, shutdown.clone()).await;
+        wait_until_next_checkpoint(&store).await;
+        shutdown.request();
+        handle.await.unwrap().unwrap();
+
+        let address = test_cluster.get_address_0();
+        let latest = store.get_latest_checkpoint_sequence_number().unwrap();
+        let at_latest = ConsistencyLevel::AtCheckpoint(latest as u64);
+        assert!(store
+            .get_owned_objects(address, None, None, usize::MAX, at_latest)
+            .is_ok());
+
+        // The objects of the checkpoints the objects handler did not commit yet are not read,
+        // even though the other handlers committed them
+        store
+            .mark_handler_lagging(IndexerHandler::Objects, latest - 1)
+            .unwrap();
+        assert!(matches!(
+            store.get_owned_objects(address, None, None, usize::MAX, at_latest),
+            Err(IndexerError::CheckpointNotCommitted(_))
+        ));
+        assert!(matches!(
+            store.get_object(ObjectID::ZERO, None, at_latest),
+            Err(IndexerError::CheckpointNotCommitted(_))
+        ));
+
+        store
+            .mark_handler_caught_up(IndexerHandler::Objects)
+            .unwrap();
+        assert!(store
+            .get_owned_objects(address, None, None, usize::MAX, at_latest)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_stream_lock() {
+        let (_test_cluster, _, store, handle) = start_test_cluster().await;
+
+        let lock = store.try_lock_stream("test-deployment").unwrap();
+        assert!(lock.is_some());
+        // Held by another session, the pool hands out another connection
+        assert!(store.try_lock_stream("test-deployment").unwrap().is_none());
+        assert!(store
+            .try_lock_stream("test-deployment/0-99")
+            .unwrap()
+            .is_some());
+
+        drop(lock);
+        assert!(store.try_lock_stream("test-deployment").unwrap().is_some());
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drain() {
+        let shutdown = ShutdownSignal::default();
+        let (test_cluster, _, store, handle) =
+            start_test_cluster_with_shutdown(|store| store, shutdown.clone()).await;
+        wait_until_next_checkpoint(&store).await;
+
+        shutdown.request();
+        // Well within the default drain timeout of 30 secs
+        tokio::time::timeout(Duration::from_secs(60), handle)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let latest = store.get_latest_checkpoint_sequence_number().unwrap();
+        // The cluster keeps making checkpoints, none of which is fetched anymore
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(store.get_latest_checkpoint_sequence_number().unwrap(), latest);
+
+        // The last checkpoint was committed whole
+        let checkpoint = test_cluster
+            .rpc_client()
+            .get_checkpoint((latest as u64).into())
+            .await
+            .unwrap();
+        assert_eq!(
+            store
+                .get_checkpoint_transaction_digests(latest as u64)
+                .unwrap(),
+            checkpoint.transactions
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chain_identifier() {
+        let (test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        // Recorded by the indexer on startup, from the genesis checkpoint of the cluster
+        let genesis = test_cluster
+            .rpc_client()
+            .get_checkpoint(CheckpointId::SequenceNumber(0))
+            .await
+            .unwrap();
+        let chain_id = Hex::encode(&genesis.digest.inner()[..4]);
+        assert_eq!(store.persist_chain_identifier(&chain_id).unwrap(), chain_id);
+        // Not replaced by another chain
+        assert_eq!(store.persist_chain_identifier("00000000").unwrap(), chain_id);
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_reference_gas_price() {
+        let (test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        // Epoch 0 is indexed from the genesis checkpoint.
+        let reference_gas_price = test_cluster
+            .rpc_client()
+            .get_reference_gas_price()
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_reference_gas_price().unwrap(),
+            reference_gas_price
+        );
+        assert_eq!(
+            store.get_reference_gas_price_at_epoch(0).unwrap(),
+            reference_gas_price
+        );
+        assert!(matches!(
+            store.get_reference_gas_price_at_epoch(1),
+            Err(IndexerError::EpochNotIndexed(_))
+        ));
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_total_supply_not_indexed() {
+        let (_test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        // The SUI treasury cap is turned into a bare supply at genesis, so it is never observed.
+        assert!(matches!(
+            store.get_total_supply("0x2::sui::SUI"),
+            Err(IndexerError::TreasuryCapNotIndexed(_))
+        ));
+        assert!(matches!(
+            store.get_total_supply("not a type"),
+            Err(IndexerError::InvalidArgumentError(_))
+        ));
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_handler_reads() {
+        let (_test_cluster, _indexer_rpc_client, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        let store = store.with_enabled_handlers(BTreeSet::from([IndexerHandler::Transactions]));
+        let checkpoint = store.get_checkpoint(0.into()).unwrap();
+        let tx = checkpoint.transactions[0].clone().unwrap();
+        assert!(store.get_transaction_by_digest(&tx).is_ok());
+        assert!(matches!(
+            store.get_object(ObjectID::ZERO, None, ConsistencyLevel::Latest),
+            Err(IndexerError::HandlerDisabled(_))
+        ));
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_get_owned_objects_by_type() {
+        let (test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+        let address = test_cluster.get_address_0();
+
+        let (gas_coins, cursor) = store
+            .get_owned_objects(
+                address,
+                Some(GasCoin::type_()),
+                None,
+                usize::MAX,
+                ConsistencyLevel::Latest,
+            )
+            .unwrap();
+        assert!(cursor.is_none());
+        assert!(!gas_coins.is_empty());
+        let gas_coins: Vec<_> = gas_coins
+            .into_iter()
+            .map(|o| o.into_object().unwrap())
+            .collect();
+        for coin in &gas_coins {
+            assert_eq!(coin.owner, Owner::AddressOwner(address));
+        }
+
+        // Paging one object at a time returns the same objects, in the same order.
+        let mut paged = vec![];
+        let mut cursor = None;
+        loop {
+            let (page, next_cursor) = store
+                .get_owned_objects(
+                    address,
+                    Some(GasCoin::type_()),
+                    cursor,
+                    1,
+                    ConsistencyLevel::Latest,
+                )
+                .unwrap();
+            assert_eq!(page.len(), 1);
+            paged.extend(page.into_iter().map(|o| o.into_object().unwrap().id()));
+            cursor = next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        let ids: Vec<_> = gas_coins.iter().map(|o| o.id()).collect();
+        assert_eq!(ids, paged);
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_get_shared_objects() {
+        let (_test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        // Genesis shares the system state and the clock.
+        let (clocks, cursor) = store
+            .get_shared_objects(Some(Clock::type_()), None, usize::MAX)
+            .unwrap();
+        assert!(cursor.is_none());
+        let clocks: Vec<_> = clocks
+            .into_iter()
+            .map(|o| o.into_object().unwrap())
+            .collect();
+        assert_eq!(clocks.len(), 1);
+        assert_eq!(clocks[0].id(), SUI_CLOCK_OBJECT_ID);
+        assert_eq!(
+            clocks[0].owner,
+            Owner::Shared {
+                initial_shared_version: SUI_CLOCK_OBJECT_SHARED_VERSION
+            }
+        );
+        assert!(clocks[0].version() >= SUI_CLOCK_OBJECT_SHARED_VERSION);
+
+        let (shared, _) = store.get_shared_objects(None, None, usize::MAX).unwrap();
+        let ids: Vec<_> = shared
+            .into_iter()
+            .map(|o| o.into_object().unwrap().id())
+            .collect();
+        assert!(ids.contains(&SUI_SYSTEM_STATE_OBJECT_ID));
+        assert!(ids.contains(&SUI_CLOCK_OBJECT_ID));
+
+        // Paging one object at a time returns the same objects, in the same order.
+        let mut paged = vec![];
+        let mut cursor = None;
+        loop {
+            let (page, next_cursor) = store.get_shared_objects(None, cursor, 1).unwrap();
+            assert_eq!(page.len(), 1);
+            paged.extend(page.into_iter().map(|o| o.into_object().unwrap().id()));
+            cursor = next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(ids, paged);
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_scan_objects_by_type() {
+        let (test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+        let address = test_cluster.get_address_0();
+
+        let batches: Vec<_> = store
+            .scan_objects_by_type(GasCoin::type_(), 2)
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(batches.iter().all(|batch| batch.len() <= 2));
+        let objects: Vec<_> = batches.into_iter().flatten().collect();
+        assert!(objects
+            .iter()
+            .all(|o| o.object_type == GasCoin::type_().to_string()));
+        // Batches follow each other in object id order, without overlapping.
+        assert!(objects
+            .windows(2)
+            .all(|pair| pair[0].object_id < pair[1].object_id));
+
+        // Gas coins of every address are scanned.
+        let (owned, _) = store
+            .get_owned_objects(
+                address,
+                Some(GasCoin::type_()),
+                None,
+                usize::MAX,
+                ConsistencyLevel::Latest,
+            )
+            .unwrap();
+        for coin in owned {
+            let id = coin.into_object().unwrap().id().to_string();
+            assert!(objects.iter().any(|o| o.object_id == id));
+        }
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_object_types_resolved() {
+        let (_test_cluster, _, store, handle) = start_test_cluster().await;
+        wait_until_next_checkpoint(&store).await;
+
+        // Framework types always resolve, so no gas coin is flagged for reprocessing.
+        let coins: Vec<_> = store
+            .scan_objects_by_type(GasCoin::type_(), 100)
+            .try_collect()
+            .await
+            .unwrap();
+        let coins: Vec<_> = coins.into_iter().flatten().collect();
+        assert!(!coins.is_empty());
+        assert!(coins.iter().all(|o| !o.type_unresolved));
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_orphaned_dynamic_fields() {
+        use diesel::RunQueryDsl;
+
+        let shutdown = ShutdownSignal::default();
+        let (_test_cluster, _, store, handle) =
+            start_test_cluster_with_shutdown(|store| store, shutdown.clone()).await;
+        wait_until_next_checkpoint(&store).await;
+        // Stop indexing, so that the row deleted below is not written again
+        shutdown.request();
+        tokio::time::timeout(Duration::from_secs(60), handle)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        // The inner state of the system state wrapper is a dynamic field of it, and the wrapper
+        // is indexed.
+        let system_state = SUI_SYSTEM_STATE_OBJECT_ID.to_string();
+        let owned_by_system_state =
+            |owner: &Option<String>| owner.as_deref() == Some(system_state.as_str());
+        let orphans = store.find_orphaned_dynamic_fields(1000).unwrap();
+        assert!(!orphans
+            .iter()
+            .any(|o| owned_by_system_state(&o.owner_address)));
+
+        // Orphan it by dropping the row of the wrapper
+        let mut conn = new_pg_connection_pool(&database_url())
+            .await
+            .unwrap()
+            .get()
+            .unwrap();
+        diesel::sql_query("DELETE FROM objects WHERE object_id = $1")
+            .bind::<diesel::sql_types::Text, _>(&system_state)
+            .execute(&mut conn)
+            .unwrap();
+
+        let orphans = store.find_orphaned_dynamic_fields(1000).unwrap();
+        assert_eq!(
+            orphans
+                .iter()
+                .filter(|o| owned_by_system_state(&o.owner_address))
+                .count(),
+            1
+        );
+        assert!(orphans
+            .iter()
+            .all(|o| o.object_type.starts_with("0x2::dynamic_field::Field<")));
+        assert!(orphans
+            .windows(2)
+            .all(|pair| pair[0].object_id < pair[1].object_id));
+        // None of their parents are indexed
+        let parents: Vec<String> = orphans
+            .iter()
+            .map(|o| o.owner_address.clone().unwrap())
+            .collect();
+        assert!(store.get_indexed_object_ids(&parents).unwrap().is_empty());
+
+        let first = store.find_orphaned_dynamic_fields(1).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].object_id, orphans[0].object_id);
+    }
+
+    async fn start_test_cluster() -> (
+        TestCluster,
+        HttpClient,
+        PgIndexerStore,
+        JoinHandle<Result<(), IndexerError>>,
+    ) {
+        start_test_cluster_with_store(|store| store).await
+    }
+
+    /// Start the cluster and an indexer writing to the store returned by `configure`
+    async fn start_test_cluster_with_store(
+        configure: impl FnOnce(PgIndexerStore) -> PgIndexerStore,
+    ) -> (
+        TestCluster,
+        HttpClient,
+        PgIndexerStore,
+        JoinHandle<Result<(), IndexerError>>,
+    ) {
+        start_test_cluster_with_shutdown(configure, ShutdownSignal::default()).await
+    }
+
+    async fn start_test_cluster_with_shutdown(
+        configure: impl FnOnce(PgIndexerStore) -> PgIndexerStore,
+        shutdown: ShutdownSignal,
+    ) -> (
+        TestCluster,
+        HttpClient,
+        PgIndexerStore,
+        JoinHandle<Result<(), IndexerError>>,
+    ) {
+        let pg_connection_pool = new_pg_connection_pool(&database_url()).await.unwrap();
+
         reset_database(&mut pg_connection_pool.get().unwrap());
 
         let test_cluster = TestClusterBuilder::new().build().await.unwrap();
-        let store = PgIndexerStore::new(pg_connection_pool);
+        let store = configure(PgIndexerStore::new(pg_connection_pool));
 
         let store_clone = store.clone();
         let registry = Registry::default();
 
         let mut config = IndexerConfig::default();
         config.rpc_client_url = test_cluster.rpc_url().to_string();
+        // every test also checks that the checkpoints of the cluster verify
+        config.verify_checkpoint_signatures = true;
         let indexer_config = config.clone();
-        let handle =
-            tokio::spawn(
-                async move { Indexer::start(&indexer_config, &registry, store_clone).await },
-            );
+        let handle = tokio::spawn(async move {
+            Indexer::start_with_shutdown(&indexer_config, &registry, store_clone, shutdown).await
+        });
 
         let http_addr_port = format!(
             "http://{}:{}",
@@ -242,6 +1443,13 @@ mod pg_integration {
         (test_cluster, http_client, store, handle)
     }
 
+    fn database_url() -> String {
+        let pg_host = env::var("POSTGRES_HOST").unwrap_or_else(|_| "localhost".into());
+        let pg_port = env::var("POSTGRES_PORT").unwrap_or_else(|_| "32771".into());
+        let pw = env::var("POSTGRES_PASSWORD").unwrap_or_else(|_| "postgrespw".into());
+        format!("postgres://postgres:{pw}@{pg_host}:{pg_port}")
+    }
+
     async fn wait_until_next_checkpoint(store: &PgIndexerStore) {
         let mut cp = store.get_latest_checkpoint_sequence_number().unwrap();
         let target = cp + 1;
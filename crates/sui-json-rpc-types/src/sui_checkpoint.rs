@@ -5,12 +5,12 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sui_types::base_types::TransactionDigest;
 use sui_types::committee::EpochId;
-use sui_types::digests::CheckpointDigest;
+use sui_types::crypto::SuiAuthorityStrongQuorumSignInfo;
+use sui_types::digests::{CheckpointContentsDigest, CheckpointDigest};
 use sui_types::gas::GasCostSummary;
-use sui_types::message_envelope::Message;
 use sui_types::messages_checkpoint::{
-    CheckpointCommitment, CheckpointContents, CheckpointSequenceNumber, CheckpointSummary,
-    CheckpointTimestamp, EndOfEpochData,
+    CertifiedCheckpointSummary, CheckpointCommitment, CheckpointContents, CheckpointSequenceNumber,
+    CheckpointSummary, CheckpointTimestamp, EndOfEpochData,
 };
 
 #[derive(Clone, Debug, JsonSchema, Serialize, Deserialize)]
@@ -22,6 +22,9 @@ pub struct Checkpoint {
     pub sequence_number: CheckpointSequenceNumber,
     /// Checkpoint digest
     pub digest: CheckpointDigest,
+    /// Digest of the checkpoint contents, committing to the transactions and effects
+    /// executed in this checkpoint as well as their user signatures
+    pub content_digest: CheckpointContentsDigest,
     /// Total number of transactions committed since genesis, including those in this
     /// checkpoint.
     pub network_total_transactions: u64,
@@ -41,18 +44,23 @@ pub struct Checkpoint {
 
     /// Commitments to checkpoint state
     pub checkpoint_commitments: Vec<CheckpointCommitment>,
+    /// Aggregated signature of the quorum of validators that certified the checkpoint
+    pub validator_signature: SuiAuthorityStrongQuorumSignInfo,
 }
 
-impl From<(CheckpointSummary, CheckpointContents)> for Checkpoint {
-    fn from((summary, contents): (CheckpointSummary, CheckpointContents)) -> Self {
-        let digest = summary.digest();
+impl From<(CertifiedCheckpointSummary, CheckpointContents)> for Checkpoint {
+    fn from((certified, contents): (CertifiedCheckpointSummary, CheckpointContents)) -> Self {
+        let digest = *certified.digest();
+        let (summary, validator_signature) = certified.into_data_and_sig();
         let CheckpointSummary {
             epoch,
             sequence_number,
             network_total_transactions,
+            content_digest,
             previous_digest,
             epoch_rolling_gas_cost_summary,
             timestamp_ms,
+            checkpoint_commitments,
             end_of_epoch_data,
             ..
         } = summary;
@@ -61,16 +69,15 @@ impl From<(CheckpointSummary, CheckpointContents)> for Checkpoint {
             epoch,
             sequence_number,
             digest,
+            content_digest,
             network_total_transactions,
             previous_digest,
             epoch_rolling_gas_cost_summary,
             timestamp_ms,
             end_of_epoch_data,
             transactions: contents.iter().map(|digest| digest.transaction).collect(),
-            // TODO: populate commitment for rpc clients. Most likely, rpc clients don't need this
-            // info (if they need it, they need to get signed BCS data anyway in order to trust
-            // it).
-            checkpoint_commitments: Default::default(),
+            checkpoint_commitments,
+            validator_signature: (&validator_signature).into(),
         }
     }
 }
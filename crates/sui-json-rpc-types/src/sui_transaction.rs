@@ -105,6 +105,10 @@ pub struct SuiTransactionResponseOptions {
     pub show_object_changes: bool,
     /// Whether to show balance_changes. Default to be False
     pub show_balance_changes: bool,
+    /// Whether to show bcs-encoded transaction input data. Default to be False
+    pub show_raw_input: bool,
+    /// Whether to show bcs-encoded transaction effects. Default to be False
+    pub show_raw_effects: bool,
 }
 
 impl SuiTransactionResponseOptions {
@@ -119,6 +123,8 @@ impl SuiTransactionResponseOptions {
             show_events: true,
             show_object_changes: true,
             show_balance_changes: true,
+            show_raw_input: false,
+            show_raw_effects: false,
         }
     }
 
@@ -147,6 +153,16 @@ impl SuiTransactionResponseOptions {
         self
     }
 
+    pub fn with_raw_input(mut self) -> Self {
+        self.show_raw_input = true;
+        self
+    }
+
+    pub fn with_raw_effects(mut self) -> Self {
+        self.show_raw_effects = true;
+        self
+    }
+
     /// default to return `WaitForEffectsCert` unless some options require
     /// local execution
     pub fn default_execution_request_type(&self) -> ExecuteTransactionRequestType {
@@ -164,6 +180,7 @@ impl SuiTransactionResponseOptions {
 
     pub fn require_effects(&self) -> bool {
         self.show_effects
+            || self.show_raw_effects
             || self.show_events
             || self.show_balance_changes
             || self.show_object_changes
@@ -174,6 +191,7 @@ impl SuiTransactionResponseOptions {
     }
 }
 
+#[serde_as]
 #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, Default)]
 #[serde(rename_all = "camelCase", rename = "TransactionResponse")]
 pub struct SuiTransactionResponse {
@@ -181,8 +199,19 @@ pub struct SuiTransactionResponse {
     /// Transaction input data
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transaction: Option<SuiTransaction>,
+    /// BCS encoded [SenderSignedData] that includes the signatures,
+    /// empty if `show_raw_input` is false
+    #[serde_as(as = "Base64")]
+    #[schemars(with = "Base64")]
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub raw_transaction: Vec<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub effects: Option<SuiTransactionEffects>,
+    /// BCS encoded [TransactionEffects], empty if `show_raw_effects` is false
+    #[serde_as(as = "Base64")]
+    #[schemars(with = "Base64")]
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub raw_effects: Vec<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub events: Option<SuiTransactionEvents>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -5,15 +5,20 @@ use std::env;
 use std::net::SocketAddr;
 use std::str::FromStr;
 
+use futures::stream;
+use hyper::body::HttpBody;
 use hyper::header::HeaderName;
 use hyper::header::HeaderValue;
-use hyper::Method;
+use hyper::http::Extensions;
+use hyper::{Body, HeaderMap, Method, StatusCode, Version};
 pub use jsonrpsee::server::ServerHandle;
 use jsonrpsee::server::{AllowHosts, ServerBuilder};
 use jsonrpsee::RpcModule;
 use prometheus::Registry;
 use tap::TapFallible;
+use tower_http::compression::{CompressionBody, CompressionLayer, DefaultPredicate, Predicate};
 use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::map_response_body::MapResponseBodyLayer;
 use tracing::{info, warn};
 
 pub use balance_changes::*;
@@ -143,7 +148,14 @@ impl JsonRpcServerBuilder {
         // We need to use the routing layer to block access to the old methods when routing is disabled.
         let routing_layer = RoutingLayer::new(routing, disable_routing);
 
+        // Responses are compressed when the request's `Accept-Encoding` allows it, which mostly
+        // pays off for large responses such as checkpoints read by indexers.
+        let compression =
+            CompressionLayer::new().compress_when(DefaultPredicate::new().and(is_success));
+
         let middleware = tower::ServiceBuilder::new()
+            .layer(MapResponseBodyLayer::new(into_hyper_body))
+            .layer(compression)
             .layer(cors)
             .layer(routing_layer);
 
@@ -166,6 +178,19 @@ impl JsonRpcServerBuilder {
     }
 }
 
+/// Leaves websocket upgrades and error responses uncompressed.
+fn is_success(status: StatusCode, _: Version, _: &HeaderMap, _: &Extensions) -> bool {
+    status.is_success()
+}
+
+/// jsonrpsee's server requires middleware to return a body whose error implements `Error`, which
+/// [CompressionBody] does not, so stream it back into a [Body].
+fn into_hyper_body(body: CompressionBody<Body>) -> Body {
+    Body::wrap_stream(stream::unfold(Box::pin(body), |mut body| async move {
+        body.data().await.map(|chunk| (chunk, body))
+    }))
+}
+
 pub trait SuiRpcModule
 where
     Self: Sized,
@@ -99,12 +99,14 @@ impl ReadApi {
     fn get_checkpoint_internal(&self, id: CheckpointId) -> Result<Checkpoint, Error> {
         Ok(match id {
             CheckpointId::SequenceNumber(seq) => {
-                let summary = self.state.get_checkpoint_summary_by_sequence_number(seq)?;
+                let summary = self
+                    .state
+                    .get_certified_checkpoint_by_sequence_number(seq)?;
                 let content = self.state.get_checkpoint_contents(summary.content_digest)?;
                 (summary, content).into()
             }
             CheckpointId::Digest(digest) => {
-                let summary = self.state.get_checkpoint_summary_by_digest(digest)?;
+                let summary = self.state.get_certified_checkpoint_by_digest(digest)?;
                 let content = self.state.get_checkpoint_contents(summary.content_digest)?;
                 (summary, content).into()
             }
@@ -361,7 +363,7 @@ impl ReadApiServer for ReadApi {
         let mut temp_response = IntermediateTransactionResponse::new(digest);
 
         // the input is needed for object_changes to retrieve the sender address.
-        if opts.show_input || opts.show_object_changes {
+        if opts.show_input || opts.show_raw_input || opts.show_object_changes {
             temp_response.transaction =
                 Some(self.state.get_executed_transaction(digest).await.tap_err(
                     |err| debug!(tx_digest=?digest, "Failed to get transaction: {:?}", err),
@@ -473,7 +475,7 @@ impl ReadApiServer for ReadApi {
             return Err(anyhow!("The list of digests in the input contain duplicates").into());
         }
 
-        if opts.show_input {
+        if opts.show_input || opts.show_raw_input {
             let transactions = self
                 .state
                 .multi_get_executed_transactions(&digests)
@@ -490,7 +492,7 @@ impl ReadApiServer for ReadApi {
         }
 
         // Fetch effects when `show_events` is true because events relies on effects
-        if opts.show_effects || opts.show_events {
+        if opts.show_effects || opts.show_raw_effects || opts.show_events {
             let effects_list = self
                 .state
                 .multi_get_executed_effects(&digests)
@@ -1066,6 +1068,17 @@ fn convert_to_response(
     let mut response = SuiTransactionResponse::new(cache.digest);
     response.errors = cache.errors;
 
+    if opts.show_raw_input && cache.transaction.is_some() {
+        match bcs::to_bytes(cache.transaction.as_ref().unwrap().data()) {
+            Ok(raw) => {
+                response.raw_transaction = raw;
+            }
+            Err(e) => {
+                response.errors.push(e.to_string());
+            }
+        }
+    }
+
     if opts.show_input && cache.transaction.is_some() {
         match cache.transaction.unwrap().into_message().try_into() {
             Ok(t) => {
@@ -1077,6 +1090,17 @@ fn convert_to_response(
         }
     }
 
+    if opts.show_raw_effects && cache.effects.is_some() {
+        match bcs::to_bytes(cache.effects.as_ref().unwrap()) {
+            Ok(raw) => {
+                response.raw_effects = raw;
+            }
+            Err(e) => {
+                response.errors.push(e.to_string());
+            }
+        }
+    }
+
     if opts.show_effects && cache.effects.is_some() {
         match cache.effects.unwrap().try_into() {
             Ok(effects) => {
@@ -78,6 +78,11 @@ impl TransactionExecutionApi {
 
         let txn = Transaction::from_generic_sig_data(tx_data, Intent::default(), sigs);
         let tx = txn.data().clone().try_into()?;
+        let raw_transaction = if opts.show_raw_input {
+            bcs::to_bytes(txn.data())?
+        } else {
+            vec![]
+        };
         let digest = *txn.digest();
 
         let transaction_orchestrator = self.transaction_orchestrator.clone();
@@ -122,10 +127,18 @@ impl TransactionExecutionApi {
                     None
                 };
 
+                let raw_effects = if opts.show_raw_effects {
+                    bcs::to_bytes(&effects.effects)?
+                } else {
+                    vec![]
+                };
+
                 Ok(SuiTransactionResponse {
                     digest,
                     transaction: opts.show_input.then_some(tx),
+                    raw_transaction,
                     effects: opts.show_effects.then_some(effects.effects.try_into()?),
+                    raw_effects,
                     events,
                     object_changes,
                     balance_changes,
@@ -5,6 +5,7 @@ use std::path::Path;
 #[cfg(not(msim))]
 use std::str::FromStr;
 
+use jsonrpsee::core::__reexports::serde_json;
 use sui_config::SUI_KEYSTORE_FILENAME;
 use sui_framework_build::compiled_package::BuildConfig;
 use sui_json::SuiJsonValue;
@@ -18,6 +19,7 @@ use sui_json_rpc_types::{
 };
 use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
 use sui_macros::sim_test;
+use sui_sdk::SuiClientBuilder;
 use sui_types::balance::Supply;
 use sui_types::base_types::ObjectID;
 use sui_types::base_types::TransactionDigest;
@@ -955,3 +957,54 @@ async fn test_staking_multiple_coins() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[sim_test]
+async fn test_compressed_responses() -> Result<(), anyhow::Error> {
+    let cluster = TestClusterBuilder::new().build().await?;
+
+    // The server compresses when the request allows it.
+    let request = hyper::Request::post(cluster.rpc_url())
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .header(hyper::header::ACCEPT_ENCODING, "gzip")
+        .body(hyper::Body::from(
+            r#"{"jsonrpc":"2.0","id":1,"method":"rpc.discover","params":[]}"#,
+        ))?;
+    let response = hyper::Client::new().request(request).await?;
+    assert_eq!(response.headers()[hyper::header::CONTENT_ENCODING], "gzip");
+
+    // Decoded responses match the uncompressed ones.
+    let plain = SuiClientBuilder::default().build(cluster.rpc_url()).await?;
+    let compressed = SuiClientBuilder::default()
+        .compressed_responses(true)
+        .build(cluster.rpc_url())
+        .await?;
+
+    let checkpoint = plain.read_api().get_checkpoint(0.into()).await?;
+    let compressed_checkpoint = compressed.read_api().get_checkpoint(0.into()).await?;
+    assert_eq!(
+        serde_json::to_value(&checkpoint)?,
+        serde_json::to_value(&compressed_checkpoint)?
+    );
+
+    let transactions = plain
+        .read_api()
+        .multi_get_transactions_with_options(
+            checkpoint.transactions.clone(),
+            SuiTransactionResponseOptions::full_content(),
+        )
+        .await?;
+    let compressed_transactions = compressed
+        .read_api()
+        .multi_get_transactions_with_options(
+            checkpoint.transactions,
+            SuiTransactionResponseOptions::full_content(),
+        )
+        .await?;
+    assert!(!transactions.is_empty());
+    assert_eq!(
+        serde_json::to_value(&transactions)?,
+        serde_json::to_value(&compressed_transactions)?
+    );
+
+    Ok(())
+}
@@ -64,6 +64,9 @@ impl Build {
                 config,
                 run_bytecode_verifier: true,
                 print_diags_to_stderr: true,
+                package_cache_dir: None,
+                retain_source_maps: false,
+                verifier_threads: None,
             },
         )?;
         if dump_bytecode_as_base64 {
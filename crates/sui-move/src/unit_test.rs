@@ -12,7 +12,8 @@ use move_unit_test::{extensions::set_extension_hook, UnitTestingConfig};
 use move_vm_runtime::native_extensions::NativeContextExtensions;
 use once_cell::sync::Lazy;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
     path::{Path, PathBuf},
 };
 use sui_core::authority::TemporaryStore;
@@ -75,6 +76,115 @@ pub fn run_move_unit_tests(
     build_config: BuildConfig,
     config: Option<UnitTestingConfig>,
     compute_coverage: bool,
+) -> anyhow::Result<UnitTestResult> {
+    run_move_unit_tests_with_writer(
+        path,
+        build_config,
+        config,
+        compute_coverage,
+        &mut std::io::stdout(),
+    )
+}
+
+/// The outcome of each Move unit test of a package, keyed by the fully qualified name of the test
+/// function, e.g. `0x2::coin::test_split`. Tests that timed out are counted as failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveUnitTestResults {
+    pub passed: BTreeSet<String>,
+    pub failed: BTreeSet<String>,
+}
+
+impl MoveUnitTestResults {
+    pub fn all_passed(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    pub fn total(&self) -> usize {
+        self.passed.len() + self.failed.len()
+    }
+}
+
+/// Build the package at `path` in test mode, so that test-only code goes through the Sui-specific
+/// verification too, then run its Move unit tests in-process and collect the outcome of every
+/// test. The report of the test runner is not printed, it is returned as part of the error when
+/// the tests could not be run.
+pub fn run_move_unit_tests_for_results(
+    path: &Path,
+    build_config: BuildConfig,
+    config: Option<UnitTestingConfig>,
+) -> anyhow::Result<MoveUnitTestResults> {
+    let with_unpublished_deps = false;
+    let dump_bytecode_as_base64 = false;
+    let generate_struct_layouts = false;
+    build::Build::execute_internal(
+        path,
+        BuildConfig {
+            test_mode: true,
+            ..build_config.clone()
+        },
+        with_unpublished_deps,
+        dump_bytecode_as_base64,
+        generate_struct_layouts,
+    )?;
+
+    let mut report = vec![];
+    let result = run_move_unit_tests_with_writer(path, build_config, config, false, &mut report)?;
+    let report = String::from_utf8_lossy(&report);
+    let results = parse_unit_test_report(&report);
+
+    // A failing run without any failed test means the tests did not get to run, e.g. because
+    // they do not compile.
+    if result == UnitTestResult::Failure && results.all_passed() {
+        anyhow::bail!("Move unit tests at {} failed to run:\n{report}", path.display());
+    }
+    Ok(results)
+}
+
+/// Collect the outcome of each test from the `[ PASS    ] <test>` lines of the report of the test
+/// runner, which may be colored.
+fn parse_unit_test_report(report: &str) -> MoveUnitTestResults {
+    let mut results = MoveUnitTestResults::default();
+    for line in strip_ansi_escapes(report).lines() {
+        let Some((status, test)) = line
+            .strip_prefix("[ ")
+            .and_then(|line| line.split_once(" ] "))
+        else {
+            continue;
+        };
+        let test = test.trim().to_string();
+        match status.trim() {
+            "PASS" => results.passed.insert(test),
+            "FAIL" | "TIMEOUT" => results.failed.insert(test),
+            _ => continue,
+        };
+    }
+    results
+}
+
+fn strip_ansi_escapes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // skip the control sequence up to and including its final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+fn run_move_unit_tests_with_writer<W: Write + Send>(
+    path: &Path,
+    build_config: BuildConfig,
+    config: Option<UnitTestingConfig>,
+    compute_coverage: bool,
+    writer: &mut W,
 ) -> anyhow::Result<UnitTestResult> {
     // bind the extension hook if it has not yet been done
     Lazy::force(&SET_EXTENSION_HOOK);
@@ -92,7 +202,7 @@ pub fn run_move_unit_tests(
         natives::all_natives(MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS),
         Some(initial_cost_schedule()),
         compute_coverage,
-        &mut std::io::stdout(),
+        writer,
     )
 }
 
@@ -280,6 +280,7 @@ impl SuiNode {
             config.authority_store_pruning_config,
             genesis.objects(),
             &db_checkpoint_config,
+            config.execution_timeout(),
         )
         .await;
         // ensure genesis txn was executed
@@ -29,14 +29,17 @@ use sui_types::base_types::{
     MoveObjectType, ObjectDigest, ObjectID, ObjectType, SequenceNumber, SuiAddress,
     TransactionDigest,
 };
-use sui_types::crypto::{get_key_pair_from_rng, AccountKeyPair};
+use sui_types::crypto::{
+    get_key_pair_from_rng, AccountKeyPair, AggregateAuthoritySignature,
+    SuiAuthorityStrongQuorumSignInfo,
+};
 use sui_types::digests::TransactionEventsDigest;
 use sui_types::event::EventID;
 use sui_types::gas_coin::GasCoin;
 use sui_types::messages::{
     CallArg, ExecuteTransactionRequestType, TransactionData, TransactionKind,
 };
-use sui_types::messages_checkpoint::CheckpointDigest;
+use sui_types::messages_checkpoint::{CheckpointContentsDigest, CheckpointDigest};
 use sui_types::object::Owner;
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::query::TransactionFilter;
@@ -288,7 +291,13 @@ impl RpcExampleProvider {
             timestamp_ms: 1676911928,
             end_of_epoch_data: None,
             transactions: vec![TransactionDigest::new(self.rng.gen())],
+            content_digest: CheckpointContentsDigest::new(self.rng.gen()),
             checkpoint_commitments: vec![],
+            validator_signature: SuiAuthorityStrongQuorumSignInfo {
+                epoch: 5000,
+                signature: (&AggregateAuthoritySignature::default()).into(),
+                signers_map: Default::default(),
+            },
         };
 
         Examples::new(
@@ -498,6 +507,8 @@ impl RpcExampleProvider {
                 data: SuiTransactionData::try_from(data1).unwrap(),
                 tx_signatures: signatures.clone(),
             }),
+            raw_transaction: vec![],
+            raw_effects: vec![],
             confirmed_local_execution: None,
             checkpoint: None,
             errors: vec![],
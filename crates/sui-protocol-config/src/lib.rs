@@ -10,11 +10,13 @@ use tracing::{info, warn};
 
 /// The minimum and maximum protocol versions supported by this build.
 const MIN_PROTOCOL_VERSION: u64 = 1;
-const MAX_PROTOCOL_VERSION: u64 = 1;
+const MAX_PROTOCOL_VERSION: u64 = 2;
 
 // Record history of protocol version allocations here:
 //
 // Version 1: Original version.
+// Version 2: Limit the number of inputs of a programmable transaction with
+//            `max_programmable_tx_inputs`.
 
 #[derive(
     Copy, Clone, Debug, Hash, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema,
@@ -177,6 +179,9 @@ pub struct ProtocolConfig {
     /// Maximum number of Commands in a ProgrammableTransaction.
     max_programmable_tx_commands: Option<u32>,
 
+    /// Maximum number of inputs in a ProgrammableTransaction, pure inputs included.
+    max_programmable_tx_inputs: Option<u32>,
+
     // ==== Move VM, Move bytecode verifier, and execution limits ===
     /// Maximum Move bytecode version the VM understands. All older versions are accepted.
     move_binary_format_version: Option<u32>,
@@ -432,6 +437,10 @@ impl ProtocolConfig {
     pub fn max_programmable_tx_commands(&self) -> u32 {
         self.max_programmable_tx_commands.expect(CONSTANT_ERR_MSG)
     }
+    /// None at the protocol versions that do not limit the inputs of a ProgrammableTransaction.
+    pub fn max_programmable_tx_inputs(&self) -> Option<u32> {
+        self.max_programmable_tx_inputs
+    }
     pub fn move_binary_format_version(&self) -> u32 {
         self.move_binary_format_version.expect(CONSTANT_ERR_MSG)
     }
@@ -735,6 +744,7 @@ impl ProtocolConfig {
                 max_type_argument_depth: Some(16),
                 max_pure_argument_size: Some(16 * 1024),
                 max_programmable_tx_commands: Some(1024),
+                max_programmable_tx_inputs: None,
                 move_binary_format_version: Some(6),
                 max_move_object_size: Some(250 * 1024),
                 max_move_package_size: Some(100 * 1024),
@@ -809,6 +819,11 @@ impl ProtocolConfig {
                 // new_constant: None,
             },
 
+            2 => Self {
+                max_programmable_tx_inputs: Some(2048),
+                ..Self::get_for_version_impl(version - 1)
+            },
+
             // Use this template when making changes:
             //
             // NEW_VERSION => Self {
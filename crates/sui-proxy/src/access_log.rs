@@ -0,0 +1,76 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::config::AccessLogConfig;
+use axum::http::{Response, StatusCode};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::trace::{DefaultOnResponse, OnResponse};
+use tracing::Span;
+
+/// SampledOnResponse only emits the access log line for one in every sample_rate successful
+/// requests.  Requests that did not succeed or that were slower than the configured threshold
+/// are always logged, so sampling never hides a problem.
+#[derive(Clone, Debug)]
+pub struct SampledOnResponse {
+    inner: DefaultOnResponse,
+    config: AccessLogConfig,
+    seen: Arc<AtomicU64>,
+}
+
+impl SampledOnResponse {
+    pub fn new(inner: DefaultOnResponse, config: AccessLogConfig) -> Self {
+        Self {
+            inner,
+            config,
+            seen: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn should_log(&self, status: StatusCode, latency: Duration) -> bool {
+        if !status.is_success() || latency >= self.config.slow_request_threshold {
+            return true;
+        }
+        // a sample rate of 0 is treated like 1, ie log everything
+        let sample_rate = self.config.sample_rate.max(1);
+        self.seen.fetch_add(1, Ordering::Relaxed) % sample_rate == 0
+    }
+}
+
+impl<B> OnResponse<B> for SampledOnResponse {
+    fn on_response(self, response: &Response<B>, latency: Duration, span: &Span) {
+        if self.should_log(response.status(), latency) {
+            self.inner.on_response(response, latency, span)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_only_fast_successful_requests() {
+        let on_response = SampledOnResponse::new(
+            DefaultOnResponse::new(),
+            AccessLogConfig {
+                sample_rate: 3,
+                slow_request_threshold: Duration::from_secs(1),
+            },
+        );
+        let fast = Duration::from_millis(10);
+
+        // one in every three successful requests is logged
+        let logged: Vec<bool> = (0..6)
+            .map(|_| on_response.should_log(StatusCode::CREATED, fast))
+            .collect();
+        assert_eq!(logged, vec![true, false, false, true, false, false]);
+
+        // errors and slow requests are always logged
+        for _ in 0..3 {
+            assert!(on_response.should_log(StatusCode::TOO_MANY_REQUESTS, fast));
+            assert!(on_response.should_log(StatusCode::BAD_GATEWAY, fast));
+            assert!(on_response.should_log(StatusCode::CREATED, Duration::from_secs(2)));
+        }
+    }
+}
@@ -1,16 +1,38 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use crate::config::{PeerValidationConfig, RemoteWriteConfig};
-use crate::handlers::publish_metrics;
-use crate::middleware::{expect_mysten_proxy_header, expect_valid_public_key};
+use crate::access_log::SampledOnResponse;
+use crate::batcher::Batcher;
+use crate::circuit_breaker::CircuitBreakers;
+use crate::config::{
+    load, AccessLogConfig, CircuitBreakerConfig, OtlpConfig, PeerValidationConfig, ProxyConfig,
+    RemoteWriteConfig, RemoteWriteShardConfig, SniCertificateConfig,
+};
+use crate::connection_limiter::{ConnectionLimitAcceptor, ConnectionLimiter};
+use crate::consumer::StrictParse;
+use crate::handlers::{list_peers, publish_metrics, refresh_peers};
+use crate::handshake_metrics::{MeteredTlsAcceptor, TlsHandshakeMetrics};
+use crate::metrics::RemoteWriteMetrics;
+use crate::middleware::{
+    expect_admin_token, expect_mysten_proxy_header, expect_peer_within_rate_limit,
+    expect_valid_public_key, AdminToken,
+};
+use crate::pagination::PageLimits;
 use crate::peers::SuiNodeProvider;
+use crate::rate_limiter::PeerRateLimiter;
+use crate::sharding::ShardRouter;
 use anyhow::Result;
 
-use axum::routing::post as axum_post;
+use axum::routing::{get as axum_get, post as axum_post};
 use axum::Extension;
 use axum::{middleware, Router};
+use axum_server::accept::DefaultAcceptor;
+use axum_server::HttpConfig;
 use fastcrypto::ed25519::{Ed25519KeyPair, Ed25519PublicKey};
 use fastcrypto::traits::KeyPair;
+use prometheus::Registry;
+use rustls::server::{ClientHello, ResolvesServerCert, ResolvesServerCertUsingSni};
+use rustls::sign::CertifiedKey;
+use std::collections::HashSet;
 use std::fs;
 use std::io::BufReader;
 use std::net::SocketAddr;
@@ -19,19 +41,21 @@ use std::time::Duration;
 
 use sui_tls::{rustls::ServerConfig, AllowAll, CertVerifier, SelfSignedCertificate, TlsAcceptor};
 use tokio::signal;
+use tokio::task::JoinHandle;
 
 use tower::ServiceBuilder;
 use tower_http::{
     trace::{DefaultOnResponse, TraceLayer},
     LatencyUnit,
 };
-use tracing::{info, Level};
+use tracing::{error, info, warn, Level};
+use x509_parser::{certificate::X509Certificate, prelude::FromDer};
 
 /// user agent we use when posting to mimir
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
 /// Configure our graceful shutdown scenarios
-pub async fn shutdown_signal(h: axum_server::Handle) {
+pub async fn shutdown_signal(h: axum_server::Handle, grace: Duration) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -54,14 +78,39 @@ pub async fn shutdown_signal(h: axum_server::Handle) {
         _ = terminate => {},
     }
 
-    let grace = 30;
     info!(
         "signal received, starting graceful shutdown, grace period {} seconds, if needed",
-        &grace
+        grace.as_secs()
     );
-    h.graceful_shutdown(Some(Duration::from_secs(grace)))
+    h.graceful_shutdown(Some(grace))
+}
+
+/// Reload the per-peer rate limits from the config file whenever we receive a SIGHUP
+#[cfg(unix)]
+pub fn reload_rate_limit_on_sighup(config_path: String, rate_limiter: Arc<PeerRateLimiter>) {
+    tokio::spawn(async move {
+        let mut hangup = signal::unix::signal(signal::unix::SignalKind::hangup())
+            .expect("failed to install signal handler");
+        while hangup.recv().await.is_some() {
+            info!(
+                "SIGHUP received, reloading rate limits from {}",
+                config_path
+            );
+            match load::<_, ProxyConfig>(&config_path) {
+                Ok(ProxyConfig {
+                    rate_limit: Some(rate_limit),
+                    ..
+                }) => rate_limiter.update_config(rate_limit),
+                Ok(_) => error!("rate-limit was removed from config; keeping the current limits"),
+                Err(error) => error!("unable to reload config: {error}"),
+            }
+        }
+    });
 }
 
+#[cfg(not(unix))]
+pub fn reload_rate_limit_on_sighup(_config_path: String, _rate_limiter: Arc<PeerRateLimiter>) {}
+
 /// Reqwest client holds the global client for remote_push api calls
 /// it also holds the username and password.  The client has an underlying
 /// connection pool.  See reqwest documentation for details
@@ -69,9 +118,22 @@ pub async fn shutdown_signal(h: axum_server::Handle) {
 pub struct ReqwestClient {
     pub client: reqwest::Client,
     pub settings: RemoteWriteConfig,
+    pub router: Arc<ShardRouter>,
+    /// if set, metrics are also converted and posted to this otlp endpoint
+    pub otlp: Option<OtlpConfig>,
+    pub metrics: Arc<RemoteWriteMetrics>,
+    pub circuit_breakers: Option<Arc<CircuitBreakers>>,
+    /// set by Batcher::spawn, pushes are queued to the batcher instead of posted right away
+    pub batcher: Option<Batcher>,
 }
 
-pub fn make_reqwest_client(settings: RemoteWriteConfig) -> ReqwestClient {
+pub fn make_reqwest_client(
+    settings: RemoteWriteConfig,
+    shards: Vec<RemoteWriteShardConfig>,
+    otlp: Option<OtlpConfig>,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    registry: &Registry,
+) -> ReqwestClient {
     ReqwestClient {
         client: reqwest::Client::builder()
             .user_agent(APP_USER_AGENT)
@@ -79,61 +141,215 @@ pub fn make_reqwest_client(settings: RemoteWriteConfig) -> ReqwestClient {
             .timeout(Duration::from_secs(15))
             .build()
             .expect("cannot create reqwest client"),
+        router: Arc::new(ShardRouter::new(shards, vec![settings.clone()])),
         settings,
+        otlp,
+        metrics: Arc::new(RemoteWriteMetrics::new(registry)),
+        circuit_breakers: circuit_breaker
+            .map(|config| Arc::new(CircuitBreakers::new(config, registry))),
+        batcher: None,
     }
 }
 
 /// App will configure our routes. This fn is also used to instrument our tests
-pub fn app(network: String, client: ReqwestClient, allower: Option<SuiNodeProvider>) -> Router {
+pub fn app(
+    network: String,
+    client: ReqwestClient,
+    allower: Option<SuiNodeProvider>,
+    rate_limiter: Option<Arc<PeerRateLimiter>>,
+    access_log: AccessLogConfig,
+    strict_parse: bool,
+) -> Router {
     // build our application with a route and our sender mpsc
     let mut router = Router::new()
         .route("/publish/metrics", axum_post(publish_metrics))
         .route_layer(middleware::from_fn(expect_mysten_proxy_header));
 
     if let Some(allower) = allower {
+        // route layers run outside-in, so the rate limit is checked after the peer is validated
+        if let Some(rate_limiter) = rate_limiter {
+            router = router
+                .route_layer(middleware::from_fn(expect_peer_within_rate_limit))
+                .layer(Extension(rate_limiter));
+        }
         router = router
             .route_layer(middleware::from_fn(expect_valid_public_key))
             .layer(Extension(Arc::new(allower)));
+    } else if rate_limiter.is_some() {
+        // peers are only known once validated, there is nothing to key their budget on
+        warn!("rate limits are disabled, peers are not validated");
     }
     router
         .layer(Extension(network))
         .layer(Extension(client))
+        .layer(Extension(StrictParse(strict_parse)))
         .layer(
             ServiceBuilder::new().layer(
-                TraceLayer::new_for_http().on_response(
+                TraceLayer::new_for_http().on_response(SampledOnResponse::new(
                     DefaultOnResponse::new()
                         .level(Level::INFO)
                         .latency_unit(LatencyUnit::Seconds),
-                ),
+                    access_log,
+                )),
             ),
         )
 }
 
-/// Server creates our http/https server
+/// Creates a new http server for operator actions, such as forcing a refresh of the peer
+/// list.  Every request must present the configured bearer token.  Listings are paged within
+/// page_limits.
+pub fn start_admin_server(
+    listener: std::net::TcpListener,
+    bearer_token: String,
+    allower: SuiNodeProvider,
+    page_limits: PageLimits,
+) -> JoinHandle<()> {
+    info!("serving admin api on {:?}", listener.local_addr());
+    let app = admin_app(bearer_token, allower, page_limits);
+
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service())
+            .await
+            .unwrap();
+    })
+}
+
+/// admin_app configures the admin routes, this fn is also used to instrument our tests
+pub fn admin_app(
+    bearer_token: String,
+    allower: SuiNodeProvider,
+    page_limits: PageLimits,
+) -> Router {
+    Router::new()
+        .route("/admin/refresh-peers", axum_post(refresh_peers))
+        .route("/admin/peers", axum_get(list_peers))
+        .route_layer(middleware::from_fn(expect_admin_token))
+        .layer(Extension(AdminToken(bearer_token)))
+        .layer(Extension(allower))
+        .layer(Extension(page_limits))
+}
+
+/// Server creates our http/https server.  Both http/1.1 and http/2 are served unless http2 is
+/// false; over tls the protocol is picked with alpn, see set_alpn_protocols.  If a connection
+/// limiter is given, connections over its per-ip limit are closed before the tls handshake.  If
+/// handshake metrics are given, the outcome of every tls handshake is counted in them.
 pub async fn server(
     listener: std::net::TcpListener,
     app: Router,
     acceptor: Option<TlsAcceptor>,
+    shutdown_grace_period: Duration,
+    http2: bool,
+    connection_limiter: Option<Arc<ConnectionLimiter>>,
+    handshake_metrics: Option<Arc<TlsHandshakeMetrics>>,
 ) -> std::io::Result<()> {
     // setup our graceful shutdown
     let handle = axum_server::Handle::new();
     // Spawn a task to gracefully shutdown server.
-    tokio::spawn(shutdown_signal(handle.clone()));
+    tokio::spawn(shutdown_signal(handle.clone(), shutdown_grace_period));
 
+    let http_config = HttpConfig::new().http1_only(!http2).build();
     if let Some(verify_peers) = acceptor {
+        let verify_peers = MeteredTlsAcceptor::new(verify_peers, handshake_metrics);
         axum_server::Server::from_tcp(listener)
-            .acceptor(verify_peers)
+            .acceptor(ConnectionLimitAcceptor::new(verify_peers, connection_limiter))
+            .http_config(http_config)
             .handle(handle)
             .serve(app.into_make_service_with_connect_info::<SocketAddr>())
             .await
     } else {
         axum_server::Server::from_tcp(listener)
+            .acceptor(ConnectionLimitAcceptor::new(DefaultAcceptor, connection_limiter))
+            .http_config(http_config)
             .handle(handle)
             .serve(app.into_make_service_with_connect_info::<SocketAddr>())
             .await
     }
 }
 
+/// Advertise the protocols we serve during the tls handshake, so that clients only negotiate
+/// http/2 when it is enabled
+pub fn set_alpn_protocols(tls_config: &mut ServerConfig, http2: bool) {
+    tls_config.alpn_protocols = if http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+}
+
+/// SniCertResolver presents the certificate configured for the server name a client asks for
+/// with sni, and the certificate the server was built with to every other client
+struct SniCertResolver {
+    names: HashSet<String>,
+    by_name: ResolvesServerCertUsingSni,
+    default: Arc<dyn ResolvesServerCert>,
+}
+
+impl ResolvesServerCert for SniCertResolver {
+    fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        let known = client_hello
+            .server_name()
+            .map_or(false, |name| self.names.contains(name));
+        if known {
+            self.by_name.resolve(client_hello)
+        } else {
+            self.default.resolve(client_hello)
+        }
+    }
+}
+
+/// Present the configured certificates to clients that ask for their hostname with sni.  Clients
+/// asking for any other name, or sending none, keep being presented the certificate tls_config
+/// was built with
+pub fn set_sni_certificates(
+    tls_config: &mut ServerConfig,
+    sni_certificates: &[SniCertificateConfig],
+) -> Result<(), sui_tls::rustls::Error> {
+    resolve_by_sni(
+        tls_config,
+        sni_certificates
+            .iter()
+            .map(|c| {
+                (
+                    c.hostname.clone(),
+                    load_certs(&c.certificate_file),
+                    load_private_key(&c.private_key),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// resolve_by_sni wraps the certificate resolver of tls_config so that each hostname is presented
+/// its certificate chain.  A hostname that is not a valid dns name, or that its certificate is not
+/// valid for, is an error
+pub(crate) fn resolve_by_sni(
+    tls_config: &mut ServerConfig,
+    certificates: Vec<(String, Vec<rustls::Certificate>, rustls::PrivateKey)>,
+) -> Result<(), sui_tls::rustls::Error> {
+    if certificates.is_empty() {
+        return Ok(());
+    }
+    let mut names = HashSet::new();
+    let mut by_name = ResolvesServerCertUsingSni::new();
+    for (hostname, chain, private_key) in certificates {
+        let key = rustls::sign::any_supported_type(&private_key).map_err(|_| {
+            rustls::Error::General(format!("unsupported private key for {hostname}"))
+        })?;
+        // names are compared lowercased, as the server name of a client hello is
+        let hostname = hostname.to_lowercase();
+        by_name.add(&hostname, CertifiedKey::new(chain, key))?;
+        names.insert(hostname);
+    }
+    tls_config.cert_resolver = Arc::new(SniCertResolver {
+        names,
+        by_name,
+        default: tls_config.cert_resolver.clone(),
+    });
+    Ok(())
+}
+
 /// Generate server certs for use with peer verification
 pub fn generate_self_cert(hostname: String) -> (SelfSignedCertificate, Ed25519PublicKey) {
     let mut rng = rand::thread_rng();
@@ -155,6 +371,17 @@ fn load_certs(filename: &str) -> Vec<rustls::Certificate> {
         .collect()
 }
 
+/// certificate_not_after reads the expiry of the first certificate in the file, as a unix
+/// timestamp in seconds
+pub fn certificate_not_after(filename: &str) -> Option<i64> {
+    load_certs(filename).first().and_then(not_after)
+}
+
+fn not_after(certificate: &rustls::Certificate) -> Option<i64> {
+    let (_, certificate) = X509Certificate::from_der(&certificate.0).ok()?;
+    Some(certificate.validity().not_after.timestamp())
+}
+
 fn load_private_key(filename: &str) -> rustls::PrivateKey {
     let keyfile = fs::File::open(filename).expect("cannot open private key file");
     let mut reader = BufReader::new(keyfile);
@@ -203,3 +430,20 @@ pub fn create_server_cert_enforce_peer(
     )?;
     Ok((c, Some(allower)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn reads_certificate_expiry() {
+        let (certificate, _) = generate_self_cert("localhost".into());
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        assert!(not_after(&certificate.rustls_certificate()).unwrap() > now);
+        assert_eq!(not_after(&rustls::Certificate(vec![0, 1, 2])), None);
+    }
+}
@@ -0,0 +1,410 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::admin::ReqwestClient;
+use crate::config::{BatchConfig, RemoteWriteConfig};
+use crate::consumer::{compress, push_otlp_unless_open, push_unless_open};
+use crate::otlp::Metric;
+use crate::remote_write::{TimeSeries, WriteRequest};
+use crate::sharding::ShardRouter;
+use anyhow::{anyhow, Result};
+use fastcrypto::encoding::{Base64, Encoding};
+use prometheus::{
+    register_histogram_with_registry, register_int_counter_with_registry, Histogram, IntCounter,
+    IntGauge, Registry,
+};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// how many inbound pushes may wait for the batcher before pushers are turned away
+const QUEUE_CAPACITY: usize = 1024;
+
+const BATCH_SIZE_BUCKETS: &[f64] = &[1.0, 10.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0];
+
+/// Batches accumulates timeseries per remote_write destination, keyed by url, until there are
+/// enough of them to fill a write request
+#[derive(Debug)]
+struct Batches {
+    max_batch_size: usize,
+    pending: HashMap<String, (RemoteWriteConfig, Vec<TimeSeries>)>,
+}
+
+impl Batches {
+    fn new(max_batch_size: usize) -> Self {
+        Self {
+            // an empty batch is never full
+            max_batch_size: max_batch_size.max(1),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// add timeseries bound for a destination, returning the batches that are now full
+    fn add(
+        &mut self,
+        settings: RemoteWriteConfig,
+        timeseries: Vec<TimeSeries>,
+    ) -> Vec<(RemoteWriteConfig, Vec<TimeSeries>)> {
+        let (settings, pending) = self
+            .pending
+            .entry(settings.url.clone())
+            .or_insert_with(|| (settings, vec![]));
+        pending.extend(timeseries);
+
+        let mut full = vec![];
+        while pending.len() >= self.max_batch_size {
+            let rest = pending.split_off(self.max_batch_size);
+            full.push((settings.clone(), std::mem::replace(pending, rest)));
+        }
+        full
+    }
+
+    /// drain takes every pending batch, none of which is larger than max_batch_size
+    fn drain(&mut self) -> Vec<(RemoteWriteConfig, Vec<TimeSeries>)> {
+        self.pending
+            .drain()
+            .filter(|(_, (_, pending))| !pending.is_empty())
+            .map(|(_, batch)| batch)
+            .collect()
+    }
+}
+
+/// Queued is a push handed to the batching task
+#[derive(Debug)]
+enum Queued {
+    RemoteWrite(RemoteWriteConfig, Vec<TimeSeries>),
+    /// metrics bound for the otlp endpoint of the client
+    Otlp(Vec<Metric>),
+}
+
+/// Batcher coalesces the timeseries of many small inbound pushes into fewer, larger write
+/// requests.  Pushes are queued to a task that posts a destination's batch once it holds
+/// max_batch_size timeseries, and every pending batch each flush_interval.  Metrics bound for
+/// otlp are batched the same way, counting metric families.
+#[derive(Clone, Debug)]
+pub struct Batcher {
+    tx: mpsc::Sender<Queued>,
+    dropped: IntCounter,
+    queue_depth: IntGauge,
+}
+
+impl Batcher {
+    /// spawn starts the batching task and has the client queue its pushes to it.  The task
+    /// flushes the pending batches, or saves them to the state file if one is configured, and
+    /// completes once every clone of the client is dropped, so await it after the server shut
+    /// down to not lose them.  Batches saved by a previous run are reloaded first.  Pending otlp
+    /// metrics are not saved, they are always flushed.
+    pub fn spawn(
+        client: &mut ReqwestClient,
+        config: BatchConfig,
+        registry: &Registry,
+    ) -> JoinHandle<()> {
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        let batch_size = register_histogram_with_registry!(
+            "proxy_remote_write_batch_size",
+            "Number of timeseries in each batched write request",
+            BATCH_SIZE_BUCKETS.to_vec(),
+            registry,
+        )
+        .unwrap();
+        let recovered = config
+            .state_file
+            .as_deref()
+            .map(|path| recover(path, &client.router))
+            .unwrap_or_default();
+        register_int_counter_with_registry!(
+            "proxy_remote_write_batches_recovered",
+            "Number of pending batches reloaded from the state file at startup",
+            registry,
+        )
+        .unwrap()
+        .inc_by(recovered.len() as u64);
+        // the task pushes with a client that does not batch, or the channel would never close
+        let handle = tokio::spawn(run(client.clone(), config, rx, batch_size, recovered));
+        client.batcher = Some(Batcher {
+            tx,
+            queue_depth: client.metrics.batch_queue_depth.clone(),
+            dropped: register_int_counter_with_registry!(
+                "proxy_remote_write_batch_dropped_total",
+                "Total number of pushes dropped because the batch queue was full",
+                registry,
+            )
+            .unwrap(),
+        });
+        handle
+    }
+
+    /// enqueue queues timeseries bound for a destination, returning false if they were dropped
+    /// because the queue is full
+    pub fn enqueue(&self, settings: RemoteWriteConfig, timeseries: Vec<TimeSeries>) -> bool {
+        self.send(Queued::RemoteWrite(settings, timeseries))
+    }
+
+    /// enqueue_otlp queues metrics bound for the otlp endpoint, returning false if they were
+    /// dropped because the queue is full
+    pub fn enqueue_otlp(&self, metrics: Vec<Metric>) -> bool {
+        self.send(Queued::Otlp(metrics))
+    }
+
+    fn send(&self, queued: Queued) -> bool {
+        // counted before sending, the batching task may take the push off the queue right away
+        self.queue_depth.inc();
+        match self.tx.try_send(queued) {
+            Ok(()) => true,
+            Err(error) => {
+                self.queue_depth.dec();
+                self.dropped.inc();
+                error!("DROPPING METRICS, unable to queue them for batching: {error}");
+                false
+            }
+        }
+    }
+}
+
+async fn run(
+    rc: ReqwestClient,
+    config: BatchConfig,
+    mut rx: mpsc::Receiver<Queued>,
+    batch_size: Histogram,
+    recovered: Vec<(RemoteWriteConfig, Vec<TimeSeries>)>,
+) {
+    let mut batches = Batches::new(config.max_batch_size);
+    for (settings, timeseries) in recovered {
+        for batch in batches.add(settings, timeseries) {
+            flush(&rc, &batch_size, batch).await;
+        }
+    }
+    let mut otlp_pending: Vec<Metric> = vec![];
+    let mut interval = tokio::time::interval(config.flush_interval);
+    loop {
+        tokio::select! {
+            received = rx.recv() => {
+                if received.is_some() {
+                    rc.metrics.batch_queue_depth.dec();
+                }
+                match received {
+                    Some(Queued::RemoteWrite(settings, timeseries)) => {
+                        for batch in batches.add(settings, timeseries) {
+                            flush(&rc, &batch_size, batch).await;
+                        }
+                    }
+                    Some(Queued::Otlp(metrics)) => {
+                        otlp_pending.extend(metrics);
+                        while otlp_pending.len() >= batches.max_batch_size {
+                            let rest = otlp_pending.split_off(batches.max_batch_size);
+                            flush_otlp(&rc, std::mem::replace(&mut otlp_pending, rest)).await;
+                        }
+                    }
+                    None => break,
+                }
+            }
+            _ = interval.tick() => {
+                for batch in batches.drain() {
+                    flush(&rc, &batch_size, batch).await;
+                }
+                flush_otlp(&rc, std::mem::take(&mut otlp_pending)).await;
+            }
+        }
+    }
+    if !otlp_pending.is_empty() {
+        info!(
+            "flushing {} pending otlp metrics on shutdown",
+            otlp_pending.len()
+        );
+        flush_otlp(&rc, otlp_pending).await;
+    }
+    let pending = batches.drain();
+    if let Some(path) = config.state_file.as_deref().filter(|_| !pending.is_empty()) {
+        match save(path, &pending) {
+            Ok(()) => {
+                info!(
+                    "saved {} pending batches to {path:?} on shutdown",
+                    pending.len()
+                );
+                return;
+            }
+            Err(error) => {
+                error!("unable to save pending batches to {path:?}, flushing them: {error}")
+            }
+        }
+    }
+    info!("flushing {} pending batches on shutdown", pending.len());
+    for batch in pending {
+        flush(&rc, &batch_size, batch).await;
+    }
+}
+
+/// PersistedBatch is a pending batch as written to the state file.  only the url of its
+/// destination is kept, the credentials are taken from the config when it is reloaded
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct PersistedBatch {
+    url: String,
+    /// the protobuf encoded write request, in base64
+    write_request: String,
+}
+
+/// save snapshots the pending batches to the state file
+fn save(path: &Path, batches: &[(RemoteWriteConfig, Vec<TimeSeries>)]) -> Result<()> {
+    let persisted: Vec<_> = batches
+        .iter()
+        .map(|(settings, timeseries)| PersistedBatch {
+            url: settings.url.clone(),
+            write_request: Base64::encode(
+                WriteRequest {
+                    timeseries: timeseries.clone(),
+                    ..Default::default()
+                }
+                .encode_to_vec(),
+            ),
+        })
+        .collect();
+    // written next to the state file and renamed over it, so that a crash mid-write leaves the
+    // previous state file in place rather than a truncated one
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    let mut file = std::fs::File::create(&tmp_path)?;
+    file.write_all(&serde_json::to_vec(&persisted)?)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// load reads the batches back from the state file, dropping those bound for destinations that
+/// are no longer configured
+fn load(path: &Path, router: &ShardRouter) -> Result<Vec<(RemoteWriteConfig, Vec<TimeSeries>)>> {
+    let persisted: Vec<PersistedBatch> = serde_json::from_slice(&std::fs::read(path)?)?;
+    let mut batches = vec![];
+    for batch in persisted {
+        let Some(settings) = router.destination(&batch.url) else {
+            warn!(
+                "dropping a saved batch for {}, it is no longer a remote_write destination",
+                batch.url
+            );
+            continue;
+        };
+        let encoded = Base64::decode(&batch.write_request).map_err(|error| anyhow!("{error}"))?;
+        let write_request = WriteRequest::decode(encoded.as_slice())?;
+        batches.push((settings.clone(), write_request.timeseries));
+    }
+    Ok(batches)
+}
+
+/// recover reloads the batches saved by a previous graceful shutdown, then removes the state
+/// file so that they are not posted again by the next restart
+fn recover(path: &Path, router: &ShardRouter) -> Vec<(RemoteWriteConfig, Vec<TimeSeries>)> {
+    if !path.exists() {
+        return vec![];
+    }
+    let batches = load(path, router).unwrap_or_else(|error| {
+        error!("unable to recover pending batches from {path:?}: {error}");
+        vec![]
+    });
+    if let Err(error) = std::fs::remove_file(path) {
+        error!("unable to remove the state file {path:?}: {error}");
+    }
+    info!("recovered {} pending batches from {path:?}", batches.len());
+    batches
+}
+
+/// flush posts a batch as a single write request, failures are logged and counted by push
+async fn flush(
+    rc: &ReqwestClient,
+    batch_size: &Histogram,
+    (settings, timeseries): (RemoteWriteConfig, Vec<TimeSeries>),
+) {
+    batch_size.observe(timeseries.len() as f64);
+    let write_request = WriteRequest {
+        timeseries,
+        ..Default::default()
+    };
+    let Ok(compressed) = compress(&write_request) else {
+        return;
+    };
+    let _ = push_unless_open(rc, &settings, &write_request, compressed).await;
+}
+
+/// flush_otlp posts the pending otlp metrics as a single export request, if there are any
+async fn flush_otlp(rc: &ReqwestClient, metrics: Vec<Metric>) {
+    let Some(settings) = rc.otlp.as_ref().filter(|_| !metrics.is_empty()) else {
+        return;
+    };
+    let _ = push_otlp_unless_open(rc, settings, metrics).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(url: &str) -> RemoteWriteConfig {
+        RemoteWriteConfig {
+            url: url.into(),
+            username: "foo".into(),
+            password: "bar".into(),
+        }
+    }
+
+    fn timeseries(n: usize) -> Vec<TimeSeries> {
+        vec![TimeSeries::default(); n]
+    }
+
+    #[test]
+    fn batches_fill_per_destination_up_to_max_batch_size() {
+        let mut batches = Batches::new(3);
+
+        // timeseries are held back until a destination has a full batch
+        assert!(batches.add(settings("a"), timeseries(2)).is_empty());
+        assert!(batches.add(settings("b"), timeseries(2)).is_empty());
+
+        // a push overflowing the batch flushes full batches and keeps the rest pending
+        let full = batches.add(settings("a"), timeseries(5));
+        assert_eq!(full.len(), 2);
+        assert!(full
+            .iter()
+            .all(|(settings, batch)| settings.url == "a" && batch.len() == 3));
+
+        let mut pending: Vec<_> = batches
+            .drain()
+            .into_iter()
+            .map(|(settings, batch)| (settings.url, batch.len()))
+            .collect();
+        pending.sort();
+        assert_eq!(pending, vec![("a".to_string(), 1), ("b".to_string(), 2)]);
+        assert!(batches.drain().is_empty());
+    }
+
+    #[test]
+    fn saved_batches_are_recovered_once() {
+        let router = ShardRouter::new(vec![], vec![settings("a")]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batches.json");
+
+        // there is nothing to recover on a first start
+        assert!(recover(&path, &router).is_empty());
+
+        // batches for destinations that are no longer configured are dropped
+        save(
+            &path,
+            &[
+                (settings("a"), timeseries(2)),
+                (settings("gone"), timeseries(1)),
+            ],
+        )
+        .unwrap();
+        // the state file is written whole through a temporary file next to it
+        assert!(!dir.path().join("batches.json.tmp").exists());
+        let recovered = recover(&path, &router);
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].0.url, "a");
+        assert_eq!(recovered[0].1, timeseries(2));
+
+        // the state file is removed so that the batches are not recovered twice
+        assert!(!path.exists());
+        assert!(recover(&path, &router).is_empty());
+    }
+}
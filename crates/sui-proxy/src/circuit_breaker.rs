@@ -0,0 +1,175 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::config::CircuitBreakerConfig;
+use prometheus::{register_int_gauge_vec_with_registry, IntGaugeVec, Registry};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// State of the breaker of a single destination, exported as 0, 1 and 2 respectively
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    /// pushes are attempted, counting the consecutive failures
+    Closed { failures: u32 },
+    /// pushes are dropped without being attempted until the cooldown is over
+    Open { until: Instant },
+    /// a single probe push is in flight, the others are dropped until it completes.  a probe
+    /// that never records its outcome, e.g. because its task was dropped, is given up on after
+    /// the cooldown and another one is let through
+    HalfOpen { since: Instant },
+}
+
+impl State {
+    fn as_metric(&self) -> i64 {
+        match self {
+            State::Closed { .. } => 0,
+            State::Open { .. } => 1,
+            State::HalfOpen { .. } => 2,
+        }
+    }
+
+    /// allow decides whether a push may be attempted, moving an open breaker whose cooldown is
+    /// over to half-open so that this push probes the destination
+    fn allow(&mut self, config: &CircuitBreakerConfig, now: Instant) -> bool {
+        match *self {
+            State::Closed { .. } => true,
+            State::Open { until } if now >= until => {
+                *self = State::HalfOpen { since: now };
+                true
+            }
+            State::HalfOpen { since } if now >= since + config.cooldown => {
+                *self = State::HalfOpen { since: now };
+                true
+            }
+            State::Open { .. } | State::HalfOpen { .. } => false,
+        }
+    }
+
+    fn record(&mut self, config: &CircuitBreakerConfig, success: bool, now: Instant) {
+        *self = match (*self, success) {
+            (_, true) => State::Closed { failures: 0 },
+            (State::Closed { failures }, false) if failures + 1 < config.failure_threshold => {
+                State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            (_, false) => State::Open {
+                until: now + config.cooldown,
+            },
+        };
+    }
+}
+
+/// CircuitBreakers keeps a breaker per remote_write destination, keyed by url, so that a
+/// destination which is down is not attempted for every batch.  After failure_threshold
+/// consecutive failed pushes the breaker opens and pushes are dropped for the cooldown, then
+/// a single push is let through to probe whether the destination recovered.
+#[derive(Debug)]
+pub struct CircuitBreakers {
+    config: CircuitBreakerConfig,
+    states: Mutex<HashMap<String, State>>,
+    state: IntGaugeVec,
+}
+
+impl CircuitBreakers {
+    pub fn new(config: CircuitBreakerConfig, registry: &Registry) -> Self {
+        Self {
+            config,
+            states: Mutex::new(HashMap::new()),
+            state: register_int_gauge_vec_with_registry!(
+                "proxy_remote_write_circuit_breaker_state",
+                "State of the circuit breaker of a remote_write destination, 0 closed, 1 open, 2 half-open",
+                &["destination"],
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+
+    /// allow returns true if a push to the destination may be attempted.  every allowed push
+    /// must be followed by a call to record
+    pub fn allow(&self, destination: &str) -> bool {
+        let mut states = self.states.lock().unwrap();
+        let state = states
+            .entry(destination.to_owned())
+            .or_insert(State::Closed { failures: 0 });
+        let allowed = state.allow(&self.config, Instant::now());
+        self.state
+            .with_label_values(&[destination])
+            .set(state.as_metric());
+        allowed
+    }
+
+    /// record the outcome of a push to the destination
+    pub fn record(&self, destination: &str, success: bool) {
+        let mut states = self.states.lock().unwrap();
+        let state = states
+            .entry(destination.to_owned())
+            .or_insert(State::Closed { failures: 0 });
+        let previous = *state;
+        state.record(&self.config, success, Instant::now());
+        match (previous, *state) {
+            (State::Closed { .. } | State::HalfOpen { .. }, State::Open { .. }) => warn!(
+                "remote_write destination {destination} is failing, dropping pushes to it for {} seconds",
+                self.config.cooldown.as_secs()
+            ),
+            (State::Open { .. } | State::HalfOpen { .. }, State::Closed { .. }) => {
+                info!("remote_write destination {destination} recovered")
+            }
+            _ => {}
+        }
+        self.state
+            .with_label_values(&[destination])
+            .set(state.as_metric());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn breaker_opens_after_consecutive_failures_and_probes_after_cooldown() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(10),
+        };
+        let start = Instant::now();
+        let mut state = State::Closed { failures: 0 };
+
+        // a success resets the count of consecutive failures
+        state.record(&config, false, start);
+        state.record(&config, true, start);
+        state.record(&config, false, start);
+        assert!(state.allow(&config, start));
+
+        // the second consecutive failure opens the breaker for the cooldown
+        state.record(&config, false, start);
+        assert!(!state.allow(&config, start));
+        assert!(!state.allow(&config, start + Duration::from_secs(9)));
+
+        // once the cooldown is over a single probe is let through
+        let later = start + Duration::from_secs(10);
+        assert!(state.allow(&config, later));
+        assert_eq!(state, State::HalfOpen { since: later });
+        assert!(!state.allow(&config, later));
+
+        // a probe that never completes is given up on after the cooldown
+        assert!(!state.allow(&config, later + Duration::from_secs(9)));
+        let abandoned = later + Duration::from_secs(10);
+        assert!(state.allow(&config, abandoned));
+        assert_eq!(state, State::HalfOpen { since: abandoned });
+        assert!(!state.allow(&config, abandoned));
+
+        // a failed probe opens the breaker again, a successful one closes it
+        state.record(&config, false, abandoned);
+        assert!(!state.allow(&config, abandoned));
+        let recovered = abandoned + Duration::from_secs(10);
+        assert!(state.allow(&config, recovered));
+        state.record(&config, true, recovered);
+        assert_eq!(state, State::Closed { failures: 0 });
+        assert!(state.allow(&config, recovered));
+    }
+}
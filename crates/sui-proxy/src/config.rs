@@ -1,10 +1,13 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+use crate::pagination::PageLimits;
 use anyhow::{Context, Result};
 use core::time::Duration;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use serde_with::{serde_as, DurationSeconds};
+use serde_with::{serde_as, DurationMilliSeconds, DurationSeconds};
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use tracing::debug;
 
 #[serde_as]
@@ -13,8 +16,49 @@ use tracing::debug;
 pub struct ProxyConfig {
     pub network: String,
     pub listen_address: SocketAddr,
+    /// the proxy's own metrics are served on this address, separate from the mtls data path.
+    /// it is unauthenticated, so bind it to an internal interface
+    pub metrics_address: Option<SocketAddr>,
     pub remote_write: RemoteWriteConfig,
+    /// if set, each peer's metrics are sent to exactly one of these shards, picked by
+    /// consistent hashing of the peer public key, instead of to remote_write
+    #[serde(default)]
+    pub remote_write_shards: Vec<RemoteWriteShardConfig>,
+    /// optional otlp/http endpoint the metrics are also sent to, converted to otlp metrics
+    pub otlp: Option<OtlpConfig>,
+    /// optional circuit breaker per remote_write destination, every push is attempted if this
+    /// is not set
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// optional batching of inbound pushes into fewer remote_write requests, each push is
+    /// forwarded as it arrives if this is not set
+    pub batch: Option<BatchConfig>,
     pub json_rpc: PeerValidationConfig,
+    /// extra certificates presented to clients that ask for their hostname with sni, other
+    /// clients are presented the json_rpc certificate
+    #[serde(default)]
+    pub sni_certificates: Vec<SniCertificateConfig>,
+    /// optional per-peer rate limits, requests are unlimited if this is not set
+    pub rate_limit: Option<RateLimitConfig>,
+    /// optional cap on the connections each source ip may hold open, enforced before the tls
+    /// handshake.  connections are unlimited if this is not set
+    pub connection_limit: Option<ConnectionLimitConfig>,
+    /// how long in-flight requests may drain after a shutdown signal
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "shutdown_grace_period_default")]
+    pub shutdown_grace_period: Duration,
+    /// sampling of the per-request access log, every request is logged if this is not set
+    #[serde(default)]
+    pub access_log: AccessLogConfig,
+    /// optional admin api, served on its own address and only when peers are validated
+    pub admin: Option<AdminConfig>,
+    /// accept http/2 on the inbound listener, negotiated with alpn over tls or with prior
+    /// knowledge in plain text.  if false, only http/1.1 is served
+    #[serde(default = "http2_default")]
+    pub http2: bool,
+    /// fully validate pushed metric families before forwarding them, rejecting malformed
+    /// payloads with a 400.  this costs cpu on every push, so it is off by default
+    #[serde(default)]
+    pub strict_parse: bool,
 }
 
 #[serde_as]
@@ -30,6 +74,29 @@ pub struct RemoteWriteConfig {
     pub password: String,
 }
 
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteWriteShardConfig {
+    /// name places the shard on the hash ring, keep it stable to avoid reshuffling peers
+    pub name: String,
+    #[serde(flatten)]
+    pub remote_write: RemoteWriteConfig,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct OtlpConfig {
+    /// the otlp/http metrics url to post data to, usually ending in /v1/metrics
+    pub url: String,
+    /// headers sent with every post, e.g. for authentication
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// send the metrics only to otlp, not to remote_write or its shards
+    #[serde(default)]
+    pub replace_remote_write: bool,
+}
+
 #[serde_as]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
@@ -51,6 +118,130 @@ pub struct PeerValidationConfig {
     pub private_key: Option<String>,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SniCertificateConfig {
+    /// the server name clients send with sni to be presented this certificate
+    pub hostname: String,
+    /// please use an absolute path
+    pub certificate_file: String,
+    /// private key for tls
+    /// please use an absolute path
+    pub private_key: String,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RateLimitConfig {
+    /// the rate at which a peer's budget is replenished
+    pub requests_per_second: f64,
+    /// the maximum number of requests a peer may send in a burst
+    pub burst: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConnectionLimitConfig {
+    /// connections from an ip that already holds this many open connections are closed as
+    /// soon as they are accepted
+    pub max_connections_per_ip: usize,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct CircuitBreakerConfig {
+    /// the breaker opens after this many consecutive failed pushes to a destination
+    pub failure_threshold: u32,
+    /// pushes to the destination are dropped for this long once the breaker opens, after
+    /// which a single push probes whether it recovered
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(rename = "cooldown-secs")]
+    pub cooldown: Duration,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchConfig {
+    /// pending timeseries are posted at least this often
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(rename = "flush-interval-ms")]
+    pub flush_interval: Duration,
+    /// a destination's batch is posted as soon as it holds this many timeseries, no write
+    /// request carries more.  otlp batches are counted in metric families
+    pub max_batch_size: usize,
+    /// if set, the pending batches are written to this file on graceful shutdown instead of
+    /// being posted, and are reloaded from it at startup, so that a restart does not drop them.
+    /// pending otlp metrics are always posted
+    #[serde(default)]
+    pub state_file: Option<PathBuf>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AdminConfig {
+    /// the admin api is served on this address, bind it to an internal interface
+    pub listen_address: SocketAddr,
+    /// requests must present this as a bearer token
+    pub bearer_token: String,
+    /// listings such as /admin/peers return at most this many entries at once, clients fetch
+    /// the rest page by page with the returned cursor
+    #[serde(default = "max_page_size_default")]
+    pub max_page_size: usize,
+    /// a page of a listing stops short of growing its json body past this many bytes
+    #[serde(default = "max_response_bytes_default")]
+    pub max_response_bytes: usize,
+}
+
+impl AdminConfig {
+    pub fn page_limits(&self) -> PageLimits {
+        PageLimits {
+            max_page_size: self.max_page_size,
+            max_response_bytes: self.max_response_bytes,
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct AccessLogConfig {
+    /// log one in every sample_rate successful requests, requests that fail are always logged
+    pub sample_rate: u64,
+    /// requests taking at least this long are always logged
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(rename = "slow-request-threshold-ms")]
+    pub slow_request_threshold: Duration,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 1,
+            slow_request_threshold: Duration::from_secs(1),
+        }
+    }
+}
+
+fn http2_default() -> bool {
+    true
+}
+
+fn shutdown_grace_period_default() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn max_page_size_default() -> usize {
+    100
+}
+
+fn max_response_bytes_default() -> usize {
+    1024 * 1024
+}
+
 fn hostname_default() -> Option<String> {
     Some("localhost".to_string())
 }
@@ -0,0 +1,216 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::config::ConnectionLimitConfig;
+use axum_server::accept::Accept;
+use hyper::server::conn::AddrStream;
+use prometheus::{register_int_counter_vec_with_registry, IntCounterVec, Registry};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tracing::debug;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// ConnectionLimiter counts the open connections of each source ip, so that a single host
+/// cannot exhaust the proxy's sockets or tls handshakes by opening connections in a loop.
+#[derive(Debug)]
+pub struct ConnectionLimiter {
+    max_connections_per_ip: usize,
+    connections: Mutex<HashMap<IpAddr, usize>>,
+    rejected: IntCounterVec,
+}
+
+impl ConnectionLimiter {
+    pub fn new(config: ConnectionLimitConfig, registry: &Registry) -> Self {
+        Self {
+            max_connections_per_ip: config.max_connections_per_ip,
+            connections: Mutex::new(HashMap::new()),
+            rejected: register_int_counter_vec_with_registry!(
+                "proxy_rejected_connections_total",
+                "Total number of inbound connections rejected by reason",
+                &["reason"],
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+
+    /// try_acquire returns a guard holding one of the ip's connection slots until it is
+    /// dropped, or None if the ip already has as many connections open as allowed
+    pub fn try_acquire(self: &Arc<Self>, ip: IpAddr) -> Option<ConnectionGuard> {
+        let mut connections = self.connections.lock().unwrap();
+        let open = connections.get(&ip).copied().unwrap_or_default();
+        if open >= self.max_connections_per_ip {
+            drop(connections);
+            self.rejected.with_label_values(&["per_ip_limit"]).inc();
+            return None;
+        }
+        connections.insert(ip, open + 1);
+        Some(ConnectionGuard {
+            limiter: self.clone(),
+            ip,
+        })
+    }
+
+    fn release(&self, ip: IpAddr) {
+        if let Entry::Occupied(mut entry) = self.connections.lock().unwrap().entry(ip) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
+/// ConnectionGuard gives its connection slot back to the limiter when dropped
+#[derive(Debug)]
+pub struct ConnectionGuard {
+    limiter: Arc<ConnectionLimiter>,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.limiter.release(self.ip);
+    }
+}
+
+/// ConnectionLimitAcceptor checks the source ip of a tcp connection against the limiter before
+/// handing it to the inner acceptor, so connections over the limit are closed before any tls
+/// handshake.  Without a limiter every connection is handed over.
+#[derive(Debug, Clone)]
+pub struct ConnectionLimitAcceptor<A> {
+    inner: A,
+    limiter: Option<Arc<ConnectionLimiter>>,
+}
+
+impl<A> ConnectionLimitAcceptor<A> {
+    pub fn new(inner: A, limiter: Option<Arc<ConnectionLimiter>>) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+impl<A, S> Accept<AddrStream, S> for ConnectionLimitAcceptor<A>
+where
+    A: Accept<LimitedStream<AddrStream>, S> + Clone + Send + 'static,
+    A::Future: Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = A::Stream;
+    type Service = A::Service;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: AddrStream, service: S) -> Self::Future {
+        let inner = self.inner.clone();
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            let remote_addr = stream.remote_addr();
+            let guard = match &limiter {
+                Some(limiter) => match limiter.try_acquire(remote_addr.ip()) {
+                    Some(guard) => Some(guard),
+                    None => {
+                        debug!("rejecting connection from {remote_addr}, too many connections");
+                        return Err(io::Error::new(
+                            io::ErrorKind::ConnectionRefused,
+                            "too many connections from this ip",
+                        ));
+                    }
+                },
+                None => None,
+            };
+            let stream = LimitedStream {
+                inner: stream,
+                _guard: guard,
+            };
+            inner.accept(stream, service).await
+        })
+    }
+}
+
+/// LimitedStream holds its connection slot for as long as the connection is open
+#[derive(Debug)]
+pub struct LimitedStream<I> {
+    inner: I,
+    _guard: Option<ConnectionGuard>,
+}
+
+impl<I: AsyncRead + Unpin> AsyncRead for LimitedStream<I> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<I: AsyncWrite + Unpin> AsyncWrite for LimitedStream<I> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_slots_are_released_on_drop() {
+        let registry = Registry::new();
+        let limiter = Arc::new(ConnectionLimiter::new(
+            ConnectionLimitConfig {
+                max_connections_per_ip: 2,
+            },
+            &registry,
+        ));
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let other_ip: IpAddr = "10.0.0.2".parse().unwrap();
+
+        let first = limiter.try_acquire(ip).unwrap();
+        let second = limiter.try_acquire(ip).unwrap();
+        assert!(limiter.try_acquire(ip).is_none());
+        // the limit is per ip
+        assert!(limiter.try_acquire(other_ip).is_some());
+
+        drop(first);
+        assert!(limiter.try_acquire(ip).is_some());
+        drop(second);
+        assert_eq!(
+            limiter.rejected.with_label_values(&["per_ip_limit"]).get(),
+            1
+        );
+        // every slot was given back
+        assert!(limiter.connections.lock().unwrap().is_empty());
+    }
+}
@@ -2,8 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::admin::ReqwestClient;
+use crate::config::{OtlpConfig, RemoteWriteConfig};
+use crate::otlp;
 use crate::prom_to_mimir::Mimir;
-use anyhow::Result;
+use crate::prom_to_otlp::{export_request, to_otlp};
+use crate::remote_write::{TimeSeries, WriteRequest};
+use anyhow::{bail, Result};
 use axum::body::Bytes;
 use axum::http::StatusCode;
 use bytes::{buf::Reader, Buf};
@@ -12,6 +16,8 @@ use multiaddr::Multiaddr;
 use prometheus::proto;
 use prost::Message;
 use protobuf::CodedInputStream;
+use serde::Serialize;
+use std::collections::HashSet;
 use std::io::Read;
 use tracing::{debug, error};
 
@@ -25,6 +31,40 @@ pub struct NodeMetric {
     pub data: Bytes,                  // raw post data from node
 }
 
+/// StrictParse enables full validation of the decoded metric families before any of them
+/// are forwarded, see validate_metric_families
+#[derive(Clone, Copy, Debug)]
+pub struct StrictParse(pub bool);
+
+/// PushSummary counts what became of the samples, one per metric, of a single push.  pushers
+/// may ask for it as the response body, so they can tell when their metrics are being filtered
+/// out instead of forwarded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PushSummary {
+    /// the samples decoded from the push
+    pub accepted: usize,
+    /// the samples no destination takes.  remote_write only takes counters and gauges, otlp
+    /// takes every type
+    pub dropped_by_filter: usize,
+    /// the samples pushed, or queued to be pushed, to at least one destination
+    pub forwarded: usize,
+}
+
+/// remote_write_samples counts the samples that are converted to remote_write timeseries, see
+/// the metric types Mimir supports
+fn remote_write_samples(metric_families: &[proto::MetricFamily]) -> usize {
+    metric_families
+        .iter()
+        .filter(|mf| {
+            matches!(
+                mf.get_field_type(),
+                proto::MetricType::COUNTER | proto::MetricType::GAUGE
+            )
+        })
+        .map(|mf| mf.get_metric().len())
+        .sum()
+}
+
 /// The ProtobufDecoder will decode message delimited protobuf messages from prom_model.proto types
 /// They are delimited by size, eg a format is such:
 /// []byte{size, data, size, data, size, data}, etc etc
@@ -52,14 +92,83 @@ impl ProtobufDecoder {
     }
 }
 
+/// validate_metric_families checks what decoding alone lets through, as every protobuf field
+/// is optional: metric and label names must be valid, label names unique within a metric,
+/// and each metric must carry a value of its family's type (counter if unset).  histogram
+/// buckets must be sorted with cumulative counts.
+pub fn validate_metric_families(metric_families: &[proto::MetricFamily]) -> Result<()> {
+    for mf in metric_families {
+        let name = mf.get_name();
+        if !is_valid_metric_name(name) {
+            bail!("invalid metric name {name:?}");
+        }
+        for m in mf.get_metric() {
+            let mut label_names = HashSet::new();
+            for label in m.get_label() {
+                if !is_valid_label_name(label.get_name()) {
+                    bail!(
+                        "metric {name} has an invalid label name {:?}",
+                        label.get_name()
+                    );
+                }
+                if !label_names.insert(label.get_name()) {
+                    bail!("metric {name} has a duplicate label {}", label.get_name());
+                }
+            }
+            let has_value = match mf.get_field_type() {
+                proto::MetricType::COUNTER => m.has_counter(),
+                proto::MetricType::GAUGE => m.has_gauge(),
+                proto::MetricType::SUMMARY => m.has_summary(),
+                proto::MetricType::UNTYPED => m.has_untyped(),
+                proto::MetricType::HISTOGRAM => {
+                    let buckets = m.get_histogram().get_bucket();
+                    if buckets.windows(2).any(|w| {
+                        w[0].get_upper_bound() >= w[1].get_upper_bound()
+                            || w[0].get_cumulative_count() > w[1].get_cumulative_count()
+                    }) {
+                        bail!("metric {name} has unsorted or non-cumulative histogram buckets");
+                    }
+                    m.has_histogram()
+                }
+            };
+            if !has_value {
+                bail!("metric {name} has no {:?} value", mf.get_field_type());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// metric names match [a-zA-Z_:][a-zA-Z0-9_:]*
+fn is_valid_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+/// label names match [a-zA-Z_][a-zA-Z0-9_]*, names starting with __ are reserved
+fn is_valid_label_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with("__")
+}
+
+/// convert_to_remote_write forwards the metrics of a push to its destinations, counting what
+/// became of its samples in summary.  every destination is pushed to even if another one fails,
+/// and the worst failure is returned.  a peer mapped to a shard has that shard as its only
+/// remote_write destination, metrics are only broadcast when no shards are configured
 pub async fn convert_to_remote_write(
     rc: ReqwestClient,
     nm: NodeMetric,
+    strict_parse: StrictParse,
+    summary: &mut PushSummary,
 ) -> (StatusCode, &'static str) {
     let mut decoder = ProtobufDecoder::new(nm.data.reader());
     let mut decoded = match decoder.parse::<proto::MetricFamily>() {
         Ok(metrics) => metrics,
         Err(error) => {
+            rc.metrics.malformed_payloads.inc();
             error!("unable to decode Vec<MetricFamily> from bytes provided by node; {error}");
             return (
                 StatusCode::BAD_REQUEST,
@@ -67,6 +176,18 @@ pub async fn convert_to_remote_write(
             );
         }
     };
+    if strict_parse.0 {
+        if let Err(error) = validate_metric_families(&decoded) {
+            rc.metrics.malformed_payloads.inc();
+            error!("rejecting malformed metrics from {}; {error}", nm.name);
+            return (StatusCode::BAD_REQUEST, "malformed metrics payload");
+        }
+    }
+    let remote_write_samples = remote_write_samples(&decoded);
+    summary.accepted = decoded.iter().map(|mf| mf.get_metric().len()).sum();
+    if rc.otlp.is_none() {
+        summary.dropped_by_filter = summary.accepted - remote_write_samples;
+    }
 
     // proto::LabelPair doesn't have pub fields so we can't use
     // struct literals to construct
@@ -87,90 +208,568 @@ pub async fn convert_to_remote_write(
         }
     }
 
-    for timeseries in Mimir::from(decoded) {
-        let mut buf = Vec::new();
-        buf.reserve(timeseries.encoded_len());
-        let Ok(()) = timeseries.encode(&mut buf) else {
-            error!("unable to encode prompb to mimirpb");
+    // the otlp metrics are converted from the families as labeled above, so they carry the
+    // same attributes as the remote_write timeseries
+    let otlp_metrics = rc.otlp.as_ref().map(|_| to_otlp(&decoded));
+    let destinations = match &rc.otlp {
+        Some(otlp) if otlp.replace_remote_write => vec![],
+        _ => rc.router.route(&nm.public_key),
+    };
+    let mut dropped = false;
+    if let Some(batcher) = &rc.batcher {
+        if !destinations.is_empty() {
+            let timeseries: Vec<TimeSeries> = Mimir::from(decoded)
+                .into_iter()
+                .flat_map(|write_request| write_request.timeseries)
+                .collect();
+            for settings in destinations {
+                if batcher.enqueue(settings.clone(), timeseries.clone()) {
+                    summary.forwarded = remote_write_samples;
+                } else {
+                    dropped = true;
+                }
+            }
+        }
+        if let Some(metrics) = otlp_metrics {
+            if batcher.enqueue_otlp(metrics) {
+                summary.forwarded = summary.accepted;
+            } else {
+                dropped = true;
+            }
+        }
+        if dropped {
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "unable to encode prompb to remote_write pb",
+                StatusCode::SERVICE_UNAVAILABLE,
+                "DROPPING METRICS while the batch queue is full",
             );
-        };
+        }
+        return (StatusCode::ACCEPTED, "accepted");
+    }
 
-        let mut s = snap::raw::Encoder::new();
-        let compressed = match s.compress_vec(&buf) {
+    let mut failure = None;
+    let write_requests: Vec<WriteRequest> = if destinations.is_empty() {
+        vec![]
+    } else {
+        Mimir::from(decoded).into_iter().collect()
+    };
+    for timeseries in write_requests {
+        let compressed = match compress(&timeseries) {
             Ok(compressed) => compressed,
-            Err(error) => {
-                error!("unable to compress to snappy block format; {error}");
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "unable to compress to snappy block format",
-                );
-            }
-        };
-
-        let response = match rc
-            .client
-            .post(rc.settings.url.to_owned())
-            .header(reqwest::header::CONTENT_ENCODING, "snappy")
-            .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf")
-            .header("X-Prometheus-Remote-Write-Version", "0.1.0")
-            .basic_auth(
-                rc.settings.username.to_owned(),
-                Some(rc.settings.password.to_owned()),
-            )
-            .body(compressed)
-            .send()
-            .await
-        {
-            Ok(response) => response,
-            Err(error) => {
-                error!("DROPPING METRICS due to post error: {error}");
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "DROPPING METRICS due to post error",
-                );
-            }
+            Err(error) => return error,
         };
-
-        match response.status() {
-            reqwest::StatusCode::OK => {
-                debug!("({}) SUCCESS: {:?}", reqwest::StatusCode::OK, timeseries);
+        let mut forwarded = false;
+        for settings in &destinations {
+            match push_unless_open(&rc, settings, &timeseries, compressed.clone()).await {
+                Ok(pushed) => {
+                    dropped |= !pushed;
+                    forwarded |= pushed;
+                }
+                Err(error) => failure = worst(failure, error),
             }
-            reqwest::StatusCode::BAD_REQUEST => {
-                error!("TRIED: {:?}", timeseries);
-                let body = response
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "response body cannot be decoded".into());
-
-                if body.contains("err-mimir-sample-out-of-order") {
-                    error!("({}) ERROR: {:?}", reqwest::StatusCode::BAD_REQUEST, body);
-                    return (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "IGNORNING METRICS due to err-mimir-sample-out-of-order",
-                    );
+        }
+        if forwarded {
+            summary.forwarded += timeseries.timeseries.len();
+        }
+    }
+    if let (Some(settings), Some(metrics)) = (&rc.otlp, otlp_metrics) {
+        match push_otlp_unless_open(&rc, settings, metrics).await {
+            Ok(pushed) => {
+                dropped |= !pushed;
+                if pushed {
+                    summary.forwarded = summary.accepted;
                 }
-                error!("({}) ERROR: {:?}", reqwest::StatusCode::BAD_REQUEST, body);
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "unknown bad request error encountered in remote_push",
-                );
             }
-            code => {
-                error!("TRIED: {:?}", timeseries);
-                let body = response
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "response body cannot be decoded".into());
-                error!("({}) ERROR: {:?}", code, body);
-                return (
+            Err(error) => failure = worst(failure, error),
+        }
+    }
+    if let Some(failure) = failure {
+        return failure;
+    }
+    if dropped {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "DROPPING METRICS while a circuit breaker is open",
+        );
+    }
+    (StatusCode::CREATED, "created")
+}
+
+/// worst keeps the failure with the highest status code, so that a server error outranks a
+/// client one
+fn worst(
+    failure: Option<(StatusCode, &'static str)>,
+    error: (StatusCode, &'static str),
+) -> Option<(StatusCode, &'static str)> {
+    match failure {
+        Some(failure) if failure.0 >= error.0 => Some(failure),
+        _ => Some(error),
+    }
+}
+
+/// compress encodes a write request as protobuf, compressed in the snappy block format that
+/// remote_write expects
+pub fn compress(timeseries: &WriteRequest) -> Result<Vec<u8>, (StatusCode, &'static str)> {
+    let mut buf = Vec::new();
+    buf.reserve(timeseries.encoded_len());
+    let Ok(()) = timeseries.encode(&mut buf) else {
+        error!("unable to encode prompb to mimirpb");
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "unable to encode prompb to remote_write pb",
+        ));
+    };
+
+    let mut s = snap::raw::Encoder::new();
+    s.compress_vec(&buf).map_err(|error| {
+        error!("unable to compress to snappy block format; {error}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "unable to compress to snappy block format",
+        )
+    })
+}
+
+/// push_unless_open pushes a compressed write request to one remote_write destination unless
+/// the destination's circuit breaker is open.  returns false if the push was dropped
+pub async fn push_unless_open(
+    rc: &ReqwestClient,
+    settings: &RemoteWriteConfig,
+    timeseries: &WriteRequest,
+    compressed: Vec<u8>,
+) -> Result<bool, (StatusCode, &'static str)> {
+    if let Some(circuit_breakers) = &rc.circuit_breakers {
+        if !circuit_breakers.allow(&settings.url) {
+            rc.metrics.pushes.with_label_values(&["circuit_open"]).inc();
+            return Ok(false);
+        }
+    }
+    push(rc, settings, timeseries, compressed).await?;
+    Ok(true)
+}
+
+/// push posts a single snappy compressed write request to one remote_write destination.
+/// the destination's circuit breaker counts posts that fail or get a server error, a bad
+/// request is the payload's fault and shows the destination is up
+async fn push(
+    rc: &ReqwestClient,
+    settings: &RemoteWriteConfig,
+    timeseries: &WriteRequest,
+    compressed: Vec<u8>,
+) -> Result<(), (StatusCode, &'static str)> {
+    let record = |success| {
+        if let Some(circuit_breakers) = &rc.circuit_breakers {
+            circuit_breakers.record(&settings.url, success);
+        }
+    };
+    let timer = rc.metrics.push_latency.start_timer();
+    let response = match rc
+        .client
+        .post(settings.url.to_owned())
+        .header(reqwest::header::CONTENT_ENCODING, "snappy")
+        .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf")
+        .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+        .basic_auth(
+            settings.username.to_owned(),
+            Some(settings.password.to_owned()),
+        )
+        .body(compressed)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(error) => {
+            record(false);
+            rc.metrics.pushes.with_label_values(&["post_error"]).inc();
+            error!("DROPPING METRICS due to post error: {error}");
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "DROPPING METRICS due to post error",
+            ));
+        }
+    };
+    timer.observe_duration();
+    record(!response.status().is_server_error());
+
+    match response.status() {
+        reqwest::StatusCode::OK => {
+            rc.metrics.pushes.with_label_values(&["success"]).inc();
+            debug!("({}) SUCCESS: {:?}", reqwest::StatusCode::OK, timeseries);
+        }
+        reqwest::StatusCode::BAD_REQUEST => {
+            error!("TRIED: {:?}", timeseries);
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "response body cannot be decoded".into());
+
+            if body.contains("err-mimir-sample-out-of-order") {
+                rc.metrics.pushes.with_label_values(&["out_of_order"]).inc();
+                error!("({}) ERROR: {:?}", reqwest::StatusCode::BAD_REQUEST, body);
+                return Err((
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    "unknown error encountered in remote_push",
-                );
+                    "IGNORNING METRICS due to err-mimir-sample-out-of-order",
+                ));
             }
+            rc.metrics.pushes.with_label_values(&["bad_request"]).inc();
+            error!("({}) ERROR: {:?}", reqwest::StatusCode::BAD_REQUEST, body);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "unknown bad request error encountered in remote_push",
+            ));
+        }
+        code => {
+            rc.metrics.pushes.with_label_values(&["error"]).inc();
+            error!("TRIED: {:?}", timeseries);
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "response body cannot be decoded".into());
+            error!("({}) ERROR: {:?}", code, body);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "unknown error encountered in remote_push",
+            ));
         }
     }
-    (StatusCode::CREATED, "created")
+    Ok(())
+}
+
+/// push_otlp_unless_open posts metrics to the otlp endpoint unless its circuit breaker is open.
+/// returns false if the push was dropped
+pub async fn push_otlp_unless_open(
+    rc: &ReqwestClient,
+    settings: &OtlpConfig,
+    metrics: Vec<otlp::Metric>,
+) -> Result<bool, (StatusCode, &'static str)> {
+    if let Some(circuit_breakers) = &rc.circuit_breakers {
+        if !circuit_breakers.allow(&settings.url) {
+            rc.metrics
+                .otlp_pushes
+                .with_label_values(&["circuit_open"])
+                .inc();
+            return Ok(false);
+        }
+    }
+    push_otlp(rc, settings, metrics).await?;
+    Ok(true)
+}
+
+/// push_otlp posts metrics as a single protobuf encoded export request to the otlp/http
+/// endpoint.  as with remote_write, only failed posts and server errors count against the
+/// endpoint's circuit breaker
+async fn push_otlp(
+    rc: &ReqwestClient,
+    settings: &OtlpConfig,
+    metrics: Vec<otlp::Metric>,
+) -> Result<(), (StatusCode, &'static str)> {
+    let record = |success| {
+        if let Some(circuit_breakers) = &rc.circuit_breakers {
+            circuit_breakers.record(&settings.url, success);
+        }
+    };
+    let mut request = rc
+        .client
+        .post(settings.url.to_owned())
+        .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf");
+    for (name, value) in &settings.headers {
+        request = request.header(name, value);
+    }
+    let response = match request
+        .body(export_request(metrics).encode_to_vec())
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(error) => {
+            record(false);
+            rc.metrics
+                .otlp_pushes
+                .with_label_values(&["post_error"])
+                .inc();
+            error!("DROPPING METRICS due to otlp post error: {error}");
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "DROPPING METRICS due to otlp post error",
+            ));
+        }
+    };
+    record(!response.status().is_server_error());
+
+    let code = response.status();
+    if code.is_success() {
+        rc.metrics.otlp_pushes.with_label_values(&["success"]).inc();
+        debug!("({code}) SUCCESS: posted metrics to otlp");
+        return Ok(());
+    }
+    let outcome = match code {
+        reqwest::StatusCode::BAD_REQUEST => "bad_request",
+        _ => "error",
+    };
+    rc.metrics.otlp_pushes.with_label_values(&[outcome]).inc();
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "response body cannot be decoded".into());
+    error!("({code}) ERROR: otlp push failed: {body:?}");
+    Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "unknown error encountered in otlp push",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::{generate_self_cert, make_reqwest_client};
+    use crate::config::OtlpConfig;
+    use crate::prom_to_mimir::tests::*;
+    use axum::{routing::post, Extension, Router};
+    use prometheus::Encoder;
+    use protobuf::RepeatedField;
+    use std::sync::{Arc, Mutex};
+
+    fn counter_family(name: &str, labels: Vec<(&str, &str)>) -> proto::MetricFamily {
+        create_metric_family(
+            name,
+            "some help this is",
+            Some(proto::MetricType::COUNTER),
+            RepeatedField::from_vec(vec![create_metric_counter(
+                RepeatedField::from_vec(create_labels(labels)),
+                create_counter(2046.0),
+            )]),
+        )
+    }
+
+    #[test]
+    fn validate_metric_families_accepts_well_formed() {
+        let mfs = vec![
+            counter_family("foo_metric", vec![("some", "label")]),
+            counter_family("foo:bar_total", vec![]),
+        ];
+        validate_metric_families(&mfs).unwrap();
+    }
+
+    #[test]
+    fn validate_metric_families_rejects_malformed() {
+        for mf in [
+            counter_family("", vec![]),
+            counter_family("1foo", vec![]),
+            counter_family("foo-metric", vec![]),
+            counter_family("foo_metric", vec![("some-label", "value")]),
+            counter_family("foo_metric", vec![("__name__", "value")]),
+            counter_family("foo_metric", vec![("some", "a"), ("some", "b")]),
+        ] {
+            assert!(validate_metric_families(&[mf]).is_err());
+        }
+
+        // a gauge family whose metric only has a counter value
+        let mut mf = counter_family("foo_metric", vec![]);
+        mf.set_field_type(proto::MetricType::GAUGE);
+        assert!(validate_metric_families(&[mf]).is_err());
+    }
+
+    #[tokio::test]
+    async fn otlp_replaces_remote_write() {
+        // a mock otlp endpoint that keeps what it is sent
+        async fn handler(
+            Extension(received): Extension<Arc<Mutex<Vec<Bytes>>>>,
+            body: Bytes,
+        ) -> StatusCode {
+            received.lock().unwrap().push(body);
+            StatusCode::OK
+        }
+        let received = Arc::new(Mutex::new(vec![]));
+        let app = Router::new()
+            .route("/v1/metrics", post(handler))
+            .layer(Extension(received.clone()));
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let otlp_url = format!(
+            "http://localhost:{}/v1/metrics",
+            listener.local_addr().unwrap().port()
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        // nothing listens on the remote_write url, a push to it would fail the request
+        let rc = make_reqwest_client(
+            RemoteWriteConfig {
+                url: "http://localhost:1/v1/push".into(),
+                username: "bar".into(),
+                password: "foo".into(),
+            },
+            vec![],
+            Some(OtlpConfig {
+                url: otlp_url,
+                headers: Default::default(),
+                replace_remote_write: true,
+            }),
+            None,
+            &prometheus::Registry::new(),
+        );
+
+        let mf = counter_family("foo_metric", vec![("some", "label")]);
+        let mut data = vec![];
+        prometheus::ProtobufEncoder::new()
+            .encode(&[mf], &mut data)
+            .unwrap();
+        let nm = NodeMetric {
+            name: "some-node".into(),
+            network: "unittest-network".into(),
+            peer_addr: Multiaddr::empty(),
+            public_key: generate_self_cert("sui".into()).1,
+            data: data.into(),
+        };
+        let mut summary = PushSummary::default();
+        let (status, _) = convert_to_remote_write(rc, nm, StrictParse(true), &mut summary).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(
+            summary,
+            PushSummary {
+                accepted: 1,
+                dropped_by_filter: 0,
+                forwarded: 1,
+            }
+        );
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let request = otlp::ExportMetricsServiceRequest::decode(received[0].clone())
+            .expect("the otlp endpoint should be sent an export request");
+        let metrics = &request.resource_metrics[0].scope_metrics[0].metrics;
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].name, "foo_metric");
+        let Some(otlp::metric::Data::Sum(sum)) = &metrics[0].data else {
+            panic!("a counter should be sent as a sum");
+        };
+        // the node's host and network are added to the attributes as they are to the labels
+        let keys: Vec<_> = sum.data_points[0]
+            .attributes
+            .iter()
+            .map(|attribute| attribute.key.as_str())
+            .collect();
+        assert_eq!(keys, vec!["host", "network", "some"]);
+    }
+
+    #[tokio::test]
+    async fn failed_destination_does_not_stop_the_others() {
+        // a mock otlp endpoint that counts what it is sent
+        let received = Arc::new(Mutex::new(0));
+        let counter = received.clone();
+        let app = Router::new().route(
+            "/v1/metrics",
+            post(move || async move {
+                *counter.lock().unwrap() += 1;
+                StatusCode::OK
+            }),
+        );
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let otlp_url = format!(
+            "http://localhost:{}/v1/metrics",
+            listener.local_addr().unwrap().port()
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        // nothing listens on the remote_write url, the push to it fails
+        let rc = make_reqwest_client(
+            RemoteWriteConfig {
+                url: "http://localhost:1/v1/push".into(),
+                username: "bar".into(),
+                password: "foo".into(),
+            },
+            vec![],
+            Some(OtlpConfig {
+                url: otlp_url,
+                headers: Default::default(),
+                replace_remote_write: false,
+            }),
+            None,
+            &prometheus::Registry::new(),
+        );
+
+        let mut data = vec![];
+        prometheus::ProtobufEncoder::new()
+            .encode(&[counter_family("foo_metric", vec![])], &mut data)
+            .unwrap();
+        let nm = NodeMetric {
+            name: "some-node".into(),
+            network: "unittest-network".into(),
+            peer_addr: Multiaddr::empty(),
+            public_key: generate_self_cert("sui".into()).1,
+            data: data.into(),
+        };
+        let mut summary = PushSummary::default();
+        let (status, _) = convert_to_remote_write(rc, nm, StrictParse(true), &mut summary).await;
+        // the failure is returned once the otlp endpoint was sent the metrics too
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(*received.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn push_summary_counts_filtered_samples() {
+        // a mock remote_write endpoint that accepts everything
+        let app = Router::new().route("/v1/push", post(|| async { StatusCode::OK }));
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let url = format!(
+            "http://localhost:{}/v1/push",
+            listener.local_addr().unwrap().port()
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        let rc = make_reqwest_client(
+            RemoteWriteConfig {
+                url,
+                username: "bar".into(),
+                password: "foo".into(),
+            },
+            vec![],
+            None,
+            None,
+            &prometheus::Registry::new(),
+        );
+
+        // remote_write does not take histograms yet
+        let mut histogram = proto::Metric::default();
+        histogram.set_histogram(proto::Histogram::default());
+        let mfs = [
+            counter_family("foo_metric", vec![("some", "label")]),
+            create_metric_family(
+                "foo_latency",
+                "some help this is",
+                Some(proto::MetricType::HISTOGRAM),
+                RepeatedField::from_vec(vec![histogram]),
+            ),
+        ];
+        let mut data = vec![];
+        prometheus::ProtobufEncoder::new()
+            .encode(&mfs, &mut data)
+            .unwrap();
+        let nm = NodeMetric {
+            name: "some-node".into(),
+            network: "unittest-network".into(),
+            peer_addr: Multiaddr::empty(),
+            public_key: generate_self_cert("sui".into()).1,
+            data: data.into(),
+        };
+        let mut summary = PushSummary::default();
+        let (status, _) = convert_to_remote_write(rc, nm, StrictParse(false), &mut summary).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(
+            summary,
+            PushSummary {
+                accepted: 2,
+                dropped_by_filter: 1,
+                forwarded: 1,
+            }
+        );
+    }
 }
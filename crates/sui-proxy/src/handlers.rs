@@ -1,36 +1,54 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::admin::ReqwestClient;
-use crate::consumer::{convert_to_remote_write, NodeMetric};
-use crate::peers::SuiPeer;
+use crate::consumer::{convert_to_remote_write, NodeMetric, PushSummary, StrictParse};
+use crate::pagination::{paginate, Page, PageLimits, PageQuery};
+use crate::peers::{SuiNodeProvider, SuiPeer};
 use axum::{
     body::Body,
-    extract::{ConnectInfo, Extension},
+    extract::{ConnectInfo, Extension, Query},
     http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
 };
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::traits::ToFromBytes;
 use multiaddr::Multiaddr;
+use serde::Serialize;
+use serde_json::{json, Value};
 use std::net::SocketAddr;
 
+/// Pushers that send this header are answered with a json summary of their push instead of a
+/// plain text status, see PushSummary
+pub const PUSH_SUMMARY_HEADER: &str = "x-sui-proxy-push-summary";
+
 /// Publish handler which receives metrics from nodes.  Nodes will call us at this endpoint
 /// and we relay them to the upstream tsdb
 ///
-/// An mpsc is used within this handler so that we can immediately return an accept to calling nodes.
-/// Downstream processing failures may still result in metrics being dropped.
+/// If batching is configured, an mpsc is used within this handler so that we can immediately
+/// return an accept to calling nodes.  Downstream processing failures may still result in
+/// metrics being dropped.
+///
+/// If the request carries the PUSH_SUMMARY_HEADER, the response body is
+/// {"message": .., "summary": {"accepted": .., "dropped_by_filter": .., "forwarded": ..}}
 pub async fn publish_metrics(
     Extension(network): Extension<String>,
     Extension(client): Extension<ReqwestClient>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(peer): Extension<SuiPeer>,
+    Extension(strict_parse): Extension<StrictParse>,
     request: Request<Body>,
-) -> (StatusCode, &'static str) {
+) -> Response {
+    let push_summary = request.headers().contains_key(PUSH_SUMMARY_HEADER);
     let data = match hyper::body::to_bytes(request.into_body()).await {
         Ok(data) => data,
         Err(_e) => {
-            return (StatusCode::BAD_REQUEST, "unable to extract post body");
+            return (StatusCode::BAD_REQUEST, "unable to extract post body").into_response();
         }
     };
 
-    convert_to_remote_write(
+    let mut summary = PushSummary::default();
+    let (status, message) = convert_to_remote_write(
         client.clone(),
         NodeMetric {
             name: peer.name,
@@ -39,6 +57,69 @@ pub async fn publish_metrics(
             peer_addr: Multiaddr::from(addr.ip()),
             public_key: peer.public_key,
         },
+        strict_parse,
+        &mut summary,
     )
-    .await
+    .await;
+    if push_summary {
+        let body = json!({ "message": message, "summary": summary });
+        return (status, Json(body)).into_response();
+    }
+    (status, message).into_response()
+}
+
+/// Refresh handler which updates the peer allow list right away instead of waiting for the
+/// next poll, so that a newly registered validator is accepted sooner.  Responds with the
+/// number of peers on the updated list.
+pub async fn refresh_peers(
+    Extension(allower): Extension<SuiNodeProvider>,
+) -> (StatusCode, Json<Value>) {
+    match allower.refresh().await {
+        Ok(peers) => (StatusCode::OK, Json(json!({ "peers": peers }))),
+        Err(error) => (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({ "error": format!("unable to refresh peer list: {error}") })),
+        ),
+    }
+}
+
+/// PeerInfo is how /admin/peers lists a peer on the allow list
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct PeerInfo {
+    /// the hex encoded public key, also the cursor of the listing
+    pub public_key: String,
+    pub name: String,
+    pub p2p_address: String,
+}
+
+impl From<&SuiPeer> for PeerInfo {
+    fn from(peer: &SuiPeer) -> Self {
+        Self {
+            public_key: Hex::encode(peer.public_key.as_bytes()),
+            name: peer.name.clone(),
+            p2p_address: peer.p2p_address.to_string(),
+        }
+    }
+}
+
+/// Peers handler which lists the peers on the allow list, ordered by public key.  The list is
+/// paged within the admin api's page limits, see paginate.
+pub async fn list_peers(
+    Extension(allower): Extension<SuiNodeProvider>,
+    Extension(limits): Extension<PageLimits>,
+    Query(query): Query<PageQuery>,
+) -> Json<Page<PeerInfo>> {
+    let mut peers: Vec<PeerInfo> = allower
+        .get_ref()
+        .load()
+        .values()
+        .map(PeerInfo::from)
+        .collect();
+    peers.sort_by(|a, b| a.public_key.cmp(&b.public_key));
+    Json(paginate(
+        peers,
+        |peer| peer.public_key.as_str(),
+        &query,
+        &limits,
+    ))
 }
@@ -0,0 +1,169 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use axum_server::accept::Accept;
+use prometheus::{
+    register_int_counter_vec_with_registry, register_int_counter_with_registry, IntCounter,
+    IntCounterVec, Registry,
+};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use tracing::debug;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// TlsHandshakeMetrics counts the outcome of the tls handshakes of inbound connections, so that a
+/// spike of rejected clients, e.g. validators whose keys the allowlist has not picked up yet, can
+/// be told apart from noise
+#[derive(Debug)]
+pub struct TlsHandshakeMetrics {
+    pub(crate) successes: IntCounter,
+    /// failed handshakes, labeled by the reason reported by rustls where there is one
+    pub(crate) failures: IntCounterVec,
+}
+
+impl TlsHandshakeMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            successes: register_int_counter_with_registry!(
+                "proxy_tls_handshake_successes_total",
+                "Total number of inbound tls handshakes that succeeded",
+                registry,
+            )
+            .unwrap(),
+            failures: register_int_counter_vec_with_registry!(
+                "proxy_tls_handshake_failures_total",
+                "Total number of inbound tls handshakes that failed by reason",
+                &["reason"],
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// handshake_failure_reason names the cause of a failed handshake.  rustls errors are handed
+/// back wrapped in an io error by tokio-rustls, any other io error comes from the socket or the
+/// handshake timeout
+pub(crate) fn handshake_failure_reason(error: &io::Error) -> &'static str {
+    let rustls_error = error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<rustls::Error>());
+    if let Some(rustls_error) = rustls_error {
+        return match rustls_error {
+            rustls::Error::NoCertificatesPresented => "no_certificate",
+            rustls::Error::InvalidCertificateEncoding
+            | rustls::Error::InvalidCertificateSignatureType
+            | rustls::Error::InvalidCertificateSignature
+            | rustls::Error::InvalidCertificateData(_)
+            | rustls::Error::InvalidSct(_) => "invalid_certificate",
+            rustls::Error::AlertReceived(_) => "alert_received",
+            rustls::Error::PeerIncompatibleError(_) | rustls::Error::NoApplicationProtocol => {
+                "incompatible"
+            }
+            rustls::Error::InappropriateMessage { .. }
+            | rustls::Error::InappropriateHandshakeMessage { .. }
+            | rustls::Error::CorruptMessage
+            | rustls::Error::CorruptMessagePayload(_)
+            | rustls::Error::DecryptError
+            | rustls::Error::PeerMisbehavedError(_)
+            | rustls::Error::PeerSentOversizedRecord => "protocol_violation",
+            _ => "other",
+        };
+    }
+    match error.kind() {
+        io::ErrorKind::TimedOut => "timeout",
+        io::ErrorKind::UnexpectedEof => "eof",
+        io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::BrokenPipe => "connection_reset",
+        _ => "io",
+    }
+}
+
+/// MeteredTlsAcceptor records the outcome of each handshake of the inner tls acceptor in the
+/// metrics.  Without metrics every connection is handed over untouched.
+#[derive(Debug, Clone)]
+pub struct MeteredTlsAcceptor<A> {
+    inner: A,
+    metrics: Option<Arc<TlsHandshakeMetrics>>,
+}
+
+impl<A> MeteredTlsAcceptor<A> {
+    pub fn new(inner: A, metrics: Option<Arc<TlsHandshakeMetrics>>) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+impl<A, I, S> Accept<I, S> for MeteredTlsAcceptor<A>
+where
+    A: Accept<I, S> + 'static,
+    A::Future: Send + 'static,
+    I: 'static,
+    S: 'static,
+{
+    type Stream = A::Stream;
+    type Service = A::Service;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let handshake = self.inner.accept(stream, service);
+        let metrics = self.metrics.clone();
+
+        Box::pin(async move {
+            let result = handshake.await;
+            if let Some(metrics) = &metrics {
+                match &result {
+                    Ok(_) => metrics.successes.inc(),
+                    Err(error) => {
+                        let reason = handshake_failure_reason(error);
+                        debug!("tls handshake failed ({reason}): {error}");
+                        metrics.failures.with_label_values(&[reason]).inc();
+                    }
+                }
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handshake_failures_are_labeled_by_reason() {
+        let rustls_error = |error: rustls::Error| io::Error::new(io::ErrorKind::InvalidData, error);
+
+        assert_eq!(
+            handshake_failure_reason(&rustls_error(rustls::Error::InvalidCertificateData(
+                "invalid certificate: UnknownIssuer".to_string()
+            ))),
+            "invalid_certificate"
+        );
+        assert_eq!(
+            handshake_failure_reason(&rustls_error(rustls::Error::NoCertificatesPresented)),
+            "no_certificate"
+        );
+        assert_eq!(
+            handshake_failure_reason(&rustls_error(rustls::Error::CorruptMessage)),
+            "protocol_violation"
+        );
+        assert_eq!(
+            handshake_failure_reason(&io::Error::new(io::ErrorKind::TimedOut, "timeout")),
+            "timeout"
+        );
+        assert_eq!(
+            handshake_failure_reason(&io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "tls handshake eof"
+            )),
+            "eof"
+        );
+        assert_eq!(
+            handshake_failure_reason(&io::ErrorKind::ConnectionReset.into()),
+            "connection_reset"
+        );
+    }
+}
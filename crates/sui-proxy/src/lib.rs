@@ -1,20 +1,36 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+pub mod access_log;
 pub mod admin;
+pub mod batcher;
+pub mod circuit_breaker;
 pub mod config;
+pub mod connection_limiter;
 pub mod consumer;
 pub mod handlers;
+pub mod handshake_metrics;
+pub mod metrics;
 pub mod middleware;
+pub mod otlp;
+pub mod pagination;
 pub mod peers;
 pub mod prom_to_mimir;
+pub mod prom_to_otlp;
+pub mod rate_limiter;
 pub mod remote_write;
+pub mod sharding;
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::prom_to_mimir::tests::*;
 
-    use crate::{config::RemoteWriteConfig, peers::SuiNodeProvider};
+    use crate::{
+        config::{AccessLogConfig, ConnectionLimitConfig, RemoteWriteConfig},
+        connection_limiter::ConnectionLimiter,
+        handshake_metrics::TlsHandshakeMetrics,
+        peers::SuiNodeProvider,
+    };
     use axum::http::{header, StatusCode};
     use axum::routing::post;
     use axum::Router;
@@ -22,10 +38,17 @@ mod tests {
     use prometheus::Encoder;
     use prometheus::PROTOBUF_FORMAT;
     use protobuf::RepeatedField;
+    use std::collections::HashMap;
     use std::net::TcpListener;
+    use std::sync::Arc;
     use std::time::Duration;
     use sui_tls::{CertVerifier, TlsAcceptor, TlsConnectionInfo};
 
+    const PAGE_LIMITS: pagination::PageLimits = pagination::PageLimits {
+        max_page_size: 100,
+        max_response_bytes: 1024 * 1024,
+    };
+
     async fn run_dummy_remote_write(listener: TcpListener) {
         /// i accept everything, send me the trash
         async fn handler() -> StatusCode {
@@ -65,7 +88,7 @@ mod tests {
             tokio::spawn(async move { run_dummy_remote_write(dummy_remote_write_listener).await });
 
         // init the tls config and allower
-        let mut allower = SuiNodeProvider::new("".into(), Duration::from_secs(30));
+        let allower = SuiNodeProvider::new("".into(), Duration::from_secs(30));
         let tls_config = CertVerifier::new(allower.clone())
             .rustls_server_config(
                 vec![server_priv_cert.rustls_certificate()],
@@ -73,17 +96,30 @@ mod tests {
             )
             .unwrap();
 
-        let client = admin::make_reqwest_client(RemoteWriteConfig {
-            url: dummy_remote_write_url.to_owned(),
-            username: "bar".into(),
-            password: "foo".into(),
-        });
+        let client = admin::make_reqwest_client(
+            RemoteWriteConfig {
+                url: dummy_remote_write_url.to_owned(),
+                username: "bar".into(),
+                password: "foo".into(),
+            },
+            vec![],
+            None,
+            None,
+            &prometheus::Registry::new(),
+        );
 
         // add handler to server
         async fn handler(tls_info: axum::Extension<TlsConnectionInfo>) -> String {
             tls_info.public_key().unwrap().to_string()
         }
-        let app = admin::app("unittest-network".into(), client, Some(allower.clone()));
+        let app = admin::app(
+            "unittest-network".into(),
+            client,
+            Some(allower.clone()),
+            None,
+            AccessLogConfig::default(),
+            true,
+        );
 
         let listener = std::net::TcpListener::bind("localhost:0").unwrap();
         let server_address = listener.local_addr().unwrap();
@@ -93,8 +129,20 @@ mod tests {
         );
 
         let acceptor = TlsAcceptor::new(tls_config);
+        let handshake_metrics = Arc::new(TlsHandshakeMetrics::new(&prometheus::Registry::new()));
+        let server_handshake_metrics = handshake_metrics.clone();
         let _server = tokio::spawn(async move {
-            admin::server(listener, app, Some(acceptor)).await.unwrap();
+            admin::server(
+                listener,
+                app,
+                Some(acceptor),
+                Duration::from_secs(30),
+                true,
+                None,
+                Some(server_handshake_metrics),
+            )
+            .await
+            .unwrap();
         });
 
         // build a client
@@ -109,14 +157,18 @@ mod tests {
         client.get(&server_url).send().await.unwrap_err();
 
         // Insert the client's public key into the allowlist and verify the request is successful
-        allower.get_mut().write().unwrap().insert(
-            client_pub_key.to_owned(),
-            peers::SuiPeer {
-                name: "some-node".into(),
-                p2p_address: Multiaddr::empty(),
-                public_key: client_pub_key.to_owned(),
-            },
-        );
+        allower.get_ref().rcu(|nodes| {
+            let mut nodes = HashMap::clone(nodes);
+            nodes.insert(
+                client_pub_key.to_owned(),
+                peers::SuiPeer {
+                    name: "some-node".into(),
+                    p2p_address: Multiaddr::empty(),
+                    public_key: client_pub_key.to_owned(),
+                },
+            );
+            nodes
+        });
 
         let mf = create_metric_family(
             "foo_metric",
@@ -140,8 +192,269 @@ mod tests {
             .await
             .expect("expected a successful post with a self-signed certificate");
         let status = res.status();
+        // the client negotiates h2 with alpn, and the publish handler still gets the peer
+        // address from ConnectInfo
+        assert_eq!(res.version(), reqwest::Version::HTTP_2);
         let body = res.text().await.unwrap();
         assert_eq!("created", body);
         assert_eq!(status, StatusCode::CREATED);
+
+        // the first handshake failed on the client certificate, the second one went through
+        assert_eq!(
+            handshake_metrics
+                .failures
+                .with_label_values(&["invalid_certificate"])
+                .get(),
+            1
+        );
+        assert_eq!(handshake_metrics.successes.get(), 1);
+    }
+
+    /// sni_selects_certificate checks that clients asking for a configured hostname with sni are
+    /// presented its certificate, and every other client the certificate the server was built with
+    #[tokio::test]
+    async fn sni_selects_certificate() {
+        let (client_priv_cert, _) = admin::generate_self_cert("sui".into());
+        let (default_priv_cert, _) = admin::generate_self_cert("localhost".into());
+        let (sni_priv_cert, _) = admin::generate_self_cert("metrics.sui.test".into());
+
+        let mut tls_config = CertVerifier::new(sui_tls::AllowAll)
+            .rustls_server_config(
+                vec![default_priv_cert.rustls_certificate()],
+                default_priv_cert.rustls_private_key(),
+            )
+            .unwrap();
+        admin::resolve_by_sni(
+            &mut tls_config,
+            vec![(
+                "Metrics.Sui.Test".into(),
+                vec![sni_priv_cert.rustls_certificate()],
+                sni_priv_cert.rustls_private_key(),
+            )],
+        )
+        .unwrap();
+
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let server_address = listener.local_addr().unwrap();
+        let app = Router::new().route("/", axum::routing::get(|| async { "ok" }));
+        let acceptor = TlsAcceptor::new(tls_config);
+        let _server = tokio::spawn(async move {
+            admin::server(
+                listener,
+                app,
+                Some(acceptor),
+                Duration::from_secs(30),
+                true,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        });
+
+        // each client only trusts one of the server certificates
+        let client = |trusted: &sui_tls::SelfSignedCertificate| {
+            reqwest::Client::builder()
+                .add_root_certificate(trusted.reqwest_certificate())
+                .identity(client_priv_cert.reqwest_identity())
+                .resolve("metrics.sui.test", server_address)
+                .https_only(true)
+                .build()
+                .unwrap()
+        };
+        let sni_url = format!("https://metrics.sui.test:{}/", server_address.port());
+        let default_url = format!("https://localhost:{}/", server_address.port());
+
+        client(&sni_priv_cert).get(&sni_url).send().await.unwrap();
+        client(&default_priv_cert)
+            .get(&sni_url)
+            .send()
+            .await
+            .unwrap_err();
+        client(&default_priv_cert)
+            .get(&default_url)
+            .send()
+            .await
+            .unwrap();
+        client(&sni_priv_cert)
+            .get(&default_url)
+            .send()
+            .await
+            .unwrap_err();
+    }
+
+    /// connections_over_the_per_ip_limit_are_closed checks that a connection from an ip that
+    /// already holds its quota of connections is closed unanswered, and that closing a connection
+    /// gives its slot back
+    #[tokio::test]
+    async fn connections_over_the_per_ip_limit_are_closed() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let server_address = listener.local_addr().unwrap();
+        let app = Router::new().route("/", axum::routing::get(|| async { "ok" }));
+        let limiter = Arc::new(ConnectionLimiter::new(
+            ConnectionLimitConfig {
+                max_connections_per_ip: 1,
+            },
+            &prometheus::Registry::new(),
+        ));
+        let _server = tokio::spawn(async move {
+            admin::server(
+                listener,
+                app,
+                None,
+                Duration::from_secs(30),
+                true,
+                Some(limiter),
+                None,
+            )
+            .await
+            .unwrap();
+        });
+
+        let request = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let mut buf = [0u8; 1024];
+        let mut first = TcpStream::connect(server_address).await.unwrap();
+        first.write_all(request).await.unwrap();
+        assert!(first.read(&mut buf).await.unwrap() > 0);
+
+        // the first connection is kept alive, so the second one is over the limit
+        let mut second = TcpStream::connect(server_address).await.unwrap();
+        assert!(matches!(second.read(&mut buf).await, Ok(0) | Err(_)));
+
+        // the slot is given back once the server notices the first connection is closed
+        drop(first);
+        let mut answered = false;
+        for _ in 0..50 {
+            let mut third = TcpStream::connect(server_address).await.unwrap();
+            let _ = third.write_all(request).await;
+            if matches!(third.read(&mut buf).await, Ok(n) if n > 0) {
+                answered = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(answered);
+    }
+
+    /// rate_limit_needs_an_allower checks that a rate limit is ignored when peers are not
+    /// validated, rather than failing every request on the missing peer
+    #[tokio::test]
+    async fn rate_limit_needs_an_allower() {
+        use tower::ServiceExt;
+
+        let client = admin::make_reqwest_client(
+            RemoteWriteConfig {
+                url: "http://localhost:1/v1/push".into(),
+                username: "bar".into(),
+                password: "foo".into(),
+            },
+            vec![],
+            None,
+            None,
+            &prometheus::Registry::new(),
+        );
+        let rate_limiter = Arc::new(rate_limiter::PeerRateLimiter::new(
+            config::RateLimitConfig {
+                requests_per_second: 1.0,
+                burst: 1,
+            },
+            &prometheus::Registry::new(),
+        ));
+        let app = admin::app(
+            "unittest-network".into(),
+            client,
+            None,
+            Some(rate_limiter),
+            AccessLogConfig::default(),
+            true,
+        );
+
+        // the request gets as far as the content-type check instead of erroring on the peer
+        let request = axum::http::Request::post("/publish/metrics")
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let res = app.oneshot(request).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// admin_refresh_requires_token checks that the admin api only acts on requests bearing the
+    /// configured token.  The rpc url is unreachable, so an authorized refresh fails upstream.
+    #[tokio::test]
+    async fn admin_refresh_requires_token() {
+        use tower::ServiceExt;
+
+        let allower = SuiNodeProvider::new("http://localhost:1".into(), Duration::from_secs(30));
+        let app = admin::admin_app("secret".into(), allower, PAGE_LIMITS);
+        let request = |token: Option<&str>| {
+            let mut builder = axum::http::Request::post("/admin/refresh-peers");
+            if let Some(token) = token {
+                builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+            builder.body(axum::body::Body::empty()).unwrap()
+        };
+
+        let res = app.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = app.clone().oneshot(request(Some("guess"))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = app.oneshot(request(Some("secret"))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    /// admin_peers_are_paged checks that the peer listing is split in pages joined by cursors
+    #[tokio::test]
+    async fn admin_peers_are_paged() {
+        use tower::ServiceExt;
+
+        let allower = SuiNodeProvider::new("http://localhost:1".into(), Duration::from_secs(30));
+        allower.get_ref().store(Arc::new(
+            ["a", "b", "c"]
+                .into_iter()
+                .map(|name| {
+                    let (_, public_key) = admin::generate_self_cert(name.into());
+                    let peer = peers::SuiPeer {
+                        name: name.into(),
+                        p2p_address: Multiaddr::empty(),
+                        public_key: public_key.clone(),
+                    };
+                    (public_key, peer)
+                })
+                .collect(),
+        ));
+        let app = admin::admin_app("secret".into(), allower, PAGE_LIMITS);
+        let get_page = |uri: String| {
+            let app = app.clone();
+            async move {
+                let request = axum::http::Request::get(uri)
+                    .header(header::AUTHORIZATION, "Bearer secret")
+                    .body(axum::body::Body::empty())
+                    .unwrap();
+                let res = app.oneshot(request).await.unwrap();
+                assert_eq!(res.status(), StatusCode::OK);
+                let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+
+        let first = get_page("/admin/peers?limit=2".into()).await;
+        assert_eq!(first["items"].as_array().unwrap().len(), 2);
+        let cursor = first["next_cursor"].as_str().unwrap();
+        let second = get_page(format!("/admin/peers?limit=2&cursor={cursor}")).await;
+        assert_eq!(second["items"].as_array().unwrap().len(), 1);
+        assert!(second["next_cursor"].is_null());
+
+        let mut names: Vec<_> = first["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .chain(second["items"].as_array().unwrap())
+            .map(|peer| peer["name"].as_str().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["a", "b", "c"]);
     }
 }
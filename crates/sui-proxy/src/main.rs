@@ -3,17 +3,24 @@
 
 use anyhow::Result;
 use clap::Parser;
+use std::sync::Arc;
 use sui_proxy::config::ProxyConfig;
 use sui_proxy::{
     admin::{
-        app, create_server_cert_default_allow, create_server_cert_enforce_peer,
-        make_reqwest_client, server,
+        app, certificate_not_after, create_server_cert_default_allow,
+        create_server_cert_enforce_peer, make_reqwest_client, reload_rate_limit_on_sighup, server,
+        set_alpn_protocols, set_sni_certificates, start_admin_server,
     },
+    batcher::Batcher,
     config::load,
+    connection_limiter::ConnectionLimiter,
+    handshake_metrics::TlsHandshakeMetrics,
+    metrics::start_prometheus_server,
+    rate_limiter::PeerRateLimiter,
 };
 use sui_tls::TlsAcceptor;
 use telemetry_subscribers::TelemetryConfig;
-use tracing::info;
+use tracing::{info, warn};
 
 #[derive(Parser, Debug)]
 #[clap(rename_all = "kebab-case")]
@@ -35,16 +42,34 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
 
-    let config: ProxyConfig = load(args.config)?;
+    let config: ProxyConfig = load(&args.config)?;
 
     info!(
         "listen on {:?} send to {:?}",
         config.listen_address, config.remote_write.url
     );
+    if let Some(otlp) = &config.otlp {
+        info!(
+            "send to otlp {:?}, replacing remote_write: {}",
+            otlp.url, otlp.replace_remote_write
+        );
+    }
 
     let listener = std::net::TcpListener::bind(config.listen_address).unwrap();
 
-    let (tls_config, allower) =
+    let registry = prometheus::default_registry();
+    if let Some(metrics_address) = config.metrics_address {
+        let metrics_listener = std::net::TcpListener::bind(metrics_address).unwrap();
+        start_prometheus_server(metrics_listener, registry.clone());
+    }
+
+    // the configured certificate is only used along with its private key
+    let certificate_file = config
+        .json_rpc
+        .private_key
+        .as_ref()
+        .and(config.json_rpc.certificate_file.clone());
+    let (mut tls_config, allower) =
         if config.json_rpc.certificate_file.is_none() || config.json_rpc.private_key.is_none() {
             (
                 create_server_cert_default_allow(config.json_rpc.hostname.unwrap())
@@ -55,11 +80,73 @@ async fn main() -> Result<()> {
             create_server_cert_enforce_peer(config.json_rpc)
                 .expect("unable to create tls server config")
         };
+    set_alpn_protocols(&mut tls_config, config.http2);
+    set_sni_certificates(&mut tls_config, &config.sni_certificates)
+        .expect("unable to load sni certificates");
     let acceptor = TlsAcceptor::new(tls_config);
-    let client = make_reqwest_client(config.remote_write);
-    let app = app(config.network, client, allower);
-    server(listener, app, Some(acceptor)).await.unwrap();
+    let mut client = make_reqwest_client(
+        config.remote_write,
+        config.remote_write_shards,
+        config.otlp,
+        config.circuit_breaker,
+        registry,
+    );
+    if let Some(not_after) = certificate_file.as_deref().and_then(certificate_not_after) {
+        client.metrics.certificate_not_after.set(not_after);
+    }
+    let batcher = config
+        .batch
+        .map(|batch| Batcher::spawn(&mut client, batch, registry));
+    let rate_limiter = config.rate_limit.map(|rate_limit| {
+        let rate_limiter = Arc::new(PeerRateLimiter::new(rate_limit, registry));
+        reload_rate_limit_on_sighup(args.config.clone(), rate_limiter.clone());
+        rate_limiter
+    });
+    let connection_limiter = config
+        .connection_limit
+        .map(|connection_limit| Arc::new(ConnectionLimiter::new(connection_limit, registry)));
+    match (config.admin, &allower) {
+        (Some(admin), Some(allower)) => {
+            let admin_listener = std::net::TcpListener::bind(admin.listen_address).unwrap();
+            let page_limits = admin.page_limits();
+            start_admin_server(
+                admin_listener,
+                admin.bearer_token,
+                allower.clone(),
+                page_limits,
+            );
+        }
+        (Some(_), None) => warn!("admin api is disabled, peers are not validated"),
+        (None, _) => {}
+    }
+    let app = app(
+        config.network,
+        client,
+        allower,
+        rate_limiter,
+        config.access_log,
+        config.strict_parse,
+    );
+    info!(
+        "graceful shutdown grace period is {} seconds",
+        config.shutdown_grace_period.as_secs()
+    );
+    server(
+        listener,
+        app,
+        Some(acceptor),
+        config.shutdown_grace_period,
+        config.http2,
+        connection_limiter,
+        Some(Arc::new(TlsHandshakeMetrics::new(registry))),
+    )
+    .await
+    .unwrap();
 
+    // the server dropped its clients, which lets the batcher flush or save what is pending and exit
+    if let Some(batcher) = batcher {
+        batcher.await?;
+    }
     Ok(())
 }
 
@@ -0,0 +1,112 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use axum::{extract::Extension, http::StatusCode, routing::get, Router};
+use prometheus::{
+    register_histogram_with_registry, register_int_counter_vec_with_registry,
+    register_int_counter_with_registry, register_int_gauge_with_registry, Histogram, IntCounter,
+    IntCounterVec, IntGauge, Registry, TextEncoder,
+};
+use std::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+const METRICS_ROUTE: &str = "/metrics";
+
+// buckets defined in seconds
+const LATENCY_SEC_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 15.0,
+];
+
+/// RemoteWriteMetrics tracks how our pushes to the upstream remote_write api are doing
+#[derive(Debug)]
+pub struct RemoteWriteMetrics {
+    /// pushes to remote_write, labeled by the outcome
+    pub pushes: IntCounterVec,
+    pub push_latency: Histogram,
+    /// pushes to the otlp endpoint, labeled by the outcome
+    pub otlp_pushes: IntCounterVec,
+    /// payloads rejected before any push because they could not be decoded or validated
+    pub malformed_payloads: IntCounter,
+    /// pushes waiting in the batch queue, see Batcher
+    pub batch_queue_depth: IntGauge,
+    /// expiry of the server certificate, as a unix timestamp in seconds
+    pub certificate_not_after: IntGauge,
+}
+
+impl RemoteWriteMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            pushes: register_int_counter_vec_with_registry!(
+                "proxy_remote_write_pushes_total",
+                "Total number of pushes to remote_write by outcome",
+                &["outcome"],
+                registry,
+            )
+            .unwrap(),
+            push_latency: register_histogram_with_registry!(
+                "proxy_remote_write_push_latency",
+                "Time spent posting a write request to remote_write",
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            otlp_pushes: register_int_counter_vec_with_registry!(
+                "proxy_otlp_pushes_total",
+                "Total number of pushes to the otlp endpoint by outcome",
+                &["outcome"],
+                registry,
+            )
+            .unwrap(),
+            malformed_payloads: register_int_counter_with_registry!(
+                "proxy_malformed_payload_total",
+                "Total number of metric payloads rejected as malformed",
+                registry,
+            )
+            .unwrap(),
+            batch_queue_depth: register_int_gauge_with_registry!(
+                "proxy_remote_write_batch_queue_depth",
+                "Number of pushes waiting in the batch queue",
+                registry,
+            )
+            .unwrap(),
+            certificate_not_after: register_int_gauge_with_registry!(
+                "proxy_tls_certificate_not_after_seconds",
+                "Expiry of the server certificate as a unix timestamp, 0 if it is not loaded from a file",
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// Creates a new http server whose sole purpose is to expose the proxy's own metrics.
+/// It is served without tls or peer verification so that our monitoring can scrape it
+/// without presenting a validator cert; bind it to an internal address only.
+pub fn start_prometheus_server(listener: TcpListener, registry: Registry) -> JoinHandle<()> {
+    info!(
+        "serving proxy metrics on {:?}{}",
+        listener.local_addr(),
+        METRICS_ROUTE
+    );
+    let app = Router::new()
+        .route(METRICS_ROUTE, get(metrics))
+        .layer(Extension(registry));
+
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service())
+            .await
+            .unwrap();
+    })
+}
+
+async fn metrics(Extension(registry): Extension<Registry>) -> (StatusCode, String) {
+    match TextEncoder.encode_to_string(&registry.gather()) {
+        Ok(metrics) => (StatusCode::OK, metrics),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("unable to encode metrics: {error}"),
+        ),
+    }
+}
@@ -1,9 +1,10 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use crate::peers::SuiNodeProvider;
+use crate::peers::{SuiNodeProvider, SuiPeer};
+use crate::rate_limiter::PeerRateLimiter;
 use axum::{
     extract::Extension,
-    headers::ContentType,
+    headers::{authorization::Bearer, Authorization, ContentType},
     http::{Request, StatusCode},
     middleware::Next,
     response::Response,
@@ -11,7 +12,7 @@ use axum::{
 };
 use std::sync::Arc;
 use sui_tls::TlsConnectionInfo;
-use tracing::error;
+use tracing::{error, warn};
 
 /// we expect sui-node to send us an http header content-type encoding.
 pub async fn expect_mysten_proxy_header<B>(
@@ -44,3 +45,40 @@ pub async fn expect_valid_public_key<B>(
     request.extensions_mut().insert(peer);
     Ok(next.run(request).await)
 }
+
+/// we expect admin requests to carry the bearer token from our config
+pub async fn expect_admin_token<B>(
+    Extension(token): Extension<AdminToken>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, (StatusCode, &'static str)> {
+    match authorization {
+        Some(TypedHeader(Authorization(bearer))) if bearer.token() == token.0 => {
+            Ok(next.run(request).await)
+        }
+        _ => {
+            warn!("rejected admin request with a missing or invalid token");
+            Err((StatusCode::UNAUTHORIZED, "invalid admin token"))
+        }
+    }
+}
+
+/// AdminToken is the bearer token admin requests must present
+#[derive(Clone)]
+pub struct AdminToken(pub String);
+
+/// we expect that peers stay within their configured request budget. this must run after
+/// expect_valid_public_key so that the peer is known
+pub async fn expect_peer_within_rate_limit<B>(
+    Extension(rate_limiter): Extension<Arc<PeerRateLimiter>>,
+    Extension(peer): Extension<SuiPeer>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, (StatusCode, &'static str)> {
+    if !rate_limiter.check(&peer) {
+        warn!("peer {} exceeded its rate limit", peer.name);
+        return Err((StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded"));
+    }
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,153 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use serde::{Deserialize, Serialize};
+
+/// PageLimits bounds the listings of the admin api, so that their responses stay small however
+/// large the network grows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageLimits {
+    /// the most entries a page holds, whatever limit the client asks for
+    pub max_page_size: usize,
+    /// a page stops short of the entry that would grow its json past this many bytes
+    pub max_response_bytes: usize,
+}
+
+/// PageQuery is the query string of a listing: ?cursor=<next_cursor of the previous page>&limit=n
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PageQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Page is one page of a listing.  next_cursor is set when there are more entries, pass it back
+/// as the cursor to fetch them
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// paginate returns the page of items that follows the query's cursor.  items must be sorted by
+/// cursor_of, which is also how the cursor of the next page is made.  a page holds at most the
+/// requested limit of items, capped by max_page_size, and stops short of max_response_bytes.
+/// an item larger than max_response_bytes is still returned on a page of its own, otherwise the
+/// listing could never get past it
+pub fn paginate<T: Serialize>(
+    items: impl IntoIterator<Item = T>,
+    cursor_of: impl Fn(&T) -> &str,
+    query: &PageQuery,
+    limits: &PageLimits,
+) -> Page<T> {
+    let limit = query
+        .limit
+        .unwrap_or(limits.max_page_size)
+        .clamp(1, limits.max_page_size.max(1));
+    let mut page = Page {
+        items: vec![],
+        next_cursor: None,
+    };
+    let mut response_bytes = 0;
+    let mut has_more = false;
+    let remaining = items.into_iter().skip_while(|item| match &query.cursor {
+        Some(cursor) => cursor_of(item) <= cursor.as_str(),
+        None => false,
+    });
+    for item in remaining {
+        // items are separated by a comma in the json array
+        let item_bytes = serde_json::to_vec(&item).map_or(0, |json| json.len() + 1);
+        if page.items.len() == limit
+            || (!page.items.is_empty() && response_bytes + item_bytes > limits.max_response_bytes)
+        {
+            has_more = true;
+            break;
+        }
+        response_bytes += item_bytes;
+        page.items.push(item);
+    }
+    if has_more {
+        page.next_cursor = page.items.last().map(|item| cursor_of(item).to_owned());
+    }
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: PageLimits = PageLimits {
+        max_page_size: 10,
+        max_response_bytes: 1024,
+    };
+
+    fn names(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("peer-{i:03}")).collect()
+    }
+
+    fn page_of(
+        names: &[String],
+        cursor: Option<&str>,
+        limit: Option<usize>,
+        limits: &PageLimits,
+    ) -> Page<String> {
+        let query = PageQuery {
+            cursor: cursor.map(Into::into),
+            limit,
+        };
+        paginate(names.to_vec(), |name| name.as_str(), &query, limits)
+    }
+
+    #[test]
+    fn pages_follow_the_cursor() {
+        let all = names(25);
+        let mut cursor = None;
+        let mut seen = vec![];
+        loop {
+            let page = page_of(&all, cursor.as_deref(), None, &LIMITS);
+            assert!(page.items.len() <= LIMITS.max_page_size);
+            seen.extend(page.items);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(seen, all);
+    }
+
+    #[test]
+    fn limit_is_capped_by_max_page_size() {
+        let all = names(25);
+        let page = page_of(&all, None, Some(3), &LIMITS);
+        assert_eq!(page.items, all[..3]);
+        assert_eq!(page.next_cursor.as_deref(), Some("peer-002"));
+
+        let page = page_of(&all, None, Some(1000), &LIMITS);
+        assert_eq!(page.items, all[..10]);
+
+        // the last page has no cursor
+        let page = page_of(&all, Some("peer-019"), Some(1000), &LIMITS);
+        assert_eq!(page.items, all[20..]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn pages_stop_short_of_max_response_bytes() {
+        let all = names(25);
+        // each name is 10 bytes of json, plus a comma
+        let limits = PageLimits {
+            max_page_size: 10,
+            max_response_bytes: 35,
+        };
+        let page = page_of(&all, None, None, &limits);
+        assert_eq!(page.items, all[..3]);
+        assert_eq!(page.next_cursor.as_deref(), Some("peer-002"));
+
+        // an item over the limit on its own still makes it onto a page
+        let limits = PageLimits {
+            max_page_size: 10,
+            max_response_bytes: 1,
+        };
+        let page = page_of(&all, None, None, &limits);
+        assert_eq!(page.items, all[..1]);
+        assert_eq!(page.next_cursor.as_deref(), Some("peer-000"));
+    }
+}
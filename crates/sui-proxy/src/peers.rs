@@ -1,21 +1,20 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use anyhow::{bail, Context, Result};
+use arc_swap::ArcSwap;
 use fastcrypto::ed25519::Ed25519PublicKey;
 use fastcrypto::traits::ToFromBytes;
 use multiaddr::Multiaddr;
 use serde::Deserialize;
 use std::time::Duration;
-use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock},
-};
+use std::{collections::HashMap, sync::Arc};
 use sui_tls::Allower;
 use sui_types::sui_system_state::sui_system_state_summary::SuiSystemStateSummary;
 use tracing::{debug, error, info};
 
-/// SuiNods a mapping of public key to SuiPeer data
-pub type SuiPeers = Arc<RwLock<HashMap<Ed25519PublicKey, SuiPeer>>>;
+/// SuiNods a mapping of public key to SuiPeer data. lookups happen on every request, so the map is
+/// never locked: a refresh builds a new map and swaps it in atomically
+pub type SuiPeers = Arc<ArcSwap<HashMap<Ed25519PublicKey, SuiPeer>>>;
 
 /// A SuiPeer is the collated sui chain data we have about validators
 #[derive(Hash, PartialEq, Eq, Debug, Clone)]
@@ -38,13 +37,13 @@ pub struct SuiNodeProvider {
 
 impl Allower for SuiNodeProvider {
     fn allowed(&self, key: &Ed25519PublicKey) -> bool {
-        self.nodes.read().unwrap().contains_key(key)
+        self.nodes.load().contains_key(key)
     }
 }
 
 impl SuiNodeProvider {
     pub fn new(rpc_url: String, rpc_poll_interval: Duration) -> Self {
-        let nodes = Arc::new(RwLock::new(HashMap::new()));
+        let nodes = Arc::new(ArcSwap::from_pointee(HashMap::new()));
         Self {
             nodes,
             rpc_url,
@@ -55,14 +54,7 @@ impl SuiNodeProvider {
     /// get is used to retrieve peer info in our handlers
     pub fn get(&self, key: &Ed25519PublicKey) -> Option<SuiPeer> {
         debug!("look for {:?}", key);
-        if let Some(v) = self.nodes.read().unwrap().get(key) {
-            return Some(SuiPeer {
-                name: v.name.to_owned(),
-                p2p_address: v.p2p_address.to_owned(),
-                public_key: v.public_key.to_owned(),
-            });
-        }
-        None
+        self.nodes.load().get(key).cloned()
     }
     /// Get a reference to the inner service
     pub fn get_ref(&self) -> &SuiPeers {
@@ -105,6 +97,20 @@ impl SuiNodeProvider {
         Ok(body.result)
     }
 
+    /// update_peers replaces the allow list with the validators currently on chain and
+    /// returns how many peers are on it
+    async fn update_peers(rpc_url: String, nodes: &SuiPeers) -> Result<usize> {
+        let summary = Self::get_validators(rpc_url).await?;
+        Ok(replace_peers(nodes, extract(summary)))
+    }
+
+    /// refresh updates the allow list right away, without waiting for the next poll
+    pub async fn refresh(&self) -> Result<usize> {
+        let count = Self::update_peers(self.rpc_url.to_owned(), &self.nodes).await?;
+        info!("{count} peers managed to make it on the allow list after a forced refresh");
+        Ok(count)
+    }
+
     /// poll_peer_list will act as a refresh interval for our cache
     pub fn poll_peer_list(&self) {
         info!("Started polling for peers using rpc: {}", self.rpc_url);
@@ -119,15 +125,8 @@ impl SuiNodeProvider {
             loop {
                 interval.tick().await;
 
-                match Self::get_validators(rpc_url.to_owned()).await {
-                    Ok(summary) => {
-                        let peers = extract(summary);
-                        // maintain the tls acceptor set
-                        let mut allow = nodes.write().unwrap();
-                        allow.clear();
-                        allow.extend(peers);
-                        info!("{} peers managed to make it on the allow list", allow.len());
-                    }
+                match Self::update_peers(rpc_url.to_owned(), &nodes).await {
+                    Ok(count) => info!("{count} peers managed to make it on the allow list"),
                     Err(error) => error!("unable to refresh peer list: {error}"),
                 }
             }
@@ -135,6 +134,19 @@ impl SuiNodeProvider {
     }
 }
 
+/// replace_peers swaps in a new allow list in one step, so lookups see either the previous or the
+/// new list and never a partially updated one. returns how many peers are on it
+fn replace_peers(
+    nodes: &SuiPeers,
+    peers: impl Iterator<Item = (Ed25519PublicKey, SuiPeer)>,
+) -> usize {
+    // maintain the tls acceptor set
+    let allow: HashMap<_, _> = peers.collect();
+    let count = allow.len();
+    nodes.store(Arc::new(allow));
+    count
+}
+
 fn extract(summary: SuiSystemStateSummary) -> impl Iterator<Item = (Ed25519PublicKey, SuiPeer)> {
     summary.active_validators.into_iter().filter_map(|vm| {
         match Ed25519PublicKey::from_bytes(&vm.network_pubkey_bytes) {
@@ -155,3 +167,61 @@ fn extract(summary: SuiSystemStateSummary) -> impl Iterator<Item = (Ed25519Publi
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::generate_self_cert;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn peer(name: &str) -> (Ed25519PublicKey, SuiPeer) {
+        let (_, public_key) = generate_self_cert(name.into());
+        (
+            public_key.clone(),
+            SuiPeer {
+                name: name.into(),
+                p2p_address: Multiaddr::empty(),
+                public_key,
+            },
+        )
+    }
+
+    #[test]
+    fn lookups_see_a_whole_peer_list_during_refresh() {
+        let provider = SuiNodeProvider::new("".into(), Duration::from_secs(1));
+        let (kept, kept_peer) = peer("kept");
+        let (left, left_peer) = peer("left");
+        let (joined, joined_peer) = peer("joined");
+        let before = vec![(kept.clone(), kept_peer.clone()), (left.clone(), left_peer)];
+        let after = vec![
+            (kept.clone(), kept_peer.clone()),
+            (joined.clone(), joined_peer),
+        ];
+        replace_peers(provider.get_ref(), before.clone().into_iter());
+
+        let done = Arc::new(AtomicBool::new(false));
+        let refresher = {
+            let nodes = provider.get_ref().clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                for i in 0..10_000 {
+                    let peers = if i % 2 == 0 { &after } else { &before };
+                    assert_eq!(replace_peers(&nodes, peers.clone().into_iter()), 2);
+                }
+                done.store(true, Ordering::SeqCst);
+            })
+        };
+
+        // a peer that is on every list is never missing, and the list always holds exactly one
+        // of the peers that changed
+        while !done.load(Ordering::SeqCst) {
+            assert_eq!(provider.get(&kept), Some(kept_peer.clone()));
+            let nodes = provider.get_ref().load();
+            assert_eq!(nodes.len(), 2);
+            assert!(nodes.contains_key(&left) != nodes.contains_key(&joined));
+        }
+        refresher.join().unwrap();
+        assert!(provider.allowed(&left));
+        assert!(!provider.allowed(&joined));
+    }
+}
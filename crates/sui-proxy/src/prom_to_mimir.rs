@@ -216,8 +216,7 @@ pub mod tests {
         mf.set_metric(metric);
         mf
     }
-    #[allow(dead_code)]
-    fn create_metric_gauge(
+    pub fn create_metric_gauge(
         labels: RepeatedField<proto::LabelPair>,
         gauge: proto::Gauge,
     ) -> proto::Metric {
@@ -250,8 +249,7 @@ pub mod tests {
             })
             .collect()
     }
-    #[allow(dead_code)]
-    fn create_gauge(value: f64) -> proto::Gauge {
+    pub fn create_gauge(value: f64) -> proto::Gauge {
         let mut g = proto::Gauge::default();
         g.set_value(value);
         g
@@ -0,0 +1,296 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::otlp::{
+    any_value, metric, number_data_point, summary_data_point, AggregationTemporality, AnyValue,
+    ExportMetricsServiceRequest, Gauge, Histogram, HistogramDataPoint, InstrumentationScope,
+    KeyValue, Metric, NumberDataPoint, ResourceMetrics, ScopeMetrics, Sum, Summary,
+    SummaryDataPoint,
+};
+use prometheus::proto::{self, MetricFamily, MetricType};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// the instrumentation scope the converted metrics are reported under
+const SCOPE_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// a metric family maps to the otlp metric of the same kind: a counter to a monotonic cumulative
+/// sum, a gauge or untyped metric to a gauge, a histogram to a cumulative histogram and a summary
+/// to a summary.  labels become string attributes.  prometheus does not tell us when a counter
+/// started, so start_time_unix_nano is left unset
+impl From<&MetricFamily> for Metric {
+    fn from(mf: &MetricFamily) -> Self {
+        let data = match mf.get_field_type() {
+            MetricType::COUNTER => metric::Data::Sum(Sum {
+                data_points: mf.get_metric().iter().map(number_data_point).collect(),
+                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+                is_monotonic: true,
+            }),
+            MetricType::GAUGE | MetricType::UNTYPED => metric::Data::Gauge(Gauge {
+                data_points: mf.get_metric().iter().map(number_data_point).collect(),
+            }),
+            MetricType::HISTOGRAM => metric::Data::Histogram(Histogram {
+                data_points: mf.get_metric().iter().map(histogram_data_point).collect(),
+                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+            }),
+            MetricType::SUMMARY => metric::Data::Summary(Summary {
+                data_points: mf.get_metric().iter().map(summary_data_point).collect(),
+            }),
+        };
+        Self {
+            name: mf.get_name().into(),
+            description: mf.get_help().into(),
+            unit: String::new(),
+            data: Some(data),
+        }
+    }
+}
+
+/// to_otlp converts the metric families of one push, skipping families without any metric
+pub fn to_otlp(metric_families: &[MetricFamily]) -> Vec<Metric> {
+    metric_families
+        .iter()
+        .filter(|mf| !mf.get_metric().is_empty())
+        .map(Metric::from)
+        .collect()
+}
+
+/// export_request wraps metrics in the request posted to an otlp/http metrics endpoint.  the
+/// host and network are per metric attributes, as a request may carry the metrics of many nodes
+pub fn export_request(metrics: Vec<Metric>) -> ExportMetricsServiceRequest {
+    ExportMetricsServiceRequest {
+        resource_metrics: vec![ResourceMetrics {
+            resource: None,
+            scope_metrics: vec![ScopeMetrics {
+                scope: Some(InstrumentationScope {
+                    name: SCOPE_NAME.into(),
+                    version: env!("CARGO_PKG_VERSION").into(),
+                }),
+                metrics,
+            }],
+        }],
+    }
+}
+
+/// attributes sorts the labels of a metric the way they are sent to remote_write
+fn attributes(m: &proto::Metric) -> Vec<KeyValue> {
+    let mut labels: Vec<(&str, &str)> = m
+        .get_label()
+        .iter()
+        .map(|label| (label.get_name(), label.get_value()))
+        .collect();
+    labels.sort();
+    labels
+        .into_iter()
+        .map(|(name, value)| KeyValue {
+            key: name.into(),
+            value: Some(AnyValue {
+                value: Some(any_value::Value::StringValue(value.into())),
+            }),
+        })
+        .collect()
+}
+
+/// time_unix_nano is the time the node sampled the metric, or now if it did not say
+fn time_unix_nano(m: &proto::Metric) -> u64 {
+    match m.get_timestamp_ms() {
+        ms if ms > 0 => ms as u64 * 1_000_000,
+        _ => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_nanos() as u64)
+            .unwrap_or_default(),
+    }
+}
+
+fn number_data_point(m: &proto::Metric) -> NumberDataPoint {
+    // as in the remote_write conversion, the value is taken from whichever field is set
+    let value = if m.has_counter() {
+        m.get_counter().get_value()
+    } else if m.has_gauge() {
+        m.get_gauge().get_value()
+    } else {
+        m.get_untyped().get_value()
+    };
+    NumberDataPoint {
+        attributes: attributes(m),
+        start_time_unix_nano: 0,
+        time_unix_nano: time_unix_nano(m),
+        value: Some(number_data_point::Value::AsDouble(value)),
+    }
+}
+
+/// histogram_data_point turns the cumulative prometheus buckets into per bucket counts.  the
+/// +Inf bucket is implicit in otlp, it is the count of the values above the last bound
+fn histogram_data_point(m: &proto::Metric) -> HistogramDataPoint {
+    let h = m.get_histogram();
+    let mut explicit_bounds = vec![];
+    let mut bucket_counts = vec![];
+    let mut cumulative = 0;
+    for bucket in h
+        .get_bucket()
+        .iter()
+        .filter(|bucket| bucket.get_upper_bound().is_finite())
+    {
+        explicit_bounds.push(bucket.get_upper_bound());
+        bucket_counts.push(bucket.get_cumulative_count().saturating_sub(cumulative));
+        cumulative = bucket.get_cumulative_count();
+    }
+    bucket_counts.push(h.get_sample_count().saturating_sub(cumulative));
+    HistogramDataPoint {
+        attributes: attributes(m),
+        start_time_unix_nano: 0,
+        time_unix_nano: time_unix_nano(m),
+        count: h.get_sample_count(),
+        sum: Some(h.get_sample_sum()),
+        bucket_counts,
+        explicit_bounds,
+    }
+}
+
+fn summary_data_point(m: &proto::Metric) -> SummaryDataPoint {
+    let s = m.get_summary();
+    SummaryDataPoint {
+        attributes: attributes(m),
+        start_time_unix_nano: 0,
+        time_unix_nano: time_unix_nano(m),
+        count: s.get_sample_count(),
+        sum: s.get_sample_sum(),
+        quantile_values: s
+            .get_quantile()
+            .iter()
+            .map(|q| summary_data_point::ValueAtQuantile {
+                quantile: q.get_quantile(),
+                value: q.get_value(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prom_to_mimir::tests::*;
+    use protobuf::RepeatedField;
+
+    fn string_attribute(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            key: key.into(),
+            value: Some(AnyValue {
+                value: Some(any_value::Value::StringValue(value.into())),
+            }),
+        }
+    }
+
+    #[test]
+    fn counters_become_monotonic_sums() {
+        let mf = create_metric_family(
+            "foo_metric",
+            "some help this is",
+            Some(proto::MetricType::COUNTER),
+            RepeatedField::from_vec(vec![create_metric_counter(
+                RepeatedField::from_vec(create_labels(vec![("some", "label"), ("host", "a")])),
+                create_counter(2046.0),
+            )]),
+        );
+
+        let metric = Metric::from(&mf);
+        assert_eq!(metric.name, "foo_metric");
+        assert_eq!(metric.description, "some help this is");
+        let Some(metric::Data::Sum(sum)) = metric.data else {
+            panic!("a counter should become a sum");
+        };
+        assert!(sum.is_monotonic);
+        assert_eq!(
+            sum.aggregation_temporality,
+            AggregationTemporality::Cumulative as i32
+        );
+        assert_eq!(
+            sum.data_points,
+            vec![NumberDataPoint {
+                attributes: vec![
+                    string_attribute("host", "a"),
+                    string_attribute("some", "label"),
+                ],
+                start_time_unix_nano: 0,
+                time_unix_nano: 12345 * 1_000_000,
+                value: Some(number_data_point::Value::AsDouble(2046.0)),
+            }]
+        );
+    }
+
+    #[test]
+    fn gauges_stay_gauges() {
+        let mf = create_metric_family(
+            "foo_gauge",
+            "some help this is",
+            Some(proto::MetricType::GAUGE),
+            RepeatedField::from_vec(vec![create_metric_gauge(
+                RepeatedField::from_vec(create_labels(vec![("some", "label")])),
+                create_gauge(-3.5),
+            )]),
+        );
+
+        let Some(metric::Data::Gauge(gauge)) = Metric::from(&mf).data else {
+            panic!("a gauge should stay a gauge");
+        };
+        assert_eq!(gauge.data_points.len(), 1);
+        assert_eq!(
+            gauge.data_points[0].value,
+            Some(number_data_point::Value::AsDouble(-3.5))
+        );
+        assert_eq!(
+            gauge.data_points[0].attributes,
+            vec![string_attribute("some", "label")]
+        );
+    }
+
+    #[test]
+    fn histogram_buckets_are_no_longer_cumulative() {
+        let mut h = proto::Histogram::default();
+        h.set_sample_count(10);
+        h.set_sample_sum(42.0);
+        h.set_bucket(RepeatedField::from_vec(
+            [(1.0, 2), (5.0, 5), (10.0, 9), (f64::INFINITY, 10)]
+                .into_iter()
+                .map(|(upper_bound, cumulative_count)| {
+                    let mut bucket = proto::Bucket::default();
+                    bucket.set_upper_bound(upper_bound);
+                    bucket.set_cumulative_count(cumulative_count);
+                    bucket
+                })
+                .collect(),
+        ));
+        let mut m = proto::Metric::default();
+        m.set_histogram(h);
+        m.set_timestamp_ms(12345);
+        let mf = create_metric_family(
+            "foo_latency",
+            "some help this is",
+            Some(proto::MetricType::HISTOGRAM),
+            RepeatedField::from_vec(vec![m]),
+        );
+
+        let Some(metric::Data::Histogram(histogram)) = Metric::from(&mf).data else {
+            panic!("a histogram should stay a histogram");
+        };
+        assert_eq!(
+            histogram.aggregation_temporality,
+            AggregationTemporality::Cumulative as i32
+        );
+        let point = &histogram.data_points[0];
+        assert_eq!(point.count, 10);
+        assert_eq!(point.sum, Some(42.0));
+        // the +Inf bucket is dropped from the bounds and counts the values above the last one
+        assert_eq!(point.explicit_bounds, vec![1.0, 5.0, 10.0]);
+        assert_eq!(point.bucket_counts, vec![2, 3, 4, 1]);
+    }
+
+    #[test]
+    fn empty_families_are_skipped() {
+        let mf = create_metric_family(
+            "foo_metric",
+            "some help this is",
+            Some(proto::MetricType::COUNTER),
+            RepeatedField::new(),
+        );
+        assert!(to_otlp(&[mf]).is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::config::RateLimitConfig;
+use crate::peers::SuiPeer;
+use fastcrypto::ed25519::Ed25519PublicKey;
+use prometheus::{register_int_counter_vec_with_registry, IntCounterVec, Registry};
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+use tracing::info;
+
+/// TokenBucket holds the remaining budget for a single peer. Tokens are refilled
+/// lazily, based on the time elapsed since the last time we looked at the bucket.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(burst: u32, now: Instant) -> Self {
+        Self {
+            tokens: burst as f64,
+            last_refill: now,
+        }
+    }
+
+    /// refill the bucket according to the configured rate and take a token if one is available
+    fn try_acquire(&mut self, config: &RateLimitConfig, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * config.requests_per_second).min(config.burst as f64);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return true;
+        }
+        false
+    }
+}
+
+/// PeerRateLimiter keeps a token bucket per peer public key so that a single misbehaving
+/// validator cannot dominate the throughput of the proxy.  The limits may be swapped at
+/// runtime with update_config.
+#[derive(Debug)]
+pub struct PeerRateLimiter {
+    config: RwLock<RateLimitConfig>,
+    buckets: Mutex<HashMap<Ed25519PublicKey, TokenBucket>>,
+    rate_limited: IntCounterVec,
+}
+
+impl PeerRateLimiter {
+    pub fn new(config: RateLimitConfig, registry: &Registry) -> Self {
+        Self {
+            config: RwLock::new(config),
+            buckets: Mutex::new(HashMap::new()),
+            rate_limited: register_int_counter_vec_with_registry!(
+                "proxy_rate_limited_total",
+                "Total number of requests rejected because a peer exceeded its rate limit",
+                &["peer"],
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+
+    /// check returns true if the peer is within its budget and consumes a token,
+    /// otherwise the rejection is recorded and false is returned
+    pub fn check(&self, peer: &SuiPeer) -> bool {
+        let config = self.config.read().unwrap().clone();
+        let allowed = self
+            .buckets
+            .lock()
+            .unwrap()
+            .entry(peer.public_key.clone())
+            .or_insert_with(|| TokenBucket::new(config.burst, Instant::now()))
+            .try_acquire(&config, Instant::now());
+        if !allowed {
+            self.rate_limited.with_label_values(&[&peer.name]).inc();
+        }
+        allowed
+    }
+
+    /// update_config swaps the limits in place; existing buckets are kept and are
+    /// clamped to the new burst on their next refill
+    pub fn update_config(&self, config: RateLimitConfig) {
+        info!(
+            "updating peer rate limits to {} requests/s with a burst of {}",
+            config.requests_per_second, config.burst
+        );
+        *self.config.write().unwrap() = config;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn token_bucket_refills_at_configured_rate() {
+        let config = RateLimitConfig {
+            requests_per_second: 1.0,
+            burst: 2,
+        };
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(config.burst, start);
+
+        // the initial burst is available immediately
+        assert!(bucket.try_acquire(&config, start));
+        assert!(bucket.try_acquire(&config, start));
+        assert!(!bucket.try_acquire(&config, start));
+
+        // half a second is not enough to earn a new token
+        assert!(!bucket.try_acquire(&config, start + Duration::from_millis(500)));
+        assert!(bucket.try_acquire(&config, start + Duration::from_secs(1)));
+
+        // a long idle period never grows the bucket past the burst size
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.try_acquire(&config, later));
+        assert!(bucket.try_acquire(&config, later));
+        assert!(!bucket.try_acquire(&config, later));
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::config::{RemoteWriteConfig, RemoteWriteShardConfig};
+use fastcrypto::ed25519::Ed25519PublicKey;
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use std::collections::BTreeMap;
+
+/// number of points each shard occupies on the ring. more points give a more even spread
+/// of peers across shards.
+const VIRTUAL_NODES_PER_SHARD: u32 = 128;
+
+/// ShardRouter decides which remote_write destinations receive a peer's metrics.
+///
+/// Shards are placed on a consistent hash ring by name, so adding or removing a shard only
+/// moves the peers that hash next to it.  If no shards are configured, peers cannot be mapped
+/// and their metrics are broadcast to every default destination instead.
+#[derive(Debug, Clone)]
+pub struct ShardRouter {
+    ring: BTreeMap<u64, usize>,
+    shards: Vec<RemoteWriteConfig>,
+    broadcast: Vec<RemoteWriteConfig>,
+}
+
+impl ShardRouter {
+    pub fn new(shards: Vec<RemoteWriteShardConfig>, broadcast: Vec<RemoteWriteConfig>) -> Self {
+        let mut ring = BTreeMap::new();
+        for (idx, shard) in shards.iter().enumerate() {
+            for vnode in 0..VIRTUAL_NODES_PER_SHARD {
+                ring.insert(hash(format!("{}-{}", shard.name, vnode)), idx);
+            }
+        }
+        Self {
+            ring,
+            shards: shards.into_iter().map(|s| s.remote_write).collect(),
+            broadcast,
+        }
+    }
+
+    /// route returns the destinations that should receive metrics from the given peer
+    pub fn route(&self, public_key: &Ed25519PublicKey) -> Vec<&RemoteWriteConfig> {
+        let point = hash(public_key);
+        match self
+            .ring
+            .range(point..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+        {
+            Some((_, idx)) => vec![&self.shards[*idx]],
+            None => self.broadcast.iter().collect(),
+        }
+    }
+
+    /// destination returns the configured shard or default destination posting to url
+    pub fn destination(&self, url: &str) -> Option<&RemoteWriteConfig> {
+        self.shards
+            .iter()
+            .chain(self.broadcast.iter())
+            .find(|settings| settings.url == url)
+    }
+}
+
+fn hash<T: AsRef<[u8]>>(data: T) -> u64 {
+    let digest = Blake2b256::digest(data);
+    u64::from_le_bytes(digest.as_ref()[..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::generate_self_cert;
+
+    fn shard(name: &str) -> RemoteWriteShardConfig {
+        RemoteWriteShardConfig {
+            name: name.into(),
+            remote_write: RemoteWriteConfig {
+                url: format!("http://{name}/api/v1/push"),
+                username: "foo".into(),
+                password: "bar".into(),
+            },
+        }
+    }
+
+    #[test]
+    fn adding_a_shard_only_moves_some_peers() {
+        let keys: Vec<Ed25519PublicKey> = (0..100)
+            .map(|_| generate_self_cert("sui".into()).1)
+            .collect();
+
+        let before = ShardRouter::new(vec![shard("a"), shard("b"), shard("c")], vec![]);
+        let after = ShardRouter::new(vec![shard("a"), shard("b"), shard("c"), shard("d")], vec![]);
+
+        for key in &keys {
+            let old = before.route(key);
+            let new = after.route(key);
+            assert_eq!(old.len(), 1);
+            assert_eq!(new.len(), 1);
+            // a peer either stays where it was or moves to the new shard
+            assert!(old[0].url == new[0].url || new[0].url == "http://d/api/v1/push");
+        }
+    }
+
+    #[test]
+    fn unmapped_peers_are_broadcast() {
+        let default = shard("default").remote_write;
+        let router = ShardRouter::new(vec![], vec![default.clone()]);
+        let key = generate_self_cert("sui".into()).1;
+        assert_eq!(router.route(&key).len(), 1);
+        assert_eq!(router.route(&key)[0].url, default.url);
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use hyper::body::HttpBody;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Request, Uri};
+use hyper_rustls::HttpsConnector;
+use jsonrpsee::core::client::{BatchResponse, ClientT};
+use jsonrpsee::core::params::BatchRequestBuilder;
+use jsonrpsee::core::traits::ToRpcParams;
+use jsonrpsee::core::{Error, JsonRawValue};
+use jsonrpsee::http_client::{HeaderMap, HeaderValue, HttpClient};
+use jsonrpsee::types::error::CallError;
+use jsonrpsee::types::{ErrorResponse, Id, NotificationSer, RequestSer, Response};
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ServerName};
+use serde::de::DeserializeOwned;
+use tokio::sync::Semaphore;
+use tower::ServiceExt;
+use tower_http::decompression::Decompression;
+
+const CONTENT_TYPE_JSON: &str = "application/json";
+
+/// The http transport of [crate::SuiClient], either the stock jsonrpsee client or one on hyper
+/// for what the stock client cannot do.
+#[derive(Debug)]
+pub(crate) enum SuiHttpClient {
+    Plain(HttpClient),
+    Hyper(HyperHttpClient),
+}
+
+#[async_trait]
+impl ClientT for SuiHttpClient {
+    async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
+    where
+        Params: ToRpcParams + Send,
+    {
+        match self {
+            Self::Plain(client) => client.notification(method, params).await,
+            Self::Hyper(client) => client.notification(method, params).await,
+        }
+    }
+
+    async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, Error>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        match self {
+            Self::Plain(client) => client.request(method, params).await,
+            Self::Hyper(client) => client.request(method, params).await,
+        }
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, Error>
+    where
+        R: DeserializeOwned + Debug + 'a,
+    {
+        match self {
+            Self::Plain(client) => client.batch_request(batch).await,
+            Self::Hyper(client) => client.batch_request(batch).await,
+        }
+    }
+}
+
+/// A JSON-RPC http client that can advertise gzip, deflate and brotli in `Accept-Encoding` and
+/// decode compressed response bodies before parsing them, which cuts the bandwidth of large
+/// responses such as checkpoints and multi-get results. It can also skip the verification of the
+/// server certificate, which the stock client always verifies.
+pub(crate) struct HyperHttpClient {
+    target: Uri,
+    client: Decompression<Client<HttpsConnector<HttpConnector>>>,
+    headers: HeaderMap,
+    request_timeout: Duration,
+    max_response_size: usize,
+    request_permits: Semaphore,
+    next_id: AtomicU64,
+}
+
+impl HyperHttpClient {
+    pub(crate) fn new(
+        target: impl AsRef<str>,
+        headers: HeaderMap,
+        request_timeout: Duration,
+        max_concurrent_requests: usize,
+        max_response_size: usize,
+        compressed_responses: bool,
+        danger_accept_invalid_certs: bool,
+    ) -> Result<Self, Error> {
+        let target: Uri = target
+            .as_ref()
+            .parse()
+            .map_err(|e| Error::Transport(anyhow!("Invalid URL: {e}")))?;
+
+        let connector = hyper_rustls::HttpsConnectorBuilder::new();
+        let connector = if danger_accept_invalid_certs {
+            connector.with_tls_config(
+                rustls::ClientConfig::builder()
+                    .with_safe_defaults()
+                    .with_custom_certificate_verifier(Arc::new(AcceptInvalidCerts))
+                    .with_no_client_auth(),
+            )
+        } else {
+            connector.with_webpki_roots()
+        };
+        let connector = connector.https_or_http().enable_http1().build();
+        // Without any encoding enabled, no `Accept-Encoding` is sent
+        let client = Decompression::new(Client::builder().build::<_, Body>(connector))
+            .gzip(compressed_responses)
+            .deflate(compressed_responses)
+            .br(compressed_responses);
+
+        let mut cached_headers = HeaderMap::with_capacity(2 + headers.len());
+        cached_headers.insert(
+            hyper::header::CONTENT_TYPE,
+            HeaderValue::from_static(CONTENT_TYPE_JSON),
+        );
+        cached_headers.insert(
+            hyper::header::ACCEPT,
+            HeaderValue::from_static(CONTENT_TYPE_JSON),
+        );
+        cached_headers.extend(headers);
+
+        Ok(Self {
+            target,
+            client,
+            headers: cached_headers,
+            request_timeout,
+            max_response_size,
+            request_permits: Semaphore::new(max_concurrent_requests),
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// Post `body` and return the decoded response body.
+    async fn send(&self, body: String) -> Result<Vec<u8>, Error> {
+        let _permit = self
+            .request_permits
+            .acquire()
+            .await
+            .map_err(|_| Error::MaxSlotsExceeded)?;
+
+        let mut request = Request::post(&self.target);
+        if let Some(headers) = request.headers_mut() {
+            *headers = self.headers.clone();
+        }
+        let request = request
+            .body(Body::from(body))
+            .expect("URI and request headers are valid; qed");
+
+        let response = self
+            .client
+            .clone()
+            .oneshot(request)
+            .await
+            .map_err(|e| Error::Transport(e.into()))?;
+        if !response.status().is_success() {
+            return Err(Error::Transport(anyhow!(
+                "Request failed with status code: {}",
+                response.status()
+            )));
+        }
+
+        // The body is decoded chunk by chunk, so the size limit applies to the decoded bytes.
+        let mut body = response.into_body();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.map_err(|e| Error::Transport(anyhow!(e)))?;
+            if bytes.len() + chunk.len() > self.max_response_size {
+                return Err(Error::Transport(anyhow!(
+                    "Response body exceeds the limit of {} bytes",
+                    self.max_response_size
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    async fn send_with_timeout(&self, body: String) -> Result<Vec<u8>, Error> {
+        tokio::time::timeout(self.request_timeout, self.send(body))
+            .await
+            .map_err(|_| Error::RequestTimeout)?
+    }
+}
+
+/// Accepts any server certificate, for fullnodes with self-signed certificates in development.
+/// The handshake is still signed with the key of the certificate, but nothing ties that key to
+/// the server, so the connection is open to anyone in the middle.
+struct AcceptInvalidCerts;
+
+impl ServerCertVerifier for AcceptInvalidCerts {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+impl Debug for HyperHttpClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HyperHttpClient {{ target: {} }}", self.target)
+    }
+}
+
+#[async_trait]
+impl ClientT for HyperHttpClient {
+    async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
+    where
+        Params: ToRpcParams + Send,
+    {
+        let params = params.to_rpc_params()?;
+        let notification = NotificationSer::borrowed(&method, params.as_deref());
+        let raw = serde_json::to_string(&notification).map_err(Error::ParseError)?;
+        self.send_with_timeout(raw).await.map(|_| ())
+    }
+
+    async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, Error>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        let id = Id::Number(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let params = params.to_rpc_params()?;
+        let request = RequestSer::borrowed(&id, &method, params.as_deref());
+        let raw = serde_json::to_string(&request).map_err(Error::ParseError)?;
+
+        let body = self.send_with_timeout(raw).await?;
+
+        let response: Response<&JsonRawValue> = match serde_json::from_slice(&body) {
+            Ok(response) => response,
+            Err(_) => {
+                let err: ErrorResponse =
+                    serde_json::from_slice(&body).map_err(Error::ParseError)?;
+                return Err(Error::Call(CallError::Custom(
+                    err.error_object().clone().into_owned(),
+                )));
+            }
+        };
+        if response.id != id {
+            return Err(Error::InvalidRequestId);
+        }
+        serde_json::from_str(response.result.get()).map_err(Error::ParseError)
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        _batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, Error>
+    where
+        R: DeserializeOwned + Debug + 'a,
+    {
+        // The Sui JSON-RPC server does not accept batch requests.
+        Err(Error::Custom(
+            "Batch requests are not supported".to_string(),
+        ))
+    }
+}
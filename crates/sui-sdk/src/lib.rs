@@ -8,11 +8,12 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use jsonrpsee::core::client::ClientT;
-use jsonrpsee::http_client::{HeaderMap, HeaderValue, HttpClient, HttpClientBuilder};
+use jsonrpsee::http_client::{HeaderMap, HeaderValue, HttpClientBuilder};
 use jsonrpsee::rpc_params;
 use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
 
 use crate::error::{Error, SuiRpcResult};
+use crate::http_client::{HyperHttpClient, SuiHttpClient};
 use serde_json::Value;
 use sui_adapter::execution_mode::Normal;
 pub use sui_json as json;
@@ -28,6 +29,7 @@ pub use sui_types as types;
 use sui_types::base_types::{ObjectID, SuiAddress};
 pub mod apis;
 pub mod error;
+mod http_client;
 pub const SUI_COIN_TYPE: &str = "0x2::sui::SUI";
 const WAIT_FOR_TX_TIMEOUT_SEC: u64 = 60;
 
@@ -35,6 +37,9 @@ pub struct SuiClientBuilder {
     request_timeout: Duration,
     max_concurrent_requests: usize,
     ws_url: Option<String>,
+    compressed_responses: bool,
+    custom_headers: HeaderMap,
+    danger_accept_invalid_certs: bool,
 }
 
 impl Default for SuiClientBuilder {
@@ -43,6 +48,9 @@ impl Default for SuiClientBuilder {
             request_timeout: Duration::from_secs(60),
             max_concurrent_requests: 256,
             ws_url: None,
+            compressed_responses: false,
+            custom_headers: HeaderMap::new(),
+            danger_accept_invalid_certs: false,
         }
     }
 }
@@ -63,6 +71,29 @@ impl SuiClientBuilder {
         self
     }
 
+    /// Ask the fullnode to gzip, deflate or brotli compress http responses, and decode them on
+    /// receipt. Worth enabling for clients that read many large responses, such as indexers.
+    pub fn compressed_responses(mut self, compressed_responses: bool) -> Self {
+        self.compressed_responses = compressed_responses;
+        self
+    }
+
+    /// Headers sent with every request, ex the API key of a gateway in front of the fullnode.
+    /// They replace the headers of the same name set by the SDK.
+    pub fn custom_headers(mut self, custom_headers: HeaderMap) -> Self {
+        self.custom_headers = custom_headers;
+        self
+    }
+
+    /// Do not verify the certificate of the fullnode over https, so that a fullnode with a
+    /// self-signed certificate can be reached in development. Anyone able to intercept the
+    /// connection can then impersonate the fullnode, never enable it in production. Only applies
+    /// to the http client, not to `ws_url`.
+    pub fn danger_accept_invalid_certs(mut self, danger_accept_invalid_certs: bool) -> Self {
+        self.danger_accept_invalid_certs = danger_accept_invalid_certs;
+        self
+    }
+
     pub async fn build(self, http: impl AsRef<str>) -> SuiRpcResult<SuiClient> {
         let client_version = env!("CARGO_PKG_VERSION");
         let mut headers = HeaderMap::new();
@@ -76,6 +107,7 @@ impl SuiClientBuilder {
             HeaderValue::from_static(client_version),
         );
         headers.insert(CLIENT_SDK_TYPE_HEADER, HeaderValue::from_static("rust"));
+        headers.extend(self.custom_headers);
 
         let ws = if let Some(url) = self.ws_url {
             Some(
@@ -91,12 +123,27 @@ impl SuiClientBuilder {
             None
         };
 
-        let http = HttpClientBuilder::default()
-            .max_request_body_size(2 << 30)
-            .max_concurrent_requests(self.max_concurrent_requests)
-            .set_headers(headers.clone())
-            .request_timeout(self.request_timeout)
-            .build(http)?;
+        // The stock client can neither decode compressed responses nor skip certificate checks
+        let http = if self.compressed_responses || self.danger_accept_invalid_certs {
+            SuiHttpClient::Hyper(HyperHttpClient::new(
+                http,
+                headers,
+                self.request_timeout,
+                self.max_concurrent_requests,
+                2 << 30,
+                self.compressed_responses,
+                self.danger_accept_invalid_certs,
+            )?)
+        } else {
+            SuiHttpClient::Plain(
+                HttpClientBuilder::default()
+                    .max_request_body_size(2 << 30)
+                    .max_concurrent_requests(self.max_concurrent_requests)
+                    .set_headers(headers)
+                    .request_timeout(self.request_timeout)
+                    .build(http)?,
+            )
+        };
 
         let info = Self::get_server_info(&http, &ws).await?;
 
@@ -121,7 +168,7 @@ impl SuiClientBuilder {
     }
 
     async fn get_server_info(
-        http: &HttpClient,
+        http: &SuiHttpClient,
         ws: &Option<WsClient>,
     ) -> Result<ServerInfo, Error> {
         let rpc_spec: Value = http.request("rpc.discover", rpc_params![]).await?;
@@ -177,7 +224,7 @@ pub struct SuiClient {
 }
 
 pub(crate) struct RpcClient {
-    http: HttpClient,
+    http: SuiHttpClient,
     ws: Option<WsClient>,
     info: ServerInfo,
 }
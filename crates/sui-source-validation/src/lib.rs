@@ -3,7 +3,6 @@
 
 use core::fmt;
 use futures::future;
-use move_binary_format::access::ModuleAccess;
 use move_binary_format::CompiledModule;
 use std::{collections::HashMap, fmt::Debug};
 use sui_types::error::UserInputError;
@@ -13,6 +12,7 @@ use move_compiler::compiled_unit::{CompiledUnitEnum, NamedCompiledModule};
 use move_core_types::account_address::AccountAddress;
 use move_package::compilation::compiled_package::CompiledPackage;
 use move_symbol_pool::Symbol;
+use sui_framework_build::compiled_package::substitute_self_address;
 use sui_sdk::apis::ReadApi;
 use sui_sdk::error::Error;
 
@@ -101,8 +101,12 @@ pub enum SourceMode {
 pub struct BytecodeSourceVerifier<'a> {
     pub verbose: bool,
     rpc_client: &'a ReadApi,
+    address_substitutions: AddressSubstitutions,
 }
 
+/// Map names of packages compiled at `0x0` to the addresses they were published at.
+pub type AddressSubstitutions = HashMap<Symbol, AccountAddress>;
+
 /// Map package addresses and module names to package names and bytecode.
 type LocalBytes = HashMap<(AccountAddress, Symbol), (Symbol, Vec<u8>)>;
 /// Map package addresses and modules names to bytecode (package names are gone in the on-chain
@@ -114,9 +118,22 @@ impl<'a> BytecodeSourceVerifier<'a> {
         BytecodeSourceVerifier {
             verbose,
             rpc_client,
+            address_substitutions: AddressSubstitutions::new(),
         }
     }
 
+    /// Verify packages compiled with a placeholder `0x0` self-address (the root package in
+    /// `SourceMode::Verify`, or dependencies) against the address they were published at in
+    /// `address_substitutions`, by substituting it before comparing bytecode. Packages at `0x0`
+    /// without a substitution are handled as unpublished.
+    pub fn with_address_substitutions(
+        mut self,
+        address_substitutions: AddressSubstitutions,
+    ) -> Self {
+        self.address_substitutions = address_substitutions;
+        self
+    }
+
     /// Helper wrapper to verify that all local Move package dependencies' and root bytecode matches
     /// the bytecode at the address specified on the Sui network we are publishing to.
     pub async fn verify_package_root_and_deps(
@@ -178,7 +195,12 @@ impl<'a> BytecodeSourceVerifier<'a> {
             }
         }
 
-        let local_modules = local_bytes(compiled_package, verify_deps, source_mode)?;
+        let local_modules = local_bytes(
+            compiled_package,
+            verify_deps,
+            source_mode,
+            &self.address_substitutions,
+        )?;
         let mut on_chain_modules = self
             .on_chain_bytes(local_modules.keys().map(|(addr, _)| *addr))
             .await?;
@@ -282,31 +304,43 @@ fn substitute_root_address(
     named_module: &NamedCompiledModule,
     root: AccountAddress,
 ) -> Result<CompiledModule, SourceVerificationError> {
-    let mut module = named_module.module.clone();
-    let address_idx = module.self_handle().address;
-
-    let Some(addr) = module.address_identifiers.get_mut(address_idx.0 as usize) else {
-        return Err(SourceVerificationError::InvalidModuleFailure {
+    substitute_self_address(&named_module.module, root).map_err(|err| {
+        SourceVerificationError::InvalidModuleFailure {
             name: named_module.name.to_string(),
-            message: "Self address field missing".into(),
-        });
-    };
+            message: err.to_string(),
+        }
+    })
+}
 
-    if *addr != AccountAddress::ZERO {
-        return Err(SourceVerificationError::InvalidModuleFailure {
-            name: named_module.name.to_string(),
-            message: "Self address already populated".to_string(),
-        });
+/// Serialize `named_module` as it was published: at its own address, or, if it was compiled at
+/// `0x0`, at the address `package` has in `address_substitutions`. Returns None for modules at
+/// `0x0` without a substitution.
+fn published_bytes(
+    package: Symbol,
+    named_module: &NamedCompiledModule,
+    address_substitutions: &AddressSubstitutions,
+) -> Result<Option<(AccountAddress, Vec<u8>)>, SourceVerificationError> {
+    let address = named_module.address.into_inner();
+    let mut bytes = vec![];
+    if address != AccountAddress::ZERO {
+        named_module.module.serialize(&mut bytes).unwrap();
+        return Ok(Some((address, bytes)));
     }
 
-    *addr = root;
-    Ok(module)
+    let Some(published_at) = address_substitutions.get(&package) else {
+        return Ok(None);
+    };
+    substitute_root_address(named_module, *published_at)?
+        .serialize(&mut bytes)
+        .unwrap();
+    Ok(Some((*published_at, bytes)))
 }
 
 fn local_bytes(
     compiled_package: &CompiledPackage,
     include_deps: bool,
     source_mode: SourceMode,
+    address_substitutions: &AddressSubstitutions,
 ) -> Result<LocalBytes, SourceVerificationError> {
     let mut map = LocalBytes::new();
 
@@ -317,13 +351,10 @@ fn local_bytes(
             };
 
             let module = m.name;
-            let address = m.address.into_inner();
-            if address == AccountAddress::ZERO {
+            let Some((address, bytes)) = published_bytes(*package, m, address_substitutions)? else {
                 continue;
-            }
+            };
 
-            let mut bytes = vec![];
-            m.module.serialize(&mut bytes).unwrap();
             map.insert((address, module), (*package, bytes));
         }
     }
@@ -340,22 +371,22 @@ fn local_bytes(
                 };
 
                 let module = m.name;
-                let address = m.address.into_inner();
-                if address == AccountAddress::ZERO {
+                let Some((address, bytes)) =
+                    published_bytes(root_package, m, address_substitutions)?
+                else {
                     return Err(SourceVerificationError::InvalidModuleFailure {
                         name: module.to_string(),
                         message: "Can't verify unpublished source".to_string(),
                     });
-                }
+                };
 
-                let mut bytes = vec![];
-                m.module.serialize(&mut bytes).unwrap();
                 map.insert((address, module), (root_package, bytes));
             }
         }
 
         // Include the root compiled units, and any unpublished dependencies with their
-        // addresses substituted
+        // addresses substituted (dependencies with an address substitution were published on
+        // their own)
         SourceMode::VerifyAt(root_address) => {
             for local_unit in &compiled_package.root_compiled_units {
                 let CompiledUnitEnum::Module(m) = &local_unit.unit else {
@@ -377,7 +408,7 @@ fn local_bytes(
 
                 let module = m.name;
                 let address = m.address.into_inner();
-                if address != AccountAddress::ZERO {
+                if address != AccountAddress::ZERO || address_substitutions.contains_key(package) {
                     continue;
                 }
 
@@ -3,6 +3,7 @@
 
 use expect_test::expect;
 use move_core_types::account_address::AccountAddress;
+use move_symbol_pool::Symbol;
 use std::collections::HashMap;
 use std::{fs, io, path::Path};
 use std::{path::PathBuf, str};
@@ -152,6 +153,47 @@ async fn successful_verification_module_ordering() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn successful_verification_address_substitution() -> anyhow::Result<()> {
+    let mut cluster = TestClusterBuilder::new().build().await?;
+    let sender = cluster.get_address_0();
+    let context = &mut cluster.wallet;
+
+    let b_ref = {
+        let fixtures = tempfile::tempdir()?;
+        let b_src = copy_package(&fixtures, "b", SuiAddress::ZERO).await?;
+        publish_package(context, sender, b_src).await
+    };
+
+    // Compile `b`, and `a` which depends on it, with `b` still at its placeholder address.
+    let (b_pkg, a_pkg) = {
+        let fixtures = tempfile::tempdir()?;
+        let b_src = copy_package(&fixtures, "b", SuiAddress::ZERO).await?;
+        let a_src = copy_package(&fixtures, "a", SuiAddress::ZERO).await?;
+        (compile_package(b_src), compile_package(a_src))
+    };
+
+    let client = context.get_client().await?;
+    let b_addr: SuiAddress = b_ref.0.into();
+    let verifier = BytecodeSourceVerifier::new(client.read_api(), false)
+        .with_address_substitutions(HashMap::from([(Symbol::from("b"), b_addr.into())]));
+
+    // Verify the root at the address it was published at
+    verifier
+        .verify_package(
+            &b_pkg.package,
+            /* verify_deps */ false,
+            SourceMode::Verify,
+        )
+        .await
+        .unwrap();
+
+    // Verify the dependency at the address it was published at
+    verifier.verify_package_deps(&a_pkg.package).await.unwrap();
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn fail_verification_bad_address() -> anyhow::Result<()> {
     let mut cluster = TestClusterBuilder::new().build().await?;
@@ -489,6 +531,60 @@ async fn module_bytecode_mismatch() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn module_bytecode_mismatch_address_substitution() -> anyhow::Result<()> {
+    let mut cluster = TestClusterBuilder::new().build().await?;
+    let sender = cluster.get_address_0();
+    let context = &mut cluster.wallet;
+    let mut stable_addrs = HashMap::new();
+
+    let b_ref = {
+        let fixtures = tempfile::tempdir()?;
+        let b_src = copy_package(&fixtures, "b", SuiAddress::ZERO).await?;
+
+        // Modify a module before publishing
+        let c_path = b_src.join("sources").join("c.move");
+        let c_file = tokio::fs::read_to_string(&c_path)
+            .await?
+            .replace("43", "44");
+        tokio::fs::write(&c_path, c_file).await?;
+
+        publish_package(context, sender, b_src).await
+    };
+    let b_addr: SuiAddress = b_ref.0.into();
+    stable_addrs.insert(b_addr, "<b_addr>");
+
+    let (b_pkg, a_pkg) = {
+        let fixtures = tempfile::tempdir()?;
+        let b_src = copy_package(&fixtures, "b", SuiAddress::ZERO).await?;
+        let a_src = copy_package(&fixtures, "a", SuiAddress::ZERO).await?;
+        (compile_package(b_src), compile_package(a_src))
+    };
+
+    let client = context.get_client().await?;
+    let verifier = BytecodeSourceVerifier::new(client.read_api(), false)
+        .with_address_substitutions(HashMap::from([(Symbol::from("b"), b_addr.into())]));
+
+    let result = verifier
+        .verify_package(&b_pkg.package, /* verify_deps */ false, SourceMode::Verify)
+        .await;
+    let Err(err) = result else {
+        panic!("Expected verification to fail");
+    };
+
+    let expected = expect!["Local dependency did not match its on-chain version at <b_addr>::b::c"];
+    expected.assert_eq(&sanitize_id(err.to_string(), &stable_addrs));
+
+    let Err(err) = verifier.verify_package_deps(&a_pkg.package).await else {
+        panic!("Expected verification to fail");
+    };
+
+    let expected = expect!["Local dependency did not match its on-chain version at <b_addr>::b::c"];
+    expected.assert_eq(&sanitize_id(err.to_string(), &stable_addrs));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn multiple_failures() -> anyhow::Result<()> {
     let mut cluster = TestClusterBuilder::new().build().await?;
@@ -153,6 +153,13 @@ pub struct TreasuryCap {
 }
 
 impl TreasuryCap {
+    /// Is this other StructTag representing a TreasuryCap?
+    pub fn is_treasury_cap(other: &StructTag) -> bool {
+        other.address == SUI_FRAMEWORK_ADDRESS
+            && other.module.as_ident_str() == COIN_MODULE_NAME
+            && other.name.as_ident_str() == COIN_TREASURE_CAP_NAME
+    }
+
     /// Create a TreasuryCap from BCS bytes
     pub fn from_bcs_bytes(content: &[u8]) -> Result<Self, SuiError> {
         bcs::from_bytes(content).map_err(|err| SuiError::TypeError {
@@ -1233,6 +1233,7 @@ pub type AuthorityStrongQuorumSignInfo = AuthorityQuorumSignInfo<true>;
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SuiAuthorityStrongQuorumSignInfo {
     pub epoch: EpochId,
+    #[schemars(with = "Base64")]
     pub signature: AggregateAuthoritySignatureAsBytes,
     #[schemars(with = "Base64")]
     #[serde_as(as = "SuiBitmap")]
@@ -21,6 +21,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     convert::TryFrom,
     ops::{Add, Deref, Mul},
+    time::{Duration, Instant},
 };
 use sui_cost_tables::{
     bytecode_tables::{GasStatus, INITIAL_COST_SCHEDULE},
@@ -316,6 +317,8 @@ pub struct SuiGasStatus<'a> {
     /// was the storage cost paid when the object was last mutated. It is not affected
     /// by the current storage gas unit price.
     storage_rebate: SuiGas,
+    /// The commands of the transaction are aborted once they have run for longer than this.
+    execution_timeout: Option<Duration>,
 
     cost_table: SuiCostTable,
 }
@@ -376,6 +379,31 @@ impl<'a> SuiGasStatus<'a> {
         self.init_budget.mul(max_gas_unit_price).into()
     }
 
+    /// Abort the commands of the transaction once they have run for longer than
+    /// `execution_timeout`. Unlike running out of gas, timing out depends on the speed of the
+    /// machine, so this must only be set for executions whose effects are not committed, such as
+    /// dry runs and dev-inspect calls.
+    pub fn set_execution_timeout(&mut self, execution_timeout: Option<Duration>) {
+        self.execution_timeout = execution_timeout;
+    }
+
+    /// Start the clock of the execution timeout, if any. Once it runs out, every charge fails as
+    /// if the gas ran out, and the whole budget is used up.
+    pub fn start_execution_timeout(&mut self) {
+        let deadline = self
+            .execution_timeout
+            .and_then(|timeout| Instant::now().checked_add(timeout));
+        self.gas_status.set_deadline(deadline);
+    }
+
+    pub fn stop_execution_timeout(&mut self) {
+        self.gas_status.set_deadline(None);
+    }
+
+    pub fn execution_timed_out(&self) -> bool {
+        self.gas_status.deadline_exceeded()
+    }
+
     pub fn create_move_gas_status(&mut self) -> &mut GasStatus<'a> {
         &mut self.gas_status
     }
@@ -497,6 +525,7 @@ impl<'a> SuiGasStatus<'a> {
             storage_gas_unit_price: ComputeGasPricePerUnit::new(storage_gas_unit_price),
             storage_gas_units: GasUnits::new(0),
             storage_rebate: 0.into(),
+            execution_timeout: None,
             cost_table,
         }
     }
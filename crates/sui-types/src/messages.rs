@@ -635,6 +635,15 @@ impl ProgrammableTransaction {
                 value: config.max_programmable_tx_commands().to_string()
             }
         );
+        if let Some(max_inputs) = config.max_programmable_tx_inputs() {
+            fp_ensure!(
+                inputs.len() < max_inputs as usize,
+                UserInputError::SizeLimitExceeded {
+                    limit: "maximum inputs in a programmable transaction".to_string(),
+                    value: max_inputs.to_string()
+                }
+            );
+        }
         for input in inputs {
             input.validity_check(config)?
         }
@@ -2270,6 +2279,11 @@ pub enum ExecutionFailureStatus {
     Limit is {max_size} bytes"
     )]
     EffectsTooLarge { current_size: u64, max_size: u64 },
+
+    // Indicates the commands of a dry run or dev-inspect call ran for longer than the execution
+    // timeout of the node serving it
+    #[error("Execution timed out. The commands ran for longer than the execution timeout")]
+    ExecutionTimeout,
     // NOTE: if you want to add a new enum,
     // please add it at the end for Rust SDK backward compatibility.
 }
@@ -4,18 +4,33 @@
 //! Utility for generating programmable transactions, either by specifying a command or for
 //! migrating legacy transactions
 
+use std::fmt;
+
 use anyhow::Context;
 use indexmap::IndexMap;
-use move_core_types::{ident_str, identifier::Identifier, language_storage::TypeTag};
-use serde::Serialize;
+use move_core_types::{
+    account_address::AccountAddress,
+    ident_str,
+    identifier::{IdentStr, Identifier},
+    language_storage::{StructTag, TypeTag},
+    u256::U256,
+};
+use serde::{
+    de::{DeserializeSeed, Error},
+    Deserialize, Deserializer, Serialize,
+};
 
 use crate::{
-    base_types::{ObjectID, ObjectRef, SuiAddress},
+    base_types::{
+        ObjectID, ObjectRef, SuiAddress, STD_ASCII_MODULE_NAME, STD_ASCII_STRUCT_NAME,
+        STD_OPTION_MODULE_NAME, STD_OPTION_STRUCT_NAME, STD_UTF8_MODULE_NAME, STD_UTF8_STRUCT_NAME,
+    },
+    id::{ID_STRUCT_NAME, OBJECT_MODULE_NAME},
     messages::{
         Argument, CallArg, Command, ObjectArg, ProgrammableMoveCall, ProgrammableTransaction,
     },
     move_package::PACKAGE_MODULE_NAME,
-    SUI_FRAMEWORK_OBJECT_ID,
+    MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS, SUI_FRAMEWORK_OBJECT_ID,
 };
 
 #[derive(PartialEq, Eq, Hash)]
@@ -67,6 +82,28 @@ impl ProgrammableTransactionBuilder {
         ))
     }
 
+    /// Like pure, for an argument of Move type `std::option::Option<type_>`. Fails if `value` is
+    /// not a value of that type, ex. `Some(1u64)` for an `Option<u8>`.
+    pub fn pure_option<T: Serialize>(
+        &mut self,
+        type_: TypeTag,
+        value: Option<T>,
+    ) -> anyhow::Result<Argument> {
+        let bytes = pure_bytes_of_type(&option_type_tag(type_), &value)?;
+        Ok(self.pure_bytes(bytes, /* force separate */ false))
+    }
+
+    /// Like pure, for an argument of Move type `vector<type_>`. Fails if `values` is not a value
+    /// of that type.
+    pub fn pure_vec<T: Serialize>(
+        &mut self,
+        type_: TypeTag,
+        values: Vec<T>,
+    ) -> anyhow::Result<Argument> {
+        let bytes = pure_bytes_of_type(&TypeTag::Vector(Box::new(type_)), &values)?;
+        Ok(self.pure_bytes(bytes, /* force separate */ false))
+    }
+
     pub fn obj(&mut self, obj_arg: ObjectArg) -> anyhow::Result<Argument> {
         let id = obj_arg.id();
         let obj_arg = if let Some(old_value) = self.inputs.get(&BuilderArg::Object(id)) {
@@ -219,6 +256,22 @@ impl ProgrammableTransactionBuilder {
         self.commands.push(Command::TransferObjects(args, rec_arg));
     }
 
+    /// Transfer all of `objects` to `recipient` with a single `TransferObjects` command.
+    pub fn transfer_objects(&mut self, objects: Vec<Argument>, recipient: SuiAddress) -> Argument {
+        let rec_arg = self.pure(recipient).unwrap();
+        self.transfer_objects_arg(objects, rec_arg)
+    }
+
+    /// Like `transfer_objects`, for a recipient that is already an argument of the transaction,
+    /// e.g. a pure input or the result of a previous command.
+    pub fn transfer_objects_arg(
+        &mut self,
+        objects: Vec<Argument>,
+        recipient: Argument,
+    ) -> Argument {
+        self.command(Command::TransferObjects(objects, recipient))
+    }
+
     pub fn transfer_object(
         &mut self,
         recipient: SuiAddress,
@@ -231,6 +284,12 @@ impl ProgrammableTransactionBuilder {
         Ok(())
     }
 
+    /// Merge the balances of `to_merge` into `primary` with a single `MergeCoins` command. The
+    /// merged coins are deleted and `primary` is mutated in place, so there is no result.
+    pub fn merge_coins(&mut self, primary: Argument, to_merge: Vec<Argument>) {
+        self.commands.push(Command::MergeCoins(primary, to_merge));
+    }
+
     pub fn transfer_sui(&mut self, recipient: SuiAddress, amount: Option<u64>) {
         let rec_arg = self.pure(recipient).unwrap();
         let coin_arg = if let Some(amount) = amount {
@@ -274,7 +333,7 @@ impl ProgrammableTransactionBuilder {
             .map(|c| self.obj(ObjectArg::ImmOrOwnedObject(c)))
             .collect::<Result<_, _>>()?;
         if !merge_args.is_empty() {
-            self.command(Command::MergeCoins(coin_arg, merge_args));
+            self.merge_coins(coin_arg, merge_args);
         }
         self.pay_impl(recipients, amounts, coin_arg)
     }
@@ -314,3 +373,165 @@ impl ProgrammableTransactionBuilder {
         Ok(())
     }
 }
+
+/// The Move type `std::option::Option<type_>`.
+pub fn option_type_tag(type_: TypeTag) -> TypeTag {
+    TypeTag::Struct(Box::new(StructTag {
+        address: MOVE_STDLIB_ADDRESS,
+        module: STD_OPTION_MODULE_NAME.to_owned(),
+        name: STD_OPTION_STRUCT_NAME.to_owned(),
+        type_params: vec![type_],
+    }))
+}
+
+/// BCS encodes `value` as a pure argument of Move type `type_`, checking it with
+/// [check_pure_layout].
+pub fn pure_bytes_of_type<T: Serialize>(type_: &TypeTag, value: &T) -> anyhow::Result<Vec<u8>> {
+    let bytes = bcs::to_bytes(value).context("Serializing pure argument.")?;
+    check_pure_layout(type_, &bytes)?;
+    Ok(bytes)
+}
+
+/// Checks that `bytes` is the BCS encoding of a value of Move type `type_`, which must be a type
+/// pure arguments can have: a primitive, a `std::string::String`, a `std::ascii::String`, an
+/// `ID`, or an option or vector of those.
+pub fn check_pure_layout(type_: &TypeTag, bytes: &[u8]) -> anyhow::Result<()> {
+    bcs::from_bytes_seed(PureLayout(type_), bytes)
+        .with_context(|| format!("Pure argument is not a value of type {type_}"))
+}
+
+struct PureLayout<'a>(&'a TypeTag);
+
+fn is_struct(tag: &StructTag, address: AccountAddress, module: &IdentStr, name: &IdentStr) -> bool {
+    tag.address == address && tag.module.as_ident_str() == module && tag.name.as_ident_str() == name
+}
+
+impl<'d, 'a> DeserializeSeed<'d> for PureLayout<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'d>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        match self.0 {
+            TypeTag::Bool => {
+                bool::deserialize(deserializer)?;
+            }
+            TypeTag::U8 => {
+                u8::deserialize(deserializer)?;
+            }
+            TypeTag::U16 => {
+                u16::deserialize(deserializer)?;
+            }
+            TypeTag::U32 => {
+                u32::deserialize(deserializer)?;
+            }
+            TypeTag::U64 => {
+                u64::deserialize(deserializer)?;
+            }
+            TypeTag::U128 => {
+                u128::deserialize(deserializer)?;
+            }
+            TypeTag::U256 => {
+                U256::deserialize(deserializer)?;
+            }
+            TypeTag::Address => {
+                AccountAddress::deserialize(deserializer)?;
+            }
+            TypeTag::Vector(inner) => deserializer.deserialize_seq(PureVectorVisitor(inner))?,
+            TypeTag::Struct(tag)
+                if is_struct(
+                    tag,
+                    MOVE_STDLIB_ADDRESS,
+                    STD_OPTION_MODULE_NAME,
+                    STD_OPTION_STRUCT_NAME,
+                ) && tag.type_params.len() == 1 =>
+            {
+                deserializer.deserialize_option(PureOptionVisitor(&tag.type_params[0]))?
+            }
+            TypeTag::Struct(tag)
+                if is_struct(
+                    tag,
+                    MOVE_STDLIB_ADDRESS,
+                    STD_UTF8_MODULE_NAME,
+                    STD_UTF8_STRUCT_NAME,
+                ) =>
+            {
+                // BCS rejects strings that are not valid UTF8
+                String::deserialize(deserializer)?;
+            }
+            TypeTag::Struct(tag)
+                if is_struct(
+                    tag,
+                    MOVE_STDLIB_ADDRESS,
+                    STD_ASCII_MODULE_NAME,
+                    STD_ASCII_STRUCT_NAME,
+                ) =>
+            {
+                if !String::deserialize(deserializer)?.is_ascii() {
+                    return Err(D::Error::custom("not an ascii string"));
+                }
+            }
+            TypeTag::Struct(tag)
+                if is_struct(
+                    tag,
+                    SUI_FRAMEWORK_ADDRESS,
+                    OBJECT_MODULE_NAME,
+                    ID_STRUCT_NAME,
+                ) =>
+            {
+                AccountAddress::deserialize(deserializer)?;
+            }
+            TypeTag::Signer | TypeTag::Struct(_) => {
+                return Err(D::Error::custom(format!(
+                    "{} is not a type of pure arguments",
+                    self.0
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct PureVectorVisitor<'a>(&'a TypeTag);
+
+impl<'d, 'a> serde::de::Visitor<'d> for PureVectorVisitor<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "vector<{}>", self.0)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'d>,
+    {
+        while seq.next_element_seed(PureLayout(self.0))?.is_some() {}
+        Ok(())
+    }
+}
+
+struct PureOptionVisitor<'a>(&'a TypeTag);
+
+impl<'d, 'a> serde::de::Visitor<'d> for PureOptionVisitor<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "std::option::Option<{}>", self.0)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(())
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'d>,
+    {
+        PureLayout(self.0).deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+#[path = "unit_tests/programmable_transaction_builder_tests.rs"]
+mod programmable_transaction_builder_tests;
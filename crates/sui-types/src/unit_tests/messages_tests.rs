@@ -875,6 +875,56 @@ fn test_sponsored_transaction_validity_check() {
         .unwrap();
 }
 
+#[test]
+fn test_programmable_transaction_size_limits() {
+    let config = ProtocolConfig::get_for_max_version();
+    let sender = SuiAddress::random_for_testing_only();
+    let check = |pt: ProgrammableTransaction| {
+        TransactionData::new_programmable_with_dummy_gas_price(
+            sender,
+            vec![random_object_ref()],
+            pt,
+            10000,
+        )
+        .validity_check(&config)
+    };
+
+    let mut builder = ProgrammableTransactionBuilder::new();
+    for _ in 0..config.max_programmable_tx_commands() {
+        builder.pay_all_sui(sender);
+    }
+    assert_eq!(
+        check(builder.finish()),
+        Err(UserInputError::SizeLimitExceeded {
+            limit: "maximum commands in a programmable transaction".to_string(),
+            value: config.max_programmable_tx_commands().to_string(),
+        })
+    );
+
+    let max_inputs = config.max_programmable_tx_inputs().unwrap();
+    let pt = ProgrammableTransaction {
+        inputs: vec![CallArg::Pure(vec![]); max_inputs as usize],
+        commands: vec![],
+    };
+    assert_eq!(
+        check(pt.clone()),
+        Err(UserInputError::SizeLimitExceeded {
+            limit: "maximum inputs in a programmable transaction".to_string(),
+            value: max_inputs.to_string(),
+        })
+    );
+
+    // The inputs are not limited before protocol version 2
+    TransactionData::new_programmable_with_dummy_gas_price(
+        sender,
+        vec![random_object_ref()],
+        pt,
+        10000,
+    )
+    .validity_check(&ProtocolConfig::get_for_version(ProtocolVersion::new(1)))
+    .unwrap();
+}
+
 #[test]
 fn verify_sender_signature_correctly_with_flag() {
     // set up authorities
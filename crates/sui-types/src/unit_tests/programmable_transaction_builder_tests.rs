@@ -0,0 +1,60 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::parse_sui_type_tag;
+
+use super::*;
+
+#[test]
+fn test_pure_option_and_vec() {
+    let utf8 = parse_sui_type_tag("0x1::string::String").unwrap();
+    let mut builder = ProgrammableTransactionBuilder::new();
+
+    builder.pure_option(utf8.clone(), Some("çå∞≠¢")).unwrap();
+    builder.pure_option::<String>(utf8.clone(), None).unwrap();
+    builder
+        .pure_vec(option_type_tag(utf8.clone()), vec![Some("çå∞≠¢")])
+        .unwrap();
+    builder
+        .pure_option(
+            TypeTag::Vector(Box::new(option_type_tag(utf8))),
+            Some(vec![Some("çå∞≠¢")]),
+        )
+        .unwrap();
+    builder.pure_vec(TypeTag::U64, Vec::<u64>::new()).unwrap();
+
+    // The encodings match the ones of the same values passed to `pure`.
+    let mut expected = ProgrammableTransactionBuilder::new();
+    expected.pure(Some("çå∞≠¢")).unwrap();
+    expected.pure(None::<String>).unwrap();
+    expected.pure(vec![Some("çå∞≠¢")]).unwrap();
+    expected.pure(Some(vec![Some("çå∞≠¢")])).unwrap();
+    expected.pure(Vec::<u64>::new()).unwrap();
+    assert_eq!(builder.finish(), expected.finish());
+}
+
+#[test]
+fn test_pure_layout_mismatch() {
+    let ascii = parse_sui_type_tag("0x1::ascii::String").unwrap();
+    let utf8 = parse_sui_type_tag("0x1::string::String").unwrap();
+    let mut builder = ProgrammableTransactionBuilder::new();
+
+    // Wrong width
+    assert!(builder.pure_option(TypeTag::U8, Some(1u64)).is_err());
+    // Missing a level of nesting
+    assert!(builder
+        .pure_vec(option_type_tag(utf8.clone()), vec!["hello"])
+        .is_err());
+    // Not ascii
+    assert!(builder.pure_option(ascii.clone(), Some("çå")).is_err());
+    assert!(builder.pure_option(ascii, Some("hello")).is_ok());
+    // Not a type of pure arguments
+    let coin = parse_sui_type_tag("0x2::coin::Coin<0x2::sui::SUI>").unwrap();
+    assert!(builder.pure_vec(coin, vec![0u64]).is_err());
+    assert!(builder
+        .pure_vec(TypeTag::Signer, vec![SuiAddress::ZERO])
+        .is_err());
+
+    let id = parse_sui_type_tag("0x2::object::ID").unwrap();
+    assert!(builder.pure_option(id, Some(ObjectID::ZERO)).is_ok());
+}
@@ -477,6 +477,9 @@ impl SuiClientCommands {
                     config,
                     run_bytecode_verifier,
                     print_diags_to_stderr,
+                    package_cache_dir: None,
+                    retain_source_maps: false,
+                    verifier_threads: None,
                 };
 
                 let resolution_graph = config.resolution_graph(&package_path)?;
@@ -493,6 +496,7 @@ impl SuiClientCommands {
                     resolution_graph,
                     run_bytecode_verifier,
                     print_diags_to_stderr,
+                    None,
                 )?;
 
                 if !compiled_package.is_framework() {
@@ -1063,6 +1067,9 @@ impl SuiClientCommands {
                         config: build_config,
                         run_bytecode_verifier: true,
                         print_diags_to_stderr: true,
+                        package_cache_dir: None,
+                        retain_source_maps: false,
+                        verifier_threads: None,
                     },
                 )?;
 
@@ -34,6 +34,7 @@
 //! ## Features
 //! - `json` - Bunyan formatter - JSON log output, optional
 //! - `tokio-console` - [Tokio-console](https://github.com/tokio-rs/console) subscriber, optional
+//! - `otlp` - export spans to an [OpenTelemetry](https://opentelemetry.io) collector over OTLP, optional
 //!
 //! ### Stdout vs file output
 //!
@@ -129,12 +130,30 @@ pub struct TelemetryConfig {
     pub crash_on_panic: bool,
     /// Optional Prometheus registry - if present, all enabled span latencies are measured
     pub prom_registry: Option<prometheus::Registry>,
+    /// If defined, export all enabled spans over OTLP (gRPC) to this collector endpoint,
+    /// ex http://localhost:4317. Requires the `otlp` feature.
+    pub otlp_endpoint: Option<String>,
+    /// Service name the exported spans are reported under
+    pub service_name: Option<String>,
 }
 
 #[must_use]
 #[allow(dead_code)]
 pub struct TelemetryGuards {
     worker_guard: WorkerGuard,
+    #[cfg(feature = "otlp")]
+    otlp_guard: Option<OtlpGuard>,
+}
+
+/// Flushes the spans still buffered by the OTLP exporter when dropped.
+#[cfg(feature = "otlp")]
+struct OtlpGuard;
+
+#[cfg(feature = "otlp")]
+impl Drop for OtlpGuard {
+    fn drop(&mut self) {
+        opentelemetry::global::shutdown_tracer_provider();
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -214,6 +233,8 @@ impl TelemetryConfig {
             panic_hook: true,
             crash_on_panic: false,
             prom_registry: None,
+            otlp_endpoint: None,
+            service_name: None,
         }
     }
 
@@ -242,6 +263,12 @@ impl TelemetryConfig {
         self
     }
 
+    pub fn with_otlp_endpoint(mut self, endpoint: &str, service_name: &str) -> Self {
+        self.otlp_endpoint = Some(endpoint.to_owned());
+        self.service_name = Some(service_name.to_owned());
+        self
+    }
+
     pub fn with_env(mut self) -> Self {
         if env::var("CRASH_ON_PANIC").is_ok() {
             self.crash_on_panic = true
@@ -304,6 +331,36 @@ impl TelemetryConfig {
             layers.push(span_lat_layer.with_filter(span_filter.clone()).boxed());
         }
 
+        // OpenTelemetry layer, spans are exported in batches from a background tokio task, so
+        // this must be initialized from within a tokio runtime.
+        #[cfg(feature = "otlp")]
+        let otlp_guard = if let Some(endpoint) = &config.otlp_endpoint {
+            use opentelemetry::{sdk, KeyValue};
+            use opentelemetry_otlp::WithExportConfig;
+
+            let service_name = config
+                .service_name
+                .clone()
+                .unwrap_or_else(|| "unknown_service".into());
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(sdk::trace::config().with_resource(sdk::Resource::new(vec![
+                    KeyValue::new("service.name", service_name),
+                ])))
+                .install_batch(opentelemetry::runtime::Tokio)
+                .expect("Could not initialize OTLP exporter");
+            let otlp_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+            layers.push(otlp_layer.with_filter(span_filter.clone()).boxed());
+            Some(OtlpGuard)
+        } else {
+            None
+        };
+
         let (nb_output, worker_guard) = get_output(config.log_file.clone());
         if config.json_log_output {
             // Output to file or to stderr in a newline-delimited JSON format
@@ -333,7 +390,11 @@ impl TelemetryConfig {
 
         // The guard must be returned and kept in the main fn of the app, as when it's dropped then the output
         // gets flushed and closed. If this is dropped too early then no output will appear!
-        let guards = TelemetryGuards { worker_guard };
+        let guards = TelemetryGuards {
+            worker_guard,
+            #[cfg(feature = "otlp")]
+            otlp_guard,
+        };
 
         (guards, filter_handle)
     }
@@ -47,10 +47,18 @@ export const ExecutionDigests = object({
   effects: TransactionEffectsDigest,
 });
 
+export const AuthorityQuorumSignInfo = object({
+  epoch: number(),
+  signature: string(),
+  signers_map: array(number()),
+});
+export type AuthorityQuorumSignInfo = Infer<typeof AuthorityQuorumSignInfo>;
+
 export const Checkpoint = object({
   epoch: number(),
   sequenceNumber: number(),
   digest: CheckpointDigest,
+  contentDigest: CheckPointContentsDigest,
   networkTotalTransactions: number(),
   previousDigest: union([CheckpointDigest, literal(null)]),
   epochRollingGasCostSummary: GasCostSummary,
@@ -58,5 +66,6 @@ export const Checkpoint = object({
   endOfEpochData: union([EndOfEpochData, literal(null)]),
   transactions: array(TransactionDigest),
   checkpointCommitments: array(CheckpointCommitment),
+  validatorSignature: AuthorityQuorumSignInfo,
 });
 export type Checkpoint = Infer<typeof Checkpoint>;